@@ -0,0 +1,19 @@
+current-version = Aktuelle Version: { $version }
+updating = Aktualisiere { $package } mit { $manager }
+updating-local = Aktualisiere { $package } in { $dir } mit { $manager }
+updated-to-version = Aktualisiert auf Version: { $version }
+updated = ✅ { $package } erfolgreich aktualisiert { $change }
+updated-version-unknown = ✅ { $package } erfolgreich aktualisiert, die Datei hat sich geändert, die Version ist aber unbekannt
+reinstalled = ✅ { $package } ({ $version }) erfolgreich neu installiert, die Datei hat sich geändert, die Version nicht
+up-to-date = ℹ️  { $package } ist bereits aktuell ({ $version }), keine Änderung
+up-to-date-according-to-manager = ℹ️  { $package } ist laut { $manager } bereits aktuell
+change-unknown = ℹ️  { $manager } lief ohne Fehler, ob sich { $package } geändert hat, ist aber unbekannt
+downgraded = ⚠️  { $package } wurde von { $old-version } auf { $new-version } herabgestuft
+release-notes = ℹ️  Was sich in { $version } geändert hat: { $link }
+
+updating-manager = Aktualisiere alles, was mit { $manager } installiert wurde
+all-updated = { $package } ({ $manager }) aktualisiert { $change }
+all-installed = { $package } ({ $manager }) installiert { $version }
+everything-up-to-date = ✅ Alles ist aktuell
+
+warning = Warnung:
@@ -0,0 +1,545 @@
+# update-bin's messages and errors, in English. Every message is here, translations in
+# locales/<language>.ftl only need the ones they translate.
+#
+# `{ $name }` is filled in by update-bin, keep it as it is in translations.
+
+current-version = Current version: { $version }
+updating = Updating { $package } with { $manager }
+updating-local = Updating { $package } in { $dir } with { $manager }
+updated-to-version = Updated to version: { $version }
+updated = ✅ Successfully updated { $package } { $change }
+updated-version-unknown = ✅ Successfully updated { $package }, the binary changed but its version is unknown
+reinstalled = ✅ Successfully reinstalled { $package } ({ $version }), the binary changed but its version didn't
+up-to-date = ℹ️  { $package } is already up to date ({ $version }), no change
+up-to-date-according-to-manager = ℹ️  { $package } is already up to date according to { $manager }
+change-unknown = ℹ️  { $manager } finished without errors, but whether { $package } changed is unknown
+downgraded = ⚠️  { $package } was downgraded from { $old-version } to { $new-version }
+release-notes = ℹ️  See what changed in { $version }: { $link }
+
+# --all
+updating-manager = Updating everything installed with { $manager }
+all-updated = Updated { $package } ({ $manager }) { $change }
+all-installed = Installed { $package } ({ $manager }) { $version }
+everything-up-to-date = ✅ Everything is up to date
+
+# in place of ⚠️ with --plain
+warning = Warning:
+
+# Errors, shown before exiting or for each update of --all that failed
+error = Error: { $message }
+unknown = unknown
+# after an answer that isn't one of the numbers offered
+enter-number = Please enter a number between 1 and { $count }
+
+# --info
+info-broken = Broken: { $problem }
+info-fix = Fix: { $command }
+info-package-manager-unknown = Package manager: unknown ({ $error })
+info-package-name = Package name: { $package }
+info-package-manager = Package manager: { $manager }
+info-linked-to = Linked to: { $dir }
+info-installed-from = Installed from: { $source }
+info-channel = Channel: { $channel }
+info-formula = Formula: { $formula }
+info-version = Version: { $version }
+info-latest-version = Latest version: { $version }
+info-outdated = Outdated: { $outdated }
+info-pinned = Pinned: true
+info-head = Built from HEAD: true
+info-cask = Cask: true
+info-root = Root: { $dir }
+info-source = Source: { $source }
+
+# --info --all-candidates
+candidates = Candidates:
+candidates-none = Candidates: none
+candidate = { $manager } ({ $package }) { $confidence }%
+candidate-detected = { $manager } ({ $package }) { $confidence }% <- detected
+candidate-path = path: { $evidence }
+candidate-metadata = metadata: { $evidence }
+candidates-set-manager = Set `binaries.{ $bin }.manager` in the config to use another one
+candidates-prompt = Use another one for { $bin } from now on? [1-{ $count }] (default keep):
+candidate-saved = ✅ { $bin } at { $path } is updated with { $manager } ({ $package }) from now on, saved to { $config }
+
+# --dry-run
+info-channel-switch = Channel: { $channel } (now { $current-channel })
+preview-up-to-date = ℹ️  { $package } is already up to date, nothing would change
+preview-would-update = Would update { $package } { $change }
+preview-homebrew-plan = Homebrew would change:
+preview-command = Command: { $command }
+preview-crate-size = Download size: { $size }, built from source
+preview-rust-version = Minimum Rust version: { $version }
+preview-rustc-too-old = ⚠️  The installed rustc is { $version }, the build would fail without `rustup update`
+preview-unpacked-size = Unpacked size: { $size }
+preview-node-range = Requires node: { $range }
+
+# update-bin explain
+explain-first = Explaining the first one, which is the one that runs (use --path to pick another)
+explain-detecting = Detecting who installed { $path }
+explain-resolves-to = It resolves to { $path }
+explain-ran = ran `{ $command }`
+explain-ran-failed = ran `{ $command }` (failed)
+explain-detected = Detected { $manager } as the package manager, updating the { $package } package
+
+# Warnings and notes
+warning-message = ⚠️  { $message }
+info-message = ℹ️  { $message }
+
+# Channels, deprecations and pins
+switching-channel = Switching { $package } from the { $current-channel } channel to { $channel }
+staying-on-channel = ℹ️  Staying on the { $channel } channel, pass `--channel stable` to leave it
+deprecated = ⚠️  { $package } is deprecated: { $reason }
+pinned = Pinned { $package } { $version } in { $path }
+
+# --all plan
+plan = Plan:
+plan-sudo = { $manager } (sudo)
+plan-needs-sudo = { $manager } (needs sudo, pass --sudo)
+duration-estimate = ℹ️  It took { $duration } last time
+running = Running `{ $command }`
+wrote-junit = Wrote the JUnit results to { $path }
+wrote-report = Wrote the report to { $path }
+wrote-metrics = Wrote the metrics to { $path }
+
+# update-bin self-update
+manager-installed-with = { $manager } was installed with { $owner }, updating it with that
+manager-cannot-self-update = ℹ️  { $manager } can't update itself, update it with whatever installed it
+managers-updated = ✅ Package managers updated
+updating-with-command = Updating { $package } with `{ $command }`
+cleaned-up = ✅ Cleaned up
+
+# update-bin audit
+audit-none = ✅ No known vulnerabilities in { $count } tools
+audit-vulnerable = ⚠️  { $package } { $version } ({ $manager })
+audit-advisory = { $id }{ $severity }: { $summary }{ $fixed }
+# after the summary, with the leading comma
+audit-fixed-in = , fixed in { $version }
+audit-fixed-one = ✅ Updated { $count } vulnerable tool, run `update-bin audit` again to check them
+audit-fixed = ✅ Updated { $count } vulnerable tools, run `update-bin audit` again to check them
+audit-vulnerable-one = { $count } tool has known vulnerabilities, `update-bin audit --fix` updates it to the latest version
+audit-vulnerable-many = { $count } tools have known vulnerabilities, `update-bin audit --fix` updates them to the latest version
+
+# update-bin adopt, sync and ensure
+adopted = ✅ Adopted { $bin } in { $config }, `update-bin { $bin }` and `update-bin --all` will run `{ $command }`
+syncing = Syncing { $package } with `{ $command }`
+synced = ✅ Synced { $count } tools
+satisfies = ✅ { $bin } { $version } satisfies { $requirement }
+updating-below-minimum = { $bin } is at { $version }, updating it
+installing-missing = { $bin } isn't installed, installing it with { $manager }
+wrote-versions = Wrote the versions to { $path }
+updating-go-mod = Updating go.mod with `{ $command }`
+
+# update-bin plan and apply
+left-out-of-plan = ⚠️  Only packages of { $managers } can be updated offline, left out { $packages }
+left-out-no-download = ⚠️  Couldn't find where to download { $packages } from or the checksums, left them out of the plan
+downloading = Downloading { $file }
+wrote-plan = Wrote the plan for { $count } packages to { $path }
+applied = ✅ Applied { $count } of { $total } updates
+already-at = ℹ️  { $package } is already at { $version }
+updating-from-plan = Updating { $package } { $change } with `{ $command }`
+
+# update-bin restore
+restored = ✅ Restored { $count } of { $total } tools
+restore-adopted = ℹ️  { $package } was adopted, install it by hand and adopt it again with `update-bin adopt { $package } --update-cmd '{ $command }'`
+already-installed = ℹ️  { $package } is already installed ({ $version })
+restoring = Restoring { $package } { $version } with `{ $command }`
+pinned-to-revision = ⚠️  { $package } is pinned to git revision { $revision }, it will be reinstalled at the same revision
+
+# More than one installation on PATH, and update-bin dedupe
+updating-first-installation = Updating the first one, which is the one that runs (use --path to pick another)
+installations-prompt = Which one do you want to update? [1-{ $count }] (default 1):
+found-installations = Found { $count } installations of { $bin }:
+runs-first = <- runs first
+shadowed-by-first = shadowed by 1
+installed-times = { $bin } is installed { $count } times:
+no-duplicates = ✅ No binary is installed by more than one package
+uninstall-with = Uninstall { $number } with `{ $command }`
+uninstall-prompt = Uninstall one of them? [1-{ $count }] (default none):
+cannot-uninstall = update-bin doesn't know how to uninstall { $path } ({ $owner }), remove it by hand
+now-runs = { $bin } now runs { $path }
+not-on-path-anymore = { $bin } isn't on PATH anymore
+
+# Homebrew renames and aliases
+brew-renamed = ℹ️  { $name } has been renamed to { $formula }, upgrading that instead
+brew-alias = ℹ️  { $name } is an alias of { $formula }, upgrading that instead
+brew-known-as = ℹ️  { $name } is known to Homebrew as { $formula }, upgrading that instead
+also-provides = ℹ️  { $package } also provides these binaries, now at { $version } as well:
+
+# update-bin outdated
+column-package = Package
+column-manager = Manager
+column-current = Current
+column-latest = Latest
+
+# Checks before updating
+manager-outdated = ⚠️  { $manager } { $version } is outdated and may fail to update packages, consider updating it first with `{ $command }`
+brew-index-stale = ⚠️  Homebrew's formula index hasn't been updated in over a week and HOMEBREW_NO_AUTO_UPDATE is set, run `brew update` first or pass --brew-update
+curl-missing = ⚠️  curl is not installed, so latest versions are looked up through the package managers, which is slower
+
+# More than one package that provides the binary
+found-providing-packages = Found { $count } { $manager } packages that provide { $bin }:
+unknown-version = unknown version
+using-first-package = Using the first one (set managers.{ $manager }.packages.{ $bin } in the config to pick another)
+package-prompt = Which one provides the { $bin } you want to update? [1-{ $count }] (default 1):
+choice-saved = Saved the choice to { $path }
+choice-save-failed = ⚠️  Could not save the choice: { $error }
+
+# The `auto` policy
+policy-skipping = Skipping { $package }: { $reason }, which the `auto` policy "{ $policy }" doesn't apply by itself
+policy-held-back = { $package }: { $reason }, which the `auto` policy "{ $policy }" doesn't apply by itself
+update-anyway-prompt = Update anyway? [y/N]:
+
+# Repairs and elevation
+repair-prompt = Run `{ $command }` to fix it? [y/N]:
+repaired = Fixed, `{ $command }` succeeded
+relaunch-elevated-prompt = Relaunch `{ $command }` as administrator? [y/N]:
+
+# Progress of --all without a terminal
+phase-done = done
+phase-failed = failed
+manager-finished = { $manager }: { $phase } in { $seconds }s
+
+# update-bin remind
+remind-one = update-bin: { $bins } hasn't been updated in over { $days } days, run `update-bin { $bin }` or `update-bin --all`
+remind = update-bin: { $bins } haven't been updated in over { $days } days, run `update-bin { $bin }` or `update-bin --all`
+remind-more = and { $count } more
+
+# Files update-bin couldn't write, and settings it couldn't use
+history-write-failed = ⚠️  Could not record the update in { $path }: { $error }
+durations-write-failed = ⚠️  Could not record how long the updates took in { $path }: { $error }
+summary-write-failed = ⚠️  Could not write the job summary: { $error }
+webhook-failed = ⚠️  Could not send the webhook notification: { $error }
+theme-ignored = Warning: ignoring the theme in the config, { $error }
+
+# update-bin stats
+stats-managers = Runs and updates per package manager:
+stats-manager = { $manager }: { $runs } runs, { $updates } updates
+stats-most-updated = Most frequently updated tools:
+stats-none-yet = (none yet)
+stats-tool = { $bin }: { $updates } updates
+stats-average-duration = Average update duration: { $seconds }s over { $runs } runs
+stats-last-runs = Last run per binary:
+stats-stale = Not updated in over { $days } days:
+stats-none = (none)
+stats-since = { $bin }: since { $time }
+
+# Errors from config.json and the files update-bin reads and writes
+failed-to-read = Failed to read { $path }: { $error }
+invalid-config = Invalid config in { $path }: { $error }
+invalid-config-not-object = Invalid config in { $path }: expected an object
+invalid-number-setting = Invalid { $key } "{ $value }" in the config, expected a whole number
+invalid-bool-setting = Invalid { $key } { $value } in the config, expected true or false
+invalid-auto-policy = Invalid auto policy "{ $policy }" for { $bin } in the config, expected one of { $policies }
+invalid-config-key-not-object = Invalid config in { $path }: expected "{ $key }" to be an object
+failed-to-create = Failed to create { $path }: { $error }
+failed-to-write = Failed to write { $path }: { $error }
+invalid-package-json = Invalid package.json in { $path }: { $error }
+manifest-unknown = Don't know how to read { $path }, expected a Brewfile, .tool-versions, package.json, tools.go or .tool-deps
+failed-to-write-plan = Failed to write the plan to { $path }: { $error }
+invalid-plan = Invalid plan in { $path }: { $error }
+plan-version-unknown = { $path } isn't a plan this version of update-bin can apply, write it again with `update-bin plan`
+invalid-plan-missing = Invalid plan in { $path }: missing { $key }
+invalid-snapshot = Invalid snapshot in { $path }: { $error }
+snapshot-version-unknown = { $path } isn't a snapshot this version of update-bin can restore, write it again with `update-bin snapshot`
+invalid-snapshot-missing = Invalid snapshot in { $path }: missing { $key }
+failed-to-write-junit = Failed to write JUnit results to { $path }: { $error }
+failed-to-write-metrics = Failed to write metrics to { $path }: { $error }
+failed-to-write-report = Failed to write the report to { $path }: { $error }
+nothing-recorded-in = Nothing has been recorded in { $path } yet
+nothing-recorded = Nothing has been recorded yet
+theme-not-object = `theme` should be an object
+theme-no-color = there is no color for "{ $role }"
+theme-no-symbol = there is no symbol for "{ $role }"
+theme-output-prefix-not-string = `output_prefix` should be a string
+theme-table-unknown = `table` should be "unicode" or "ascii"
+theme-unknown-setting = unknown setting `{ $key }`
+theme-not-names-to-strings = `{ $key }` should map names to strings
+theme-not-color = "{ $name }" is not a color
+
+# Errors from running other programs
+curl-not-installed = curl is not installed
+failed-to-run-curl = Failed to run curl: { $error }
+failed-to-write-to-curl = Failed to write to curl: { $error }
+failed-to-wait-for-curl = Failed to wait for curl: { $error }
+failed-to-download = Failed to download { $url }: { $output }
+failed-to-run = Failed to run { $command }: { $error }
+killed-still-running = it was still running after { $seconds } seconds
+killed-no-output = it produced no output for { $seconds } seconds
+failed-to-wait-for = Failed to wait for { $command }: { $error }
+# in place of the last lines of a command that printed nothing
+no-output = (no output)
+argument-empty = The { $what } is empty
+argument-looks-like-option = Refusing to use { $what } "{ $value }", it would be read as an option
+argument-has-whitespace = Refusing to use { $what } { $value }, it contains whitespace or control characters
+unsupported-hash-algorithm = Unsupported hash algorithm { $algorithm }
+checksum-needs-openssl = openssl is needed to check the checksum of { $path }
+failed-to-run-openssl = Failed to run openssl: { $error }
+openssl-dgst-failed = `openssl dgst -{ $algorithm }` failed for { $path }: { $output }
+signature-needs-openssl = openssl is needed to check the registry's signature
+signature-unknown-key = it was signed with { $keyid }, which isn't a known registry key (add it to managers.npm.signing_keys in the config if the registry is yours)
+invalid-signature-in-plan = Invalid signature { $keyid } in the plan
+failed-to-write-to = Failed to write to { $dir }: { $error }
+signature-doesnt-match = The registry's signature { $keyid } doesn't match { $message }
+failed-to-write-to-openssl = Failed to write to openssl: { $error }
+failed-to-wait-for-openssl = Failed to wait for openssl: { $error }
+signature-needs-gpg = gpg is needed to check { $signature }
+failed-to-run-gpg = Failed to run gpg: { $error }
+signature-doesnt-verify = { $signature } doesn't verify { $artifact }: { $output }
+healthcheck-couldnt-start = { $description } couldn't start: { $error }
+healthcheck-timed-out = { $description } didn't finish within { $seconds }s
+healthcheck-failed = { $description } failed ({ $status }): { $last-line }
+healthcheck-failed-no-output = { $description } failed ({ $status })
+failed-to-run-sudo = Failed to run `sudo -v`: { $error }
+sudo-failed = `sudo -v` failed, nothing was updated
+osv-unreachable = Could not reach OSV.dev to look up vulnerabilities
+
+# Why an update can't go ahead
+channels-unknown = update-bin doesn't know the channels of { $manager }
+channel-unknown = { $package } has no { $channel } channel, expected { $stable } or { $channels }
+crate-yanked = The version of { $crate } that cargo tried to install has been yanked, install another one with `cargo install { $crate } --version <version>`
+crate-yanked-from = The version of { $crate } that cargo tried to install has been yanked from { $registry }, install another one with `cargo install { $crate } --version <version>`
+crate-gone = { $crate } is no longer available on the registry, it may have been renamed or had every version yanked
+crate-gone-from = { $crate } is no longer available on { $registry }, it may have been renamed or had every version yanked
+crate-gone-from-crates-io = { $crate } is no longer available on crates-io, it may have been renamed or had every version yanked, see https://crates.io/crates/{ $crate }
+npm-package-gone = { $package } is no longer published on the registry, it may have been renamed or unpublished, see https://www.npmjs.com/package/{ $package } and reinstall it under its new name with { $manager }
+registry-has-no-version = The registry has no version of { $package } that { $manager } can install, the latest one may have been unpublished or deprecated
+elevate-winget-machine-wide = { $bin-path } is a machine-wide winget install in Program Files
+elevate-needs-admin = { $manager } can't write to { $bin-dir } without administrator rights
+elevate-run-elevated = { $reason }. Run `{ $command }` from an elevated terminal, or pass --elevate
+nothing-was-changed = Nothing was changed
+repair-run-to-fix = { $problem }, run `{ $command }` to fix it
+command-failed-with-output = `{ $command }` failed, the last output was:
+    { $output }
+failed-to-get-current-dir = Failed to get the current dir: { $error }
+local-venv-no-script = { $bin-path } is in the virtualenv, but no package installed there has it as a script
+local-not-found = There's no { $bin } in node_modules/.bin of { $current-dir } or a parent dir, or in an active virtualenv
+local-no-package-json = { $project-dir } has a node_modules but no package.json to update
+local-not-a-dependency = { $bin } in { $node-modules-dir } doesn't come from a dependency in package.json, update the dependency that brings it in
+lock-retrying = { $manager } was locked by another process, trying again
+lock-still-held = { $manager }'s lock is still held by { $holder } after { $seconds }s, try again once it's done or pass a longer --lock-timeout
+lock-waiting = Waiting for { $manager }'s lock, held by { $holder } (up to { $seconds }s)
+lock-holder-pacman = another pacman (or remove /var/lib/pacman/db.lck if none is running)
+lock-holder-unknown = another process
+manager-not-working = { $manager } is not working: { $error }
+manager-version-failed = { $manager } is not working, `{ $executable } { $version-arg }` failed: { $reason }
+failed-to-run-brew-update = Failed to run brew update: { $error }
+network-unreachable = Network unreachable, could not connect to { $host } for { $manager }: { $reason }
+no-address-found = no address found
+policy-latest-unknown = its latest version is unknown
+policy-cant-compare = { $old-version } can't be compared with it
+policy-major-update = { $old-version } -> { $latest-version } is a major update
+policy-minor-update = { $old-version } -> { $latest-version } is a minor update
+policy-patch-update = { $old-version } -> { $latest-version } is a patch update
+shim-target-gone = { $bin } is a { $manager } shim for { $target }, which no longer exists
+shim-target-unknown = { $bin } is a { $manager } shim, but { $manager } can't tell what it runs: { $reason }
+shim-version-unknown = { $bin } is a { $manager } shim, but { $manager } couldn't tell which version it runs, check `{ $manager } which { $bin }`
+asdf-install-latest = `asdf install { $tool } latest` then `asdf set -u { $tool } latest`, or pass --write-pins to do both
+version-manager-install = `{ $manager } install <version>` then `{ $manager } global <version>`
+version-manager-installs-side-by-side = { $bin } comes with { $tool } { $version } installed by { $manager }, which installs new versions next to the old ones instead of updating them. Install a newer one with { $install-command }
+asdf-latest-unknown = asdf couldn't tell the latest version of { $tool }
+pins-not-moved = Installed { $tool } { $version }, but no { $file-name } pins { $tool } to move to it
+
+# Invalid arguments
+output-format-expected = expected `text`, `gha` or `junit=<path>`
+
+# What points to the package that owns a binary, in --info --all-candidates
+candidate-package-json-declares = { $package }'s package.json declares it
+candidate-package-jsons-declare = the package.json of { $package-names } declare it
+candidate-adopted-is-in-config = `adopted.{ $bin }` is in the config
+candidate-in-shims-dir = in { $manager }'s shims dir
+candidate-whalebrew-wrapper = a whalebrew wrapper for { $image }
+candidate-runs-image = runs the image { $image }
+candidate-winget-links = winget links it to { $package-id }
+candidate-in-scoop-shims = in scoop's shims or apps dir
+candidate-shim-runs-app = the shim runs the app { $app }
+candidate-in-chocolatey-bin-dir = in chocolatey's bin dir
+candidate-choco-lists-as-providing = choco lists { $package } as providing it
+candidate-resolves-into-cellar = resolves into the Cellar of { $formula }
+candidate-in-opt-dir = the opt dir of { $package } has it
+candidate-in-homebrew-prefix = in the Homebrew prefix
+candidate-in-bun-global-bin = in bun's global bin dir
+candidate-in-dir = in { $cargo-bin-dir }
+candidate-crates2-json-lists = .crates2.json lists it in { $crate }
+candidate-in-go-bin-dir = in Go's bin dir
+candidate-was-built = was built from { $package-path }
+candidate-in-deno-bin-dir = in deno's bin dir
+candidate-deno-shim = a deno shim for { $specifier }
+candidate-resolves-into = resolves into { $tool-dir }
+candidate-belongs-to-uv-tool = belongs to the uv tool { $tool }
+candidate-in-pnpm-global-bin = in pnpm's global bin dir
+candidate-in-npm-global-bin = in npm's global bin dir { $npm-bin-dir }
+candidate-in-yarn-global-bin = in yarn's global bin dir
+candidate-owns = { $manager } owns it in { $package }
+
+# Why an update failed or can't go ahead
+detect-failed-adopt-hint = { $error }. If it was built or downloaded by hand, `update-bin adopt { $bin } --update-cmd <command>` lets update-bin update it
+linked-package = { $package } is linked to { $linked-dir } with `{ $manager } link`, update that checkout instead, or run `{ $unlink-command }` to go back to the published package
+system-package = { $bin-path } belongs to the { $package } package of the system package manager ({ $manager })
+global-dir-owned-by-root = { $manager }'s global packages in { $global-dir } are owned by root
+root-only-run-sudo = { $root-reason }, which only root can update. Run `{ $command } { $command-args }`, or pass --sudo
+
+# GitHub Actions output
+gha-summary = | Binary | Package | Manager | From | To | Status |
+    | --- | --- | --- | --- | --- | --- |
+    | { $bin } | { $package } | { $manager } | { $old-version } | { $new-version } | { $status } |
+already-up-to-date = Already up to date
+
+# Why an update failed or can't go ahead, continued
+killed-while-updating = Killed { $manager } while updating { $package } because { $reason }, the last output was:
+    { $output }
+diagnosis-and-deprecation = { $diagnosis }
+    { $package } is deprecated: { $deprecation }
+failed-to-update-with = Failed to update { $package } with { $manager }
+healthcheck-failed-after-update = { $bin } doesn't work after updating { $package } with { $manager }: { $problem }
+rollback-would-undo = { $error }. Going back to { $old-version } would undo the update
+rolled-back = { $error }, went back to { $old-version }
+success-change-unknown = { $manager } reported success but whether { $package } changed is unknown
+success-but-unchanged = { $manager } reported success but { $package } did not change ({ $old-version })
+downgraded-error = { $package } was DOWNGRADED from { $old-version } to { $new-version } by { $manager }, check that { $package } is the right package, or pass --allow-downgrade to accept it
+local-manager-not-installed = { $dir } uses { $manager }, which isn't installed
+
+# --all
+no-selected-managers = None of the selected package managers is installed
+failed-to-update-manager = Failed to update with { $manager }: { $error }
+gha-output-of = Output of { $manager }
+failed-to-update-managers = Failed to update with { $managers }
+junit-updated-from = Updated from { $old-version } to { $version }
+junit-installed = Installed { $version }
+nothing-to-update = Nothing to update
+notify-updated-one = update-bin: { $count } tool updated
+notify-updated = update-bin: { $count } tools updated
+# after the summary, with the leading comma
+notify-failed-count = , { $count } failed
+notify-updated-package = • { $package } ({ $manager }): { $old-version } → { $version }
+notify-installed-package = • { $package } ({ $manager }): new → { $version }
+notify-failed-manager = • { $manager } failed: { $error }
+held-back = Held back { $package }: { $reason }, the `auto` policy doesn't apply it by itself, update it alone to confirm
+root-only-packages = Only root can update { $manager }'s packages. Run `{ $command } { $command-args }`, or pass --sudo
+command-failed = `{ $command } { $command-args }` failed
+killed-command = Killed `{ $command } { $command-args }` because { $reason }
+post-update-command-failed = `{ $command } { $command-args }` failed after the update, run it again by hand
+yarn-berry-no-global = Yarn Berry removed `yarn global`, its packages can't be updated with yarn anymore
+
+# update-bin self-update and cleanup
+no-managers-installed = None of the package managers update-bin knows is installed
+failed-to-update = Failed to update { $names }
+npm-global-dir-owned-by-root-run-sudo = npm's global packages in { $global-dir } are owned by root. Run `{ $command } { $command-args }`, or pass --sudo
+killed-self-update = Killed { $manager } while updating itself because { $reason }
+cleanup-unknown-manager = Can't clean up after "{ $name }", expected one of { $managers }
+failed-to-clean-up = Failed to clean up with { $names }
+
+# update-bin audit, adopt, sync and ensure
+adopt-already-managed = { $bin } is installed with { $manager } ({ $package }), update-bin already updates it
+adopted-version-not-printed = `{ $version-command }` didn't print a version
+sync-version-not-found = Could not find the version of { $tool } that was installed
+failed-to-sync = Failed to sync { $names }
+ensure-unknown-manager = Can't install with "{ $manager }", expected homebrew, npm or cargo
+ensure-not-installed = { $bin } isn't installed, pass --install-with to install it
+ensure-still-too-old = { $bin } is at { $version }, which still doesn't satisfy { $requirement }
+ensure-still-not-found = { $bin } still can't be found on PATH
+could-not-satisfy = Could not satisfy { $names }
+invalid-constraint = Invalid constraint "{ $constraint }", only a lowest version like 'node>=20' is supported
+invalid-constraint-version = Invalid version "{ $min-version }" in "{ $constraint }"
+go-get-failed = `go { $command-args }` failed
+
+# update-bin plan, apply and restore
+invalid-sbom = Invalid SBOM in { $inventory-path }: { $error }
+checksum-mismatch = { $artifact } doesn't match { $checksum } for { $package } { $version }, download it again from { $url }
+checksum-in-file = its checksum in { $sha256sums-path }
+signature-missing = { $package } { $version } has no signature to verify, and --require-signature was given
+signature-unverified = Couldn't verify the signature of { $package } { $version }, { $reason }
+signature-required = { $problem }, and --require-signature was given
+offline-unsupported = Can't update { $package } with { $manager } offline
+invalid-version-in-plan = Invalid version "{ $version }" in the plan
+artifact-missing = { $artifact } is missing, download it from { $url }
+failed-to-resolve = Failed to resolve { $artifact }: { $error }
+failed-to-restore = Failed to restore { $names }
+restore-manager-not-installed = { $tool } needs { $manager }, which isn't installed
+global-dir-owned-by-root-run-sudo = { $manager }'s global packages in { $global-dir } are owned by root. Run `{ $command } { $command-args }`, or pass --sudo
+invalid-snapshot-argument = Invalid argument "{ $arg }" for { $tool } in the snapshot
+snapshot-no-source = { $tool } has no source in the snapshot
+cant-restore-packages = Can't restore { $manager } packages
+restore-no-version-manager = { $tool } is in .tool-versions, but neither mise nor asdf is installed
+unknown-manager = Unknown package manager "{ $name }", expected one of { $managers }
+
+# What update-bin checked to find the package manager, in update-bin explain
+explain-adopted = looked for `adopted.{ $bin }` in the config
+explain-binaries-manager = looked for `binaries.{ $bin }.manager` in the config
+explain-in-shims-dir = { $bin-path } is in { $manager }'s shims dir
+explain-not-in-shims-dir = { $bin-path } isn't in the shims dir of asdf, mise, volta, pyenv, nodenv or rbenv
+explain-launches = { $bin-path } launches { $target-path }
+explain-not-windows-shim = { $bin-path } isn't a .cmd or .ps1 shim
+explain-script-runs = { $bin-path } runs { $target-path }
+explain-not-wrapper-script = { $bin-path } isn't a script that runs a binary of the same name
+explain-shim-runs = the { $bin } shim runs { $target }
+explain-shim-target-unknown = { $manager } couldn't tell what the { $bin } shim runs
+
+# Finding the binary
+bin-not-found = Binary '{ $bin }' not found
+bin-path-missing = '{ $bin-path }' does not exist
+bin-target-gone = { $bin-path } points to { $target }, which no longer exists
+broken-bin-unknown-owner = { $problem }, and it's unclear what installed it. Remove { $bin-path } and reinstall { $bin }
+broken-bin-owner = { $problem }, it was installed by { $manager } as { $package }
+
+# Channels
+channels-unsupported-source = { $package } was installed from { $source }, which has no channels
+crates-prerelease-not-found = Could not find the latest prerelease of { $package } on crates.io
+homebrew-cant-switch-to-head = Homebrew can't move { $package } between stable and HEAD in place, run `brew uninstall { $package } && brew install --HEAD { $package }`
+homebrew-cant-switch-to-stable = Homebrew can't move { $package } between stable and HEAD in place, run `brew uninstall { $package } && brew install { $package }`
+
+# More than one installation
+owner-shim = { $manager } shim
+owner-unknown = unknown package manager
+
+# What update-bin checked to find the package manager, in update-bin explain, continued
+explain-read-for-whalebrew-package = read { $bin-path } for a whalebrew package's image
+explain-read-for-docker-run = read { $bin-path } for a `docker run` of an image
+explain-compared-with-winget-links = compared { $bin-path } with winget's Links dir and the WindowsApps aliases
+explain-runs-scoop-app = { $bin-path } runs the scoop app { $app }
+explain-isnt-in-scoop-shims = { $bin-path } isn't in scoop's shims or apps dir
+explain-compared-with-chocolatey-bin = compared { $bin-path } with chocolatey's bin dir { $install-dir }, and looked for it in the installed packages
+explain-resolves-into-cellar = { $bin-path } resolves into the Cellar of { $formula }
+explain-doesnt-resolve-into-homebrew = { $bin-path } doesn't resolve into a Homebrew Cellar or opt dir
+explain-compared-with-homebrew-prefix = compared { $bin-path } with the Homebrew prefix { $prefix }
+explain-homebrew-not-installed = Homebrew isn't installed
+explain-compared-with-bun = compared { $bin-path } with ~/.bun and bun's global bin dir { $bin-dir }
+explain-isnt-in-bun = { $bin-path } isn't in ~/.bun, and bun isn't installed
+explain-compared-with-cargo-bin = compared { $bin-path } with ~/.cargo/bin and { $cargo-bin-dir }
+explain-crates2-json-lists = .crates2.json lists { $bin } in { $crate }
+explain-crates2-json-doesnt-list = .crates2.json doesn't list { $bin }, using it as the crate name
+explain-crates2-json-in-root-lists = { $root }/.crates2.json lists { $bin } in { $crate }
+explain-crates2-json-in-root-doesnt-list = { $root }/.crates2.json doesn't list { $bin }
+explain-isnt-in-bin-dir = { $bin-path } isn't in the bin dir of a cargo install root
+explain-was-built-from = { $bin-path } was built from { $package-path }
+explain-compared-with-go-bin = compared { $bin-path } with Go's bin dir { $bin-dir }, and read its build info
+explain-go-not-installed = go isn't installed
+explain-is-deno-shim = { $bin-path } is a deno shim for { $specifier }
+explain-compared-with-deno-bin = compared { $bin-path } with deno's bin dir { $bin-dir }, and read it for a deno shim
+explain-deno-no-bin-dir = deno has no bin dir
+explain-belongs-to-uv-tool = { $bin-path } belongs to the uv tool { $tool }
+explain-doesnt-resolve-into-uv = { $bin-path } doesn't resolve into uv's tool dir { $tool-dir }, and no tool's receipt lists it
+explain-uv-no-tool-dir = uv has no tool dir
+explain-compared-with-pnpm-global = compared { $bin-path } with pnpm's global bin dir { $dir }
+explain-pnpm-no-bin-dir = pnpm has no global bin dir
+explain-npm-package-provides = { $bin-path } is in npm's global bin dir { $npm-bin-dir }, and { $package } in { $global-node-modules-dir } provides it
+explain-npm-no-package-declares = { $bin-path } is in npm's global bin dir { $npm-bin-dir }, no package in { $global-node-modules-dir } declares it
+explain-npm-system-package-owns = { $bin-path } is in npm's global bin dir { $npm-bin-dir }, but no package in { $global-node-modules-dir } declares it and a system package owns it
+explain-compared-with-npm-global = compared { $bin-path } with npm's global bin dir { $npm-bin-dir }
+explain-npm-no-global-dir = npm isn't installed or has no global dir
+explain-compared-with-yarn-global = compared { $bin-path } with yarn's global bin dir { $dir }
+explain-yarn-no-bin-dir = yarn isn't installed or has no global bin dir
+explain-owns = { $manager } owns { $bin-path } in { $package }
+explain-no-installed-package-database = no installed package database (dpkg, pacman, rpm, apk) owns { $bin-path }
+
+# Detection and update commands
+detect-failed = Could not detect package manager for '{ $bin }'
+detect-failed-explain-hint = { $error }, run `update-bin explain { $bin }` to see what was checked
+invalid-strategy = Invalid strategy "{ $strategy }" for { $manager } in the config, expected "update" or "latest"
+adopted-no-longer = { $package } isn't adopted anymore
+homebrew-pinned = { $package } is pinned in Homebrew, run `brew unpin { $package }` to allow upgrading it
+yarn-berry-no-global-upgrade = Yarn { $major } (Berry) removed `yarn global`, so { $package } can't be upgraded with yarn anymore. Upgrade it with Yarn Classic via `npx yarn@1 global upgrade { $package }`, or reinstall it with `npm install -g { $package }`
+unsupported-package-manager = Unsupported package manager: { $manager }
+docker-inspect-failed = Could not inspect image { $package }
+homebrew-package-not-found = Package not found in homebrew
+version-unknown = Could not determine version
+yarn-unlink-in-checkout = yarn unlink (in the checkout)
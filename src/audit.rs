@@ -0,0 +1,109 @@
+use crate::http;
+use crate::inventory::InstalledPackage;
+use crate::l10n;
+use crate::version;
+use std::cmp::Ordering;
+
+const OSV_API_URL: &str = "https://api.osv.dev/v1";
+
+// A published vulnerability of a package, from OSV.dev
+pub struct Advisory {
+    pub id: String,
+    pub summary: String,
+    // GitHub's LOW, MODERATE, HIGH or CRITICAL, when the advisory has one
+    pub severity: Option<String>,
+    // the first version with the fix, newer than the installed one
+    pub fixed_version: Option<String>,
+}
+
+// OSV's names for the registries the packages come from. Homebrew has no ecosystem in OSV,
+// and update-bin doesn't install anything from PyPI
+pub fn get_ecosystem(package_manager: &str) -> Option<&'static str> {
+    match package_manager {
+        "npm" | "pnpm" | "yarn" | "bun" => Some("npm"),
+        "cargo" => Some("crates.io"),
+        _ => None,
+    }
+}
+
+// The ids of the vulnerabilities affecting each package's installed version, all looked up in
+// a single request. Packages of other ecosystems have none
+pub fn find_vulnerabilities(packages: &[&InstalledPackage]) -> Result<Vec<Vec<String>>, String> {
+    let queries: Vec<serde_json::Value> = packages
+        .iter()
+        .filter_map(|package| {
+            let ecosystem = get_ecosystem(&package.package_manager)?;
+            Some(serde_json::json!({
+                "package": { "name": package.name, "ecosystem": ecosystem },
+                "version": package.version,
+            }))
+        })
+        .collect();
+    if queries.is_empty() {
+        return Ok(packages.iter().map(|_| Vec::new()).collect());
+    }
+
+    let body = serde_json::json!({ "queries": queries }).to_string();
+    let response = http::query_json(&format!("{}/querybatch", OSV_API_URL), &body)
+        .ok_or_else(|| l10n::text("osv-unreachable", &[]))?;
+    let empty_results = Vec::new();
+    let mut results = response["results"]
+        .as_array()
+        .unwrap_or(&empty_results)
+        .iter();
+
+    // the results are in the order of the queries, which skipped the other ecosystems
+    Ok(packages
+        .iter()
+        .map(|package| {
+            if get_ecosystem(&package.package_manager).is_none() {
+                return Vec::new();
+            }
+            let empty_vulns = Vec::new();
+            results
+                .next()
+                .and_then(|result| result["vulns"].as_array())
+                .unwrap_or(&empty_vulns)
+                .iter()
+                .filter_map(|vuln| vuln["id"].as_str())
+                .map(|id| id.to_string())
+                .collect()
+        })
+        .collect())
+}
+
+pub fn get_advisory(id: &str, package: &InstalledPackage) -> Advisory {
+    let vuln = http::get_json(&format!("{}/vulns/{}", OSV_API_URL, id)).unwrap_or_default();
+    let summary = vuln["summary"]
+        .as_str()
+        .or_else(|| {
+            vuln["details"]
+                .as_str()
+                .and_then(|details| details.lines().next())
+        })
+        .unwrap_or("no summary")
+        .to_string();
+    let severity = vuln["database_specific"]["severity"]
+        .as_str()
+        .map(|severity| severity.to_string());
+
+    let empty_array = Vec::new();
+    let fixed_version = vuln["affected"]
+        .as_array()
+        .unwrap_or(&empty_array)
+        .iter()
+        .filter(|affected| affected["package"]["name"].as_str() == Some(&package.name))
+        .flat_map(|affected| affected["ranges"].as_array().unwrap_or(&empty_array))
+        .flat_map(|range| range["events"].as_array().unwrap_or(&empty_array))
+        .filter_map(|event| event["fixed"].as_str())
+        .filter(|fixed| version::compare_versions(&package.version, fixed) == Some(Ordering::Less))
+        .min_by(|a, b| version::compare_versions(a, b).unwrap_or(Ordering::Equal))
+        .map(|fixed| fixed.to_string());
+
+    Advisory {
+        id: id.to_string(),
+        summary,
+        severity,
+        fixed_version,
+    }
+}
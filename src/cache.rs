@@ -0,0 +1,54 @@
+use crate::history;
+use crate::paths;
+use std::path::PathBuf;
+
+// Answers that take a network round trip and stay true for a while, like the latest version
+// of a package, kept as `{ "<key>": { "value": "...", "checked": <unix time> } }` in the cache dir
+pub struct Cache {
+    path: Option<PathBuf>,
+    entries: serde_json::Map<String, serde_json::Value>,
+}
+
+// A missing or broken cache file is an empty cache
+pub fn load(name: &str) -> Cache {
+    let path = paths::get_cache_dir().map(|cache_dir| cache_dir.join(format!("{}.json", name)));
+    let entries = path
+        .as_ref()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|value| value.as_object().cloned())
+        .unwrap_or_default();
+
+    Cache { path, entries }
+}
+
+impl Cache {
+    pub fn get(&self, key: &str, ttl_secs: u64) -> Option<&str> {
+        let entry = self.entries.get(key)?;
+        let checked = entry["checked"].as_u64()?;
+        if history::now().saturating_sub(checked) > ttl_secs {
+            return None;
+        }
+        entry["value"].as_str()
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) {
+        self.entries.insert(
+            key.to_string(),
+            serde_json::json!({ "value": value, "checked": history::now() }),
+        );
+    }
+
+    // Failing to write the cache only means asking again next time
+    pub fn save(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+        if let Some(cache_dir) = path.parent() {
+            let _ = std::fs::create_dir_all(cache_dir);
+        }
+        let content = serde_json::Value::Object(self.entries.clone()).to_string();
+        let _ = std::fs::write(path, content);
+    }
+}
@@ -0,0 +1,464 @@
+use std::path::{Path, PathBuf};
+
+const CRATES_IO_INDEX: &str = "https://github.com/rust-lang/crates.io-index";
+const CRATES_IO_SPARSE_INDEX: &str = "sparse+https://index.crates.io/";
+
+// A crate installed by `cargo install`, as recorded in $CARGO_HOME/.crates2.json
+pub struct CargoInstall {
+    pub crate_name: String,
+    pub version: String,
+    pub source: String,
+    pub bins: Vec<String>,
+    pub features: Vec<String>,
+    pub all_features: bool,
+    pub no_default_features: bool,
+    pub profile: Option<String>,
+}
+
+pub fn get_cargo_home() -> PathBuf {
+    if let Ok(cargo_home) = std::env::var("CARGO_HOME") {
+        if !cargo_home.is_empty() {
+            return PathBuf::from(cargo_home);
+        }
+    }
+
+    crate::paths::get_home_dir()
+        .unwrap_or_else(|| PathBuf::from("~"))
+        .join(".cargo")
+}
+
+pub fn read_cargo_installs() -> Vec<CargoInstall> {
+    read_cargo_installs_in(&get_cargo_home())
+}
+
+// The installs recorded in a root's .crates2.json, CARGO_HOME's or one given to
+// `cargo install --root`
+pub fn read_cargo_installs_in(root: &Path) -> Vec<CargoInstall> {
+    let crates2_path = root.join(".crates2.json");
+    let crates2_content = std::fs::read_to_string(crates2_path).unwrap_or_default();
+    let crates2: serde_json::Value = serde_json::from_str(&crates2_content).unwrap_or_default();
+
+    let empty_map = serde_json::Map::new();
+    let installs = crates2["installs"].as_object().unwrap_or(&empty_map);
+
+    let mut cargo_installs = Vec::new();
+    for (package_id, info) in installs {
+        if let Some((crate_name, version, source)) = parse_package_id(package_id) {
+            let bins = info["bins"]
+                .as_array()
+                .map(|bins| {
+                    bins.iter()
+                        .filter_map(|bin| bin.as_str())
+                        .map(|bin| bin.to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let features = info["features"]
+                .as_array()
+                .map(|features| {
+                    features
+                        .iter()
+                        .filter_map(|feature| feature.as_str())
+                        .map(|feature| feature.to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            cargo_installs.push(CargoInstall {
+                crate_name,
+                version,
+                source,
+                bins,
+                features,
+                all_features: info["all_features"].as_bool().unwrap_or(false),
+                no_default_features: info["no_default_features"].as_bool().unwrap_or(false),
+                profile: info["profile"].as_str().map(|profile| profile.to_string()),
+            });
+        }
+    }
+
+    cargo_installs
+}
+
+// find the installed crate that provides the bin, binaries on Windows are recorded with
+// their .exe extension
+pub fn find_cargo_install(bin_name: &str) -> Option<CargoInstall> {
+    find_cargo_install_in(&get_cargo_home(), bin_name)
+}
+
+pub fn find_cargo_install_in(root: &Path, bin_name: &str) -> Option<CargoInstall> {
+    read_cargo_installs_in(root).into_iter().find(|install| {
+        install
+            .bins
+            .iter()
+            .any(|bin| bin == bin_name || bin.strip_suffix(".exe") == Some(bin_name))
+    })
+}
+
+// package ids look like `ripgrep 14.1.0 (registry+https://github.com/rust-lang/crates.io-index)`
+fn parse_package_id(package_id: &str) -> Option<(String, String, String)> {
+    let mut parts = package_id.splitn(3, ' ');
+    let crate_name = parts.next()?.to_string();
+    let version = parts.next()?.to_string();
+    let source = parts
+        .next()?
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .to_string();
+
+    Some((crate_name, version, source))
+}
+
+pub fn find_cargo_install_by_crate(crate_name: &str) -> Option<CargoInstall> {
+    find_cargo_install_by_crate_in(&get_cargo_home(), crate_name)
+}
+
+pub fn find_cargo_install_by_crate_in(root: &Path, crate_name: &str) -> Option<CargoInstall> {
+    read_cargo_installs_in(root)
+        .into_iter()
+        .find(|install| install.crate_name == crate_name)
+}
+
+// The root of a `cargo install --root ./.tools` the binary is in, `<root>/bin/<bin>` next to
+// the root's own .crates2.json. CARGO_HOME isn't one, it's the default
+pub fn find_install_root(bin_path: &Path) -> Option<PathBuf> {
+    let bin_dir = bin_path.parent()?;
+    if bin_dir.file_name()? != "bin" {
+        return None;
+    }
+    let root = std::path::absolute(bin_dir.parent()?).ok()?;
+    if !root.join(".crates2.json").is_file() {
+        return None;
+    }
+    let is_cargo_home = match (
+        std::fs::canonicalize(&root),
+        std::fs::canonicalize(get_cargo_home()),
+    ) {
+        (Ok(root), Ok(cargo_home)) => root == cargo_home,
+        _ => false,
+    };
+    (!is_cargo_home).then_some(root)
+}
+
+// The crate's repository (or homepage) from the Cargo.toml cargo downloaded it with, or the
+// git URL it was installed from
+pub fn get_repository_url(install: &CargoInstall) -> Option<String> {
+    if let Some(git_url) = install.source.strip_prefix("git+") {
+        return Some(git_url.split(['?', '#']).next()?.to_string());
+    }
+
+    let crate_dir = format!("{}-{}", install.crate_name, install.version);
+    let registry_dirs = std::fs::read_dir(get_cargo_home().join("registry").join("src")).ok()?;
+    let manifest = registry_dirs
+        .filter_map(|registry_dir| registry_dir.ok())
+        .find_map(|registry_dir| {
+            std::fs::read_to_string(registry_dir.path().join(&crate_dir).join("Cargo.toml")).ok()
+        })?;
+
+    // published manifests are normalized by cargo, so every key is on its own line
+    let get_key = |key: &str| {
+        manifest.lines().find_map(|line| {
+            let (name, value) = line.split_once('=')?;
+            if name.trim() != key {
+                return None;
+            }
+            Some(value.trim().trim_matches('"').to_string())
+        })
+    };
+    get_key("repository").or_else(|| get_key("homepage"))
+}
+
+pub fn is_from_crates_io(install: &CargoInstall) -> bool {
+    install.source == format!("registry+{}", CRATES_IO_INDEX)
+        || install.source == CRATES_IO_SPARSE_INDEX
+}
+
+// `ripgrep = "14.1.1"    # ripgrep is a line-oriented search tool...`
+pub fn get_latest_version(crate_name: &str) -> Option<String> {
+    let output = crate::process::query_command("cargo")
+        .args(["search", "--limit", "1", crate_name])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next()?;
+    let (name, rest) = line.split_once(" = ")?;
+    if name != crate_name {
+        return None;
+    }
+    Some(rest.split('"').nth(1)?.to_string())
+}
+
+// reproduce the `cargo install` source flags from the recorded source, e.g.
+// `git+https://github.com/user/repo?branch=dev#<sha>` or `path+file:///home/user/repo`
+pub fn get_source_args(source: &str) -> Vec<String> {
+    if let Some(git) = source.strip_prefix("git+") {
+        let git = git.split('#').next().unwrap_or(git);
+        let (url, query) = git.split_once('?').unwrap_or((git, ""));
+        let mut args = vec![format!("--git={}", url)];
+        for pair in query.split('&') {
+            if let Some((key, value)) = pair.split_once('=') {
+                if matches!(key, "branch" | "tag" | "rev") {
+                    args.push(format!("--{}={}", key, value));
+                }
+            }
+        }
+        return args;
+    }
+
+    if let Some(path) = source.strip_prefix("path+file://") {
+        return vec![format!("--path={}", file_url_to_path(path))];
+    }
+
+    if let Some(index) = source.strip_prefix("registry+") {
+        if index != CRATES_IO_INDEX {
+            return vec![format!("--index={}", index)];
+        }
+    }
+
+    if source.starts_with("sparse+") && source != CRATES_IO_SPARSE_INDEX {
+        return vec![format!("--index={}", source)];
+    }
+
+    Vec::new()
+}
+
+// Like get_source_args, at exactly the version that was installed: a git install at the commit
+// it was built from, a registry one at its version. A path install is whatever the path has
+pub fn get_pinned_source_args(source: &str, version: &str) -> Vec<String> {
+    if let Some(git) = source.strip_prefix("git+") {
+        if let Some((url, commit)) = git.split_once('#') {
+            let url = url.split('?').next().unwrap_or(url);
+            return vec![format!("--git={}", url), format!("--rev={}", commit)];
+        }
+    }
+    let mut args = get_source_args(source);
+    if source.starts_with("registry+") || source.starts_with("sparse+") {
+        args.push(format!("--version={}", version));
+    }
+    args
+}
+
+// re-apply the features and profile the crate was originally installed with. Values are
+// attached with `=` so one starting with a dash can't be taken for an option
+pub fn get_build_args(install: &CargoInstall) -> Vec<String> {
+    let mut args = Vec::new();
+
+    if install.all_features {
+        args.push("--all-features".to_string());
+    }
+    if install.no_default_features {
+        args.push("--no-default-features".to_string());
+    }
+    if !install.features.is_empty() {
+        args.push(format!("--features={}", install.features.join(",")));
+    }
+    if let Some(profile) = &install.profile {
+        if profile != "release" {
+            args.push(format!("--profile={}", profile));
+        }
+    }
+
+    args
+}
+
+// a crate installed with `--rev` can only ever be reinstalled at that revision
+pub fn get_pinned_rev(install: &CargoInstall) -> Option<String> {
+    let git = install.source.strip_prefix("git+")?;
+    let git = git.split('#').next().unwrap_or(git);
+    let (_, query) = git.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("rev="))
+        .map(|rev| rev.to_string())
+}
+
+fn file_url_to_path(path: &str) -> String {
+    let mut decoded = Vec::with_capacity(path.len());
+    let bytes = path.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        // on bytes, a multi-byte character after the `%` isn't an escape
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        if let Some(byte) = escaped {
+            decoded.push(byte);
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    let decoded = String::from_utf8_lossy(&decoded).to_string();
+
+    // file:///C:/Users/... on Windows
+    if decoded.len() > 2 && decoded.starts_with('/') && decoded.as_bytes()[2] == b':' {
+        return decoded[1..].to_string();
+    }
+
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_crates2_json() {
+        let root =
+            std::env::temp_dir().join(format!("update-bin-test-cargo-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join(".crates2.json"),
+            r#"{"installs":{
+                "ripgrep 14.1.0 (registry+https://github.com/rust-lang/crates.io-index)":
+                    {"bins":["rg"],"features":["pcre2"],"all_features":false,"no_default_features":true,"profile":"release"},
+                "tool 0.1.0 (git+https://github.com/user/tool?branch=dev#0123abc)":
+                    {"bins":["tool.exe"],"features":[],"all_features":true,"no_default_features":false,"profile":"dist"},
+                "broken 1.0.0": {"bins":["broken"]}
+            }}"#,
+        )
+        .unwrap();
+        let installs = read_cargo_installs_in(&root);
+        let ripgrep = find_cargo_install_in(&root, "rg").unwrap();
+        let tool = find_cargo_install_in(&root, "tool").unwrap();
+        let missing = find_cargo_install_in(&root, "broken");
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(installs.len(), 2);
+        assert!(missing.is_none());
+        assert_eq!(ripgrep.crate_name, "ripgrep");
+        assert_eq!(ripgrep.version, "14.1.0");
+        assert!(is_from_crates_io(&ripgrep));
+        assert_eq!(ripgrep.features, ["pcre2"]);
+        assert!(ripgrep.no_default_features);
+        assert_eq!(
+            get_build_args(&ripgrep),
+            ["--no-default-features", "--features=pcre2"]
+        );
+        assert_eq!(
+            tool.source,
+            "git+https://github.com/user/tool?branch=dev#0123abc"
+        );
+        assert!(!is_from_crates_io(&tool));
+        assert_eq!(get_build_args(&tool), ["--all-features", "--profile=dist"]);
+    }
+
+    #[test]
+    fn install_roots() {
+        let root =
+            std::env::temp_dir().join(format!("update-bin-test-cargo-root-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("bin")).unwrap();
+        let before_crates2 = find_install_root(&root.join("bin").join("tool"));
+        std::fs::write(root.join(".crates2.json"), "{}").unwrap();
+        let found = find_install_root(&root.join("bin").join("tool"));
+        let not_bin = find_install_root(&root.join("tool"));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(before_crates2, None);
+        assert_eq!(found, Some(root));
+        assert_eq!(not_bin, None);
+    }
+
+    #[test]
+    fn source_args() {
+        assert!(
+            get_source_args("registry+https://github.com/rust-lang/crates.io-index").is_empty()
+        );
+        assert!(get_source_args("sparse+https://index.crates.io/").is_empty());
+        assert_eq!(
+            get_source_args("registry+https://example.com/index"),
+            ["--index=https://example.com/index"]
+        );
+        assert_eq!(
+            get_source_args("sparse+https://example.com/index/"),
+            ["--index=sparse+https://example.com/index/"]
+        );
+        assert_eq!(
+            get_source_args("git+https://github.com/user/tool?branch=dev&foo=bar#0123abc"),
+            ["--git=https://github.com/user/tool", "--branch=dev"]
+        );
+        assert_eq!(
+            get_source_args("git+https://github.com/user/tool#0123abc"),
+            ["--git=https://github.com/user/tool"]
+        );
+        assert_eq!(
+            get_source_args("path+file:///home/user/my%20tool"),
+            ["--path=/home/user/my tool"]
+        );
+    }
+
+    #[test]
+    fn pinned_source_args() {
+        assert_eq!(
+            get_pinned_source_args(
+                "registry+https://github.com/rust-lang/crates.io-index",
+                "14.1.0"
+            ),
+            ["--version=14.1.0"]
+        );
+        assert_eq!(
+            get_pinned_source_args("sparse+https://example.com/index/", "1.0.0"),
+            [
+                "--index=sparse+https://example.com/index/",
+                "--version=1.0.0"
+            ]
+        );
+        assert_eq!(
+            get_pinned_source_args(
+                "git+https://github.com/user/tool?branch=dev#0123abc",
+                "0.1.0"
+            ),
+            ["--git=https://github.com/user/tool", "--rev=0123abc"]
+        );
+        assert_eq!(
+            get_pinned_source_args("path+file:///home/user/tool", "0.1.0"),
+            ["--path=/home/user/tool"]
+        );
+    }
+
+    #[test]
+    fn pinned_rev() {
+        let install = |source: &str| CargoInstall {
+            crate_name: "tool".to_string(),
+            version: "0.1.0".to_string(),
+            source: source.to_string(),
+            bins: Vec::new(),
+            features: Vec::new(),
+            all_features: false,
+            no_default_features: false,
+            profile: None,
+        };
+        assert_eq!(
+            get_pinned_rev(&install(
+                "git+https://github.com/user/tool?rev=0123abc#0123abcdef"
+            )),
+            Some("0123abc".to_string())
+        );
+        assert_eq!(
+            get_pinned_rev(&install(
+                "git+https://github.com/user/tool?branch=dev#0123abc"
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn file_urls() {
+        assert_eq!(
+            file_url_to_path("/home/user/my%20tool"),
+            "/home/user/my tool"
+        );
+        assert_eq!(file_url_to_path("/C:/Users/me/tool"), "C:/Users/me/tool");
+        assert_eq!(file_url_to_path("/home/%C3%A9t%C3%A9"), "/home/été");
+        // not escapes, and no panic on a multi-byte character after the `%`
+        assert_eq!(file_url_to_path("/home/100%"), "/home/100%");
+        assert_eq!(file_url_to_path("/home/%zz"), "/home/%zz");
+        assert_eq!(file_url_to_path("/home/%é"), "/home/%é");
+        assert_eq!(file_url_to_path("/home/%1é"), "/home/%1é");
+    }
+}
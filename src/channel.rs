@@ -0,0 +1,155 @@
+use crate::homebrew;
+use crate::l10n;
+use crate::registry;
+
+// Where everyone is unless they chose otherwise, npm's `latest` dist-tag
+pub const STABLE: &str = "stable";
+
+const NODE_MANAGERS: [&str; 4] = ["npm", "pnpm", "yarn", "bun"];
+
+// The channel the installed version came from when it isn't stable: the dist-tag pointing at
+// it for node packages (`next`, `beta`), `prerelease` for a crate like 2.0.0-rc.1, and `head`
+// for a formula built with --HEAD
+pub fn detect(
+    package_manager: &str,
+    package_name: &str,
+    installed_version: &str,
+) -> Option<String> {
+    match package_manager {
+        "homebrew" => homebrew::get_brew_info(package_name)?
+            .head
+            .then(|| "head".to_string()),
+        "cargo" => installed_version
+            .contains('-')
+            .then(|| "prerelease".to_string()),
+        manager if NODE_MANAGERS.contains(&manager) => {
+            find_dist_tag(&registry::get_dist_tags(package_name)?, installed_version)
+        }
+        _ => None,
+    }
+}
+
+// The dist-tag pointing at the installed version, unless `latest` does too. Right after a
+// release `beta` or `next` often points at the same version as `latest`, and the tags come
+// back sorted by name, so `beta` would win over `latest` if it were just the first match
+fn find_dist_tag(dist_tags: &[(String, String)], installed_version: &str) -> Option<String> {
+    let mut matching = dist_tags
+        .iter()
+        .filter(|(_, version)| version == installed_version)
+        .map(|(tag, _)| tag);
+    if matching.clone().any(|tag| tag == "latest") {
+        return None;
+    }
+    matching.next().cloned()
+}
+
+// Whether the manager can install from the channel, listing the ones it has when it can't
+pub fn check(package_manager: &str, package_name: &str, channel: &str) -> Result<(), String> {
+    let channels: Vec<String> = match package_manager {
+        "homebrew" => vec!["head".to_string()],
+        "cargo" => vec!["prerelease".to_string()],
+        manager if NODE_MANAGERS.contains(&manager) => {
+            match registry::get_dist_tags(package_name) {
+                Some(dist_tags) => dist_tags
+                    .into_iter()
+                    .map(|(tag, _)| tag)
+                    .filter(|tag| tag != "latest")
+                    .collect(),
+                // the registry can't be asked, let the manager tell
+                None => return Ok(()),
+            }
+        }
+        _ => {
+            return Err(l10n::text(
+                "channels-unknown",
+                &[("manager", package_manager)],
+            ))
+        }
+    };
+    if channel == STABLE || channels.iter().any(|name| name == channel) {
+        return Ok(());
+    }
+    Err(l10n::text(
+        "channel-unknown",
+        &[
+            ("package", package_name),
+            ("channel", channel),
+            ("stable", STABLE),
+            ("channels", &channels.join(", ")),
+        ],
+    ))
+}
+
+// The version the channel is at, for comparing with the installed one. `None` for HEAD, which
+// has no version
+pub fn get_latest_version(
+    package_manager: &str,
+    package_name: &str,
+    channel: &str,
+) -> Option<String> {
+    match package_manager {
+        "cargo" if channel != STABLE => registry::get_latest_prerelease(package_name),
+        manager if NODE_MANAGERS.contains(&manager) => {
+            let tag = if channel == STABLE { "latest" } else { channel };
+            registry::get_dist_tags(package_name)?
+                .into_iter()
+                .find(|(name, _)| name == tag)
+                .map(|(_, version)| version)
+        }
+        _ => None,
+    }
+}
+
+// `cargo install ... <crate>` at the channel's version, attached with `=` like the other
+// values update-bin passes to cargo
+pub fn add_cargo_version(args: &mut Vec<String>, version: &str) {
+    let crate_index = args.len().saturating_sub(1);
+    args.insert(crate_index, format!("--version={}", version));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dist_tags(tags: &[(&str, &str)]) -> Vec<(String, String)> {
+        tags.iter()
+            .map(|(tag, version)| (tag.to_string(), version.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn find_dist_tag_prefers_latest() {
+        let tags = dist_tags(&[("beta", "2.0.0"), ("canary", "2.0.0"), ("latest", "2.0.0")]);
+        assert_eq!(find_dist_tag(&tags, "2.0.0"), None);
+    }
+
+    #[test]
+    fn find_dist_tag_finds_other_channels() {
+        let tags = dist_tags(&[("beta", "2.1.0-beta.1"), ("latest", "2.0.0")]);
+        assert_eq!(
+            find_dist_tag(&tags, "2.1.0-beta.1"),
+            Some("beta".to_string())
+        );
+        assert_eq!(find_dist_tag(&tags, "2.0.0"), None);
+        assert_eq!(find_dist_tag(&tags, "1.0.0"), None);
+    }
+
+    #[test]
+    fn add_cargo_version_before_the_crate() {
+        let mut args = vec![
+            "install".to_string(),
+            "--features=pcre2".to_string(),
+            "ripgrep".to_string(),
+        ];
+        add_cargo_version(&mut args, "15.0.0-beta.1");
+        assert_eq!(
+            args,
+            [
+                "install",
+                "--features=pcre2",
+                "--version=15.0.0-beta.1",
+                "ripgrep"
+            ]
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use crate::paths;
+use crate::process;
+use std::path::{Path, PathBuf};
+
+// ChocolateyInstall, or where the installer puts it
+pub fn get_install_dir() -> PathBuf {
+    std::env::var_os("ChocolateyInstall")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData\chocolatey"))
+}
+
+// The package behind a shim in chocolatey's bin dir. The shims are generated executables that
+// say nothing readable about their target, so look for the package whose files in lib have
+// one by the shim's name, `lib\ripgrep\tools\ripgrep-14.1.0-x86_64-pc-windows-msvc\rg.exe`
+pub fn get_package_from_path(bin_path: &str) -> Option<String> {
+    let path = Path::new(bin_path);
+    let install_dir = get_install_dir();
+    if !paths::starts_with(path, &install_dir.join("bin")) {
+        return None;
+    }
+    let file_name = path.file_name()?;
+    let bin_name = path.file_stem()?.to_string_lossy().to_string();
+
+    let packages = get_installed_packages();
+    let lib_dir = install_dir.join("lib");
+    packages
+        .iter()
+        .map(|(package_name, _)| package_name)
+        .find(|package_name| has_file(&lib_dir.join(package_name), file_name, 4))
+        // packages that only install a shim for an exe they download elsewhere
+        .or_else(|| {
+            packages
+                .iter()
+                .map(|(package_name, _)| package_name)
+                .find(|package_name| package_name.eq_ignore_ascii_case(&bin_name))
+        })
+        .cloned()
+}
+
+fn has_file(dir: &Path, file_name: &std::ffi::OsStr, depth: usize) -> bool {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return false,
+    };
+    entries.flatten().any(|entry| {
+        let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+        if is_dir {
+            depth > 0 && has_file(&entry.path(), file_name, depth - 1)
+        } else {
+            entry.file_name().eq_ignore_ascii_case(file_name)
+        }
+    })
+}
+
+// (package, version) of what's installed, from `choco list`'s `<package>|<version>` lines.
+// Chocolatey 1.x lists the community feed without --local-only, 2.x removed the flag
+pub fn get_installed_packages() -> Vec<(String, String)> {
+    let args: [&[&str]; 2] = [
+        &["list", "--local-only", "--limit-output"],
+        &["list", "--limit-output"],
+    ];
+    let stdout = args
+        .iter()
+        .find_map(|args| {
+            process::query_command("choco")
+                .args(*args)
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+        })
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        .unwrap_or_default();
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (package_name, version) = line.trim().split_once('|')?;
+            Some((package_name.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
+pub fn get_installed_version(package_name: &str) -> Option<String> {
+    get_installed_packages()
+        .into_iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(package_name))
+        .map(|(_, version)| version)
+}
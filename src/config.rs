@@ -0,0 +1,340 @@
+use crate::l10n;
+use crate::paths;
+use crate::policy;
+use std::path::PathBuf;
+
+// Settings from the config file, e.g.
+//
+// {
+//   "strategy": "latest",
+//   "managers": { "npm": { "strategy": "update" } },
+//   "notifications": { "webhook": "https://hooks.slack.com/services/..." },
+//   "cache_ttl_hours": 6,
+//   "proxy": "http://proxy.example.com:3128",
+//   "language": "de",
+//   "cleanup_after_all": true,
+//   "auto": "patch",
+//   "binaries": { "node": { "auto": "none" } },
+//   "theme": { "colors": { "success": "bright-green" }, "table": "ascii" }
+// }
+//
+// `adopted` holds the binaries registered with `update-bin adopt`, e.g.
+// `{ "mytool": { "update_cmd": "make -C ~/src/mytool install", "version_cmd": "mytool -V" } }`
+//
+// `managers.<manager>.packages` maps a bin that several packages provide to the one to update,
+// e.g. `{ "tsc": "typescript" }`, and is written when the user picks one
+//
+// `healthchecks` maps a bin to the command that tells whether it still works after an update,
+// e.g. `{ "node": "node -e 1" }`, instead of running its `--version`
+//
+// `managers.<manager>.registry` is the registry or mirror npm, pnpm, yarn, bun, cargo, homebrew
+// (its bottle domain), pip or uv downloads from instead of the default one, e.g.
+// `{ "npm": { "registry": "https://npm.example.com" } }`
+//
+// `binaries.<bin>.manager` is the manager that owns the binary at `binaries.<bin>.path` (or
+// wherever it is without one), when detection picks the wrong one, e.g.
+// `{ "rg": { "manager": "cargo", "path": "/usr/local/bin/rg" } }`
+//
+// `binaries.<bin>.channel` is the channel chosen with --channel that updates stay on, like
+// `next` for an npm dist-tag, `prerelease` for cargo or `head` for Homebrew
+//
+// `auto` is the biggest update applied without asking, "none", "patch", "minor" or "major",
+// for a binary (or package) in `binaries`, a manager or everything
+//
+// where a manager's own settings take precedence over the top-level ones
+pub struct Config {
+    value: serde_json::Value,
+}
+
+// UPDATE_BIN_CONFIG, or `$XDG_CONFIG_HOME/update-bin/config.json` (~/.config by default,
+// %APPDATA% on Windows)
+pub fn get_config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("UPDATE_BIN_CONFIG").filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+
+    let config_dir = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)?
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|config_home| !config_home.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| paths::get_home_dir().map(|home| home.join(".config")))?
+    };
+    Some(config_dir.join("update-bin").join("config.json"))
+}
+
+// A missing config file is the same as an empty one, a broken one is an error so settings
+// aren't silently ignored
+pub fn load_config() -> Result<Config, String> {
+    let path = match get_config_path() {
+        Some(path) if path.exists() => path,
+        _ => {
+            return Ok(Config {
+                value: serde_json::Value::Null,
+            })
+        }
+    };
+
+    let content = std::fs::read_to_string(&path).map_err(|e| {
+        l10n::text(
+            "failed-to-read",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })?;
+    let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
+        l10n::text(
+            "invalid-config",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })?;
+    if !value.is_object() {
+        return Err(l10n::text(
+            "invalid-config-not-object",
+            &[("path", &path.display().to_string())],
+        ));
+    }
+
+    Ok(Config { value })
+}
+
+impl Config {
+    pub fn get_manager_setting(&self, package_manager: &str, key: &str) -> Option<&str> {
+        self.value["managers"][package_manager][key]
+            .as_str()
+            .or_else(|| self.value[key].as_str())
+    }
+
+    // Not a top-level setting, each manager's registry is its own
+    pub fn get_registry(&self, package_manager: &str) -> Option<&str> {
+        self.value["managers"][package_manager]["registry"]
+            .as_str()
+            .filter(|registry| !registry.is_empty())
+    }
+
+    pub fn get_number(&self, key: &str) -> Result<Option<u64>, String> {
+        match &self.value[key] {
+            serde_json::Value::Null => Ok(None),
+            value => value.as_u64().map(Some).ok_or_else(|| {
+                l10n::text(
+                    "invalid-number-setting",
+                    &[("key", key), ("value", &value.to_string())],
+                )
+            }),
+        }
+    }
+
+    pub fn get_bool(&self, key: &str) -> Result<Option<bool>, String> {
+        match &self.value[key] {
+            serde_json::Value::Null => Ok(None),
+            value => value.as_bool().map(Some).ok_or_else(|| {
+                l10n::text(
+                    "invalid-bool-setting",
+                    &[("key", key), ("value", &value.to_string())],
+                )
+            }),
+        }
+    }
+
+    // The binary's own policy, then its manager's, then the top-level one
+    pub fn get_auto_policy(
+        &self,
+        bin_name: &str,
+        package_manager: &str,
+    ) -> Result<Option<&str>, String> {
+        let policy = self.value["binaries"][bin_name]["auto"]
+            .as_str()
+            .or_else(|| self.get_manager_setting(package_manager, "auto"));
+        match policy {
+            Some(policy) if !policy::POLICIES.contains(&policy) => Err(l10n::text(
+                "invalid-auto-policy",
+                &[
+                    ("policy", policy),
+                    ("bin", bin_name),
+                    ("policies", &policy::POLICIES.join(", ")),
+                ],
+            )),
+            policy => Ok(policy),
+        }
+    }
+
+    // (manager, path) from `binaries.<bin>`
+    pub fn get_binary_owner(&self, bin_name: &str) -> Option<(&str, Option<&str>)> {
+        let binary = &self.value["binaries"][bin_name];
+        let manager = binary["manager"]
+            .as_str()
+            .filter(|manager| !manager.is_empty())?;
+        Some((manager, binary["path"].as_str()))
+    }
+
+    pub fn get_binary_channel(&self, bin_name: &str) -> Option<&str> {
+        self.value["binaries"][bin_name]["channel"]
+            .as_str()
+            .filter(|channel| !channel.is_empty())
+    }
+
+    // (key ID, base64 DER public key) from `managers.<manager>.signing_keys`, the keys a
+    // registry of one's own signs with
+    pub fn get_signing_keys(&self, package_manager: &str) -> Vec<(String, String)> {
+        let empty_map = serde_json::Map::new();
+        self.value["managers"][package_manager]["signing_keys"]
+            .as_object()
+            .unwrap_or(&empty_map)
+            .iter()
+            .filter_map(|(keyid, key)| Some((keyid.to_string(), key.as_str()?.to_string())))
+            .collect()
+    }
+
+    pub fn get_package_mapping(&self, package_manager: &str, bin_name: &str) -> Option<&str> {
+        self.value["managers"][package_manager]["packages"][bin_name].as_str()
+    }
+
+    // (bin, update command, version command) of the binaries no package manager owns
+    pub fn get_adopted(&self) -> Vec<(String, String, Option<String>)> {
+        let empty_map = serde_json::Map::new();
+        self.value["adopted"]
+            .as_object()
+            .unwrap_or(&empty_map)
+            .iter()
+            .filter_map(|(bin_name, adopted)| {
+                Some((
+                    bin_name.to_string(),
+                    adopted["update_cmd"].as_str()?.to_string(),
+                    adopted["version_cmd"]
+                        .as_str()
+                        .map(|command| command.to_string()),
+                ))
+            })
+            .collect()
+    }
+
+    // `healthchecks.<bin>`, an empty command turns the check off
+    pub fn get_healthcheck(&self, bin_name: &str) -> Option<&str> {
+        self.value["healthchecks"][bin_name].as_str()
+    }
+
+    // for update-bin's own requests, the managers read their own config and the environment
+    pub fn get_proxy(&self) -> Option<&str> {
+        self.value["proxy"]
+            .as_str()
+            .filter(|proxy| !proxy.is_empty())
+    }
+
+    // `de` or `pt-BR`, instead of the one LANG asks for
+    pub fn get_language(&self) -> Option<&str> {
+        self.value["language"]
+            .as_str()
+            .filter(|language| !language.is_empty())
+    }
+
+    // `theme`, read by style
+    pub fn get_theme(&self) -> Option<&serde_json::Value> {
+        Some(&self.value["theme"]).filter(|theme| !theme.is_null())
+    }
+
+    pub fn get_webhook_url(&self) -> Option<&str> {
+        self.value["notifications"]["webhook"]
+            .as_str()
+            .filter(|url| !url.is_empty())
+    }
+}
+
+// Sets `managers.<manager>.packages.<bin>`, keeping the rest of the file
+pub fn save_package_mapping(
+    package_manager: &str,
+    bin_name: &str,
+    package_name: &str,
+) -> Result<PathBuf, String> {
+    save_setting(
+        &["managers", package_manager, "packages", bin_name],
+        serde_json::json!(package_name),
+    )
+}
+
+// Sets `binaries.<bin>.manager` and `.path`, keeping its other settings
+pub fn save_binary_owner(
+    bin_name: &str,
+    package_manager: &str,
+    bin_path: &str,
+) -> Result<PathBuf, String> {
+    save_setting(
+        &["binaries", bin_name, "manager"],
+        serde_json::json!(package_manager),
+    )?;
+    save_setting(&["binaries", bin_name, "path"], serde_json::json!(bin_path))
+}
+
+// Sets `binaries.<bin>.channel`, keeping its other settings
+pub fn save_binary_channel(bin_name: &str, channel: &str) -> Result<PathBuf, String> {
+    save_setting(
+        &["binaries", bin_name, "channel"],
+        serde_json::json!(channel),
+    )
+}
+
+// Sets `adopted.<bin>`, replacing what was adopted under that name before
+pub fn save_adopted(
+    bin_name: &str,
+    update_command: &str,
+    version_command: Option<&str>,
+) -> Result<PathBuf, String> {
+    let mut adopted = serde_json::json!({ "update_cmd": update_command });
+    if let Some(version_command) = version_command {
+        adopted["version_cmd"] = serde_json::json!(version_command);
+    }
+    save_setting(&["adopted", bin_name], adopted)
+}
+
+// Writes one setting into the config file, creating the objects on the way to it
+fn save_setting(keys: &[&str], setting: serde_json::Value) -> Result<PathBuf, String> {
+    let path = get_config_path().ok_or("Could not find the config dir")?;
+    let mut value = load_config()?.value;
+    if value.is_null() {
+        value = serde_json::json!({});
+    }
+
+    let (last_key, parent_keys) = keys.split_last().ok_or("No setting to save")?;
+    let mut parent = &mut value;
+    for key in parent_keys {
+        parent = &mut parent[*key];
+        if parent.is_null() {
+            *parent = serde_json::json!({});
+        }
+        if !parent.is_object() {
+            return Err(l10n::text(
+                "invalid-config-key-not-object",
+                &[("path", &path.display().to_string()), ("key", key)],
+            ));
+        }
+    }
+    parent[*last_key] = setting;
+
+    if let Some(config_dir) = path.parent() {
+        std::fs::create_dir_all(config_dir).map_err(|e| {
+            l10n::text(
+                "failed-to-create",
+                &[
+                    ("path", &config_dir.display().to_string()),
+                    ("error", &e.to_string()),
+                ],
+            )
+        })?;
+    }
+    let content = serde_json::to_string_pretty(&value).unwrap_or_default();
+    std::fs::write(&path, content + "\n").map_err(|e| {
+        l10n::text(
+            "failed-to-write",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })?;
+    Ok(path)
+}
@@ -0,0 +1,211 @@
+use crate::paths;
+use crate::process;
+use std::path::Path;
+
+// `docker run` options that take a separate value, so the value isn't mistaken for the image.
+// Every other word starting with a dash is a flag, or an option with its value attached
+// (`--env=KEY=value`, `-eKEY=value`)
+const DOCKER_RUN_VALUE_OPTIONS: [&str; 98] = [
+    "-a",
+    "--attach",
+    "--add-host",
+    "--annotation",
+    "--blkio-weight",
+    "--blkio-weight-device",
+    "--cap-add",
+    "--cap-drop",
+    "--cgroup-parent",
+    "--cgroupns",
+    "--cidfile",
+    "-c",
+    "--cpu-shares",
+    "--cpu-period",
+    "--cpu-quota",
+    "--cpu-rt-period",
+    "--cpu-rt-runtime",
+    "--cpus",
+    "--cpuset-cpus",
+    "--cpuset-mems",
+    "--detach-keys",
+    "--device",
+    "--device-cgroup-rule",
+    "--device-read-bps",
+    "--device-read-iops",
+    "--device-write-bps",
+    "--device-write-iops",
+    "--dns",
+    "--dns-opt",
+    "--dns-option",
+    "--dns-search",
+    "--domainname",
+    "--entrypoint",
+    "-e",
+    "--env",
+    "--env-file",
+    "--expose",
+    "--gpus",
+    "--group-add",
+    "--health-cmd",
+    "--health-interval",
+    "--health-retries",
+    "--health-start-interval",
+    "--health-start-period",
+    "--health-timeout",
+    "-h",
+    "--hostname",
+    "--ip",
+    "--ip6",
+    "--ipc",
+    "--isolation",
+    "--kernel-memory",
+    "-l",
+    "--label",
+    "--label-file",
+    "--link",
+    "--link-local-ip",
+    "--log-driver",
+    "--log-opt",
+    "--mac-address",
+    "-m",
+    "--memory",
+    "--memory-reservation",
+    "--memory-swap",
+    "--memory-swappiness",
+    "--mount",
+    "--name",
+    "--network",
+    "--net",
+    "--network-alias",
+    "--net-alias",
+    "--oom-score-adj",
+    "--pid",
+    "--pids-limit",
+    "--platform",
+    "-p",
+    "--publish",
+    "--pull",
+    "--restart",
+    "--runtime",
+    "--security-opt",
+    "--shm-size",
+    "--stop-signal",
+    "--stop-timeout",
+    "--storage-opt",
+    "--sysctl",
+    "--tmpfs",
+    "--ulimit",
+    "-u",
+    "--user",
+    "--userns",
+    "--uts",
+    "-v",
+    "--volume",
+    "--volume-driver",
+    "--volumes-from",
+    "-w",
+    "--workdir",
+];
+
+// Whalebrew packages are YAML files run through a `#!/usr/bin/env whalebrew` shebang, naming
+// the image they run, e.g. `image: whalebrew/wget`
+pub fn get_whalebrew_image(bin_path: &str) -> Option<String> {
+    let content = read_script(Path::new(bin_path))?;
+    let shebang = content.lines().next()?;
+    if !shebang.starts_with("#!") || !shebang.contains("whalebrew") {
+        return None;
+    }
+
+    content.lines().find_map(|line| {
+        let image = line.trim().strip_prefix("image:")?.trim();
+        Some(image.trim_matches(|c| c == '"' || c == '\'').to_string())
+    })
+}
+
+// Wrapper scripts along the lines of `exec docker run --rm -it -v "$PWD:/work" org/tool "$@"`
+pub fn get_docker_run_image(bin_path: &str) -> Option<String> {
+    let content = read_script(Path::new(bin_path))?;
+    if !content.starts_with("#!") {
+        return None;
+    }
+
+    // commands are often split over several lines with trailing backslashes
+    let content = content.replace("\\\r\n", " ").replace("\\\n", " ");
+    content.lines().find_map(get_image_from_docker_run)
+}
+
+fn get_image_from_docker_run(line: &str) -> Option<String> {
+    let mut words = line
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c| c == '"' || c == '\''));
+    words.find(|word| *word == "docker" || word.ends_with("/docker"))?;
+    if words.next()? != "run" {
+        return None;
+    }
+
+    while let Some(word) = words.next() {
+        if DOCKER_RUN_VALUE_OPTIONS.contains(&word) {
+            words.next();
+        } else if !word.starts_with('-') && !word.starts_with('$') {
+            return Some(word.to_string());
+        }
+    }
+
+    None
+}
+
+// Pulled images have no version to ask for without running them, the image id changes on
+// every update though
+pub fn get_image_id(image: &str) -> Option<String> {
+    if !paths::is_installed("docker") {
+        return None;
+    }
+
+    let output = process::query_command("docker")
+        .args(["image", "inspect", "--format", "{{.Id}}", image])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    // keep the `sha256:` prefix so the id is never mistaken for a version number
+    let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(id.chars().take("sha256:".len() + 12).collect())
+}
+
+fn read_script(path: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if metadata.len() > 64 * 1024 {
+        return None;
+    }
+    std::fs::read_to_string(path).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_from_docker_run() {
+        let image = |line: &str| get_image_from_docker_run(line);
+        assert_eq!(
+            image(r#"exec docker run --rm -it -v "$PWD:/work" -w /work org/tool "$@""#),
+            Some("org/tool".to_string())
+        );
+        assert_eq!(
+            image("docker run --device /dev/fuse --gpus all --cap-add SYS_ADMIN --cap-drop NET_RAW --ipc host org/tool"),
+            Some("org/tool".to_string())
+        );
+        assert_eq!(
+            image("docker run --add-host db:10.0.0.2 --label team=x --dns 1.1.1.1 --ulimit nofile=1024 --pull always org/tool:1.2"),
+            Some("org/tool:1.2".to_string())
+        );
+        assert_eq!(
+            image("/usr/bin/docker run --env=HOME=/root --network=host -eTERM=xterm --init org/tool --flag value"),
+            Some("org/tool".to_string())
+        );
+        assert_eq!(
+            image("docker run --rm -e $TOKEN_VAR org/tool"),
+            Some("org/tool".to_string())
+        );
+        assert_eq!(image("docker pull org/tool"), None);
+        assert_eq!(image("echo docker"), None);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::paths;
+use std::path::{Path, PathBuf};
+
+// Where `deno install -g` puts its shims, DENO_INSTALL_ROOT's bin or ~/.deno/bin
+pub fn get_bin_dir() -> Option<PathBuf> {
+    let install_root = std::env::var_os("DENO_INSTALL_ROOT")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| paths::get_home_dir().map(|home| home.join(".deno")))?;
+    Some(install_root.join("bin"))
+}
+
+// What a shim runs: the flags given to `deno install` and the module, from
+// `exec deno run --allow-net --no-config 'jsr:@std/http/file-server' "$@"`, or
+// `@deno "run" "--allow-net" "jsr:@std/http/file-server" %*` in the .cmd on Windows
+pub struct Shim {
+    pub flags: Vec<String>,
+    pub specifier: String,
+}
+
+pub fn read_shim(bin_path: &Path) -> Option<Shim> {
+    // shims are tiny, and binaries aren't valid UTF-8
+    if std::fs::metadata(bin_path).ok()?.len() > 64 * 1024 {
+        return None;
+    }
+    let content = std::fs::read_to_string(bin_path).ok()?;
+    if !content.contains("generated by deno install") {
+        return None;
+    }
+    content.lines().find_map(|line| {
+        let words = split_words(line.trim().trim_start_matches('@'));
+        let mut words = words.iter().map(|word| word.as_str());
+        if words.next() == Some("exec") {
+            words.next();
+        }
+        if words.next() != Some("run") {
+            return None;
+        }
+        let mut arguments: Vec<String> = words
+            .take_while(|word| *word != "$@" && *word != "%*")
+            .map(|word| word.to_string())
+            .collect();
+        let specifier = arguments.pop()?;
+        Some(Shim {
+            flags: arguments,
+            specifier,
+        })
+    })
+}
+
+// Words of a shell or batch line, with their single or double quotes removed
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    for c in line.chars() {
+        match quote {
+            Some(quote_char) if c == quote_char => quote = None,
+            Some(_) => word.push(c),
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            None => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+// The version pinned in the specifier, `jsr:@std/http@1.0.10/file-server`,
+// `npm:cowsay@1.6.0` or `https://deno.land/x/foo@v1.2.3/cli.ts`
+pub fn get_pinned_version(specifier: &str) -> Option<String> {
+    let (_, rest) = split_package(specifier);
+    let version = rest?.split('/').next()?;
+    let version = version.strip_prefix('v').unwrap_or(version);
+    version
+        .starts_with(|c: char| c.is_ascii_digit())
+        .then(|| version.to_string())
+}
+
+// The specifier without its version, so it installs the latest one:
+// `jsr:@std/http@1.0.10/file-server` -> `jsr:@std/http/file-server`. URLs are left alone,
+// there's no telling where their version goes
+pub fn unpin(specifier: &str) -> String {
+    match split_package(specifier) {
+        (name, Some(rest)) if !specifier.starts_with("http") => match rest.split_once('/') {
+            Some((_, path)) => format!("{}/{}", name, path),
+            None => name.to_string(),
+        },
+        _ => specifier.to_string(),
+    }
+}
+
+// (`jsr:@std/http`, `1.0.10/file-server`) from a jsr: or npm: specifier, or the part of a URL
+// up to its `@`
+fn split_package(specifier: &str) -> (&str, Option<&str>) {
+    // a scope's @ isn't the version's
+    let start = specifier
+        .find(':')
+        .map(|colon| colon + 1)
+        .unwrap_or_default();
+    let version_start = specifier[start..]
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| *c == '@')
+        .map(|(index, _)| start + index);
+    match version_start {
+        Some(index) => (&specifier[..index], Some(&specifier[index + 1..])),
+        None => (specifier, None),
+    }
+}
+
+// The jsr or npm package of the specifier, to ask its registry for the latest version,
+// `jsr:@std/http/file-server` -> (`jsr`, `@std/http`)
+pub fn get_registry_package(specifier: &str) -> Option<(&str, String)> {
+    let (registry, _) = specifier.split_once(':')?;
+    if registry != "jsr" && registry != "npm" {
+        return None;
+    }
+    let unpinned = unpin(specifier);
+    let module = unpinned[registry.len() + 1..].trim_start_matches('/');
+    let parts = if module.starts_with('@') { 2 } else { 1 };
+    let name: Vec<&str> = module.split('/').take(parts).collect();
+    Some((registry, name.join("/")))
+}
@@ -0,0 +1,228 @@
+use crate::l10n;
+use crate::runner::{RunOutput, RunStatus};
+
+// Turn a failed update's output into an explanation of what went wrong, for the failures
+// where the manager's own error doesn't say what to do about it
+pub fn diagnose_failure(
+    package_manager: &str,
+    package_name: &str,
+    output_lines: &[String],
+) -> Option<String> {
+    match package_manager {
+        "cargo" => diagnose_cargo_failure(package_name, output_lines),
+        "npm" | "pnpm" | "yarn" | "bun" => {
+            diagnose_node_failure(package_manager, package_name, output_lines)
+        }
+        _ => None,
+    }
+}
+
+fn diagnose_cargo_failure(crate_name: &str, output_lines: &[String]) -> Option<String> {
+    // error: cannot install package `foo 1.2.3`, it has been yanked from registry `crates-io`
+    if let Some(line) = output_lines
+        .iter()
+        .find(|line| line.contains("has been yanked"))
+    {
+        return Some(match get_registry(line) {
+            Some(registry) => l10n::text(
+                "crate-yanked-from",
+                &[("crate", crate_name), ("registry", registry)],
+            ),
+            None => l10n::text("crate-yanked", &[("crate", crate_name)]),
+        });
+    }
+
+    // error: could not find `foo` in registry `crates-io` with version `*`
+    let not_found = format!("could not find `{}` in registry", crate_name);
+    if let Some(line) = output_lines.iter().find(|line| line.contains(&not_found)) {
+        return Some(match get_registry(line) {
+            Some("crates-io") => l10n::text("crate-gone-from-crates-io", &[("crate", crate_name)]),
+            Some(registry) => l10n::text(
+                "crate-gone-from",
+                &[("crate", crate_name), ("registry", registry)],
+            ),
+            None => l10n::text("crate-gone", &[("crate", crate_name)]),
+        });
+    }
+
+    None
+}
+
+fn diagnose_node_failure(
+    package_manager: &str,
+    package_name: &str,
+    output_lines: &[String],
+) -> Option<String> {
+    let is_not_found = output_lines.iter().any(|line| {
+        line.contains("E404")
+            || line.contains("404 Not Found")
+            || line.contains("ERR_PNPM_FETCH_404")
+            || line.contains("is not in this registry")
+            || line.contains("Couldn't find package")
+    });
+    if is_not_found {
+        return Some(l10n::text(
+            "npm-package-gone",
+            &[("package", package_name), ("manager", package_manager)],
+        ));
+    }
+
+    let no_matching_version = output_lines.iter().any(|line| {
+        line.contains("ETARGET")
+            || line.contains("ERR_PNPM_NO_MATCHING_VERSION")
+            || line.contains("No matching version found")
+    });
+    if no_matching_version {
+        return Some(l10n::text(
+            "registry-has-no-version",
+            &[("package", package_name), ("manager", package_manager)],
+        ));
+    }
+
+    None
+}
+
+// What each manager prints when there was nothing to update. Only managers whose messages are
+// English whatever the locale, apt, dnf, yum, zypper, winget and IPS translate theirs and are
+// told by their exit code, or the version
+const UP_TO_DATE_SIGNALS: [(&str, &str); 11] = [
+    ("homebrew", "already installed"),
+    ("npm", "up to date in"),
+    ("npm", "up to date, audited"),
+    ("pnpm", "Already up to date"),
+    ("yarn", "Already up-to-date"),
+    ("bun", "(no changes)"),
+    ("cargo", "is already installed, use --force to override"),
+    ("uv", "Nothing to upgrade"),
+    ("whalebrew", "Image is up to date"),
+    ("docker", "Image is up to date"),
+    ("pkg", "Your packages are up to date"),
+];
+
+// Exit codes that mean there was nothing to update, rather than a failure
+const UP_TO_DATE_EXIT_CODES: [(&str, i32); 2] = [
+    // APPINSTALLER_CLI_ERROR_UPDATE_NOT_APPLICABLE, 0x8A15002B
+    ("winget", -1978335189),
+    ("ips", 4),
+];
+
+// Whether the manager said there was nothing to do, which is all there is to go on when the
+// version can't be read. A run that failed only counts when its exit code says so
+pub fn reports_up_to_date(package_manager: &str, output: &RunOutput) -> bool {
+    match output.status {
+        RunStatus::Succeeded => UP_TO_DATE_SIGNALS
+            .iter()
+            .filter(|(name, _)| *name == package_manager)
+            .any(|(_, signal)| output.lines.iter().any(|line| line.contains(signal))),
+        RunStatus::Failed => exits_up_to_date(package_manager, output.exit_code),
+        RunStatus::TimedOut(_) => false,
+    }
+}
+
+pub fn exits_up_to_date(package_manager: &str, exit_code: Option<i32>) -> bool {
+    UP_TO_DATE_EXIT_CODES
+        .iter()
+        .any(|(name, code)| *name == package_manager && Some(*code) == exit_code)
+}
+
+// `npm warn deprecated foo@1.2.3: use bar instead`, pnpm prints the same without the prefix
+pub fn find_deprecation(package_name: &str, output_lines: &[String]) -> Option<String> {
+    let marker = format!("deprecated {}@", package_name);
+    output_lines.iter().find_map(|line| {
+        let rest = &line[line.find(&marker)? + marker.len()..];
+        let (_, notice) = rest.split_once(':')?;
+        let notice = notice.trim();
+        if notice.is_empty() {
+            None
+        } else {
+            Some(notice.to_string())
+        }
+    })
+}
+
+fn get_registry(line: &str) -> Option<&str> {
+    let rest = &line[line.find("registry `")? + "registry `".len()..];
+    rest.split('`').next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(status: RunStatus, exit_code: Option<i32>, lines: &[&str]) -> RunOutput {
+        RunOutput {
+            status,
+            lines: lines.iter().map(|line| line.to_string()).collect(),
+            exit_code,
+        }
+    }
+
+    #[test]
+    fn up_to_date_from_output() {
+        let brew = output(
+            RunStatus::Succeeded,
+            Some(0),
+            &["Warning: ripgrep 14.1.0 already installed"],
+        );
+        assert!(reports_up_to_date("homebrew", &brew));
+        // the signal is another manager's
+        assert!(!reports_up_to_date("npm", &brew));
+        let npm = output(RunStatus::Succeeded, Some(0), &["added 1 package in 2s"]);
+        assert!(!reports_up_to_date("npm", &npm));
+    }
+
+    #[test]
+    fn failed_run_is_not_up_to_date() {
+        let failed = output(
+            RunStatus::Failed,
+            Some(1),
+            &["Warning: ripgrep 14.1.0 already installed"],
+        );
+        assert!(!reports_up_to_date("homebrew", &failed));
+        let timed_out = output(
+            RunStatus::TimedOut("it hung".to_string()),
+            None,
+            &["Warning: ripgrep 14.1.0 already installed"],
+        );
+        assert!(!reports_up_to_date("homebrew", &timed_out));
+    }
+
+    #[test]
+    fn failed_batch_is_not_up_to_date() {
+        let batch = output(
+            RunStatus::Failed,
+            Some(1),
+            &[
+                "Warning: a 1.0.0 already installed",
+                "==> Upgrading c",
+                "Error: c: Failed to download resource",
+            ],
+        );
+        assert!(!reports_up_to_date("homebrew", &batch));
+        assert!(!exits_up_to_date("homebrew", batch.exit_code));
+    }
+
+    #[test]
+    fn up_to_date_from_exit_code() {
+        // translated messages don't matter
+        let winget = output(
+            RunStatus::Failed,
+            Some(-1978335189),
+            &["Kein anwendbares Update gefunden."],
+        );
+        assert!(reports_up_to_date("winget", &winget));
+        assert!(reports_up_to_date(
+            "ips",
+            &output(RunStatus::Failed, Some(4), &[])
+        ));
+        assert!(!reports_up_to_date(
+            "ips",
+            &output(RunStatus::Failed, Some(1), &[])
+        ));
+        assert!(!reports_up_to_date(
+            "apt",
+            &output(RunStatus::Failed, Some(4), &[])
+        ));
+        assert!(!exits_up_to_date("winget", None));
+    }
+}
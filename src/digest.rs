@@ -0,0 +1,207 @@
+use crate::l10n;
+use crate::paths;
+use crate::process;
+use std::path::Path;
+
+// The hashes registries publish for their downloads, so a package copied to a machine without
+// network access can be checked before it's installed. openssl computes them, it's what checks
+// the signatures too, and update-bin keeps to clap and serde_json for its own dependencies
+
+// `openssl dgst` of the file, for `sha1`, `sha256` or `sha512`
+pub fn hash_file(path: &Path, algorithm: &str) -> Result<Vec<u8>, String> {
+    let length = match algorithm {
+        "sha1" => 20,
+        "sha256" => 32,
+        "sha512" => 64,
+        _ => {
+            return Err(l10n::text(
+                "unsupported-hash-algorithm",
+                &[("algorithm", algorithm)],
+            ))
+        }
+    };
+    if !paths::is_installed("openssl") {
+        return Err(l10n::text(
+            "checksum-needs-openssl",
+            &[("path", &path.display().to_string())],
+        ));
+    }
+    let output = process::query_command("openssl")
+        .args(["dgst", &format!("-{}", algorithm), "-binary"])
+        .arg(path)
+        .output()
+        .map_err(|e| l10n::text("failed-to-run-openssl", &[("error", &e.to_string())]))?;
+    if !output.status.success() || output.stdout.len() != length {
+        return Err(l10n::text(
+            "openssl-dgst-failed",
+            &[
+                ("algorithm", algorithm),
+                ("path", &path.display().to_string()),
+                ("output", String::from_utf8_lossy(&output.stderr).trim()),
+            ],
+        ));
+    }
+    Ok(output.stdout)
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn to_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| {
+            group | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+pub fn from_base64(encoded: &str) -> Option<Vec<u8>> {
+    let encoded = encoded.trim_end_matches('=');
+    let mut bytes = Vec::new();
+    let mut group = 0u32;
+    let mut bits = 0;
+    for c in encoded.bytes() {
+        let value = BASE64_ALPHABET.iter().position(|letter| *letter == c)? as u32;
+        group = group << 6 | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((group >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+// Whether the file matches a Subresource Integrity string like npm's `dist.integrity`,
+// `sha512-<base64>`, with several space separated hashes when the registry lists more than one.
+// An algorithm this doesn't know is no match
+pub fn matches_integrity(path: &Path, integrity: &str) -> Result<bool, String> {
+    let hashes: Vec<&str> = integrity.split_whitespace().collect();
+    if hashes.is_empty() {
+        return Ok(false);
+    }
+    for hash in hashes {
+        let (algorithm, expected) = match hash.split_once('-') {
+            Some(hash) if ["sha1", "sha256", "sha512"].contains(&hash.0) => hash,
+            _ => return Ok(false),
+        };
+        if from_base64(expected) != Some(hash_file(path, algorithm)?) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_temp_file(name: &str, content: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "update-bin-test-digest-{}-{}",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn known_hashes() {
+        if !paths::is_installed("openssl") {
+            return;
+        }
+        let path = write_temp_file("abc", b"abc");
+        assert_eq!(
+            hash_file(&path, "sha1").unwrap(),
+            from_hex("a9993e364706816aba3e25717850c26c9cd0d89d").unwrap()
+        );
+        assert_eq!(
+            hash_file(&path, "sha256").unwrap(),
+            from_hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad").unwrap()
+        );
+        assert_eq!(
+            hash_file(&path, "sha512").unwrap(),
+            from_hex(
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+            )
+            .unwrap()
+        );
+        assert!(hash_file(&path, "md5").is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        assert_eq!(to_base64(b""), "");
+        assert_eq!(to_base64(b"f"), "Zg==");
+        assert_eq!(to_base64(b"fo"), "Zm8=");
+        assert_eq!(to_base64(b"foo"), "Zm9v");
+        assert_eq!(to_base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(to_base64(b"fooba"), "Zm9vYmE=");
+        assert_eq!(to_base64(b"foob"), "Zm9vYg==");
+        assert_eq!(to_base64(&[0xfb, 0xff, 0xbf]), "+/+/");
+        assert_eq!(from_base64("Zm9vYg==").unwrap(), b"foob");
+        assert_eq!(from_base64("Zm9vYmE=").unwrap(), b"fooba");
+        assert_eq!(from_base64("+/+/").unwrap(), vec![0xfb, 0xff, 0xbf]);
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(from_base64(&to_base64(&bytes)).unwrap(), bytes);
+        assert_eq!(from_base64("not base64!"), None);
+        assert_eq!(from_base64("Zm9v-_"), None);
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(from_hex("a9ff").unwrap(), vec![0xa9, 0xff]);
+        assert_eq!(from_hex("A9FF").unwrap(), vec![0xa9, 0xff]);
+        assert_eq!(from_hex("a9f"), None);
+        assert_eq!(from_hex("zz"), None);
+        assert_eq!(to_hex(&[0x00, 0x0f, 0xff]), "000fff");
+    }
+
+    #[test]
+    fn integrity() {
+        if !paths::is_installed("openssl") {
+            return;
+        }
+        let path = write_temp_file("package", b"package");
+        let integrity = format!("sha512-{}", to_base64(&hash_file(&path, "sha512").unwrap()));
+        assert_eq!(matches_integrity(&path, &integrity), Ok(true));
+        let sha1_integrity = format!("sha1-{}", to_base64(&hash_file(&path, "sha1").unwrap()));
+        assert_eq!(
+            matches_integrity(&path, &format!("{} {}", integrity, sha1_integrity)),
+            Ok(true)
+        );
+        assert_eq!(matches_integrity(&path, "md5-abc"), Ok(false));
+        assert_eq!(matches_integrity(&path, ""), Ok(false));
+
+        let tampered = write_temp_file("tampered", b"tampered");
+        assert_eq!(matches_integrity(&tampered, &integrity), Ok(false));
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&tampered).unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+use crate::l10n;
+use crate::paths;
+use crate::process;
+use std::io::Write;
+use std::path::Path;
+use std::process::Stdio;
+
+// Why updating the binary needs administrator rights on Windows, found before starting the
+// update rather than from an access denied error halfway through it. `None` when it doesn't or
+// update-bin already runs elevated
+pub fn get_elevation_reason(package_manager: &str, bin_path: &str) -> Option<String> {
+    if !cfg!(windows) {
+        return None;
+    }
+
+    let path = Path::new(bin_path);
+    let reason = if package_manager == "winget" && is_in_program_files(path) {
+        l10n::text("elevate-winget-machine-wide", &[("bin-path", bin_path)])
+    } else {
+        let bin_dir = path.parent()?;
+        if is_writable(bin_dir) {
+            return None;
+        }
+        l10n::text(
+            "elevate-needs-admin",
+            &[
+                ("manager", package_manager),
+                ("bin-dir", &bin_dir.display().to_string()),
+            ],
+        )
+    };
+    if is_elevated() {
+        return None;
+    }
+    Some(reason)
+}
+
+// Ask before relaunching the update as administrator, Windows then asks too. Without a terminal
+// to ask on, say how to run it instead
+pub fn offer(reason: &str, command: &str, args: &[String]) -> Result<(), String> {
+    let full_command = format!("{} {}", command, args.join(" "));
+    if !process::can_prompt() {
+        return Err(l10n::text(
+            "elevate-run-elevated",
+            &[("reason", reason), ("command", &full_command)],
+        ));
+    }
+
+    println!("{}", reason);
+    print!(
+        "{} ",
+        l10n::text("relaunch-elevated-prompt", &[("command", &full_command)])
+    );
+    std::io::stdout().flush().ok();
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).ok();
+    if !matches!(answer.trim(), "y" | "Y" | "yes") {
+        return Err(l10n::text("nothing-was-changed", &[]));
+    }
+    Ok(())
+}
+
+// ShellExecute's "runas" verb (through PowerShell's Start-Process) starts the command elevated,
+// in a console of its own that output can't be piped from. It writes to a log instead, which
+// is printed as it grows, so the update's output is streamed like any other and the timeouts
+// still apply. The elevated command's exit code is passed on
+pub fn get_elevated_command(command: &str, args: &[String]) -> (String, Vec<String>) {
+    let log_path =
+        std::env::temp_dir().join(format!("update-bin-elevated-{}.log", std::process::id()));
+    let log_path = log_path.to_string_lossy();
+    let executable = paths::find_executable(command)
+        .map(|executable| executable.to_string_lossy().to_string())
+        .unwrap_or_else(|| command.to_string());
+    // `cmd /s /c "..."` drops the outer quotes and runs the rest as is
+    let command_line = format!(
+        "/d /s /c \"\"{}\" {} > \"{}\" 2>&1\"",
+        executable,
+        args.iter()
+            .map(|arg| quote_cmd_argument(arg))
+            .collect::<Vec<String>>()
+            .join(" "),
+        log_path
+    );
+
+    let script = [
+        "$ErrorActionPreference = 'Stop'".to_string(),
+        format!("$log = {}", quote_powershell_string(&log_path)),
+        format!(
+            "$process = Start-Process -FilePath 'cmd.exe' -ArgumentList {} -Verb RunAs -WindowStyle Hidden -PassThru",
+            quote_powershell_string(&command_line)
+        ),
+        // the exit code is only kept once the handle has been asked for
+        "$null = $process.Handle".to_string(),
+        "$shown = 0".to_string(),
+        "function Show-Log { $lines = @(Get-Content -LiteralPath $log -ErrorAction SilentlyContinue); if ($lines.Count -gt $script:shown) { $lines[$script:shown..($lines.Count - 1)] | Write-Output; $script:shown = $lines.Count } }".to_string(),
+        "while (-not $process.HasExited) { Start-Sleep -Milliseconds 250; Show-Log }".to_string(),
+        "Show-Log".to_string(),
+        "Remove-Item -LiteralPath $log -ErrorAction SilentlyContinue".to_string(),
+        "exit $process.ExitCode".to_string(),
+    ]
+    .join("\n");
+
+    (
+        "powershell".to_string(),
+        vec![
+            "-NoProfile".to_string(),
+            "-NonInteractive".to_string(),
+            "-ExecutionPolicy".to_string(),
+            "Bypass".to_string(),
+            "-Command".to_string(),
+            script,
+        ],
+    )
+}
+
+// `net session` needs administrator rights to list anything
+fn is_elevated() -> bool {
+    process::query_command("net")
+        .arg("session")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+fn is_in_program_files(path: &Path) -> bool {
+    ["ProgramFiles", "ProgramFiles(x86)", "ProgramW6432"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .filter(|dir| !dir.is_empty())
+        .any(|dir| path.starts_with(dir))
+}
+
+// Whether a file can be created in the dir, which is what the update will do. Only an access
+// denied error counts, anything else is left for the update to report
+fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".update-bin-{}", std::process::id()));
+    match std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+    {
+        Ok(_) => {
+            std::fs::remove_file(&probe).ok();
+            true
+        }
+        Err(e) => e.kind() != std::io::ErrorKind::PermissionDenied,
+    }
+}
+
+fn quote_cmd_argument(arg: &str) -> String {
+    if arg.is_empty()
+        || arg
+            .chars()
+            .any(|c| c.is_whitespace() || "&|<>^()%!\"".contains(c))
+    {
+        format!("\"{}\"", arg.replace('"', "\"\""))
+    } else {
+        arg.to_string()
+    }
+}
+
+fn quote_powershell_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
@@ -0,0 +1,53 @@
+use crate::l10n;
+use std::io::Write;
+
+// GitHub Actions sets GITHUB_ACTIONS=true for every step, `--output text` turns the workflow
+// commands off again. JUnit results go to a file, so they don't change what's printed
+pub fn is_enabled(output: Option<&str>) -> bool {
+    match output {
+        Some("gha") => true,
+        Some("text") => false,
+        _ => std::env::var("GITHUB_ACTIONS").as_deref() == Ok("true"),
+    }
+}
+
+// Folds everything printed until end_group() in the log
+pub fn start_group(title: &str) {
+    println!("::group::{}", escape(title));
+}
+
+pub fn end_group() {
+    println!("::endgroup::");
+}
+
+// Shows up as an annotation on the workflow run
+pub fn warning(message: &str) {
+    println!("::warning::{}", escape(message));
+}
+
+// Markdown for the job summary page, appended since every step shares the file
+pub fn append_summary(markdown: &str) {
+    let path = match std::env::var_os("GITHUB_STEP_SUMMARY").filter(|path| !path.is_empty()) {
+        Some(path) => path,
+        None => return,
+    };
+    let result = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", markdown));
+    if let Err(e) = result {
+        eprintln!(
+            "{}",
+            l10n::text("summary-write-failed", &[("error", &e.to_string())])
+        );
+    }
+}
+
+// Workflow commands end at a newline, so they have to be encoded
+fn escape(message: &str) -> String {
+    message
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
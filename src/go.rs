@@ -0,0 +1,84 @@
+use crate::process;
+use std::path::{Path, PathBuf};
+
+// Where `go install` puts binaries, GOBIN or the first GOPATH's bin
+pub fn get_bin_dir() -> Option<PathBuf> {
+    let output = process::query_command("go")
+        .args(["env", "GOBIN", "GOPATH"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines().map(|line| line.trim());
+    let gobin = lines.next().unwrap_or_default();
+    if !gobin.is_empty() {
+        return Some(PathBuf::from(gobin));
+    }
+    let gopath = lines.next().filter(|gopath| !gopath.is_empty())?;
+    let gopath = std::env::split_paths(gopath).next()?;
+    Some(gopath.join("bin"))
+}
+
+// `golang.org/x/tools/cmd/stringer` -> `stringer`, and a major version suffix isn't the name:
+// `github.com/user/tool/v2` -> `tool`
+pub fn get_bin_name(package_path: &str) -> &str {
+    let mut parts = package_path.rsplit('/');
+    let last = parts.next().unwrap_or(package_path);
+    let is_major_suffix =
+        last.len() > 1 && last.starts_with('v') && last[1..].chars().all(|c| c.is_ascii_digit());
+    match parts.next() {
+        Some(parent) if is_major_suffix => parent,
+        _ => last,
+    }
+}
+
+// The module version a binary was built from, `go version -m` prints it as
+// `\tmod\t<module>\t<version>\t<sum>`
+pub fn get_module_version(bin_path: &Path) -> Option<String> {
+    let output = process::query_command("go")
+        .arg("version")
+        .arg("-m")
+        .arg(bin_path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        if fields.next() != Some("mod") {
+            return None;
+        }
+        fields.nth(1).map(|version| version.to_string())
+    })
+}
+
+// The main package a binary was built from, which is what `go install` takes, printed by
+// `go version -m` as `\tpath\t<package>`. `None` for a binary Go didn't build
+pub fn get_package_path(bin_path: &Path) -> Option<String> {
+    let output = process::query_command("go")
+        .arg("version")
+        .arg("-m")
+        .arg(bin_path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        if fields.next() != Some("path") {
+            return None;
+        }
+        fields.next().map(|package_path| package_path.to_string())
+    })
+}
+
+// The version of the tool `go install` put into the bin dir
+pub fn get_installed_version(package_path: &str) -> Option<String> {
+    let bin_name = get_bin_name(package_path);
+    let bin_dir = get_bin_dir()?;
+    let bin_path = [bin_name.to_string(), format!("{}.exe", bin_name)]
+        .into_iter()
+        .map(|name| bin_dir.join(name))
+        .find(|path| path.is_file())?;
+    get_module_version(&bin_path)
+}
@@ -0,0 +1,103 @@
+use crate::l10n;
+use crate::process;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+// A binary that hangs on --version is as broken as one that doesn't start
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Runs the check configured for the binary, or the binary's own `--version`. Plenty of tools
+// exit non-zero on --version, so the default check only fails when the binary can't start,
+// crashes or hangs, a custom one fails on any non-zero exit
+pub fn check(bin_path: &str, custom_command: Option<&str>) -> Result<(), String> {
+    let (mut command, description) = match custom_command {
+        Some(custom_command) => {
+            let (shell, args) = process::get_shell_command(custom_command);
+            let mut command = Command::new(shell);
+            command.args(args);
+            (command, format!("`{}`", custom_command))
+        }
+        None => {
+            // it runs from the temp dir, where a relative --path wouldn't be found
+            let mut command =
+                Command::new(std::path::absolute(bin_path).unwrap_or_else(|_| bin_path.into()));
+            command.arg("--version");
+            (command, format!("`{} --version`", bin_path))
+        }
+    };
+    // nothing to answer a prompt with, and nothing in the user's project to touch
+    command
+        .current_dir(std::env::temp_dir())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let mut child = command.spawn().map_err(|e| {
+        l10n::text(
+            "healthcheck-couldnt-start",
+            &[("description", &description), ("error", &e.to_string())],
+        )
+    })?;
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() < TIMEOUT => {
+                std::thread::sleep(Duration::from_millis(50))
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(l10n::text(
+                    "healthcheck-timed-out",
+                    &[
+                        ("description", &description),
+                        ("seconds", &TIMEOUT.as_secs().to_string()),
+                    ],
+                ));
+            }
+            Err(e) => {
+                return Err(l10n::text(
+                    "failed-to-wait-for",
+                    &[("command", &description), ("error", &e.to_string())],
+                ))
+            }
+        }
+    };
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = std::io::Read::read_to_string(&mut pipe, &mut stderr);
+    }
+    let failed = match (custom_command, status.code()) {
+        (Some(_), code) => code != Some(0),
+        // 126 and 127 are the loader's "can't execute" and "missing library", a negative
+        // code on Windows is a crash like STATUS_DLL_NOT_FOUND
+        (None, Some(code)) => code == 126 || code == 127 || (cfg!(windows) && code < 0),
+        // killed by a signal
+        (None, None) => true,
+    };
+    if !failed {
+        return Ok(());
+    }
+    let last_line = stderr
+        .lines()
+        .map(|line| line.trim())
+        .rfind(|line| !line.is_empty());
+    Err(match last_line {
+        Some(last_line) => l10n::text(
+            "healthcheck-failed",
+            &[
+                ("description", &description),
+                ("status", &status.to_string()),
+                ("last-line", last_line),
+            ],
+        ),
+        None => l10n::text(
+            "healthcheck-failed-no-output",
+            &[
+                ("description", &description),
+                ("status", &status.to_string()),
+            ],
+        ),
+    })
+}
@@ -0,0 +1,217 @@
+use crate::l10n;
+use crate::paths;
+use crate::schema;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// One finished (or killed) update, appended as a line of JSON to the history file
+pub struct HistoryEntry<'a> {
+    pub bin_name: &'a str,
+    pub package_name: &'a str,
+    pub package_manager: &'a str,
+    pub old_version: &'a str,
+    pub new_version: Option<&'a str>,
+    // updated, reinstalled, unchanged, downgraded, unknown, failed or timed_out
+    pub status: &'a str,
+    pub duration: Duration,
+    pub error: Option<&'a str>,
+}
+
+// An entry read back from the history file
+pub struct RecordedUpdate {
+    pub timestamp: u64,
+    pub bin_name: String,
+    pub package_manager: String,
+    pub status: String,
+    pub duration_secs: f64,
+}
+
+// `$XDG_STATE_HOME/update-bin/history.jsonl`, or `%LOCALAPPDATA%\update-bin` on Windows
+pub fn get_history_path() -> Option<PathBuf> {
+    let state_dir = if cfg!(windows) {
+        std::env::var_os("LOCALAPPDATA").map(PathBuf::from)?
+    } else {
+        std::env::var_os("XDG_STATE_HOME")
+            .filter(|state_home| !state_home.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| paths::get_home_dir().map(|home| home.join(".local").join("state")))?
+    };
+    Some(state_dir.join("update-bin").join("history.jsonl"))
+}
+
+// History is a nice to have, failing to write it never fails the update
+pub fn record(entry: &HistoryEntry) {
+    let path = match get_history_path() {
+        Some(path) => path,
+        None => return,
+    };
+
+    let timestamp = now();
+    let line = serde_json::json!({
+        "schema_version": schema::SCHEMA_VERSION,
+        "timestamp": timestamp,
+        "bin_name": entry.bin_name,
+        "package_name": entry.package_name,
+        "package_manager": entry.package_manager,
+        "old_version": entry.old_version,
+        "new_version": entry.new_version,
+        "status": entry.status,
+        "duration_secs": entry.duration.as_secs_f64(),
+        "error": entry.error,
+    });
+
+    let result = path
+        .parent()
+        .map(std::fs::create_dir_all)
+        .unwrap_or(Ok(()))
+        .and_then(|_| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+        })
+        .and_then(|mut file| writeln!(file, "{}", line));
+    if let Err(e) = result {
+        eprintln!(
+            "{}",
+            l10n::text(
+                "history-write-failed",
+                &[
+                    ("path", &path.display().to_string()),
+                    ("error", &e.to_string())
+                ]
+            )
+        );
+    }
+}
+
+// Lines that can't be parsed, e.g. one cut short by a full disk, are skipped
+pub fn read_history() -> Vec<RecordedUpdate> {
+    let content = get_history_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .unwrap_or_default();
+
+    content
+        .lines()
+        .filter_map(|line| {
+            let entry: serde_json::Value = serde_json::from_str(line).ok()?;
+            Some(RecordedUpdate {
+                timestamp: entry["timestamp"].as_u64()?,
+                bin_name: entry["bin_name"].as_str()?.to_string(),
+                package_manager: entry["package_manager"].as_str()?.to_string(),
+                status: entry["status"].as_str()?.to_string(),
+                duration_secs: entry["duration_secs"].as_f64().unwrap_or(0.0),
+            })
+        })
+        .collect()
+}
+
+// How long the binary's last actual update with the manager took, an update that found
+// nothing to do says nothing about the next one
+pub fn get_last_duration(bin_name: &str, package_manager: &str) -> Option<Duration> {
+    read_history()
+        .into_iter()
+        .filter(|entry| entry.bin_name == bin_name && entry.package_manager == package_manager)
+        .filter(|entry| entry.status == "updated" || entry.status == "reinstalled")
+        .max_by_key(|entry| entry.timestamp)
+        .map(|entry| Duration::from_secs_f64(entry.duration_secs.max(0.0)))
+}
+
+// `durations.json` next to the history, with how long each manager's last successful --all run
+// took. A run covers every package of the manager, so it isn't a history entry
+fn get_durations_path() -> Option<PathBuf> {
+    Some(get_history_path()?.with_file_name("durations.json"))
+}
+
+pub fn get_manager_durations() -> HashMap<String, Duration> {
+    let content = get_durations_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .unwrap_or_default();
+    let durations: serde_json::Value = serde_json::from_str(&content).unwrap_or_default();
+    durations["managers"]
+        .as_object()
+        .map(|managers| {
+            managers
+                .iter()
+                .filter_map(|(manager, secs)| {
+                    let secs = secs.as_f64().filter(|secs| *secs >= 0.0)?;
+                    Some((manager.clone(), Duration::from_secs_f64(secs)))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Like the history, failing to write it never fails the run
+pub fn record_manager_durations(runs: &[(&str, Duration)]) {
+    let path = match get_durations_path() {
+        Some(path) => path,
+        None => return,
+    };
+    if runs.is_empty() {
+        return;
+    }
+
+    let mut managers: serde_json::Map<String, serde_json::Value> = get_manager_durations()
+        .into_iter()
+        .map(|(manager, duration)| (manager, serde_json::json!(duration.as_secs_f64())))
+        .collect();
+    for (manager, duration) in runs {
+        managers.insert(
+            manager.to_string(),
+            serde_json::json!(duration.as_secs_f64()),
+        );
+    }
+    let content = serde_json::json!({ "managers": managers }).to_string();
+    let result = path
+        .parent()
+        .map(std::fs::create_dir_all)
+        .unwrap_or(Ok(()))
+        .and_then(|_| std::fs::write(&path, content));
+    if let Err(e) = result {
+        eprintln!(
+            "{}",
+            l10n::text(
+                "durations-write-failed",
+                &[
+                    ("path", &path.display().to_string()),
+                    ("error", &e.to_string())
+                ]
+            )
+        );
+    }
+}
+
+// Statuses that mean the tool was up to date right after the run
+const UP_TO_DATE_STATUSES: [&str; 3] = ["updated", "reinstalled", "unchanged"];
+
+// When each binary was last known to be up to date, or first seen if it never was, oldest
+// first
+pub fn get_last_up_to_date(entries: &[RecordedUpdate]) -> Vec<(String, u64)> {
+    let mut last_up_to_date: HashMap<&str, (u64, bool)> = HashMap::new();
+    for entry in entries {
+        let is_up_to_date = UP_TO_DATE_STATUSES.contains(&entry.status.as_str());
+        let last = last_up_to_date
+            .entry(&entry.bin_name)
+            .or_insert((entry.timestamp, is_up_to_date));
+        if is_up_to_date && (!last.1 || entry.timestamp > last.0) {
+            *last = (entry.timestamp, true);
+        }
+    }
+
+    let mut bins: Vec<(String, u64)> = last_up_to_date
+        .into_iter()
+        .map(|(bin_name, (timestamp, _))| (bin_name.to_string(), timestamp))
+        .collect();
+    bins.sort_by_key(|(_, timestamp)| *timestamp);
+    bins
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
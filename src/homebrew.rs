@@ -0,0 +1,468 @@
+use crate::mapping::Candidate;
+use crate::paths;
+use crate::process;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+// An installed formula or cask, as reported by `brew info --json=v2`
+pub struct BrewPackage {
+    pub name: String,
+    // `user/tap/formula` for a formula of a third-party tap
+    pub full_name: String,
+    // names that `brew` still accepts for this package
+    pub aliases: Vec<String>,
+    pub oldnames: Vec<String>,
+    pub installed_version: Option<String>,
+    pub latest_version: Option<String>,
+    pub outdated: bool,
+    pub pinned: bool,
+    pub head: bool,
+    pub cask: bool,
+    pub homepage: Option<String>,
+    // where the stable version is downloaded from, usually a GitHub archive or release
+    pub source_url: Option<String>,
+}
+
+// Work out the formula from the binary's location in the Cellar, either directly
+// (`<prefix>/Cellar/<formula>/<version>/bin/foo`), through an opt link
+// (`<prefix>/opt/<formula>/bin/foo`, used by keg-only formulas), or by following the
+// symlink that `brew link` creates in `<prefix>/bin` (casks link into `<prefix>/Caskroom`)
+pub fn get_formula_from_path(bin_path: &str) -> Option<String> {
+    let path = Path::new(bin_path);
+
+    if let Ok(resolved_path) = std::fs::canonicalize(path) {
+        if let Some(formula) = get_formula_from_components(&resolved_path) {
+            return Some(formula);
+        }
+    }
+
+    get_formula_from_components(path)
+}
+
+fn get_formula_from_components(path: &Path) -> Option<String> {
+    let components: Vec<Component> = path.components().collect();
+    for (index, component) in components.iter().enumerate() {
+        let name = component.as_os_str();
+        let is_name = |expected: &str| paths::names_equal(name, std::ffi::OsStr::new(expected));
+        if !is_name("Cellar") && !is_name("Caskroom") && !is_name("opt") {
+            continue;
+        }
+
+        let formula = match components.get(index + 1) {
+            Some(Component::Normal(formula)) => formula.to_string_lossy().to_string(),
+            _ => continue,
+        };
+
+        // only accept `opt` when it sits next to a Cellar, so `/opt/...` itself isn't
+        // mistaken for a Homebrew prefix
+        let prefix: std::path::PathBuf = components[..index].iter().collect();
+        if is_name("opt") && !prefix.join("Cellar").is_dir() {
+            continue;
+        }
+
+        return Some(formula);
+    }
+
+    None
+}
+
+pub fn get_brew_info(name: &str) -> Option<BrewPackage> {
+    let output = process::query_command(get_brew_executable()?)
+        .args(["info", "--json=v2", name])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    parse_brew_info(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .next()
+}
+
+// The lines of `brew upgrade --dry-run` under its "==> Would upgrade" and "==> Would install"
+// headers, which include the dependencies that come along, e.g. `pcre2 10.42 -> 10.44`
+pub fn get_upgrade_plan(formula: &str) -> Vec<String> {
+    let output = get_brew_executable().and_then(|brew| {
+        process::query_command(brew)
+            .args(["upgrade", "--dry-run", formula])
+            // the auto update would change what's installed, which a dry run mustn't
+            .env("HOMEBREW_NO_AUTO_UPDATE", "1")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+    });
+    let output = match output {
+        Some(output) => output,
+        None => return Vec::new(),
+    };
+    let mut plan = Vec::new();
+    let mut in_plan = false;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(header) = line.strip_prefix("==> ") {
+            in_plan = header.starts_with("Would");
+        } else if in_plan && !line.trim().is_empty() {
+            plan.push(line.trim().to_string());
+        }
+    }
+    plan
+}
+
+pub fn get_installed_packages() -> Vec<BrewPackage> {
+    let brew = match get_brew_executable() {
+        Some(brew) => brew,
+        None => return Vec::new(),
+    };
+
+    let output = process::query_command(brew)
+        .args(["info", "--json=v2", "--installed"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+
+    match output {
+        Some(output) => parse_brew_info(&String::from_utf8_lossy(&output.stdout)),
+        None => Vec::new(),
+    }
+}
+
+fn parse_brew_info(json: &str) -> Vec<BrewPackage> {
+    let info: serde_json::Value = serde_json::from_str(json).unwrap_or_default();
+    let mut packages = Vec::new();
+
+    let empty_vec = Vec::new();
+    for formula in info["formulae"].as_array().unwrap_or(&empty_vec) {
+        let name = match formula["name"].as_str() {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+
+        // prefer the linked keg, which is the version that's actually on PATH
+        let installed_version = formula["linked_keg"]
+            .as_str()
+            .map(|version| version.to_string())
+            .or_else(|| {
+                formula["installed"]
+                    .as_array()
+                    .and_then(|installed| installed.last())
+                    .and_then(|installed| installed["version"].as_str())
+                    .map(|version| version.to_string())
+            });
+
+        let head = installed_version
+            .as_deref()
+            .map(|version| version.starts_with("HEAD"))
+            .unwrap_or(false);
+
+        // Homebrew < 4.1 had a single `oldname`
+        let mut oldnames = get_strings(&formula["oldnames"]);
+        if let Some(oldname) = formula["oldname"].as_str() {
+            oldnames.push(oldname.to_string());
+        }
+
+        packages.push(BrewPackage {
+            full_name: formula["full_name"].as_str().unwrap_or(&name).to_string(),
+            name,
+            aliases: get_strings(&formula["aliases"]),
+            oldnames,
+            installed_version,
+            latest_version: formula["versions"]["stable"]
+                .as_str()
+                .map(|version| version.to_string()),
+            outdated: formula["outdated"].as_bool().unwrap_or(false),
+            pinned: formula["pinned"].as_bool().unwrap_or(false),
+            head,
+            cask: false,
+            homepage: formula["homepage"].as_str().map(|url| url.to_string()),
+            source_url: formula["urls"]["stable"]["url"]
+                .as_str()
+                .map(|url| url.to_string()),
+        });
+    }
+
+    for cask in info["casks"].as_array().unwrap_or(&empty_vec) {
+        let name = match cask["token"].as_str() {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+
+        packages.push(BrewPackage {
+            full_name: cask["full_token"].as_str().unwrap_or(&name).to_string(),
+            name,
+            aliases: Vec::new(),
+            oldnames: get_strings(&cask["old_tokens"]),
+            installed_version: cask["installed"]
+                .as_str()
+                .map(|version| version.to_string()),
+            latest_version: cask["version"].as_str().map(|version| version.to_string()),
+            outdated: cask["outdated"].as_bool().unwrap_or(false),
+            pinned: false,
+            head: false,
+            cask: true,
+            homepage: cask["homepage"].as_str().map(|url| url.to_string()),
+            source_url: cask["url"].as_str().map(|url| url.to_string()),
+        });
+    }
+
+    packages
+}
+
+// The GitHub repository the package is built from, or its homepage
+pub fn get_repository_url(package: &BrewPackage) -> Option<String> {
+    let github_repository = package.source_url.as_deref().and_then(|url| {
+        let path = url.strip_prefix("https://github.com/")?;
+        let mut parts = path.split('/');
+        Some(format!(
+            "https://github.com/{}/{}",
+            parts.next()?,
+            parts.next()?
+        ))
+    });
+    github_repository.or_else(|| package.homepage.clone())
+}
+
+fn get_strings(value: &serde_json::Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str())
+                .map(|value| value.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Where Homebrew installs by default on macOS (Apple Silicon and Intel) and Linux
+const DEFAULT_PREFIXES: [&str; 3] = ["/opt/homebrew", "/usr/local", "/home/linuxbrew/.linuxbrew"];
+
+// `brew` isn't necessarily on PATH for installs in a custom prefix, e.g. ~/homebrew
+pub fn get_brew_executable() -> Option<PathBuf> {
+    static BREW: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+    BREW.get_or_init(|| {
+        paths::find_executable("brew").or_else(|| {
+            std::env::var_os("HOMEBREW_PREFIX")
+                .filter(|prefix| !prefix.is_empty())
+                .map(PathBuf::from)
+                .into_iter()
+                .chain(DEFAULT_PREFIXES.iter().map(PathBuf::from))
+                .map(|prefix| prefix.join("bin").join("brew"))
+                .find(|brew| brew.is_file())
+        })
+    })
+    .clone()
+}
+
+// The command to run brew with, for building the update command
+pub fn get_brew_command() -> String {
+    get_brew_executable()
+        .map(|brew| brew.to_string_lossy().to_string())
+        .unwrap_or_else(|| "brew".to_string())
+}
+
+// HOMEBREW_PREFIX is exported by `brew shellenv`, so it's usually there without having to ask
+// brew, which takes a while to start. Cached for the whole run
+pub fn get_brew_prefix() -> Option<String> {
+    static PREFIX: OnceLock<Option<String>> = OnceLock::new();
+
+    PREFIX
+        .get_or_init(|| {
+            if let Some(prefix) = std::env::var("HOMEBREW_PREFIX")
+                .ok()
+                .filter(|prefix| !prefix.is_empty())
+            {
+                return Some(prefix);
+            }
+
+            let output = process::query_command(get_brew_executable()?)
+                .arg("--prefix")
+                .output()
+                .ok()
+                .filter(|output| output.status.success())?;
+
+            let prefix = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if prefix.is_empty() {
+                None
+            } else {
+                Some(prefix)
+            }
+        })
+        .clone()
+}
+
+// Whether the binary was linked into a Homebrew prefix's bin or sbin dir
+pub fn is_in_brew_prefix(bin_path: &Path) -> bool {
+    if get_brew_executable().is_none() {
+        return false;
+    }
+
+    get_brew_prefix()
+        .into_iter()
+        .chain(DEFAULT_PREFIXES.iter().map(|prefix| prefix.to_string()))
+        .any(|prefix| {
+            let prefix = Path::new(&prefix);
+            paths::starts_with(bin_path, &prefix.join("bin"))
+                || paths::starts_with(bin_path, &prefix.join("sbin"))
+        })
+}
+
+// For binaries that aren't a symlink into the Cellar, look for the installed formulas
+// whose opt dir provides the binary
+pub fn find_formulas_providing_bin(bin_name: &str) -> Vec<Candidate> {
+    let opt_dir = match get_brew_prefix() {
+        Some(prefix) => Path::new(&prefix).join("opt"),
+        None => return Vec::new(),
+    };
+    get_installed_packages()
+        .into_iter()
+        .filter(|package| !package.cask)
+        .filter_map(|package| {
+            let formula_dir = opt_dir.join(&package.name);
+            let provides_bin = formula_dir.join("bin").join(bin_name).exists()
+                || formula_dir.join("sbin").join(bin_name).exists();
+            provides_bin.then_some(Candidate {
+                package_name: package.name,
+                version: package.installed_version,
+                path: formula_dir,
+            })
+        })
+        .collect()
+}
+
+// Everything a formula links into the prefix, from its opt dir
+pub fn get_formula_bins(formula: &str) -> Vec<String> {
+    let formula_dir = match get_brew_prefix() {
+        Some(prefix) => Path::new(&prefix).join("opt").join(formula),
+        None => return Vec::new(),
+    };
+
+    let mut bins: Vec<String> = ["bin", "sbin"]
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(formula_dir.join(dir)).ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    bins.sort();
+    bins
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BREW_INFO: &str = r#"{
+        "formulae": [
+            {
+                "name": "ripgrep",
+                "full_name": "ripgrep",
+                "aliases": ["rg"],
+                "oldnames": [],
+                "versions": {"stable": "14.1.1", "head": "HEAD"},
+                "urls": {"stable": {"url": "https://github.com/BurntSushi/ripgrep/archive/refs/tags/14.1.1.tar.gz"}},
+                "homepage": "https://github.com/BurntSushi/ripgrep",
+                "linked_keg": "14.1.0",
+                "installed": [{"version": "14.0.3"}, {"version": "14.1.0"}],
+                "outdated": true,
+                "pinned": false
+            },
+            {
+                "name": "neovim",
+                "full_name": "neovim",
+                "oldname": "nvim-old",
+                "versions": {"stable": "0.10.2"},
+                "urls": {"stable": {"url": "https://example.com/neovim.tar.gz"}},
+                "homepage": "https://neovim.io/",
+                "linked_keg": null,
+                "installed": [{"version": "HEAD-1a2b3c4"}],
+                "pinned": true
+            },
+            {
+                "full_name": "no/name/formula"
+            },
+            {
+                "name": "foo",
+                "full_name": "user/tap/foo",
+                "versions": {"stable": "1.0"}
+            }
+        ],
+        "casks": [
+            {
+                "token": "wezterm",
+                "full_token": "wezterm",
+                "old_tokens": ["wez-term"],
+                "installed": "20240203",
+                "version": "20240520",
+                "homepage": "https://wezfurlong.org/wezterm/",
+                "url": "https://github.com/wez/wezterm/releases/download/20240520/WezTerm.zip",
+                "outdated": true
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn parses_brew_info() {
+        let packages = parse_brew_info(BREW_INFO);
+        let names: Vec<&str> = packages
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect();
+        assert_eq!(names, ["ripgrep", "neovim", "foo", "wezterm"]);
+
+        let ripgrep = &packages[0];
+        assert_eq!(ripgrep.aliases, ["rg"]);
+        // the linked keg rather than the newest installed one
+        assert_eq!(ripgrep.installed_version.as_deref(), Some("14.1.0"));
+        assert_eq!(ripgrep.latest_version.as_deref(), Some("14.1.1"));
+        assert!(ripgrep.outdated && !ripgrep.pinned && !ripgrep.head && !ripgrep.cask);
+
+        let neovim = &packages[1];
+        assert_eq!(neovim.oldnames, ["nvim-old"]);
+        assert_eq!(neovim.installed_version.as_deref(), Some("HEAD-1a2b3c4"));
+        assert!(neovim.head && neovim.pinned && !neovim.outdated);
+
+        let foo = &packages[2];
+        assert_eq!(foo.full_name, "user/tap/foo");
+        assert_eq!(foo.installed_version, None);
+        assert_eq!(foo.homepage, None);
+
+        let wezterm = &packages[3];
+        assert!(wezterm.cask && wezterm.outdated);
+        assert_eq!(wezterm.oldnames, ["wez-term"]);
+        assert_eq!(wezterm.installed_version.as_deref(), Some("20240203"));
+        assert_eq!(wezterm.latest_version.as_deref(), Some("20240520"));
+
+        assert!(parse_brew_info("").is_empty());
+        assert!(parse_brew_info("Error: No available formula").is_empty());
+    }
+
+    #[test]
+    fn repository_url() {
+        let packages = parse_brew_info(BREW_INFO);
+        let urls: Vec<Option<String>> = packages.iter().map(get_repository_url).collect();
+        assert_eq!(
+            urls,
+            [
+                Some("https://github.com/BurntSushi/ripgrep".to_string()),
+                Some("https://neovim.io/".to_string()),
+                None,
+                Some("https://github.com/wez/wezterm".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn formula_from_path() {
+        let formula = |path: &str| get_formula_from_components(Path::new(path));
+        assert_eq!(
+            formula("/opt/homebrew/Cellar/ripgrep/14.1.0/bin/rg").as_deref(),
+            Some("ripgrep")
+        );
+        assert_eq!(
+            formula("/usr/local/Caskroom/wezterm/20240520/wezterm").as_deref(),
+            Some("wezterm")
+        );
+        // `opt` only counts next to a Cellar
+        assert_eq!(formula("/opt/tool/bin/tool"), None);
+        assert_eq!(formula("/usr/bin/rg"), None);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::history;
+use crate::l10n;
+use crate::paths;
+
+// How often the reminder looks at the history, opening a new shell shouldn't repeat it
+const REMIND_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+// What to put in the shell's startup file, e.g. `eval "$(update-bin hook zsh)"`
+pub fn get_hook(shell: &str, days: u64) -> String {
+    match shell {
+        "fish" => format!(
+            "if command -q update-bin\n    update-bin remind --days {}\nend",
+            days
+        ),
+        "pwsh" => format!(
+            "if (Get-Command update-bin -ErrorAction SilentlyContinue) {{ update-bin remind --days {} }}",
+            days
+        ),
+        _ => format!(
+            "if command -v update-bin >/dev/null 2>&1; then\n  update-bin remind --days {}\nfi",
+            days
+        ),
+    }
+}
+
+// One line about the tools update-bin has updated before that haven't been brought up to date
+// in `days`, at most once a day. Only reads files, so it's fast enough for shell startup
+pub fn remind(days: u64) -> Result<(), String> {
+    let now = history::now();
+    let stamp_path = paths::get_cache_dir().map(|cache_dir| cache_dir.join("last-reminder"));
+    if let Some(stamp_path) = &stamp_path {
+        let last_reminder = std::fs::read_to_string(stamp_path)
+            .ok()
+            .and_then(|stamp| stamp.trim().parse::<u64>().ok());
+        if let Some(last_reminder) = last_reminder {
+            if now.saturating_sub(last_reminder) < REMIND_INTERVAL_SECS {
+                return Ok(());
+            }
+        }
+    }
+
+    let stale_bins: Vec<(String, u64)> = history::get_last_up_to_date(&history::read_history())
+        .into_iter()
+        .filter(|(_, timestamp)| now.saturating_sub(*timestamp) > days * 24 * 60 * 60)
+        .collect();
+
+    if !stale_bins.is_empty() {
+        let names: Vec<&str> = stale_bins
+            .iter()
+            .take(3)
+            .map(|(bin_name, _)| bin_name.as_str())
+            .collect();
+        let more = stale_bins.len().saturating_sub(names.len());
+        let more = if more > 0 {
+            format!(
+                " {}",
+                l10n::text("remind-more", &[("count", &more.to_string())])
+            )
+        } else {
+            String::new()
+        };
+        let id = if stale_bins.len() == 1 {
+            "remind-one"
+        } else {
+            "remind"
+        };
+        println!(
+            "{}",
+            l10n::text(
+                id,
+                &[
+                    ("bins", &format!("{}{}", names.join(", "), more)),
+                    ("days", &days.to_string()),
+                    ("bin", names[0])
+                ]
+            )
+        );
+    }
+
+    // a reminder that can't be rate limited is still better than none
+    if let Some(stamp_path) = stamp_path {
+        if let Some(cache_dir) = stamp_path.parent() {
+            let _ = std::fs::create_dir_all(cache_dir);
+        }
+        let _ = std::fs::write(stamp_path, now.to_string());
+    }
+    Ok(())
+}
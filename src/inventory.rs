@@ -0,0 +1,125 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// A package installed globally with one of the package managers
+pub struct InstalledPackage {
+    pub package_manager: String,
+    pub name: String,
+    pub version: String,
+}
+
+// Package URLs (https://github.com/package-url/purl-spec), Homebrew has no purl type of its own
+pub fn get_purl(package: &InstalledPackage) -> String {
+    let (purl_type, namespace, name) = match package.package_manager.as_str() {
+        "npm" | "pnpm" | "yarn" | "bun" => match package.name.split_once('/') {
+            // the `@` of a scope is percent-encoded
+            Some((scope, name)) => ("npm", Some(scope.replace('@', "%40")), name),
+            None => ("npm", None, package.name.as_str()),
+        },
+        "cargo" => ("cargo", None, package.name.as_str()),
+        manager => ("generic", Some(manager.to_string()), package.name.as_str()),
+    };
+
+    match namespace {
+        Some(namespace) => format!(
+            "pkg:{}/{}/{}@{}",
+            purl_type, namespace, name, package.version
+        ),
+        None => format!("pkg:{}/{}@{}", purl_type, name, package.version),
+    }
+}
+
+// A CycloneDX 1.5 SBOM with one component per package
+pub fn to_cyclonedx(packages: &[InstalledPackage]) -> serde_json::Value {
+    let components: Vec<serde_json::Value> = packages
+        .iter()
+        .map(|package| {
+            let purl = get_purl(package);
+            serde_json::json!({
+                "type": "application",
+                // npm and pnpm can both have the same package installed
+                "bom-ref": format!("{}:{}", package.package_manager, purl),
+                "name": package.name,
+                "version": package.version,
+                "purl": purl,
+                "properties": [
+                    { "name": "update-bin:package-manager", "value": package.package_manager },
+                ],
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "timestamp": format_timestamp(SystemTime::now()),
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": "update-bin",
+                    "version": env!("CARGO_PKG_VERSION"),
+                }],
+            },
+        },
+        "components": components,
+    })
+}
+
+// The packages of an SBOM written by `update-bin export`, components without the
+// package-manager property were added by something else
+pub fn from_cyclonedx(sbom: &serde_json::Value) -> Vec<InstalledPackage> {
+    let empty_components = Vec::new();
+    sbom["components"]
+        .as_array()
+        .unwrap_or(&empty_components)
+        .iter()
+        .filter_map(|component| {
+            let package_manager = component["properties"]
+                .as_array()?
+                .iter()
+                .find(|property| property["name"] == "update-bin:package-manager")?["value"]
+                .as_str()?;
+            Some(InstalledPackage {
+                package_manager: package_manager.to_string(),
+                name: component["name"].as_str()?.to_string(),
+                version: component["version"].as_str()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+// `2024-05-01T12:00:00Z`, converting days since the epoch to a civil date
+// (https://howardhinnant.github.io/date_algorithms.html#civil_from_days)
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let (days, secs_of_day) = ((secs / 86400) as i64, secs % 86400);
+
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
@@ -0,0 +1,208 @@
+use crate::inventory;
+use crate::l10n;
+use crate::process;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+pub enum Outcome {
+    Passed,
+    Failed(String),
+    Skipped(String),
+}
+
+// An update as a JUnit test case, grouped into a test suite per package manager
+pub struct TestCase {
+    pub package_manager: String,
+    pub name: String,
+    pub duration: Duration,
+    pub outcome: Outcome,
+    pub output: Vec<String>,
+}
+
+// `--output junit=<path>`
+pub fn get_path(output: Option<&str>) -> Option<&Path> {
+    output?.strip_prefix("junit=").map(Path::new)
+}
+
+pub fn write_junit(path: &Path, test_cases: &[TestCase]) -> Result<(), String> {
+    std::fs::write(path, to_junit(test_cases)).map_err(|e| {
+        l10n::text(
+            "failed-to-write-junit",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })
+}
+
+fn to_junit(test_cases: &[TestCase]) -> String {
+    let count = |cases: &[&TestCase], failed: bool| {
+        cases
+            .iter()
+            .filter(|case| match case.outcome {
+                Outcome::Failed(_) => failed,
+                Outcome::Skipped(_) => !failed,
+                Outcome::Passed => false,
+            })
+            .count()
+    };
+    let all_cases: Vec<&TestCase> = test_cases.iter().collect();
+    let total_secs: f64 = test_cases
+        .iter()
+        .map(|case| case.duration.as_secs_f64())
+        .sum();
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"update-bin\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+        all_cases.len(),
+        count(&all_cases, true),
+        count(&all_cases, false),
+        total_secs
+    );
+
+    // the machine is what tells results from a fleet apart
+    let hostname = get_hostname().unwrap_or_else(|| "localhost".to_string());
+    let timestamp = inventory::format_timestamp(SystemTime::now());
+    let mut package_managers: Vec<&str> = Vec::new();
+    for case in test_cases {
+        if !package_managers.contains(&case.package_manager.as_str()) {
+            package_managers.push(&case.package_manager);
+        }
+    }
+    for package_manager in package_managers {
+        let cases: Vec<&TestCase> = test_cases
+            .iter()
+            .filter(|case| case.package_manager == package_manager)
+            .collect();
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\" timestamp=\"{}\" hostname=\"{}\">\n",
+            escape_xml(package_manager),
+            cases.len(),
+            count(&cases, true),
+            count(&cases, false),
+            cases.iter().map(|case| case.duration.as_secs_f64()).sum::<f64>(),
+            timestamp,
+            escape_xml(&hostname)
+        ));
+        for case in cases {
+            xml.push_str(&format!(
+                "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\">\n",
+                escape_xml(&case.package_manager),
+                escape_xml(&case.name),
+                case.duration.as_secs_f64()
+            ));
+            match &case.outcome {
+                Outcome::Passed => {}
+                Outcome::Failed(message) => xml.push_str(&format!(
+                    "      <failure message=\"{}\">{}</failure>\n",
+                    escape_xml(message.lines().next().unwrap_or_default()),
+                    escape_xml(message)
+                )),
+                Outcome::Skipped(message) => xml.push_str(&format!(
+                    "      <skipped message=\"{}\"/>\n",
+                    escape_xml(message)
+                )),
+            }
+            if !case.output.is_empty() {
+                xml.push_str(&format!(
+                    "      <system-out>{}</system-out>\n",
+                    escape_xml(&case.output.join("\n"))
+                ));
+            }
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn get_hostname() -> Option<String> {
+    if let Some(hostname) = ["COMPUTERNAME", "HOSTNAME"]
+        .into_iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+    {
+        return Some(hostname);
+    }
+    let output = process::query_command("hostname").output().ok()?;
+    let hostname = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(hostname).filter(|hostname| output.status.success() && !hostname.is_empty())
+}
+
+// Managers color their output, and escape sequences aren't allowed in XML even when escaped
+fn escape_xml(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_case(package_manager: &str, name: &str, outcome: Outcome) -> TestCase {
+        TestCase {
+            package_manager: package_manager.to_string(),
+            name: name.to_string(),
+            duration: Duration::from_millis(1500),
+            outcome,
+            output: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn junit_path() {
+        assert_eq!(
+            get_path(Some("junit=report.xml")),
+            Some(Path::new("report.xml"))
+        );
+        assert_eq!(get_path(Some("json")), None);
+        assert_eq!(get_path(None), None);
+    }
+
+    #[test]
+    fn escapes_xml() {
+        assert_eq!(
+            escape_xml("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+        // color codes are dropped, line breaks and tabs kept
+        assert_eq!(
+            escape_xml("\x1b[31merror\x1b[0m\n\tdone"),
+            "[31merror[0m\n\tdone"
+        );
+    }
+
+    #[test]
+    fn test_suite_per_package_manager() {
+        let mut failed = test_case("npm", "a&b", Outcome::Failed("exit 1\nmore".to_string()));
+        failed.output = vec!["line <1>".to_string(), "line 2".to_string()];
+        let xml = to_junit(&[
+            test_case("cargo", "ripgrep", Outcome::Passed),
+            failed,
+            test_case("cargo", "bat", Outcome::Skipped("pinned".to_string())),
+        ]);
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(xml.contains(
+            "<testsuites name=\"update-bin\" tests=\"3\" failures=\"1\" skipped=\"1\" time=\"4.500\">"
+        ));
+        assert!(xml.contains(
+            "<testsuite name=\"cargo\" tests=\"2\" failures=\"0\" errors=\"0\" skipped=\"1\" time=\"3.000\""
+        ));
+        assert!(xml.contains(
+            "<testsuite name=\"npm\" tests=\"1\" failures=\"1\" errors=\"0\" skipped=\"0\" time=\"1.500\""
+        ));
+        // suites keep the order their managers first appear in
+        assert!(xml.find("name=\"cargo\"") < xml.find("name=\"npm\""));
+        assert!(xml.contains("<testcase classname=\"npm\" name=\"a&amp;b\" time=\"1.500\">"));
+        assert!(xml.contains("<failure message=\"exit 1\">exit 1\nmore</failure>"));
+        assert!(xml.contains("<skipped message=\"pinned\"/>"));
+        assert!(xml.contains("<system-out>line &lt;1&gt;\nline 2</system-out>"));
+        assert_eq!(xml.matches("<system-out>").count(), 1);
+        assert!(xml.ends_with("  </testsuite>\n</testsuites>\n"));
+    }
+}
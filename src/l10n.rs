@@ -0,0 +1,250 @@
+use crate::config;
+use crate::style;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+// English is built in and has every message, a translation only needs the ones it translates
+const BASE_BUNDLE: &str = include_str!("../locales/en.ftl");
+
+// Translations that ship with update-bin, as (language, bundle)
+const BUNDLED_TRANSLATIONS: [(&str, &str); 1] = [("de", include_str!("../locales/de.ftl"))];
+
+// The message in the user's language, with `{ $name }` placeables filled in, falling back to
+// English and then to the message id. In plain mode without its emoji, see style::text
+pub fn text(id: &str, args: &[(&str, &str)]) -> String {
+    static BUNDLES: OnceLock<Vec<HashMap<String, String>>> = OnceLock::new();
+
+    let bundles = BUNDLES.get_or_init(load_bundles);
+    style::text(&format_pattern(find_message(bundles, id), args))
+}
+
+// The first bundle that has the message, in the order load_bundles puts them
+fn find_message<'a>(bundles: &'a [HashMap<String, String>], id: &'a str) -> &'a str {
+    bundles
+        .iter()
+        .find_map(|bundle| bundle.get(id))
+        .map(|pattern| pattern.as_str())
+        .unwrap_or(id)
+}
+
+// The translation first and English last. A translation in the config dir
+// (`locales/<language>.ftl` next to config.json) takes precedence over a bundled one, so
+// translators can try theirs without building update-bin
+fn load_bundles() -> Vec<HashMap<String, String>> {
+    let mut bundles = Vec::new();
+    for language in get_languages() {
+        let locales_dir =
+            config::get_config_path().and_then(|path| Some(path.parent()?.join("locales")));
+        if let Some(content) = locales_dir
+            .and_then(|dir| std::fs::read_to_string(dir.join(format!("{}.ftl", language))).ok())
+        {
+            bundles.push(parse_bundle(&content));
+        }
+        if let Some((_, content)) = BUNDLED_TRANSLATIONS
+            .iter()
+            .find(|(bundled_language, _)| *bundled_language == language)
+        {
+            bundles.push(parse_bundle(content));
+        }
+    }
+    bundles.push(parse_bundle(BASE_BUNDLE));
+    bundles
+}
+
+// The config's `language`, or the locale from LC_ALL, LC_MESSAGES or LANG, e.g. `pt_BR.UTF-8`
+// tries `pt-BR` and then `pt`
+fn get_languages() -> Vec<String> {
+    let language = config::load_config()
+        .ok()
+        .and_then(|config| config.get_language().map(|language| language.to_string()))
+        .or_else(|| {
+            ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .iter()
+                .find_map(|variable| {
+                    std::env::var(variable)
+                        .ok()
+                        .filter(|value| !value.is_empty())
+                })
+        });
+    let language = match language {
+        Some(language) => language,
+        None => return Vec::new(),
+    };
+    let language = language
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-");
+    if language.is_empty() || language == "C" || language == "POSIX" {
+        return Vec::new();
+    }
+
+    let mut languages = vec![language.clone()];
+    if let Some((base, _)) = language.split_once('-') {
+        languages.push(base.to_string());
+    }
+    languages
+}
+
+// The part of Fluent (https://projectfluent.org/fluent/guide/) that messages use: `id = value`,
+// values continued on indented lines, `#` comments and `{ $name }` placeables
+fn parse_bundle(content: &str) -> HashMap<String, String> {
+    let mut messages = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+    for line in content.lines() {
+        let is_continuation = line.starts_with([' ', '\t']) && !line.trim().is_empty();
+        if is_continuation {
+            if let Some((_, value)) = current.as_mut() {
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some((id, value)) = current.take() {
+            messages.insert(id, value);
+        }
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        if let Some((id, value)) = line.split_once('=') {
+            current = Some((id.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    if let Some((id, value)) = current {
+        messages.insert(id, value);
+    }
+    messages
+}
+
+fn format_pattern(pattern: &str, args: &[(&str, &str)]) -> String {
+    let mut text = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => {
+                text.push_str(&rest[start..]);
+                rest = "";
+                break;
+            }
+        };
+        let placeable = rest[start + 1..end].trim();
+        let value = match placeable.strip_prefix('$') {
+            Some(name) => args
+                .iter()
+                .find(|(arg, _)| *arg == name)
+                .map(|(_, value)| value.to_string())
+                .unwrap_or_else(|| format!("{{${}}}", name)),
+            // `{ "{" }`, a string literal
+            None => placeable.trim_matches('"').to_string(),
+        };
+        text.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bundle_messages() {
+        let bundle = parse_bundle(
+            "\
+# a comment = not a message
+## a group comment
+
+greeting = Hello, { $name }!
+multi-line = First line
+    second line
+  third line
+
+after-blank = Still { $count }
+",
+        );
+        assert_eq!(bundle.len(), 3);
+        assert_eq!(bundle["greeting"], "Hello, { $name }!");
+        assert_eq!(bundle["multi-line"], "First line\nsecond line\nthird line");
+        assert_eq!(bundle["after-blank"], "Still { $count }");
+        assert!(!bundle.contains_key("# a comment"));
+    }
+
+    #[test]
+    fn parse_bundle_value_on_next_line() {
+        let bundle = parse_bundle("long =\n    The value\n    goes on\nnext = x\n");
+        assert_eq!(bundle["long"], "The value\ngoes on");
+        assert_eq!(bundle["next"], "x");
+    }
+
+    #[test]
+    fn format_pattern_placeables() {
+        assert_eq!(
+            format_pattern(
+                "Updated { $package } to {$version}",
+                &[("package", "rg"), ("version", "14.1.0")]
+            ),
+            "Updated rg to 14.1.0"
+        );
+        assert_eq!(
+            format_pattern(
+                "{ $old-version } -> { $new-version }",
+                &[("old-version", "1"), ("new-version", "2")]
+            ),
+            "1 -> 2"
+        );
+        // a missing argument stays visible instead of disappearing
+        assert_eq!(format_pattern("Hi { $name }", &[]), "Hi {$name}");
+        assert_eq!(format_pattern("{ \"{\" }literal", &[]), "{literal");
+        assert_eq!(format_pattern("unclosed { $name", &[]), "unclosed { $name");
+    }
+
+    #[test]
+    fn find_message_falls_back() {
+        let translation = parse_bundle("greeting = Hallo\n");
+        let local = parse_bundle("greeting = Servus\n");
+        let base = parse_bundle("greeting = Hello\nfarewell = Bye\n");
+        let bundles = vec![local, translation, base];
+        assert_eq!(find_message(&bundles, "greeting"), "Servus");
+        assert_eq!(find_message(&bundles[1..], "greeting"), "Hallo");
+        assert_eq!(find_message(&bundles, "farewell"), "Bye");
+        assert_eq!(find_message(&bundles, "missing-id"), "missing-id");
+    }
+
+    #[test]
+    fn bundled_translations_fall_back_to_english_per_message() {
+        let base = parse_bundle(BASE_BUNDLE);
+        for (_, content) in BUNDLED_TRANSLATIONS {
+            let translation = parse_bundle(content);
+            let bundles = vec![translation.clone(), base.clone()];
+            for (id, pattern) in &base {
+                let expected = translation.get(id).unwrap_or(pattern);
+                assert_eq!(find_message(&bundles, id), expected);
+            }
+            // a message a translation drops falls back too
+            let mut partial = translation.clone();
+            let id = partial.keys().min().cloned().unwrap();
+            partial.remove(&id);
+            assert_eq!(find_message(&[partial, base.clone()], &id), base[&id]);
+        }
+    }
+
+    #[test]
+    fn bundled_translations_only_have_english_ids() {
+        let base = parse_bundle(BASE_BUNDLE);
+        for (language, content) in BUNDLED_TRANSLATIONS {
+            for id in parse_bundle(content).keys() {
+                assert!(
+                    base.contains_key(id),
+                    "{} has {} that en.ftl doesn't",
+                    language,
+                    id
+                );
+            }
+        }
+    }
+}
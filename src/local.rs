@@ -0,0 +1,254 @@
+use crate::l10n;
+use std::path::{Path, PathBuf};
+
+// A binary of the project in the current dir, from its node_modules or the active virtualenv,
+// updated as the project's dependency rather than a global package
+pub struct LocalBin {
+    // npm, pnpm, yarn or bun from the project's lockfile, or pip for a virtualenv
+    pub package_manager: String,
+    pub package_name: String,
+    pub version: Option<String>,
+    // the project for node, the virtualenv for pip
+    pub dir: PathBuf,
+}
+
+pub fn find_local_bin(bin_name: &str) -> Result<LocalBin, String> {
+    let current_dir = std::env::current_dir()
+        .map_err(|e| l10n::text("failed-to-get-current-dir", &[("error", &e.to_string())]))?;
+    if let Some(project_dir) = current_dir
+        .ancestors()
+        .find(|dir| has_node_bin(dir, bin_name))
+    {
+        return find_node_dependency(project_dir, bin_name);
+    }
+
+    let venv = std::env::var_os("VIRTUAL_ENV").filter(|venv| !venv.is_empty());
+    if let Some(venv) = venv.map(PathBuf::from) {
+        if let Some(bin_path) = find_venv_bin(&venv, bin_name) {
+            return find_venv_distribution(&venv, bin_name).ok_or_else(|| {
+                l10n::text(
+                    "local-venv-no-script",
+                    &[("bin-path", &bin_path.display().to_string())],
+                )
+            });
+        }
+    }
+
+    Err(l10n::text(
+        "local-not-found",
+        &[
+            ("bin", bin_name),
+            ("current-dir", &current_dir.display().to_string()),
+        ],
+    ))
+}
+
+// npm and yarn link the bins, pnpm writes scripts, and all of them write .cmd shims on Windows
+fn has_node_bin(dir: &Path, bin_name: &str) -> bool {
+    let bin_dir = dir.join("node_modules").join(".bin");
+    bin_dir.join(bin_name).exists() || bin_dir.join(format!("{}.cmd", bin_name)).exists()
+}
+
+// Only a direct dependency is the project's to update, one of a dependency moves with it
+fn find_node_dependency(project_dir: &Path, bin_name: &str) -> Result<LocalBin, String> {
+    let package_json = read_json(&project_dir.join("package.json")).ok_or_else(|| {
+        l10n::text(
+            "local-no-package-json",
+            &[("project-dir", &project_dir.display().to_string())],
+        )
+    })?;
+    let mut dependencies = Vec::new();
+    for key in ["dependencies", "devDependencies", "optionalDependencies"] {
+        if let Some(declared) = package_json[key].as_object() {
+            dependencies.extend(declared.keys().cloned());
+        }
+    }
+
+    let node_modules_dir = project_dir.join("node_modules");
+    for dependency in dependencies {
+        let dependency_dir = dependency
+            .split('/')
+            .fold(node_modules_dir.clone(), |dir, part| dir.join(part));
+        let dependency_json = match read_json(&dependency_dir.join("package.json")) {
+            Some(dependency_json) => dependency_json,
+            None => continue,
+        };
+        // `"bin": "cli.js"` is named after the package, without its scope
+        let provides_bin = match &dependency_json["bin"] {
+            serde_json::Value::String(_) => dependency.rsplit('/').next() == Some(bin_name),
+            serde_json::Value::Object(bins) => bins.contains_key(bin_name),
+            _ => false,
+        };
+        if provides_bin {
+            return Ok(LocalBin {
+                package_manager: get_project_package_manager(project_dir).to_string(),
+                package_name: dependency,
+                version: dependency_json["version"]
+                    .as_str()
+                    .map(|version| version.to_string()),
+                dir: project_dir.to_path_buf(),
+            });
+        }
+    }
+    Err(l10n::text(
+        "local-not-a-dependency",
+        &[
+            ("bin", bin_name),
+            (
+                "node-modules-dir",
+                &node_modules_dir.join(".bin").display().to_string(),
+            ),
+        ],
+    ))
+}
+
+// The lockfile tells which manager the project uses, npm without one
+fn get_project_package_manager(project_dir: &Path) -> &'static str {
+    let lockfiles = [
+        ("pnpm-lock.yaml", "pnpm"),
+        ("yarn.lock", "yarn"),
+        ("bun.lock", "bun"),
+        ("bun.lockb", "bun"),
+    ];
+    lockfiles
+        .into_iter()
+        .find(|(lockfile, _)| project_dir.join(lockfile).is_file())
+        .map(|(_, package_manager)| package_manager)
+        .unwrap_or("npm")
+}
+
+fn find_venv_bin(venv: &Path, bin_name: &str) -> Option<PathBuf> {
+    let bin_dir = get_venv_bin_dir(venv);
+    [bin_name.to_string(), format!("{}.exe", bin_name)]
+        .into_iter()
+        .map(|name| bin_dir.join(name))
+        .find(|path| path.is_file())
+}
+
+fn get_venv_bin_dir(venv: &Path) -> PathBuf {
+    if cfg!(windows) {
+        venv.join("Scripts")
+    } else {
+        venv.join("bin")
+    }
+}
+
+pub fn get_venv_python(venv: &Path) -> PathBuf {
+    if cfg!(windows) {
+        venv.join("Scripts").join("python.exe")
+    } else {
+        venv.join("bin").join("python")
+    }
+}
+
+// The distribution whose entry_points.txt declares the script, in
+// `<venv>/lib/python3.x/site-packages` (`<venv>/Lib/site-packages` on Windows)
+fn find_venv_distribution(venv: &Path, bin_name: &str) -> Option<LocalBin> {
+    let site_packages_dirs: Vec<PathBuf> = if cfg!(windows) {
+        vec![venv.join("Lib").join("site-packages")]
+    } else {
+        std::fs::read_dir(venv.join("lib"))
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path().join("site-packages"))
+            .collect()
+    };
+
+    site_packages_dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "dist-info")
+        })
+        .find(|dist_info| declares_script(dist_info, bin_name))
+        .and_then(|dist_info| {
+            let metadata = std::fs::read_to_string(dist_info.join("METADATA")).ok()?;
+            let get_field = |field: &str| {
+                metadata.lines().find_map(|line| {
+                    line.strip_prefix(field)
+                        .map(|value| value.trim().to_string())
+                })
+            };
+            Some(LocalBin {
+                package_manager: "pip".to_string(),
+                package_name: get_field("Name:")?,
+                version: get_field("Version:"),
+                dir: venv.to_path_buf(),
+            })
+        })
+}
+
+// `black = black:patched_main` under [console_scripts] or [gui_scripts]
+fn declares_script(dist_info: &Path, bin_name: &str) -> bool {
+    let entry_points = match std::fs::read_to_string(dist_info.join("entry_points.txt")) {
+        Ok(entry_points) => entry_points,
+        Err(_) => return false,
+    };
+    let mut in_scripts = false;
+    for line in entry_points.lines().map(|line| line.trim()) {
+        if line.starts_with('[') {
+            in_scripts = line == "[console_scripts]" || line == "[gui_scripts]";
+        } else if in_scripts {
+            if let Some((name, _)) = line.split_once('=') {
+                if name.trim() == bin_name {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+// Updating within the range package.json allows only changes the lockfile, `latest` moves the
+// range in package.json to the latest version
+pub fn get_update_args(local: &LocalBin, install_latest: bool) -> Vec<String> {
+    let dir = local.dir.to_string_lossy().to_string();
+    let package = local.package_name.as_str();
+    let latest_package = format!("{}@latest", package);
+    let args: Vec<&str> = match (local.package_manager.as_str(), install_latest) {
+        ("npm", false) => vec!["update", "--prefix", &dir, package],
+        ("npm", true) => vec!["install", "--prefix", &dir, &latest_package],
+        ("pnpm", false) => vec!["update", "--dir", &dir, package],
+        ("pnpm", true) => vec!["update", "--dir", &dir, "--latest", package],
+        // Yarn Berry's `up` always goes to the latest version
+        ("yarn", _) if is_yarn_berry(&local.dir) => vec!["--cwd", &dir, "up", package],
+        ("yarn", false) => vec!["--cwd", &dir, "upgrade", package],
+        ("yarn", true) => vec!["--cwd", &dir, "upgrade", "--latest", package],
+        ("bun", false) => vec!["update", "--cwd", &dir, package],
+        ("bun", true) => vec!["update", "--cwd", &dir, "--latest", package],
+        _ => vec!["-m", "pip", "install", "--upgrade", package],
+    };
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+// Yarn Berry writes a `__metadata:` block at the top of yarn.lock, Classic doesn't
+fn is_yarn_berry(project_dir: &Path) -> bool {
+    std::fs::read_to_string(project_dir.join("yarn.lock"))
+        .is_ok_and(|lockfile| lockfile.contains("__metadata:"))
+}
+
+// The version installed now, after updating
+pub fn get_installed_version(local: &LocalBin, bin_name: &str) -> Option<String> {
+    match local.package_manager.as_str() {
+        "pip" => find_venv_distribution(&local.dir, bin_name)?.version,
+        _ => {
+            let package_json_path = local
+                .package_name
+                .split('/')
+                .fold(local.dir.join("node_modules"), |dir, part| dir.join(part))
+                .join("package.json");
+            read_json(&package_json_path)?["version"]
+                .as_str()
+                .map(|version| version.to_string())
+        }
+    }
+}
+
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
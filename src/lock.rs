@@ -0,0 +1,242 @@
+use crate::homebrew;
+use crate::l10n;
+use crate::runner::{RunOutput, RunStatus};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+// How long to wait for another process to let go of a manager's lock, unless --lock-timeout
+// says otherwise. unattended-upgrades can hold apt's for a few minutes after boot
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(300);
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// What each manager prints when it gives up on a lock right away, for the locks that can't be
+// seen from outside, like Homebrew's on macOS. Only managers whose messages are English whatever
+// the locale, apt, pacman and zypper translate theirs and are told by their lock's holder. dnf
+// and yum wait by themselves
+const LOCK_ERRORS: [(&str, &str); 2] = [
+    ("homebrew", "has already locked"),
+    ("apk", "Unable to lock database"),
+];
+
+// Runs the update once the manager's lock is free, and again when it still failed because of
+// the lock, until `timeout` is up. `report` shows what it's waiting for
+pub fn run_unlocked(
+    package_manager: &str,
+    timeout: Duration,
+    report: &dyn Fn(&str),
+    mut run: impl FnMut() -> Result<RunOutput, String>,
+) -> Result<RunOutput, String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        wait_for_lock(package_manager, timeout, deadline, report)?;
+        let output = run()?;
+        // a lock taken between the check and the update still has its holder
+        let is_locked = matches!(output.status, RunStatus::Failed)
+            && (output
+                .lines
+                .iter()
+                .any(|line| is_lock_error(package_manager, line))
+                || find_lock_holder(package_manager).is_some());
+        if !is_locked || Instant::now() >= deadline {
+            return Ok(output);
+        }
+        report(&l10n::text(
+            "lock-retrying",
+            &[("manager", package_manager)],
+        ));
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn wait_for_lock(
+    package_manager: &str,
+    timeout: Duration,
+    deadline: Instant,
+    report: &dyn Fn(&str),
+) -> Result<(), String> {
+    let mut reported_holder = None;
+    while let Some(holder) = find_lock_holder(package_manager) {
+        if Instant::now() >= deadline {
+            return Err(l10n::text(
+                "lock-still-held",
+                &[
+                    ("manager", package_manager),
+                    ("holder", &holder),
+                    ("seconds", &timeout.as_secs().to_string()),
+                ],
+            ));
+        }
+        if reported_holder.as_ref() != Some(&holder) {
+            report(&l10n::text(
+                "lock-waiting",
+                &[
+                    ("manager", package_manager),
+                    ("holder", &holder),
+                    ("seconds", &timeout.as_secs().to_string()),
+                ],
+            ));
+            reported_holder = Some(holder);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+fn is_lock_error(package_manager: &str, line: &str) -> bool {
+    LOCK_ERRORS
+        .iter()
+        .any(|(manager, error)| *manager == package_manager && line.contains(error))
+}
+
+// Who holds the manager's lock, like `PID 1234 (unattended-upgr)`, `None` when it's free or
+// there's no telling
+fn find_lock_holder(package_manager: &str) -> Option<String> {
+    match package_manager {
+        "apt" => find_file_lock_holder(&[
+            PathBuf::from("/var/lib/dpkg/lock-frontend"),
+            PathBuf::from("/var/lib/dpkg/lock"),
+            PathBuf::from("/var/lib/apt/lists/lock"),
+            PathBuf::from("/var/cache/apt/archives/lock"),
+        ]),
+        "apk" => find_file_lock_holder(&[PathBuf::from("/lib/apk/db/lock")]),
+        // a lock file per formula and for `brew update`, flocked while they run
+        "homebrew" => {
+            let locks_dir = Path::new(&homebrew::get_brew_prefix()?)
+                .join("var")
+                .join("homebrew")
+                .join("locks");
+            let lock_files: Vec<PathBuf> = std::fs::read_dir(locks_dir)
+                .ok()?
+                .flatten()
+                .map(|entry| entry.path())
+                .collect();
+            find_file_lock_holder(&lock_files)
+        }
+        // zypper writes its PID, and leaves the file behind when it's gone
+        "zypper" => {
+            let content = std::fs::read_to_string("/run/zypp.pid").ok()?;
+            let pid: u32 = content.trim().parse().ok().filter(|pid| *pid != 0)?;
+            if !Path::new(&format!("/proc/{}", pid)).exists() {
+                return None;
+            }
+            Some(describe_process(pid))
+        }
+        // the file itself is the lock, and says nothing about who made it
+        "pacman" => Path::new("/var/lib/pacman/db.lck")
+            .exists()
+            .then(|| l10n::text("lock-holder-pacman", &[])),
+        _ => None,
+    }
+}
+
+// The fcntl and flock locks on the files, which Linux lists in /proc/locks with the holder's
+// PID and the file's device and inode. Not knowing (another OS, an unreadable file) is free
+#[cfg(target_os = "linux")]
+fn find_file_lock_holder(lock_files: &[PathBuf]) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let locked_files: Vec<(u64, u64, u64)> = lock_files
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|metadata| {
+            let dev = metadata.dev();
+            let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+            let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+            (major, minor, metadata.ino())
+        })
+        .collect();
+    if locked_files.is_empty() {
+        return None;
+    }
+
+    let locks = std::fs::read_to_string("/proc/locks").ok()?;
+    let (pid, _) = parse_proc_locks(&locks)
+        .into_iter()
+        .find(|(_, file)| locked_files.contains(file))?;
+    // an open file description lock has no single owner, shown as -1
+    Some(match pid {
+        Some(pid) => describe_process(pid),
+        None => l10n::text("lock-holder-unknown", &[]),
+    })
+}
+
+// The held locks in /proc/locks as (PID, (major, minor, inode)), e.g.
+// `1: POSIX  ADVISORY  WRITE 1234 08:01:131075 0 EOF` with the device in hex. Processes
+// waiting for a lock are listed under it as `1: -> POSIX ...`, and hold nothing
+#[cfg(target_os = "linux")]
+fn parse_proc_locks(locks: &str) -> Vec<(Option<u32>, (u64, u64, u64))> {
+    locks
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (pid, file) = match fields.as_slice() {
+                [_, "->", ..] => return None,
+                [_, _, _, _, pid, file, ..] => (*pid, *file),
+                _ => return None,
+            };
+            let mut parts = file.split(':');
+            let major = u64::from_str_radix(parts.next()?, 16).ok()?;
+            let minor = u64::from_str_radix(parts.next()?, 16).ok()?;
+            let inode: u64 = parts.next()?.parse().ok()?;
+            Some((pid.parse().ok(), (major, minor, inode)))
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn find_file_lock_holder(_lock_files: &[PathBuf]) -> Option<String> {
+    None
+}
+
+// `PID 1234 (unattended-upgr)`, with the name when /proc has it
+fn describe_process(pid: u32) -> String {
+    match std::fs::read_to_string(format!("/proc/{}/comm", pid)) {
+        Ok(name) if !name.trim().is_empty() => format!("PID {} ({})", pid, name.trim()),
+        _ => format!("PID {}", pid),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_errors() {
+        assert!(is_lock_error(
+            "homebrew",
+            "Error: A `brew upgrade wget` process has already locked /opt/homebrew/Cellar/wget"
+        ));
+        assert!(is_lock_error(
+            "apk",
+            "ERROR: Unable to lock database: temporary error (try again later)"
+        ));
+        assert!(!is_lock_error("apk", "Error: has already locked"));
+        // translated messages aren't matched, apt's lock is told by its holder
+        assert!(!is_lock_error(
+            "apt",
+            "E: Could not get lock /var/lib/dpkg/lock-frontend"
+        ));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn proc_locks() {
+        let locks = "\
+1: POSIX  ADVISORY  WRITE 1234 08:01:131075 0 EOF
+1: -> POSIX  ADVISORY  WRITE 5678 08:01:131075 0 EOF
+2: FLOCK  ADVISORY  WRITE 910 fd:00:42 0 EOF
+3: OFDLCK ADVISORY  READ  -1 00:19:7 0 EOF
+4: LEASE  ACTIVE    READ 11 00:zz:1 0 EOF
+5: broken
+";
+        assert_eq!(
+            parse_proc_locks(locks),
+            [
+                (Some(1234), (0x08, 0x01, 131075)),
+                (Some(910), (0xfd, 0x00, 42)),
+                (None, (0x00, 0x19, 7)),
+            ]
+        );
+    }
+}
@@ -1,360 +1,6628 @@
-use clap::Parser;
-use serde_json;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
-use std::process::{exit, Command, Stdio};
+mod audit;
+mod cache;
+mod cargo;
+mod channel;
+mod chocolatey;
+mod config;
+mod container;
+mod deno;
+mod diagnose;
+mod digest;
+mod elevate;
+mod gha;
+mod go;
+mod healthcheck;
+mod history;
+mod homebrew;
+mod hook;
+mod http;
+mod inventory;
+mod junit;
+mod l10n;
+mod local;
+mod lock;
+mod manifest;
+mod mapping;
+mod metrics;
+mod notify;
+mod paths;
+mod plan;
+mod policy;
+mod preflight;
+mod process;
+mod progress;
+mod registry;
+mod release;
+mod repair;
+mod report;
+mod runner;
+mod schema;
+mod scoop;
+mod shim;
+mod signature;
+mod snapshot;
+mod stats;
+mod style;
+mod system;
+mod trail;
+mod uv;
+mod version;
+mod version_manager;
+mod winget;
 
-#[derive(Parser)]
+use clap::{Parser, Subcommand};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::time::{Duration, Instant};
+
+#[derive(Parser, Clone)]
 #[command(name = "update-bin")]
 #[command(about = "Update a binary to its latest version by using the original package manager")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
-    bin_name: String,
-    #[arg(long, help = "Display package name and package manager instead of updating")]
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[arg(required_unless_present = "all")]
+    bin_name: Option<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["bin_name", "info", "path"],
+        help = "Update everything installed with every package manager found"
+    )]
+    all: bool,
+    #[arg(
+        long,
+        value_name = "MANAGERS",
+        value_delimiter = ',',
+        conflicts_with = "bin_name",
+        help = "Only update packages of these package managers with --all, e.g. npm,cargo"
+    )]
+    managers: Vec<String>,
+    #[arg(
+        long,
+        value_name = "MANAGERS",
+        value_delimiter = ',',
+        conflicts_with = "bin_name",
+        help = "Leave the packages of these package managers alone with --all, e.g. apt"
+    )]
+    skip_managers: Vec<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "bin_name",
+        help = "Write a report of the --all run to this file, as HTML when it ends in .html and Markdown otherwise"
+    )]
+    report: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "bin_name",
+        help = "Write Prometheus metrics of the --all run and of what's still outdated to this file, for node_exporter's textfile collector"
+    )]
+    metrics_file: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        conflicts_with = "bin_name",
+        help = "Update with this many package managers at the same time with --all, showing their progress in a table"
+    )]
+    jobs: usize,
+    #[arg(
+        long,
+        help = "Display package name and package manager instead of updating"
+    )]
     info: bool,
+    #[arg(
+        long,
+        requires = "info",
+        help = "With --info, list every package manager that could own the binary with the evidence for each, and pick another one to use from now on"
+    )]
+    all_candidates: bool,
+    #[arg(
+        long,
+        value_name = "CHANNEL",
+        conflicts_with_all = ["all", "info"],
+        help = "Move the binary to another release channel and keep it there: stable, an npm dist-tag like next, prerelease for cargo or head for Homebrew"
+    )]
+    channel: Option<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["all", "info"],
+        help = "Show what updating would change, with the new version's size and requirements, instead of updating"
+    )]
+    dry_run: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["all", "info", "dry_run", "path"],
+        help = "Update the binary of the project in the current dir, from node_modules/.bin or the active virtualenv, as the project's dependency"
+    )]
+    local: bool,
+    #[arg(
+        long,
+        help = "Fail when the package manager reports success but the binary didn't change"
+    )]
+    strict: bool,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Update the installation at this path instead of looking the binary up on PATH"
+    )]
+    path: Option<String>,
+    #[arg(long, help = "Run `brew update` before upgrading a Homebrew formula")]
+    brew_update: bool,
+    #[arg(
+        long,
+        help = "Run `brew cleanup` after upgrading with Homebrew, removing the old versions"
+    )]
+    brew_cleanup: bool,
+    #[arg(
+        long,
+        help = "Use sudo to update binaries only root can update, like distro packages or npm globals in /usr"
+    )]
+    sudo: bool,
+    #[arg(
+        long,
+        help = "For tools from asdf or mise, also move the version pinned in .tool-versions or mise.toml to the one installed"
+    )]
+    write_pins: bool,
+    #[arg(
+        long,
+        help = "On Windows, relaunch updates that need administrator rights elevated without asking first"
+    )]
+    elevate: bool,
+    #[arg(
+        long,
+        help = "Make sure the package registry is reachable before updating"
+    )]
+    check_network: bool,
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Kill the update when it takes longer than this"
+    )]
+    timeout: Option<u64>,
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Kill the update when it produces no output for this long"
+    )]
+    idle_timeout: Option<u64>,
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "How long to wait for another process holding the package manager's lock, like apt's during unattended upgrades [default: 300]"
+    )]
+    lock_timeout: Option<u64>,
+    #[arg(
+        long,
+        help = "Treat an update that ends up on a lower version as a success"
+    )]
+    allow_downgrade: bool,
+    #[arg(
+        long,
+        value_name = "STRATEGY",
+        value_parser = ["update", "latest"],
+        help = "How npm, pnpm, yarn and bun update packages: `update` stays within the installed major version, `latest` installs the latest version"
+    )]
+    strategy: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "FORMAT",
+        value_parser = parse_output,
+        help = "`gha` groups each update's output, annotates failures and writes a job summary in GitHub Actions, the default when GITHUB_ACTIONS=true. `junit=<path>` also writes each update as a JUnit test case"
+    )]
+    output: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        help = "Print plain ASCII without emoji, symbols or colors, for screen readers and log files. The default when the terminal or locale can't show Unicode"
+    )]
+    plain: bool,
+}
+
+#[derive(Subcommand, Clone)]
+enum Command {
+    #[command(about = "Print an inventory of the packages installed with each package manager")]
+    Export {
+        #[arg(
+            long,
+            value_parser = ["cyclonedx"],
+            default_value = "cyclonedx",
+            help = "The format of the inventory"
+        )]
+        format: String,
+    },
+    #[command(about = "Print the JSON Schema of the history file and the webhook report")]
+    Schema,
+    #[command(
+        about = "Print a snippet for the shell's startup file that reminds of tools that haven't been updated in a while"
+    )]
+    Hook {
+        #[arg(value_parser = ["zsh", "bash", "fish", "pwsh"])]
+        shell: String,
+        #[arg(
+            long,
+            default_value_t = 30,
+            help = "Remind of tools that haven't been updated for this many days"
+        )]
+        days: u64,
+    },
+    #[command(about = "Remind of tools that haven't been updated in a while, at most once a day")]
+    Remind {
+        #[arg(
+            long,
+            default_value_t = 30,
+            help = "Remind of tools that haven't been updated for this many days"
+        )]
+        days: u64,
+    },
+    #[command(about = "List the installed packages that have a newer version")]
+    Outdated {
+        #[arg(
+            long,
+            help = "Look up every latest version again instead of using the cached ones"
+        )]
+        refresh: bool,
+        #[arg(
+            long,
+            default_value_t = LATEST_VERSION_JOBS,
+            help = "How many latest versions to look up at the same time"
+        )]
+        jobs: usize,
+    },
+    #[command(
+        about = "Check the versions of the installed npm, pnpm, yarn, bun and cargo packages against the vulnerabilities OSV.dev knows of"
+    )]
+    Audit {
+        #[arg(long, help = "Update the vulnerable packages to their latest version")]
+        fix: bool,
+    },
+    #[command(
+        about = "Remove what package managers keep around after updating, like old versions and stale caches"
+    )]
+    Cleanup {
+        #[arg(
+            long,
+            value_name = "MANAGERS",
+            value_delimiter = ',',
+            help = "Only clean up after these package managers, e.g. homebrew,npm"
+        )]
+        managers: Vec<String>,
+    },
+    #[command(
+        about = "Show every package manager detector tried on a binary, what it checked and why it matched or not"
+    )]
+    Explain {
+        bin_name: String,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Explain the installation at this path instead of the one on PATH"
+        )]
+        path: Option<String>,
+    },
+    // what shell completions call, so it isn't listed
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        #[arg(default_value = "")]
+        prefix: String,
+    },
+    #[command(
+        about = "Install and update the tools declared in a Brewfile, .tool-versions, the package.json of a tools workspace, or a Go module's tools.go or .tool-deps"
+    )]
+    Sync {
+        #[arg(
+            help = "The files to read, the Brewfile, .tool-versions, package.json, tools.go and .tool-deps in the current dir by default"
+        )]
+        files: Vec<PathBuf>,
+        #[arg(
+            long,
+            help = "Write the versions of the Go tools installed back to .tool-deps, or to go.mod for tools.go"
+        )]
+        write: bool,
+    },
+    #[command(
+        about = "Update everything like --all, for meta-updaters like topgrade: never asks, exits with 0 when done, 1 when an update failed, 2 on invalid options or config and 3 when no package manager is installed"
+    )]
+    Step {
+        #[arg(
+            long,
+            help = "Print NDJSON events on stdout instead of the managers' output"
+        )]
+        json: bool,
+        #[arg(
+            long,
+            value_name = "MANAGERS",
+            value_delimiter = ',',
+            help = "Only update packages of these package managers, e.g. npm,cargo"
+        )]
+        managers: Vec<String>,
+        #[arg(
+            long,
+            value_name = "MANAGERS",
+            value_delimiter = ',',
+            help = "Leave the packages of these package managers alone, e.g. apt"
+        )]
+        skip_managers: Vec<String>,
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 1,
+            help = "Update with this many package managers at the same time"
+        )]
+        jobs: usize,
+        #[arg(
+            long,
+            help = "Use sudo for the package managers only root can update, without a prompt for a password"
+        )]
+        sudo: bool,
+    },
+    #[command(
+        about = "Update the package managers themselves, an outdated one is often why updating packages fails"
+    )]
+    Managers,
+    #[command(
+        about = "Find binaries installed more than once, by different package managers, and offer to uninstall the redundant copy"
+    )]
+    Dedupe {
+        #[arg(
+            help = "The binaries to check, every one that's in more than one PATH dir by default"
+        )]
+        bins: Vec<String>,
+    },
+    #[command(
+        about = "Make sure tools are at least at a version, updating or installing the ones that aren't"
+    )]
+    Ensure {
+        #[arg(
+            required = true,
+            value_name = "CONSTRAINT",
+            help = "A binary and the lowest version it may be at, like 'node>=20', or only its name to have it installed"
+        )]
+        constraints: Vec<String>,
+        #[arg(
+            long,
+            value_name = "MANAGER",
+            help = "Install missing binaries with homebrew, npm or cargo, as the package of the same name"
+        )]
+        install_with: Option<String>,
+    },
+    #[command(
+        about = "Write the updates for a machine without network access to a file, to make there with `update-bin apply`"
+    )]
+    Plan {
+        #[arg(help = "Where to write the plan")]
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "The SBOM `update-bin export` wrote on the machine without network access, the packages installed here by default"
+        )]
+        inventory: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Also download the packages the plan needs into this directory"
+        )]
+        artifacts: Option<PathBuf>,
+    },
+    #[command(
+        about = "Make the updates of a plan written by `update-bin plan`, installing the packages from a directory instead of the network"
+    )]
+    Apply {
+        file: PathBuf,
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "The directory with the packages `update-bin plan --artifacts` downloaded"
+        )]
+        artifacts: PathBuf,
+        #[arg(
+            long,
+            help = "Refuse to install a package whose signature couldn't be verified: the registry's, checked with openssl, or a <package>.asc next to it, checked with gpg"
+        )]
+        require_signature: bool,
+    },
+    #[command(
+        about = "Print every tool installed with a user package manager, with its exact version and where it came from, for `update-bin restore`"
+    )]
+    Snapshot,
+    #[command(
+        about = "Install the tools of a snapshot written by `update-bin snapshot`, at the versions it has where the package manager can"
+    )]
+    Restore { file: PathBuf },
+    #[command(
+        about = "Register a binary no package manager owns, like one built from source, with the command that updates it"
+    )]
+    Adopt {
+        bin_name: String,
+        #[arg(
+            long,
+            value_name = "COMMAND",
+            help = "The shell command that updates the binary"
+        )]
+        update_cmd: String,
+        #[arg(
+            long,
+            value_name = "COMMAND",
+            help = "The shell command that prints the binary's version, `<bin> --version` by default"
+        )]
+        version_cmd: Option<String>,
+    },
+    #[command(about = "Summarize the history of updates")]
+    Stats {
+        #[arg(
+            long,
+            default_value_t = 30,
+            help = "List tools that haven't been updated for this many days"
+        )]
+        days: u64,
+    },
+}
+
+fn main() {
+    let args = Args::parse();
+    if args.plain {
+        style::set_plain();
+    }
+
+    let result = match (&args.command, &args.bin_name) {
+        (Some(Command::Export { .. }), _) => export_inventory(),
+        (Some(Command::Schema), _) => print_json(&schema::get_schemas()),
+        (Some(Command::Hook { shell, days }), _) => {
+            println!("{}", hook::get_hook(shell, *days));
+            Ok(())
+        }
+        (Some(Command::Remind { days }), _) => hook::remind(*days),
+        (Some(Command::Stats { days }), _) => stats::print_stats(*days),
+        (Some(Command::Outdated { refresh, jobs }), _) => print_outdated(*refresh, *jobs),
+        (Some(Command::Audit { fix }), _) => audit_installed_tools(*fix, &args),
+        (Some(Command::Cleanup { managers }), _) => cleanup_managers(managers, &args),
+        (Some(Command::Managers), _) => update_package_managers(&args),
+        (Some(Command::Dedupe { bins }), _) => dedupe_installations(bins),
+        (
+            Some(Command::Step {
+                json,
+                managers,
+                skip_managers,
+                jobs,
+                sudo,
+            }),
+            _,
+        ) => {
+            // the same selection as --all's own options
+            let step_args = Args {
+                managers: managers.clone(),
+                skip_managers: skip_managers.clone(),
+                jobs: *jobs,
+                sudo: *sudo,
+                ..args.clone()
+            };
+            exit(run_step(*json, &step_args))
+        }
+        (Some(Command::Sync { files, write }), _) => sync_manifests(files, *write, &args),
+        (
+            Some(Command::Ensure {
+                constraints,
+                install_with,
+            }),
+            _,
+        ) => ensure_versions(constraints, install_with.as_deref(), &args),
+        (
+            Some(Command::Plan {
+                file,
+                inventory,
+                artifacts,
+            }),
+            _,
+        ) => write_update_plan(file, inventory.as_deref(), artifacts.as_deref()),
+        (
+            Some(Command::Apply {
+                file,
+                artifacts,
+                require_signature,
+            }),
+            _,
+        ) => apply_update_plan(file, artifacts, *require_signature, &args),
+        (Some(Command::Snapshot), _) => print_snapshot(),
+        (Some(Command::Restore { file }), _) => restore_snapshot(file, &args),
+        (
+            Some(Command::Adopt {
+                bin_name,
+                update_cmd,
+                version_cmd,
+            }),
+            _,
+        ) => adopt_binary(bin_name, update_cmd, version_cmd.as_deref()),
+        (Some(Command::Explain { bin_name, path }), _) => {
+            explain_detection(&paths::normalize_bin_name(bin_name), path.as_deref())
+        }
+        (Some(Command::Complete { prefix }), _) => {
+            print_completions(prefix);
+            Ok(())
+        }
+        (None, None) => update_all(&args),
+        (None, Some(bin_name)) => {
+            let bin_name = paths::normalize_bin_name(bin_name);
+            match process::check_argument(&bin_name, "binary name") {
+                Err(e) => Err(e),
+                Ok(_) if args.info => {
+                    display_info(&bin_name, args.path.as_deref(), args.all_candidates)
+                }
+                Ok(_) if args.dry_run => preview_update(&bin_name, &args),
+                Ok(_) if args.local => update_local_binary(&bin_name, &args),
+                Ok(_) => {
+                    // update_binary writes the results once the manager ran, so whether the
+                    // file exists afterwards tells if it failed before getting there
+                    let junit_path = junit::get_path(args.output.as_deref());
+                    if let Some(junit_path) = junit_path {
+                        let _ = std::fs::remove_file(junit_path);
+                    }
+                    let result = update_binary(&bin_name, &args);
+                    match (junit_path, &result) {
+                        (Some(junit_path), Err(e)) if !junit_path.exists() => {
+                            let test_case = junit::TestCase {
+                                package_manager: "update-bin".to_string(),
+                                name: bin_name.clone(),
+                                duration: Duration::ZERO,
+                                outcome: junit::Outcome::Failed(e.clone()),
+                                output: Vec::new(),
+                            };
+                            result.and(junit::write_junit(junit_path, &[test_case]))
+                        }
+                        _ => result,
+                    }
+                }
+            }
+        }
+    };
+    if let Err(e) = result {
+        // --all has already annotated each manager that failed
+        let is_update_all = args.command.is_none() && args.bin_name.is_none();
+        if gha::is_enabled(args.output.as_deref()) && !is_update_all {
+            gha::warning(&e);
+        }
+        eprintln!("{}", l10n::text("error", &[("message", &e)]));
+        exit(1);
+    }
+}
+
+fn parse_output(output: &str) -> Result<String, String> {
+    match output {
+        "text" | "gha" => Ok(output.to_string()),
+        _ if junit::get_path(Some(output)).is_some_and(|path| !path.as_os_str().is_empty()) => {
+            Ok(output.to_string())
+        }
+        _ => Err(l10n::text("output-format-expected", &[])),
+    }
+}
+
+fn display_info(
+    bin_name: &str,
+    bin_path: Option<&str>,
+    all_candidates: bool,
+) -> Result<(), String> {
+    let bin_path = match bin_path {
+        Some(bin_path) => check_bin_path(bin_path)?,
+        None => {
+            let bin_paths = find_all_bin_paths(bin_name);
+            if bin_paths.len() > 1 {
+                print_installations(bin_name, &bin_paths);
+            }
+            find_bin_path(bin_name)?
+        }
+    };
+    if let Some(repair) = find_broken_bin(bin_name, &bin_path) {
+        println!(
+            "{}",
+            l10n::text("info-broken", &[("problem", &repair.problem)])
+        );
+        if let Some((command, command_args)) = &repair.command {
+            let command = format!("{} {}", command, command_args.join(" "));
+            println!("{}", l10n::text("info-fix", &[("command", &command)]));
+        }
+        return Ok(());
+    }
+    let package_manager = match detect_package_manager(bin_name, &bin_path) {
+        // the candidates are what's left to go on
+        Err(e) if all_candidates => {
+            println!(
+                "{}",
+                l10n::text("info-package-manager-unknown", &[("error", &e)])
+            );
+            return review_owner_candidates(bin_name, &bin_path, None);
+        }
+        result => result?,
+    };
+    println!(
+        "{}",
+        l10n::text(
+            "info-package-name",
+            &[("package", &package_manager.package_name)]
+        )
+    );
+    println!(
+        "{}",
+        l10n::text(
+            "info-package-manager",
+            &[("manager", &package_manager.name)]
+        )
+    );
+    if all_candidates {
+        return review_owner_candidates(bin_name, &bin_path, Some(&package_manager));
+    }
+    if let Some(linked_dir) = get_linked_package_dir(&bin_path, &package_manager.name) {
+        println!(
+            "{}",
+            l10n::text(
+                "info-linked-to",
+                &[("dir", &linked_dir.display().to_string())]
+            )
+        );
+    }
+    if package_manager.name == "npm" {
+        if let Some(source) = get_npm_install_source(&package_manager.package_name) {
+            println!(
+                "{}",
+                l10n::text("info-installed-from", &[("source", &source)])
+            );
+        }
+    }
+    if let Ok(version) = get_version(&bin_path, &package_manager) {
+        let config = config::load_config()?;
+        let (channel, _) = get_channels(&config, bin_name, None, &package_manager, &version);
+        if channel != channel::STABLE {
+            println!("{}", l10n::text("info-channel", &[("channel", &channel)]));
+        }
+    }
+    if package_manager.name == "homebrew" {
+        if let Some(package) = homebrew::get_brew_info(&package_manager.package_name) {
+            let unknown = l10n::text("unknown", &[]);
+            if package.name != package_manager.package_name {
+                println!(
+                    "{}",
+                    l10n::text("info-formula", &[("formula", &package.name)])
+                );
+            }
+            println!(
+                "{}",
+                l10n::text(
+                    "info-version",
+                    &[(
+                        "version",
+                        package.installed_version.as_deref().unwrap_or(&unknown)
+                    )]
+                )
+            );
+            println!(
+                "{}",
+                l10n::text(
+                    "info-latest-version",
+                    &[(
+                        "version",
+                        package.latest_version.as_deref().unwrap_or(&unknown)
+                    )]
+                )
+            );
+            println!(
+                "{}",
+                l10n::text(
+                    "info-outdated",
+                    &[("outdated", &package.outdated.to_string())]
+                )
+            );
+            if package.pinned {
+                println!("{}", l10n::text("info-pinned", &[]));
+            }
+            if package.head {
+                println!("{}", l10n::text("info-head", &[]));
+            }
+            if package.cask {
+                println!("{}", l10n::text("info-cask", &[]));
+            }
+        }
+    }
+    if package_manager.name == "cargo" {
+        let cargo_root = cargo::find_install_root(Path::new(&bin_path));
+        let install = match &cargo_root {
+            Some(root) => cargo::find_cargo_install_in(root, bin_name),
+            None => cargo::find_cargo_install(bin_name),
+        };
+        if let Some(root) = &cargo_root {
+            println!(
+                "{}",
+                l10n::text("info-root", &[("dir", &root.display().to_string())])
+            );
+        }
+        if let Some(install) = install {
+            println!(
+                "{}",
+                l10n::text("info-version", &[("version", &install.version)])
+            );
+            println!(
+                "{}",
+                l10n::text("info-source", &[("source", &install.source)])
+            );
+        }
+    }
+    Ok(())
+}
+
+// A package manager that could own a binary, with what points to it: the bin being where the
+// manager puts its bins, and the manager's own records listing it
+struct OwnerCandidate {
+    manager: String,
+    package_name: String,
+    path_evidence: Option<String>,
+    metadata_evidence: Option<String>,
+}
+
+// Records are worth more than the location, a bin dir can be shared
+fn get_owner_confidence(candidate: &OwnerCandidate) -> u32 {
+    let mut confidence = 0;
+    if candidate.path_evidence.is_some() {
+        confidence += 40;
+    }
+    if candidate.metadata_evidence.is_some() {
+        confidence += 60;
+    }
+    confidence
+}
+
+// Lists every owner detection could have picked, best first, and offers to pin another one in
+// the config for the binary at that path
+fn review_owner_candidates(
+    bin_name: &str,
+    bin_path: &str,
+    detected: Option<&PackageManager>,
+) -> Result<(), String> {
+    let candidates = find_owner_candidates(bin_name, bin_path);
+    if candidates.is_empty() {
+        println!("{}", l10n::text("candidates-none", &[]));
+        return Ok(());
+    }
+
+    println!("{}", l10n::text("candidates", &[]));
+    for (index, candidate) in candidates.iter().enumerate() {
+        let is_detected = detected.is_some_and(|detected| detected.name == candidate.manager);
+        let id = if is_detected {
+            "candidate-detected"
+        } else {
+            "candidate"
+        };
+        println!(
+            "  {}. {}",
+            index + 1,
+            l10n::text(
+                id,
+                &[
+                    ("manager", &candidate.manager),
+                    ("package", &candidate.package_name),
+                    ("confidence", &get_owner_confidence(candidate).to_string())
+                ]
+            )
+        );
+        println!(
+            "     {}",
+            l10n::text(
+                "candidate-path",
+                &[(
+                    "evidence",
+                    candidate.path_evidence.as_deref().unwrap_or("-")
+                )]
+            )
+        );
+        println!(
+            "     {}",
+            l10n::text(
+                "candidate-metadata",
+                &[(
+                    "evidence",
+                    candidate.metadata_evidence.as_deref().unwrap_or("-")
+                )]
+            )
+        );
+    }
+
+    if !process::can_prompt() {
+        println!(
+            "{}",
+            l10n::text("candidates-set-manager", &[("bin", bin_name)])
+        );
+        return Ok(());
+    }
+    let index = loop {
+        print!(
+            "{} ",
+            l10n::text(
+                "candidates-prompt",
+                &[("bin", bin_name), ("count", &candidates.len().to_string())]
+            )
+        );
+        std::io::stdout().flush().ok();
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer).unwrap_or(0) == 0 {
+            return Ok(());
+        }
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Ok(());
+        }
+        match answer.parse::<usize>() {
+            Ok(index) if index >= 1 && index <= candidates.len() => break index - 1,
+            _ => println!(
+                "{}",
+                l10n::text("enter-number", &[("count", &candidates.len().to_string())])
+            ),
+        }
+    };
+
+    let candidate = &candidates[index];
+    if detected.is_some_and(|detected| {
+        detected.name == candidate.manager && detected.package_name == candidate.package_name
+    }) {
+        return Ok(());
+    }
+    if candidate.package_name != bin_name {
+        config::save_package_mapping(&candidate.manager, bin_name, &candidate.package_name)?;
+    }
+    let config_path = config::save_binary_owner(bin_name, &candidate.manager, bin_path)?;
+    println!(
+        "{}",
+        style::paint(
+            &l10n::text(
+                "candidate-saved",
+                &[
+                    ("bin", bin_name),
+                    ("path", bin_path),
+                    ("manager", &candidate.manager),
+                    ("package", &candidate.package_name),
+                    ("config", &config_path.display().to_string())
+                ]
+            ),
+            style::SUCCESS
+        )
+    );
+    Ok(())
+}
+
+// Every manager is asked on its own, unlike detection that stops at the first match. Nothing
+// here prompts, the packages are the first the records list
+fn find_owner_candidates(bin_name: &str, bin_path: &str) -> Vec<OwnerCandidate> {
+    let path = Path::new(bin_path);
+    let mut candidates = Vec::new();
+    let mut add = |manager: &str,
+                   package_name: Option<String>,
+                   path_evidence: Option<String>,
+                   metadata_evidence: Option<String>| {
+        if path_evidence.is_some() || metadata_evidence.is_some() {
+            candidates.push(OwnerCandidate {
+                manager: manager.to_string(),
+                package_name: package_name.unwrap_or_else(|| bin_name.to_string()),
+                path_evidence,
+                metadata_evidence,
+            });
+        }
+    };
+    // the evidence from packages whose package.json declares the bin
+    let describe_node_candidates = |candidates: &[mapping::Candidate]| {
+        let package_names: Vec<&str> = candidates
+            .iter()
+            .map(|candidate| candidate.package_name.as_str())
+            .collect();
+        match package_names.as_slice() {
+            [] => None,
+            [package_name] => Some(l10n::text(
+                "candidate-package-json-declares",
+                &[("package", package_name)],
+            )),
+            package_names => Some(l10n::text(
+                "candidate-package-jsons-declare",
+                &[("package-names", &package_names.join(", "))],
+            )),
+        }
+    };
+
+    if get_adopted(bin_name).is_some() {
+        add(
+            "adopted",
+            None,
+            None,
+            Some(l10n::text(
+                "candidate-adopted-is-in-config",
+                &[("bin", bin_name)],
+            )),
+        );
+    }
+
+    if let Some(manager) = version_manager::get_shim_manager(bin_path) {
+        add(
+            manager,
+            None,
+            Some(l10n::text(
+                "candidate-in-shims-dir",
+                &[("manager", manager)],
+            )),
+            None,
+        );
+    }
+
+    if let Some(image) = container::get_whalebrew_image(bin_path) {
+        add(
+            "whalebrew",
+            Some(image.clone()),
+            None,
+            Some(l10n::text(
+                "candidate-whalebrew-wrapper",
+                &[("image", &image)],
+            )),
+        );
+    }
+    if let Some(image) = container::get_docker_run_image(bin_path) {
+        add(
+            "docker",
+            Some(image.clone()),
+            None,
+            Some(l10n::text("candidate-runs-image", &[("image", &image)])),
+        );
+    }
+    if let Some(package_id) = winget::get_package_from_path(bin_path) {
+        add(
+            "winget",
+            Some(package_id.clone()),
+            None,
+            Some(l10n::text(
+                "candidate-winget-links",
+                &[("package-id", &package_id)],
+            )),
+        );
+    }
+
+    if let Some(app) = scoop::get_app_from_path(bin_path) {
+        add(
+            "scoop",
+            Some(app.clone()),
+            Some(l10n::text("candidate-in-scoop-shims", &[])),
+            Some(l10n::text("candidate-shim-runs-app", &[("app", &app)])),
+        );
+    }
+
+    if let Some(package_name) = chocolatey::get_package_from_path(bin_path) {
+        add(
+            "chocolatey",
+            Some(package_name.clone()),
+            Some(l10n::text("candidate-in-chocolatey-bin-dir", &[])),
+            Some(l10n::text(
+                "candidate-choco-lists-as-providing",
+                &[("package", &package_name)],
+            )),
+        );
+    }
+
+    let is_in_brew_prefix = homebrew::is_in_brew_prefix(path);
+    let formula = homebrew::get_formula_from_path(bin_path)
+        .map(|formula| {
+            let evidence = l10n::text("candidate-resolves-into-cellar", &[("formula", &formula)]);
+            (formula, evidence)
+        })
+        .or_else(|| {
+            homebrew::get_brew_executable()?;
+            let formula = homebrew::find_formulas_providing_bin(bin_name)
+                .into_iter()
+                .next()?;
+            let evidence = l10n::text(
+                "candidate-in-opt-dir",
+                &[("package", &formula.package_name)],
+            );
+            Some((formula.package_name, evidence))
+        });
+    add(
+        "homebrew",
+        formula.as_ref().map(|(formula, _)| formula.clone()),
+        is_in_brew_prefix.then(|| l10n::text("candidate-in-homebrew-prefix", &[])),
+        formula.map(|(_, evidence)| evidence),
+    );
+
+    if paths::is_installed("bun") {
+        let is_bun_global_bin = paths::has_components(path, &[".bun"])
+            || get_bun_global_bin_dir().is_some_and(|bin_dir| paths::starts_with(path, &bin_dir));
+        let bun_packages = get_bun_global_dir()
+            .map(|global_dir| find_global_dependencies_providing_bin(&global_dir, bin_name))
+            .unwrap_or_default();
+        add(
+            "bun",
+            bun_packages
+                .first()
+                .map(|package| package.package_name.clone()),
+            is_bun_global_bin.then(|| l10n::text("candidate-in-bun-global-bin", &[])),
+            describe_node_candidates(&bun_packages),
+        );
+    }
+
+    // a `cargo install --root` takes the place of CARGO_HOME
+    let cargo_root = cargo::find_install_root(path);
+    let cargo_bin_dir = match &cargo_root {
+        Some(root) => root.join("bin"),
+        None => cargo::get_cargo_home().join("bin"),
+    };
+    let is_cargo_bin = cargo_root.is_some()
+        || paths::has_components(path, &[".cargo", "bin"])
+        || paths::starts_with(path, &cargo_bin_dir);
+    let install = match &cargo_root {
+        Some(root) => cargo::find_cargo_install_in(root, bin_name),
+        None => cargo::find_cargo_install(bin_name),
+    };
+    add(
+        "cargo",
+        install.as_ref().map(|install| install.crate_name.clone()),
+        is_cargo_bin.then(|| {
+            l10n::text(
+                "candidate-in-dir",
+                &[("cargo-bin-dir", &cargo_bin_dir.display().to_string())],
+            )
+        }),
+        install.map(|install| {
+            l10n::text(
+                "candidate-crates2-json-lists",
+                &[("crate", &install.crate_name)],
+            )
+        }),
+    );
+
+    if paths::is_installed("go") {
+        let is_go_bin = go::get_bin_dir().is_some_and(|bin_dir| paths::starts_with(path, &bin_dir));
+        let package_path = go::get_package_path(path);
+        add(
+            "go",
+            package_path.clone(),
+            is_go_bin.then(|| l10n::text("candidate-in-go-bin-dir", &[])),
+            package_path.map(|package_path| {
+                l10n::text("candidate-was-built", &[("package-path", &package_path)])
+            }),
+        );
+    }
+
+    if let Some(shim) = deno::read_shim(path) {
+        let is_deno_bin =
+            deno::get_bin_dir().is_some_and(|bin_dir| paths::starts_with(path, &bin_dir));
+        add(
+            "deno",
+            Some(shim.specifier.clone()),
+            is_deno_bin.then(|| l10n::text("candidate-in-deno-bin-dir", &[])),
+            Some(l10n::text(
+                "candidate-deno-shim",
+                &[("specifier", &shim.specifier)],
+            )),
+        );
+    }
+
+    if let Some(tool_dir) = uv::get_tool_dir() {
+        let tool_dir = std::fs::canonicalize(tool_dir).unwrap_or_else(|_| tool_dir.to_path_buf());
+        let is_uv_bin = std::fs::canonicalize(path)
+            .is_ok_and(|resolved_path| resolved_path.starts_with(&tool_dir));
+        let tool = uv::get_tool_from_path(bin_path);
+        add(
+            "uv",
+            tool.clone(),
+            is_uv_bin.then(|| {
+                l10n::text(
+                    "candidate-resolves-into",
+                    &[("tool-dir", &tool_dir.display().to_string())],
+                )
+            }),
+            tool.map(|tool| l10n::text("candidate-belongs-to-uv-tool", &[("tool", &tool)])),
+        );
+    }
+
+    let is_pnpm_bin =
+        get_pnpm_global_bin_dir().is_some_and(|bin_dir| paths::starts_with(path, &bin_dir));
+    let pnpm_packages: Vec<mapping::Candidate> = get_pnpm_global_node_modules_dirs()
+        .iter()
+        .flat_map(|node_modules_dir| find_packages_providing_bin(node_modules_dir, bin_name))
+        .collect();
+    add(
+        "pnpm",
+        pnpm_packages
+            .first()
+            .map(|package| package.package_name.clone()),
+        is_pnpm_bin.then(|| l10n::text("candidate-in-pnpm-global-bin", &[])),
+        describe_node_candidates(&pnpm_packages),
+    );
+
+    if let Some((npm_bin_dir, global_node_modules_dir)) = get_npm_global_dirs() {
+        let npm_packages = find_packages_providing_bin(&global_node_modules_dir, bin_name);
+        add(
+            "npm",
+            npm_packages
+                .first()
+                .map(|package| package.package_name.clone()),
+            paths::starts_with(path, &npm_bin_dir).then(|| {
+                l10n::text(
+                    "candidate-in-npm-global-bin",
+                    &[("npm-bin-dir", &npm_bin_dir.display().to_string())],
+                )
+            }),
+            describe_node_candidates(&npm_packages),
+        );
+    }
+
+    let is_yarn_bin =
+        get_yarn_global_bin_dir().is_some_and(|bin_dir| paths::starts_with(path, &bin_dir));
+    let yarn_packages = get_yarn_global_dir()
+        .map(|global_dir| find_global_dependencies_providing_bin(&global_dir, bin_name))
+        .unwrap_or_default();
+    add(
+        "yarn",
+        yarn_packages
+            .first()
+            .map(|package| package.package_name.clone()),
+        is_yarn_bin.then(|| l10n::text("candidate-in-yarn-global-bin", &[])),
+        describe_node_candidates(&yarn_packages),
+    );
+
+    if let Some((package_manager, package_name)) = system::get_package_from_path(bin_path) {
+        let evidence = l10n::text(
+            "candidate-owns",
+            &[("manager", &package_manager), ("package", &package_name)],
+        );
+        add(&package_manager, Some(package_name), None, Some(evidence));
+    }
+
+    // stable, so equal ones keep detection's order
+    candidates.sort_by_key(|candidate| std::cmp::Reverse(get_owner_confidence(candidate)));
+    candidates
+}
+
+// What `update-bin <bin>` would change, asking the registry about the new version instead of
+// running the manager
+fn preview_update(bin_name: &str, args: &Args) -> Result<(), String> {
+    let bin_path = match args.path.as_deref() {
+        Some(bin_path) => check_bin_path(bin_path)?,
+        None => select_bin_path(bin_name)?,
+    };
+    if let Some(repair) = find_broken_bin(bin_name, &bin_path) {
+        return Err(repair.problem);
+    }
+    let package_manager = detect_package_manager(bin_name, &bin_path)?;
+    let config = config::load_config()?;
+    let strategy = match &args.strategy {
+        Some(strategy) => strategy.as_str(),
+        None => get_strategy(&config, &package_manager.name)?,
+    };
+    let (mut command, mut command_args) =
+        get_bin_update_command(&bin_path, &package_manager, strategy == "latest")?;
+
+    let current_version =
+        get_version(&bin_path, &package_manager).unwrap_or_else(|_| "unknown".to_string());
+    let (current_channel, channel) = get_channels(
+        &config,
+        bin_name,
+        args.channel.as_deref(),
+        &package_manager,
+        &current_version,
+    );
+    if channel != current_channel {
+        channel::check(
+            &package_manager.name,
+            &package_manager.package_name,
+            &channel,
+        )?;
+    }
+    if let Some(channel_command) = get_channel_update_command(
+        &bin_path,
+        &package_manager,
+        &channel,
+        channel != current_channel,
+    )? {
+        (command, command_args) = channel_command;
+    }
+    let latest_version = query_channel_latest_version(&package_manager, &channel);
+    println!(
+        "{}",
+        l10n::text(
+            "info-package-name",
+            &[("package", &package_manager.package_name)]
+        )
+    );
+    println!(
+        "{}",
+        l10n::text(
+            "info-package-manager",
+            &[("manager", &package_manager.name)]
+        )
+    );
+    if channel != current_channel {
+        println!(
+            "{}",
+            l10n::text(
+                "info-channel-switch",
+                &[("channel", &channel), ("current-channel", &current_channel)]
+            )
+        );
+    } else if channel != channel::STABLE {
+        println!("{}", l10n::text("info-channel", &[("channel", &channel)]));
+    }
+    println!(
+        "{}",
+        l10n::text("current-version", &[("version", &current_version)])
+    );
+    println!(
+        "{}",
+        l10n::text(
+            "info-latest-version",
+            &[(
+                "version",
+                &latest_version
+                    .clone()
+                    .unwrap_or_else(|| l10n::text("unknown", &[]))
+            )]
+        )
+    );
+
+    if let Some(latest_version) = &latest_version {
+        if version::compare_versions(&current_version, latest_version) != Some(Ordering::Less) {
+            println!(
+                "{}",
+                l10n::text(
+                    "preview-up-to-date",
+                    &[("package", &package_manager.package_name)]
+                )
+            );
+            return Ok(());
+        }
+        println!(
+            "{}",
+            l10n::text(
+                "preview-would-update",
+                &[
+                    ("package", &package_manager.package_name),
+                    (
+                        "change",
+                        &version::format_change(&current_version, latest_version)
+                    )
+                ]
+            )
+        );
+        if let Some(details) = registry::get_release_details(
+            &package_manager.name,
+            &package_manager.package_name,
+            latest_version,
+        ) {
+            print_release_details(&package_manager.name, &details);
+        }
+    }
+    // dependencies that are upgraded along with the formula
+    if package_manager.name == "homebrew" {
+        let plan = homebrew::get_upgrade_plan(&package_manager.package_name);
+        if !plan.is_empty() {
+            println!("{}", l10n::text("preview-homebrew-plan", &[]));
+            for line in plan {
+                println!("  {}", line);
+            }
+        }
+    }
+    let command = format!("{} {}", command, command_args.join(" "));
+    println!(
+        "{}",
+        l10n::text("preview-command", &[("command", &command)])
+    );
+    Ok(())
+}
+
+fn print_release_details(package_manager: &str, details: &registry::ReleaseDetails) {
+    if package_manager == "cargo" {
+        if let Some(size) = details.size {
+            println!(
+                "{}",
+                l10n::text("preview-crate-size", &[("size", &format_size(size))])
+            );
+        }
+        if let Some(rust_version) = &details.requires {
+            println!(
+                "{}",
+                l10n::text("preview-rust-version", &[("version", rust_version)])
+            );
+            let rustc_version = get_command_output("rustc", &["--version"]).and_then(|output| {
+                output
+                    .split_whitespace()
+                    .nth(1)
+                    .map(|version| version.to_string())
+            });
+            if let Some(rustc_version) = rustc_version.filter(|rustc_version| {
+                version::compare_versions(rustc_version, rust_version) == Some(Ordering::Less)
+            }) {
+                eprintln!(
+                    "{}",
+                    l10n::text("preview-rustc-too-old", &[("version", &rustc_version)])
+                );
+            }
+        }
+    } else {
+        if let Some(size) = details.size {
+            println!(
+                "{}",
+                l10n::text("preview-unpacked-size", &[("size", &format_size(size))])
+            );
+        }
+        if let Some(node_range) = &details.requires {
+            println!(
+                "{}",
+                l10n::text("preview-node-range", &[("range", node_range)])
+            );
+        }
+    }
+}
+
+// `1.4 MB`
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1_000 => format!("{} B", bytes),
+        1_000..1_000_000 => format!("{:.1} kB", bytes as f64 / 1e3),
+        _ => format!("{:.1} MB", bytes as f64 / 1e6),
+    }
+}
+
+// The detectors in the order they're tried, with the commands they ran. Detection stops at
+// the first that matches, so the last one is the answer
+fn explain_detection(bin_name: &str, bin_path: Option<&str>) -> Result<(), String> {
+    process::check_argument(bin_name, "binary name")?;
+    let bin_path = match bin_path {
+        Some(bin_path) => check_bin_path(bin_path)?,
+        None => {
+            let bin_paths = find_all_bin_paths(bin_name);
+            if bin_paths.len() > 1 {
+                print_installations(bin_name, &bin_paths);
+                println!("{}", l10n::text("explain-first", &[]));
+            }
+            find_bin_path(bin_name)?
+        }
+    };
+    println!(
+        "{}",
+        l10n::text("explain-detecting", &[("path", &bin_path)])
+    );
+    if let Ok(resolved_path) = std::fs::canonicalize(&bin_path) {
+        if resolved_path != Path::new(&bin_path) {
+            println!(
+                "{}",
+                l10n::text(
+                    "explain-resolves-to",
+                    &[("path", &resolved_path.display().to_string())]
+                )
+            );
+        }
+    }
+
+    trail::start();
+    let result = detect_package_manager(bin_name, &bin_path);
+    for step in trail::finish() {
+        match step {
+            trail::Step::Check {
+                detector,
+                matched,
+                evidence,
+            } => {
+                let symbol = if matched {
+                    style::paint(&style::symbol(style::SUCCESS), style::SUCCESS)
+                } else {
+                    style::paint(&style::symbol(style::FAILURE), style::FAILURE)
+                };
+                println!("  {} {}: {}", symbol, detector, evidence);
+            }
+            trail::Step::Command(command, succeeded) => {
+                let id = if succeeded {
+                    "explain-ran"
+                } else {
+                    "explain-ran-failed"
+                };
+                println!("      {}", l10n::text(id, &[("command", &command)]));
+            }
+        }
+    }
+    let package_manager = result.map_err(|e| {
+        l10n::text(
+            "detect-failed-adopt-hint",
+            &[("error", &e), ("bin", bin_name)],
+        )
+    })?;
+    println!(
+        "{}",
+        l10n::text(
+            "explain-detected",
+            &[
+                ("manager", &package_manager.name),
+                ("package", &package_manager.package_name)
+            ]
+        )
+    );
+    Ok(())
+}
+
+fn update_binary(bin_name: &str, args: &Args) -> Result<(), String> {
+    let bin_path = match args.path.as_deref() {
+        Some(bin_path) => check_bin_path(bin_path)?,
+        None => select_bin_path(bin_name)?,
+    };
+    // a binary that can't run would only fail to detect or report a version further down
+    if let Some(repair) = find_broken_bin(bin_name, &bin_path) {
+        return repair::offer(&repair);
+    }
+    let package_manager = detect_package_manager(bin_name, &bin_path)?;
+
+    // updating from the registry would replace the link with the published package
+    if let Some(linked_dir) = get_linked_package_dir(&bin_path, &package_manager.name) {
+        return Err(l10n::text(
+            "linked-package",
+            &[
+                ("package", &package_manager.package_name),
+                ("linked-dir", &linked_dir.display().to_string()),
+                ("manager", &package_manager.name),
+                (
+                    "unlink-command",
+                    &get_unlink_command(&package_manager.name, &package_manager.package_name),
+                ),
+            ],
+        ));
+    }
+
+    // some managers report success without touching anything (npm update -g is notorious for
+    // it), so remember what the binary looked like before updating
+    let old_fingerprint = get_file_fingerprint(&bin_path);
+
+    let old_version =
+        get_version(&bin_path, &package_manager).unwrap_or_else(|_| "unknown".to_string());
+    println!(
+        "{}",
+        l10n::text("current-version", &[("version", &old_version)])
+    );
+
+    // installing another version changes nothing while the old one stays pinned
+    if package_manager.name == "asdf" && !args.write_pins {
+        return Err(version_manager::explain(
+            "asdf",
+            bin_name,
+            version_manager::resolve_shim("asdf", bin_name).as_deref(),
+        ));
+    }
+
+    let config = config::load_config()?;
+    let strategy = match &args.strategy {
+        Some(strategy) => strategy.as_str(),
+        None => get_strategy(&config, &package_manager.name)?,
+    };
+    let (mut command, mut command_args) =
+        get_bin_update_command(&bin_path, &package_manager, strategy == "latest")?;
+    // `mise upgrade` stays within what's pinned unless it may bump the pin
+    if package_manager.name == "mise" && args.write_pins {
+        command_args.insert(1, "--bump".to_string());
+    }
+
+    // next, prerelease or HEAD installs stay there, unless --channel moves them
+    let (current_channel, channel) = get_channels(
+        &config,
+        bin_name,
+        args.channel.as_deref(),
+        &package_manager,
+        &old_version,
+    );
+    if channel != current_channel {
+        channel::check(
+            &package_manager.name,
+            &package_manager.package_name,
+            &channel,
+        )?;
+        println!(
+            "{}",
+            l10n::text(
+                "switching-channel",
+                &[
+                    ("package", &package_manager.package_name),
+                    ("current-channel", &current_channel),
+                    ("channel", &channel)
+                ]
+            )
+        );
+    } else if channel != channel::STABLE {
+        println!(
+            "{}",
+            l10n::text("staying-on-channel", &[("channel", &channel)])
+        );
+    }
+    if let Some(channel_command) = get_channel_update_command(
+        &bin_path,
+        &package_manager,
+        &channel,
+        channel != current_channel,
+    )? {
+        (command, command_args) = channel_command;
+    }
+
+    // a scheduled run mustn't jump a major version by itself
+    if let Some(policy) = config.get_auto_policy(bin_name, &package_manager.name)? {
+        let latest_version = if policy == "major" {
+            None
+        } else {
+            query_channel_latest_version(&package_manager, &channel)
+        };
+        if let Some(reason) = policy::check(policy, &old_version, latest_version.as_deref()) {
+            if !policy::confirm(&package_manager.package_name, policy, &reason) {
+                return Ok(());
+            }
+        }
+    }
+
+    // an adopted binary's command is the user's own, there's no manager to check
+    if package_manager.name != "adopted" {
+        preflight::check_package_manager(&package_manager.name, &command, args.brew_update)?;
+    }
+    if args.check_network {
+        preflight::check_network(&package_manager.name)?;
+    }
+
+    // distro packages and npm's global dir with a distro's nodejs are root's, relaying the
+    // manager's permission error wouldn't tell the user why
+    let root_reason = if system::is_system_package_manager(&package_manager.name) {
+        Some(l10n::text(
+            "system-package",
+            &[
+                ("bin-path", &bin_path),
+                ("package", &package_manager.package_name),
+                ("manager", &package_manager.name),
+            ],
+        ))
+    } else {
+        get_root_owned_global_dir(&package_manager.name).map(|global_dir| {
+            l10n::text(
+                "global-dir-owned-by-root",
+                &[
+                    ("manager", &package_manager.name),
+                    ("global-dir", &global_dir.display().to_string()),
+                ],
+            )
+        })
+    };
+    if let Some(root_reason) = root_reason.filter(|_| !system::is_root()) {
+        (command, command_args) = get_sudo_command(command, command_args);
+        if !args.sudo {
+            return Err(l10n::text(
+                "root-only-run-sudo",
+                &[
+                    ("root-reason", &root_reason),
+                    ("command", &command),
+                    ("command-args", &command_args.join(" ")),
+                ],
+            ));
+        }
+    }
+    if let Some(elevation_reason) = elevate::get_elevation_reason(&package_manager.name, &bin_path)
+    {
+        if !args.elevate {
+            elevate::offer(&elevation_reason, &command, &command_args)?;
+        }
+        (command, command_args) = elevate::get_elevated_command(&command, &command_args);
+    }
+
+    println!(
+        "{}",
+        l10n::text(
+            "updating",
+            &[
+                ("package", &package_manager.package_name),
+                ("manager", &package_manager.name)
+            ]
+        )
+    );
+
+    print_duration_estimate(history::get_last_duration(bin_name, &package_manager.name));
+
+    let github_actions = gha::is_enabled(args.output.as_deref());
+    if github_actions {
+        gha::start_group(&l10n::text(
+            "updating",
+            &[
+                ("package", &package_manager.package_name),
+                ("manager", &package_manager.name),
+            ],
+        ));
+    }
+    let started = Instant::now();
+    let output = lock::run_unlocked(
+        &package_manager.name,
+        get_lock_timeout(args),
+        &|message| println!("{}", message),
+        || {
+            runner::run(
+                &command,
+                &command_args,
+                args.timeout.map(Duration::from_secs),
+                args.idle_timeout.map(Duration::from_secs),
+            )
+        },
+    );
+    if github_actions {
+        gha::end_group();
+    }
+    let output = output?;
+    let reports_up_to_date = diagnose::reports_up_to_date(&package_manager.name, &output);
+    let output_lines = output.lines;
+
+    let record = |status: &str, new_version: Option<&str>, error: Option<&str>| {
+        if github_actions {
+            gha::append_summary(&l10n::text(
+                "gha-summary",
+                &[
+                    ("bin", bin_name),
+                    ("package", &package_manager.package_name),
+                    ("manager", &package_manager.name),
+                    ("old-version", &old_version),
+                    ("new-version", new_version.unwrap_or("-")),
+                    ("status", status),
+                ],
+            ));
+        }
+        if let Some(junit_path) = junit::get_path(args.output.as_deref()) {
+            let outcome = match (status, error) {
+                (_, Some(error)) => junit::Outcome::Failed(error.to_string()),
+                ("unchanged", None) => {
+                    junit::Outcome::Skipped(l10n::text("already-up-to-date", &[]))
+                }
+                _ => junit::Outcome::Passed,
+            };
+            let test_case = junit::TestCase {
+                package_manager: package_manager.name.clone(),
+                name: bin_name.to_string(),
+                duration: started.elapsed(),
+                outcome,
+                output: output_lines.clone(),
+            };
+            if let Err(e) = junit::write_junit(junit_path, &[test_case]) {
+                eprintln!("{}", l10n::text("warning-message", &[("message", &e)]));
+            }
+        }
+        history::record(&history::HistoryEntry {
+            bin_name,
+            package_name: &package_manager.package_name,
+            package_manager: &package_manager.name,
+            old_version: &old_version,
+            new_version,
+            status,
+            duration: started.elapsed(),
+            error,
+        })
+    };
+
+    let deprecation = diagnose::find_deprecation(&package_manager.package_name, &output_lines);
+
+    match output.status {
+        runner::RunStatus::Succeeded => {}
+        runner::RunStatus::Failed if reports_up_to_date => {}
+        runner::RunStatus::TimedOut(reason) => {
+            let error = l10n::text(
+                "killed-while-updating",
+                &[
+                    ("manager", &package_manager.name),
+                    ("package", &package_manager.package_name),
+                    ("reason", &reason),
+                    ("output", &runner::format_last_lines(&output_lines)),
+                ],
+            );
+            record("timed_out", None, Some(&error));
+            return Err(error);
+        }
+        runner::RunStatus::Failed => {
+            let diagnosis = diagnose::diagnose_failure(
+                &package_manager.name,
+                &package_manager.package_name,
+                &output_lines,
+            );
+            let error = match (diagnosis, deprecation) {
+                (Some(diagnosis), Some(deprecation)) => l10n::text(
+                    "diagnosis-and-deprecation",
+                    &[
+                        ("diagnosis", &diagnosis),
+                        ("package", &package_manager.package_name),
+                        ("deprecation", &deprecation),
+                    ],
+                ),
+                (Some(diagnosis), None) => diagnosis,
+                _ => l10n::text(
+                    "failed-to-update-with",
+                    &[
+                        ("package", &package_manager.package_name),
+                        ("manager", &package_manager.name),
+                    ],
+                ),
+            };
+            record("failed", None, Some(&error));
+            return Err(error);
+        }
+    }
+
+    if args.channel.is_some() {
+        if let Err(e) = config::save_binary_channel(bin_name, &channel) {
+            eprintln!("{}", l10n::text("warning-message", &[("message", &e)]));
+        }
+    }
+
+    if let Some(deprecation) = deprecation {
+        eprintln!(
+            "{}",
+            l10n::text(
+                "deprecated",
+                &[
+                    ("package", &package_manager.package_name),
+                    ("reason", &deprecation)
+                ]
+            )
+        );
+    }
+
+    // the version `asdf install` was given
+    if package_manager.name == "asdf" {
+        let version = command_args.last().cloned().unwrap_or_default();
+        let pin_path = version_manager::write_asdf_pin(&package_manager.package_name, &version)
+            .inspect_err(|e| {
+                record("failed", None, Some(e));
+            })?;
+        println!(
+            "{}",
+            l10n::text(
+                "pinned",
+                &[
+                    ("package", &package_manager.package_name),
+                    ("version", &version),
+                    ("path", &pin_path.display().to_string())
+                ]
+            )
+        );
+    }
+
+    if !reports_up_to_date {
+        let post_update_commands = get_post_update_commands(
+            &package_manager.name,
+            Some(&package_manager.package_name),
+            Some(&bin_path),
+            args.brew_cleanup,
+        );
+        run_post_update_commands(&post_update_commands, None, &mut Vec::new());
+    }
+
+    // a missing library or a changed runtime only shows once the new version runs
+    let healthcheck = config.get_healthcheck(bin_name);
+    if !reports_up_to_date && healthcheck != Some("") {
+        if let Err(problem) = healthcheck::check(&bin_path, healthcheck) {
+            let error = l10n::text(
+                "healthcheck-failed-after-update",
+                &[
+                    ("bin", bin_name),
+                    ("package", &package_manager.package_name),
+                    ("manager", &package_manager.name),
+                    ("problem", &problem),
+                ],
+            );
+            record("failed", None, Some(&error));
+            let undo_command = Some(&old_version)
+                .filter(|old_version| *old_version != "unknown")
+                .and_then(|old_version| {
+                    get_install_version_command(
+                        &package_manager.name,
+                        &package_manager.package_name,
+                        old_version,
+                    )
+                })
+                // the update itself needed root
+                .map(|(undo, undo_args)| {
+                    if command == "sudo" || command == "doas" {
+                        get_sudo_command(undo, undo_args)
+                    } else {
+                        (undo, undo_args)
+                    }
+                });
+            if undo_command.is_none() {
+                return Err(error);
+            }
+            repair::offer(&repair::Repair {
+                problem: l10n::text(
+                    "rollback-would-undo",
+                    &[("error", &error), ("old-version", &old_version)],
+                ),
+                command: undo_command,
+            })?;
+            return Err(l10n::text(
+                "rolled-back",
+                &[("error", &error), ("old-version", &old_version)],
+            ));
+        }
+    }
+
+    let new_version =
+        get_version(&bin_path, &package_manager).unwrap_or_else(|_| "unknown".to_string());
+
+    // without versions to compare, only the manager's own output and the binary itself can
+    // tell whether anything happened
+    if old_version == "unknown" || new_version == "unknown" {
+        let new_fingerprint = get_file_fingerprint(&bin_path);
+        if reports_up_to_date {
+            record("unchanged", None, None);
+            println!(
+                "{}",
+                l10n::text(
+                    "up-to-date-according-to-manager",
+                    &[
+                        ("package", &package_manager.package_name),
+                        ("manager", &package_manager.name)
+                    ]
+                )
+            );
+        } else if old_fingerprint.is_some() && old_fingerprint != new_fingerprint {
+            record("updated", None, None);
+            println!(
+                "{}",
+                l10n::text(
+                    "updated-version-unknown",
+                    &[("package", &package_manager.package_name)]
+                )
+            );
+        } else if args.strict {
+            let error = l10n::text(
+                "success-change-unknown",
+                &[
+                    ("manager", &package_manager.name),
+                    ("package", &package_manager.package_name),
+                ],
+            );
+            record("unknown", None, Some(&error));
+            return Err(error);
+        } else {
+            record("unknown", None, None);
+            println!(
+                "{}",
+                l10n::text(
+                    "change-unknown",
+                    &[
+                        ("package", &package_manager.package_name),
+                        ("manager", &package_manager.name)
+                    ]
+                )
+            );
+        }
+        return Ok(());
+    }
+
+    // `v1.2.0` and `1.2.0` are the same version, fall back to comparing the raw strings when
+    // either of them isn't a version at all
+    let ordering = version::compare_versions(&old_version, &new_version).unwrap_or(
+        if old_version == new_version {
+            Ordering::Equal
+        } else {
+            Ordering::Less
+        },
+    );
+
+    match ordering {
+        Ordering::Less => {
+            record("updated", Some(&new_version), None);
+            println!(
+                "{}",
+                l10n::text("updated-to-version", &[("version", &new_version)])
+            );
+            println!(
+                "{}",
+                l10n::text(
+                    "updated",
+                    &[
+                        ("package", &package_manager.package_name),
+                        (
+                            "change",
+                            &version::format_change(&old_version, &new_version)
+                        )
+                    ]
+                )
+            );
+            print_sibling_bins(bin_name, &package_manager, &new_version);
+            if let Some(link) = get_repository_url(&package_manager)
+                .and_then(|url| release::get_release_link(&url, &new_version))
+            {
+                println!(
+                    "{}",
+                    l10n::text(
+                        "release-notes",
+                        &[("version", &new_version), ("link", &link)]
+                    )
+                );
+            }
+        }
+        Ordering::Equal => {
+            let new_fingerprint = get_file_fingerprint(&bin_path);
+            if old_fingerprint.is_some() && old_fingerprint != new_fingerprint {
+                record("reinstalled", Some(&new_version), None);
+                println!(
+                    "{}",
+                    l10n::text(
+                        "reinstalled",
+                        &[
+                            ("package", &package_manager.package_name),
+                            ("version", &new_version)
+                        ]
+                    )
+                );
+                print_sibling_bins(bin_name, &package_manager, &new_version);
+            } else if args.strict {
+                let error = l10n::text(
+                    "success-but-unchanged",
+                    &[
+                        ("manager", &package_manager.name),
+                        ("package", &package_manager.package_name),
+                        ("old-version", &old_version),
+                    ],
+                );
+                record("unchanged", Some(&new_version), Some(&error));
+                return Err(error);
+            } else {
+                record("unchanged", Some(&new_version), None);
+                println!(
+                    "{}",
+                    l10n::text(
+                        "up-to-date",
+                        &[
+                            ("package", &package_manager.package_name),
+                            ("version", &old_version)
+                        ]
+                    )
+                );
+            }
+        }
+        // a lower version means a registry rollback, a channel switch or the wrong package
+        Ordering::Greater => {
+            if !args.allow_downgrade {
+                let error = l10n::text(
+                    "downgraded-error",
+                    &[
+                        ("package", &package_manager.package_name),
+                        ("old-version", &old_version),
+                        ("new-version", &new_version),
+                        ("manager", &package_manager.name),
+                    ],
+                );
+                record("downgraded", Some(&new_version), Some(&error));
+                return Err(error);
+            }
+            record("downgraded", Some(&new_version), None);
+            println!(
+                "{}",
+                l10n::text(
+                    "downgraded",
+                    &[
+                        ("package", &package_manager.package_name),
+                        ("old-version", &old_version),
+                        ("new-version", &new_version)
+                    ]
+                )
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// A project's dependency is updated with the project's package manager, in the range its
+// package.json allows unless --strategy latest, and a virtualenv's package with the
+// virtualenv's pip. The update goes into the project's lockfile, nothing global changes
+fn update_local_binary(bin_name: &str, args: &Args) -> Result<(), String> {
+    let local = local::find_local_bin(bin_name)?;
+    process::check_argument(&local.package_name, "package name")?;
+    let command = match local.package_manager.as_str() {
+        "pip" => local::get_venv_python(&local.dir)
+            .to_string_lossy()
+            .to_string(),
+        "npm" => get_npm_executable().to_string(),
+        package_manager => package_manager.to_string(),
+    };
+    if !paths::is_installed(&command) {
+        return Err(l10n::text(
+            "local-manager-not-installed",
+            &[
+                ("dir", &local.dir.display().to_string()),
+                ("manager", &local.package_manager),
+            ],
+        ));
+    }
+    let install_latest = args.strategy.as_deref() == Some("latest");
+    let command_args = local::get_update_args(&local, install_latest);
+
+    let old_version = local
+        .version
+        .clone()
+        .unwrap_or_else(|| "unknown".to_string());
+    println!(
+        "{}",
+        l10n::text("current-version", &[("version", &old_version)])
+    );
+    println!(
+        "{}",
+        l10n::text(
+            "updating-local",
+            &[
+                ("package", &local.package_name),
+                ("dir", &local.dir.to_string_lossy()),
+                ("manager", &local.package_manager)
+            ]
+        )
+    );
+    let output = runner::run(
+        &command,
+        &command_args,
+        args.timeout.map(Duration::from_secs),
+        args.idle_timeout.map(Duration::from_secs),
+    )?;
+    if !matches!(output.status, runner::RunStatus::Succeeded) {
+        return Err(l10n::text(
+            "command-failed-with-output",
+            &[
+                (
+                    "command",
+                    &format!("{} {}", command, command_args.join(" ")),
+                ),
+                ("output", &runner::format_last_lines(&output.lines)),
+            ],
+        ));
+    }
+
+    let new_version = local::get_installed_version(&local, bin_name);
+    match new_version {
+        Some(new_version) if old_version != "unknown" && new_version != old_version => {
+            println!(
+                "{}",
+                l10n::text(
+                    "updated",
+                    &[
+                        ("package", &local.package_name),
+                        (
+                            "change",
+                            &version::format_change(&old_version, &new_version)
+                        )
+                    ]
+                )
+            )
+        }
+        Some(new_version) if new_version == old_version => println!(
+            "{}",
+            l10n::text(
+                "up-to-date",
+                &[("package", &local.package_name), ("version", &old_version)]
+            )
+        ),
+        _ => println!(
+            "{}",
+            l10n::text(
+                "change-unknown",
+                &[
+                    ("package", &local.package_name),
+                    ("manager", &local.package_manager)
+                ]
+            )
+        ),
+    }
+    Ok(())
+}
+
+// sudo resets PATH, so make sure it runs the same manager
+fn get_sudo_command(command: String, mut command_args: Vec<String>) -> (String, Vec<String>) {
+    let executable = paths::find_executable(&command)
+        .map(|executable| executable.to_string_lossy().to_string())
+        .unwrap_or(command);
+    command_args.insert(0, executable);
+    // with nobody to type the password, fail right away instead of waiting for it
+    if !process::can_prompt() {
+        command_args.insert(0, "-n".to_string());
+    }
+    // OpenBSD ships doas instead
+    let sudo = if !paths::is_installed("sudo") && paths::is_installed("doas") {
+        "doas"
+    } else {
+        "sudo"
+    };
+    (sudo.to_string(), command_args)
+}
+
+// The managers --all knows how to update everything of, in the order they're updated
+const ALL_MANAGERS: [&str; 17] = [
+    "homebrew", "npm", "pnpm", "yarn", "bun", "cargo", "adopted", "winget", "apt", "dnf", "yum",
+    "zypper", "pacman", "apk", "pkg", "pkg_add", "ips",
+];
+
+// Only successful runs, one that failed early says nothing about how long the next one takes
+fn record_manager_durations<'a>(runs: impl Iterator<Item = &'a report::ManagerRun>) {
+    let durations: Vec<(&str, Duration)> = runs
+        .filter(|run| run.error.is_none())
+        .map(|run| (run.package_manager.as_str(), run.duration))
+        .collect();
+    history::record_manager_durations(&durations);
+}
+
+fn get_lock_timeout(args: &Args) -> Duration {
+    args.lock_timeout
+        .map(Duration::from_secs)
+        .unwrap_or(lock::DEFAULT_LOCK_TIMEOUT)
+}
+
+// Below this an update is quick enough not to be worth a heads-up
+const LONG_UPDATE: Duration = Duration::from_secs(30);
+
+fn print_duration_estimate(last_duration: Option<Duration>) {
+    if let Some(last_duration) = last_duration.filter(|duration| *duration >= LONG_UPDATE) {
+        println!(
+            "{}",
+            l10n::text(
+                "duration-estimate",
+                &[("duration", &report::format_duration(last_duration))]
+            )
+        );
+    }
+}
+
+// Whether updating the manager's packages runs through sudo
+fn needs_elevation(manager: &str) -> bool {
+    (system::is_system_package_manager(manager) || get_root_owned_global_dir(manager).is_some())
+        && !system::is_root()
+}
+
+// The managers in the order they'll run, with the ones that need sudo marked, so it's clear up
+// front what the password is for, or what --sudo would add
+fn print_all_plan<'a>(
+    managers: impl Iterator<Item = &'a str>,
+    elevated_managers: &[&str],
+    sudo: bool,
+) {
+    println!("{}", l10n::text("plan", &[]));
+    for manager in managers {
+        if !elevated_managers.contains(&manager) {
+            println!("  {}", manager);
+        } else if sudo {
+            println!("  {}", l10n::text("plan-sudo", &[("manager", manager)]));
+        } else {
+            println!(
+                "  {}",
+                l10n::text("plan-needs-sudo", &[("manager", manager)])
+            );
+        }
+    }
+}
+
+// Runs every installed manager's own "update everything", one manager after the other so a
+// failing one doesn't stop the rest
+fn update_all(args: &Args) -> Result<(), String> {
+    let config = config::load_config()?;
+    let cleanup_after_all = config.get_bool("cleanup_after_all")? == Some(true);
+    let managers = get_selected_managers(args, &config)?;
+    if managers.is_empty() {
+        return Err(l10n::text("no-selected-managers", &[]));
+    }
+
+    // a password prompt for sudo can't share the terminal with the others, so the managers
+    // that need it always run one at a time after them
+    let elevated_managers: Vec<&str> = managers
+        .iter()
+        .copied()
+        .filter(|manager| needs_elevation(manager))
+        .collect();
+    let (parallel_managers, managers): (Vec<&str>, Vec<&str>) = managers
+        .into_iter()
+        .partition(|manager| args.jobs > 1 && !elevated_managers.contains(manager));
+    if !elevated_managers.is_empty() {
+        print_all_plan(
+            parallel_managers.iter().chain(&managers).copied(),
+            &elevated_managers,
+            args.sudo,
+        );
+    }
+    // asked for once, before anything runs, rather than by whichever update gets there first
+    let _sudo_session = if args.sudo && !elevated_managers.is_empty() {
+        system::SudoSession::start()?
+    } else {
+        None
+    };
+
+    let durations = history::get_manager_durations();
+    let terminal_progress =
+        progress::TerminalProgress::start(parallel_managers.len() + managers.len());
+    let github_actions = gha::is_enabled(args.output.as_deref());
+    let mut updates = Vec::new();
+    let mut runs = Vec::new();
+    let mut report_run = |manager: &str, run: report::ManagerRun, manager_updates| {
+        if let Some(e) = &run.error {
+            if github_actions {
+                gha::warning(&l10n::text(
+                    "failed-to-update-manager",
+                    &[("manager", manager), ("error", e)],
+                ));
+            }
+            eprintln!("{}", l10n::text("error", &[("message", e)]));
+        }
+        runs.push(run);
+        updates.extend(manager_updates);
+    };
+
+    if !parallel_managers.is_empty() {
+        let table = progress::LiveTable::new(&parallel_managers);
+        let indices: Vec<usize> = (0..parallel_managers.len()).collect();
+        let results = table.show_while(|| {
+            process::map_concurrently(&indices, args.jobs, |index| {
+                let result =
+                    update_with_manager(parallel_managers[*index], args, Some((&table, *index)));
+                terminal_progress.finish_one(result.0.error.is_none());
+                result
+            })
+        });
+        for (manager, (run, manager_updates)) in parallel_managers.iter().zip(results) {
+            // the output that was kept off the table
+            if github_actions {
+                gha::start_group(&l10n::text("gha-output-of", &[("manager", manager)]));
+                for line in &run.lines {
+                    println!("{}", line);
+                }
+                gha::end_group();
+            }
+            report_run(manager, run, manager_updates);
+        }
+    }
+    for manager in managers {
+        if github_actions {
+            gha::start_group(&l10n::text("updating-manager", &[("manager", manager)]));
+        } else {
+            println!(
+                "{}",
+                l10n::text("updating-manager", &[("manager", manager)])
+            );
+        }
+        print_duration_estimate(durations.get(manager).copied());
+        let (run, manager_updates) = update_with_manager(manager, args, None);
+        terminal_progress.finish_one(run.error.is_none());
+        if github_actions {
+            gha::end_group();
+        }
+        report_run(manager, run, manager_updates);
+    }
+
+    // Homebrew has already cleaned up after itself with --brew-cleanup
+    if cleanup_after_all {
+        let updated_managers: Vec<&str> = runs
+            .iter()
+            .filter(|run| run.error.is_none())
+            .map(|run| run.package_manager.as_str())
+            .filter(|manager| CLEANUP_MANAGERS.contains(manager))
+            .filter(|manager| *manager != "homebrew" || !args.brew_cleanup)
+            .collect();
+        if let Err(e) = run_cleanup(&updated_managers, args) {
+            eprintln!("{}", l10n::text("warning-message", &[("message", &e)]));
+        }
+    }
+
+    for (package, old_version) in &updates {
+        match old_version {
+            Some(old_version) => println!(
+                "{}",
+                l10n::text(
+                    "all-updated",
+                    &[
+                        ("package", &package.name),
+                        ("manager", &package.package_manager),
+                        (
+                            "change",
+                            &version::format_change(old_version, &package.version)
+                        )
+                    ]
+                )
+            ),
+            None => println!(
+                "{}",
+                l10n::text(
+                    "all-installed",
+                    &[
+                        ("package", &package.name),
+                        ("manager", &package.package_manager),
+                        ("version", &package.version)
+                    ]
+                )
+            ),
+        }
+    }
+    if let Some(url) = config.get_webhook_url() {
+        notify_all_result(url, &updates, &runs);
+    }
+    if github_actions {
+        gha::append_summary(&report::to_markdown(&updates, &runs));
+    }
+    if let Some(junit_path) = junit::get_path(args.output.as_deref()) {
+        junit::write_junit(junit_path, &get_all_test_cases(&updates, &runs))?;
+        println!(
+            "{}",
+            l10n::text(
+                "wrote-junit",
+                &[("path", &junit_path.display().to_string())]
+            )
+        );
+    }
+    if let Some(report_path) = &args.report {
+        report::write_report(report_path, &updates, &runs)?;
+        println!(
+            "{}",
+            l10n::text(
+                "wrote-report",
+                &[("path", &report_path.display().to_string())]
+            )
+        );
+    }
+
+    record_manager_durations(runs.iter());
+    let failed_managers: Vec<&str> = runs
+        .iter()
+        .filter(|run| run.error.is_some())
+        .map(|run| run.package_manager.as_str())
+        .collect();
+    if let Some(metrics_path) = &args.metrics_file {
+        // what's still outdated is what an alert cares about, an update that failed or a
+        // package that's pinned. The updates are done by now, so a registry that can't be
+        // reached only costs the metrics
+        let written = get_latest_versions(false, LATEST_VERSION_JOBS).and_then(|packages| {
+            metrics::write_metrics(
+                metrics_path,
+                &metrics::RunMetrics {
+                    updated: updates.len(),
+                    failed_managers: failed_managers.clone(),
+                    managers: runs
+                        .iter()
+                        .map(|run| run.package_manager.as_str())
+                        .collect(),
+                    packages: &packages,
+                },
+            )
+        });
+        match written {
+            Ok(()) => println!(
+                "{}",
+                l10n::text(
+                    "wrote-metrics",
+                    &[("path", &metrics_path.display().to_string())]
+                )
+            ),
+            Err(e) => eprintln!("{}", l10n::text("warning-message", &[("message", &e)])),
+        }
+    }
+    if failed_managers.is_empty() {
+        println!("{}", l10n::text("everything-up-to-date", &[]));
+        Ok(())
+    } else {
+        Err(l10n::text(
+            "failed-to-update-managers",
+            &[("managers", &failed_managers.join(", "))],
+        ))
+    }
+}
+
+// A passed test case per updated package, and one for the manager's own run when it failed or
+// had nothing to update
+fn get_all_test_cases(
+    updates: &[(inventory::InstalledPackage, Option<String>)],
+    runs: &[report::ManagerRun],
+) -> Vec<junit::TestCase> {
+    let mut test_cases = Vec::new();
+    for run in runs {
+        let mut updated = 0;
+        for (package, old_version) in updates
+            .iter()
+            .filter(|(package, _)| package.package_manager == run.package_manager)
+        {
+            updated += 1;
+            test_cases.push(junit::TestCase {
+                package_manager: run.package_manager.clone(),
+                name: package.name.clone(),
+                duration: Duration::ZERO,
+                outcome: junit::Outcome::Passed,
+                output: vec![match old_version {
+                    Some(old_version) => l10n::text(
+                        "junit-updated-from",
+                        &[("old-version", old_version), ("version", &package.version)],
+                    ),
+                    None => l10n::text("junit-installed", &[("version", &package.version)]),
+                }],
+            });
+        }
+        let outcome = match &run.error {
+            Some(error) => junit::Outcome::Failed(error.clone()),
+            None if updated == 0 => junit::Outcome::Skipped(l10n::text("nothing-to-update", &[])),
+            None => continue,
+        };
+        test_cases.push(junit::TestCase {
+            package_manager: run.package_manager.clone(),
+            name: format!("{} (all packages)", run.package_manager),
+            duration: run.duration,
+            outcome,
+            output: run.lines.clone(),
+        });
+    }
+    test_cases
+}
+
+// "3 tools updated, 1 failed", with a line per package and failure
+fn notify_all_result(
+    url: &str,
+    updates: &[(inventory::InstalledPackage, Option<String>)],
+    runs: &[report::ManagerRun],
+) {
+    let failures: Vec<(&str, &str)> = runs
+        .iter()
+        .filter_map(|run| Some((run.package_manager.as_str(), run.error.as_deref()?)))
+        .collect();
+    let id = if updates.len() == 1 {
+        "notify-updated-one"
+    } else {
+        "notify-updated"
+    };
+    let mut summary = l10n::text(id, &[("count", &updates.len().to_string())]);
+    if !failures.is_empty() {
+        summary.push_str(&l10n::text(
+            "notify-failed-count",
+            &[("count", &failures.len().to_string())],
+        ));
+    }
+
+    let mut details = Vec::new();
+    let mut report_updates = Vec::new();
+    for (package, old_version) in updates {
+        details.push(match old_version {
+            Some(old_version) => l10n::text(
+                "notify-updated-package",
+                &[
+                    ("package", &package.name),
+                    ("manager", &package.package_manager),
+                    ("old-version", old_version),
+                    ("version", &package.version),
+                ],
+            ),
+            None => l10n::text(
+                "notify-installed-package",
+                &[
+                    ("package", &package.name),
+                    ("manager", &package.package_manager),
+                    ("version", &package.version),
+                ],
+            ),
+        });
+        report_updates.push(serde_json::json!({
+            "package_name": package.name,
+            "package_manager": package.package_manager,
+            "old_version": old_version,
+            "new_version": package.version,
+        }));
+    }
+    let mut report_failures = Vec::new();
+    for (manager, error) in &failures {
+        details.push(l10n::text(
+            "notify-failed-manager",
+            &[("manager", manager), ("error", error)],
+        ));
+        report_failures.push(serde_json::json!({
+            "package_manager": manager,
+            "error": error,
+        }));
+    }
+
+    notify::send_webhook(
+        url,
+        &summary,
+        &details,
+        serde_json::json!({
+            "schema_version": schema::SCHEMA_VERSION,
+            "updated": report_updates,
+            "failed": report_failures,
+        }),
+    );
+}
+
+// Everything the manager installed, and which packages changed. With a table the manager runs
+// alongside others, so its output is only kept
+fn update_with_manager(
+    manager: &str,
+    args: &Args,
+    table: Option<(&progress::LiveTable, usize)>,
+) -> (
+    report::ManagerRun,
+    Vec<(inventory::InstalledPackage, Option<String>)>,
+) {
+    if let Some((table, index)) = table {
+        table.set_phase(index, "listing packages");
+    }
+    // comparing what's installed before and after tells which packages were updated
+    let old_packages = get_installed_packages(manager);
+    let started = Instant::now();
+    let mut lines = Vec::new();
+    let result = update_manager_packages(manager, args, &mut lines, table);
+    let duration = started.elapsed();
+    if let Some((table, index)) = table {
+        table.set_phase(index, "listing packages");
+    }
+    let succeeded = result.is_ok();
+    let run = report::ManagerRun {
+        package_manager: manager.to_string(),
+        duration,
+        error: result.err(),
+        lines,
+    };
+
+    let mut updates = Vec::new();
+    for package in get_installed_packages(manager) {
+        let old_version = old_packages
+            .iter()
+            .find(|old_package| old_package.name == package.name)
+            .map(|old_package| old_package.version.clone());
+        if old_version.as_deref() != Some(package.version.as_str()) {
+            updates.push((package, old_version));
+        }
+    }
+    if let Some((table, index)) = table {
+        table.finish(index, succeeded);
+    }
+    (run, updates)
+}
+
+fn update_manager_packages(
+    manager: &str,
+    args: &Args,
+    lines: &mut Vec<String>,
+    table: Option<(&progress::LiveTable, usize)>,
+) -> Result<(), String> {
+    let needs_root = needs_elevation(manager);
+
+    let config = config::load_config()?;
+    let strategy = match &args.strategy {
+        Some(strategy) => strategy.as_str(),
+        None => get_strategy(&config, manager)?,
+    };
+    let commands = match get_policy_update_commands(manager, &config, strategy == "latest")? {
+        Some((commands, held_back)) => {
+            for (package, reason) in held_back {
+                let message =
+                    l10n::text("held-back", &[("package", &package), ("reason", &reason)]);
+                match table {
+                    Some(_) => lines.push(message),
+                    None => println!("{}", message),
+                }
+            }
+            commands
+        }
+        None => get_update_all_commands(manager, strategy == "latest")?,
+    };
+    for (command, command_args) in commands {
+        let (command, command_args) = if needs_root {
+            let (command, command_args) = get_sudo_command(command, command_args);
+            if !args.sudo {
+                return Err(l10n::text(
+                    "root-only-packages",
+                    &[
+                        ("manager", manager),
+                        ("command", &command),
+                        ("command-args", &command_args.join(" ")),
+                    ],
+                ));
+            }
+            (command, command_args)
+        } else {
+            (command, command_args)
+        };
+
+        let timeout = args.timeout.map(Duration::from_secs);
+        let idle_timeout = args.idle_timeout.map(Duration::from_secs);
+        let report = |message: &str| match table {
+            Some((table, index)) => table.set_phase(index, message),
+            None => println!("{}", message),
+        };
+        let output =
+            lock::run_unlocked(manager, get_lock_timeout(args), &report, || match table {
+                Some((table, index)) => {
+                    table.set_phase(index, &format!("{} {}", command, command_args.join(" ")));
+                    runner::run_captured(&command, &command_args, timeout, idle_timeout)
+                }
+                None => runner::run(&command, &command_args, timeout, idle_timeout),
+            })?;
+        lines.extend(output.lines.iter().cloned());
+        match output.status {
+            runner::RunStatus::Succeeded => {}
+            // a batch like `brew upgrade a b c` failed when any package did, whatever the others
+            // printed. Only winget's and IPS's whole-system upgrades exit with "nothing to do"
+            runner::RunStatus::Failed if diagnose::exits_up_to_date(manager, output.exit_code) => {}
+            runner::RunStatus::Failed => {
+                return Err(l10n::text(
+                    "command-failed",
+                    &[
+                        ("command", &command),
+                        ("command-args", &command_args.join(" ")),
+                    ],
+                ))
+            }
+            runner::RunStatus::TimedOut(reason) => {
+                return Err(l10n::text(
+                    "killed-command",
+                    &[
+                        ("command", &command),
+                        ("command-args", &command_args.join(" ")),
+                        ("reason", &reason),
+                    ],
+                ))
+            }
+        }
+    }
+
+    let post_update_commands = get_post_update_commands(manager, None, None, args.brew_cleanup);
+    run_post_update_commands(&post_update_commands, table, lines);
+    Ok(())
+}
+
+// The commands to run and the (package, reason) pairs held back
+type PolicyUpdateCommands = (Vec<(String, Vec<String>)>, Vec<(String, String)>);
+
+// With an `auto` policy holding back some of the manager's updates, the commands updating only
+// the others, and the packages held back with why. `None` when nothing is held back and the
+// manager can update everything as usual. Only managers updating package by package can leave
+// some out
+fn get_policy_update_commands(
+    manager: &str,
+    config: &config::Config,
+    install_latest: bool,
+) -> Result<Option<PolicyUpdateCommands>, String> {
+    if !matches!(
+        manager,
+        "homebrew" | "npm" | "pnpm" | "yarn" | "bun" | "cargo"
+    ) {
+        return Ok(None);
+    }
+    let mut outdated = Vec::new();
+    let mut held_back = Vec::new();
+    for package in get_installed_packages(manager) {
+        let policy = match config.get_auto_policy(&package.name, manager)? {
+            Some(policy) if policy != "major" => policy,
+            _ => {
+                outdated.push(package.name);
+                continue;
+            }
+        };
+        let latest_version = query_latest_version(manager, &package.name);
+        match policy::check(policy, &package.version, latest_version.as_deref()) {
+            Some(reason) => held_back.push((package.name, reason)),
+            None if latest_version.is_some_and(|latest_version| {
+                version::compare_versions(&package.version, &latest_version) == Some(Ordering::Less)
+            }) =>
+            {
+                outdated.push(package.name)
+            }
+            None => {}
+        }
+    }
+    if held_back.is_empty() {
+        return Ok(None);
+    }
+
+    let commands = outdated
+        .iter()
+        .map(|package_name| get_update_command(manager, package_name, install_latest))
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(Some((merge_update_commands(commands), held_back)))
+}
+
+// What has to run after an update for it to be complete: rebuilding the version manager's
+// shims when the binary or the manager itself runs through one, and removing Homebrew's old
+// versions when asked to
+fn get_post_update_commands(
+    manager: &str,
+    package_name: Option<&str>,
+    bin_path: Option<&str>,
+    brew_cleanup: bool,
+) -> Vec<(String, Vec<String>)> {
+    let manager_path = paths::find_executable(get_manager_executable(manager))
+        .map(|path| path.to_string_lossy().to_string());
+    let mut commands = Vec::new();
+    for path in bin_path.into_iter().chain(manager_path.as_deref()) {
+        if let Some(command) = version_manager::get_reshim_after_update(path) {
+            if !commands.contains(&command) {
+                commands.push(command);
+            }
+        }
+    }
+    if manager == "homebrew" && brew_cleanup {
+        let mut cleanup_args = vec!["cleanup".to_string()];
+        cleanup_args.extend(package_name.map(|package_name| package_name.to_string()));
+        commands.push((homebrew::get_brew_command(), cleanup_args));
+    }
+    commands
+}
+
+// The update itself went through, so a failing one only warns
+fn run_post_update_commands(
+    commands: &[(String, Vec<String>)],
+    table: Option<(&progress::LiveTable, usize)>,
+    lines: &mut Vec<String>,
+) {
+    for (command, command_args) in commands {
+        let output = match table {
+            Some((table, index)) => {
+                table.set_phase(index, &format!("{} {}", command, command_args.join(" ")));
+                runner::run_captured(command, command_args, None, None)
+            }
+            None => {
+                println!(
+                    "{}",
+                    l10n::text(
+                        "running",
+                        &[(
+                            "command",
+                            &format!("{} {}", command, command_args.join(" "))
+                        )]
+                    )
+                );
+                runner::run(command, command_args, None, None)
+            }
+        };
+        let succeeded = match output {
+            Ok(output) => {
+                lines.extend(output.lines);
+                matches!(output.status, runner::RunStatus::Succeeded)
+            }
+            Err(_) => false,
+        };
+        if !succeeded {
+            let warning = l10n::text(
+                "post-update-command-failed",
+                &[
+                    ("command", command),
+                    ("command-args", &command_args.join(" ")),
+                ],
+            );
+            match table {
+                Some(_) => lines.push(warning),
+                None => eprintln!(
+                    "{}",
+                    l10n::text("warning-message", &[("message", &warning)])
+                ),
+            }
+        }
+    }
+}
+
+fn get_manager_executable(manager: &str) -> &str {
+    match manager {
+        "homebrew" => "brew",
+        "adopted" => process::get_shell_command("").0,
+        "npm" => get_npm_executable(),
+        manager => system::get_executable(manager),
+    }
+}
+
+// cargo has no command to update every installed crate, and the node managers' own one stays
+// within the installed major versions, so those install their packages again in as few
+// commands as possible
+fn get_update_all_commands(
+    manager: &str,
+    install_latest: bool,
+) -> Result<Vec<(String, Vec<String>)>, String> {
+    if install_latest && matches!(manager, "npm" | "pnpm" | "yarn" | "bun") {
+        let commands = get_installed_packages(manager)
+            .iter()
+            .map(|package| get_update_command(manager, &package.name, true))
+            .collect::<Result<Vec<_>, String>>()?;
+        return Ok(merge_update_commands(commands));
+    }
+    let args: &[&str] = match manager {
+        "homebrew" => {
+            return Ok(vec![(
+                homebrew::get_brew_command(),
+                vec!["upgrade".to_string()],
+            )])
+        }
+        "cargo" => {
+            let commands = cargo::read_cargo_installs()
+                .iter()
+                .map(|install| get_update_command("cargo", &install.crate_name, false))
+                .collect::<Result<Vec<_>, String>>()?;
+            return Ok(merge_update_commands(commands));
+        }
+        "adopted" => {
+            return Ok(config::load_config()?
+                .get_adopted()
+                .iter()
+                .map(|(_, update_command, _)| process::get_shell_command(update_command))
+                .map(|(shell, args)| (shell.to_string(), args))
+                .collect())
+        }
+        "yarn" => {
+            if get_yarn_major_version()
+                .filter(|major| *major >= 2)
+                .is_some()
+            {
+                return Err(l10n::text("yarn-berry-no-global", &[]));
+            }
+            &["global", "upgrade"]
+        }
+        // the packages from git or a tarball are installed from there again after it
+        "npm" => {
+            let mut commands = vec![(
+                get_npm_executable().to_string(),
+                vec!["update".to_string(), "-g".to_string()],
+            )];
+            for package in get_installed_packages("npm") {
+                if let Some(source) = get_npm_install_source(&package.name) {
+                    commands.push((
+                        get_npm_executable().to_string(),
+                        vec!["install".to_string(), "-g".to_string(), source],
+                    ));
+                }
+            }
+            return Ok(commands);
+        }
+        "pnpm" | "bun" => &["update", "-g"],
+        "winget" => &[
+            "upgrade",
+            "--all",
+            "--accept-source-agreements",
+            "--disable-interactivity",
+        ],
+        _ => return Ok(vec![system::get_upgrade_all_command(manager)]),
+    };
+    Ok(vec![(
+        get_manager_executable(manager).to_string(),
+        args.iter().map(|arg| arg.to_string()).collect(),
+    )])
+}
+
+// Update commands that only differ in the package at the end become one, like
+// `cargo install a b c` or `npm install -g a@latest b@latest`, so the manager starts and
+// resolves its index once. Packages with their own flags, like a git source or features, stay
+// in a command of their own
+fn merge_update_commands(commands: Vec<(String, Vec<String>)>) -> Vec<(String, Vec<String>)> {
+    let mut merged: Vec<(String, Vec<String>, Vec<String>)> = Vec::new();
+    for (command, mut args) in commands {
+        let package = match args.pop() {
+            Some(package) => package,
+            None => continue,
+        };
+        match merged.iter_mut().find(|(merged_command, merged_args, _)| {
+            *merged_command == command && *merged_args == args
+        }) {
+            Some((_, _, packages)) => packages.push(package),
+            None => merged.push((command, args, vec![package])),
+        }
+    }
+    merged
+        .into_iter()
+        .map(|(command, mut args, packages)| {
+            args.extend(packages);
+            (command, args)
+        })
+        .collect()
+}
+
+// The managers that install packages for the user, as opposed to the system's
+const USER_MANAGERS: [&str; 7] = ["homebrew", "npm", "pnpm", "yarn", "bun", "cargo", "adopted"];
+
+fn export_inventory() -> Result<(), String> {
+    let packages: Vec<inventory::InstalledPackage> = USER_MANAGERS
+        .into_iter()
+        .filter(|manager| paths::is_installed(get_manager_executable(manager)))
+        .flat_map(get_installed_packages)
+        .collect();
+    print_json(&inventory::to_cyclonedx(&packages))
+}
+
+fn print_json(value: &serde_json::Value) -> Result<(), String> {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).map_err(|e| e.to_string())?
+    );
+    Ok(())
+}
+
+// How long a looked up latest version is trusted, unless the config says otherwise
+const DEFAULT_CACHE_TTL_HOURS: u64 = 6;
+
+// How many latest versions are looked up at the same time by default
+const LATEST_VERSION_JOBS: usize = 8;
+
+// Tools with known vulnerabilities come first, with the advisories, the outdated ones are
+// often where they are
+fn print_outdated(refresh: bool, jobs: usize) -> Result<(), String> {
+    let mut outdated: Vec<(inventory::InstalledPackage, String)> = Vec::new();
+    for (package, latest_version) in get_latest_versions(refresh, jobs)? {
+        let latest_version = match latest_version {
+            Some(latest_version) => latest_version,
+            None => continue,
+        };
+        if version::compare_versions(&package.version, &latest_version) == Some(Ordering::Less) {
+            outdated.push((package, latest_version));
+        }
+    }
+
+    if outdated.is_empty() {
+        println!("{}", l10n::text("everything-up-to-date", &[]));
+        return Ok(());
+    }
+    // without OSV.dev, the list is still worth showing
+    let packages: Vec<&inventory::InstalledPackage> =
+        outdated.iter().map(|(package, _)| package).collect();
+    let vulnerabilities = audit::find_vulnerabilities(&packages)
+        .unwrap_or_else(|_| packages.iter().map(|_| Vec::new()).collect());
+    let mut outdated: Vec<_> = outdated.iter().zip(&vulnerabilities).collect();
+    outdated.sort_by_key(|(_, vulnerabilities)| vulnerabilities.is_empty());
+
+    let package_header = l10n::text("column-package", &[]);
+    let name_width = outdated
+        .iter()
+        .map(|((package, _), _)| package.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(package_header.chars().count());
+    println!(
+        "{:name_width$}  {:8}  {:12}  {}",
+        package_header,
+        l10n::text("column-manager", &[]),
+        l10n::text("column-current", &[]),
+        l10n::text("column-latest", &[])
+    );
+    for ((package, latest_version), vulnerabilities) in &outdated {
+        let mut line = format!(
+            "{:name_width$}  {:8}  {:12}  {}",
+            package.name,
+            package.package_manager,
+            package.version,
+            version::highlight_change(&package.version, latest_version)
+        );
+        if !vulnerabilities.is_empty() {
+            line.push_str(&format!(
+                "  {}",
+                style::paint(
+                    &l10n::text(
+                        "warning-message",
+                        &[("message", &vulnerabilities.join(", "))]
+                    ),
+                    style::ALERT
+                )
+            ));
+        }
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+// Each package manager's executable and how it updates itself, Homebrew first since it may
+// have installed the others. yarn and pipx have no way of their own, they're updated by
+// whatever installed them or not at all
+const MANAGER_SELF_UPDATES: [(&str, &str, &[&str]); 10] = [
+    ("homebrew", "brew", &["update"]),
+    ("npm", "npm", &["install", "-g", "npm@latest"]),
+    ("pnpm", "pnpm", &["self-update"]),
+    ("yarn", "yarn", &[]),
+    ("bun", "bun", &["upgrade"]),
+    ("rustup", "rustup", &["self", "update"]),
+    ("pipx", "pipx", &[]),
+    ("uv", "uv", &["self", "update"]),
+    ("mise", "mise", &["self-update", "--yes"]),
+    ("scoop", "scoop", &["update"]),
+];
+
+// The managers whose installs of another manager update-bin updates like any binary. cargo
+// isn't one, rustup sits in ~/.cargo/bin without being a crate
+const MANAGER_OWNERS: [&str; 7] = ["homebrew", "npm", "pnpm", "yarn", "bun", "volta", "winget"];
+
+fn update_package_managers(args: &Args) -> Result<(), String> {
+    let installed: Vec<(&str, &str, &[&str])> = MANAGER_SELF_UPDATES
+        .into_iter()
+        .filter(|(_, executable, _)| paths::is_installed(executable))
+        .collect();
+    if installed.is_empty() {
+        return Err(l10n::text("no-managers-installed", &[]));
+    }
+
+    let mut failed = Vec::new();
+    for (manager, executable, self_update_args) in installed {
+        // pnpm from Homebrew or yarn from npm go through the manager that installed them, which
+        // a self-update would fight with or refuse
+        let owner = find_bin_path(executable)
+            .and_then(|bin_path| detect_package_manager(executable, &bin_path))
+            .ok()
+            .filter(|owner| owner.name != manager)
+            .filter(|owner| {
+                MANAGER_OWNERS.contains(&owner.name.as_str())
+                    || system::is_system_package_manager(&owner.name)
+            });
+        let result = match owner {
+            Some(owner) => {
+                println!(
+                    "{}",
+                    l10n::text(
+                        "manager-installed-with",
+                        &[("manager", manager), ("owner", &owner.name)]
+                    )
+                );
+                update_binary(executable, args)
+            }
+            None if self_update_args.is_empty() => {
+                println!(
+                    "{}",
+                    l10n::text("manager-cannot-self-update", &[("manager", manager)])
+                );
+                continue;
+            }
+            None => update_manager_itself(manager, executable, self_update_args, args),
+        };
+        if let Err(e) = result {
+            eprintln!("{}", l10n::text("error", &[("message", &e)]));
+            failed.push(manager);
+        }
+    }
+
+    if failed.is_empty() {
+        println!("{}", l10n::text("managers-updated", &[]));
+        Ok(())
+    } else {
+        Err(l10n::text(
+            "failed-to-update",
+            &[("names", &failed.join(", "))],
+        ))
+    }
+}
+
+fn update_manager_itself(
+    manager: &str,
+    executable: &str,
+    self_update_args: &[&str],
+    args: &Args,
+) -> Result<(), String> {
+    let mut command = match (manager, executable) {
+        ("homebrew", _) => homebrew::get_brew_command(),
+        ("npm", _) => get_npm_executable().to_string(),
+        (_, executable) => executable.to_string(),
+    };
+    let mut command_args: Vec<String> =
+        self_update_args.iter().map(|arg| arg.to_string()).collect();
+    if manager == "npm" && !system::is_root() {
+        if let Some(global_dir) = get_root_owned_global_dir("npm") {
+            (command, command_args) = get_sudo_command(command, command_args);
+            if !args.sudo {
+                return Err(l10n::text(
+                    "npm-global-dir-owned-by-root-run-sudo",
+                    &[
+                        ("global-dir", &global_dir.display().to_string()),
+                        ("command", &command),
+                        ("command-args", &command_args.join(" ")),
+                    ],
+                ));
+            }
+        }
+    }
+
+    println!(
+        "{}",
+        l10n::text(
+            "updating-with-command",
+            &[
+                ("package", manager),
+                (
+                    "command",
+                    &format!("{} {}", command, command_args.join(" "))
+                )
+            ]
+        )
+    );
+    let output = runner::run(
+        &command,
+        &command_args,
+        args.timeout.map(Duration::from_secs),
+        args.idle_timeout.map(Duration::from_secs),
+    )?;
+    match output.status {
+        runner::RunStatus::Succeeded => Ok(()),
+        runner::RunStatus::TimedOut(reason) => Err(l10n::text(
+            "killed-self-update",
+            &[("manager", manager), ("reason", &reason)],
+        )),
+        runner::RunStatus::Failed => Err(l10n::text(
+            "command-failed-with-output",
+            &[
+                (
+                    "command",
+                    &format!("{} {}", command, command_args.join(" ")),
+                ),
+                ("output", &runner::format_last_lines(&output.lines)),
+            ],
+        )),
+    }
+}
+
+// The managers `update-bin cleanup` knows how to clean up after
+const CLEANUP_MANAGERS: [&str; 6] = ["homebrew", "npm", "pnpm", "yarn", "bun", "cargo"];
+
+fn cleanup_managers(names: &[String], args: &Args) -> Result<(), String> {
+    for name in names {
+        if !CLEANUP_MANAGERS.contains(&name.as_str()) {
+            return Err(l10n::text(
+                "cleanup-unknown-manager",
+                &[("name", name), ("managers", &CLEANUP_MANAGERS.join(", "))],
+            ));
+        }
+    }
+    let managers: Vec<&str> = CLEANUP_MANAGERS
+        .into_iter()
+        .filter(|manager| names.is_empty() || names.iter().any(|name| name == manager))
+        .filter(|manager| paths::is_installed(get_manager_executable(manager)))
+        .collect();
+    if managers.is_empty() {
+        return Err(l10n::text("no-selected-managers", &[]));
+    }
+    run_cleanup(&managers, args)?;
+    println!("{}", l10n::text("cleaned-up", &[]));
+    Ok(())
+}
+
+fn run_cleanup(managers: &[&str], args: &Args) -> Result<(), String> {
+    let mut failed = Vec::new();
+    for manager in managers {
+        let (command, command_args) = match get_cleanup_command(manager) {
+            Ok(command) => command,
+            Err(reason) => {
+                println!("{}", l10n::text("info-message", &[("message", &reason)]));
+                continue;
+            }
+        };
+        let output = runner::run(
+            &command,
+            &command_args,
+            args.timeout.map(Duration::from_secs),
+            args.idle_timeout.map(Duration::from_secs),
+        );
+        if !matches!(
+            output.map(|output| output.status),
+            Ok(runner::RunStatus::Succeeded)
+        ) {
+            failed.push(format!("`{} {}`", command, command_args.join(" ")));
+        }
+    }
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(l10n::text(
+            "failed-to-clean-up",
+            &[("names", &failed.join(", "))],
+        ))
+    }
+}
+
+// Each manager's own way to drop old versions and verify or prune its cache. The reason when
+// there's none
+fn get_cleanup_command(manager: &str) -> Result<(String, Vec<String>), String> {
+    let (command, args): (String, &[&str]) = match manager {
+        "homebrew" => (homebrew::get_brew_command(), &["cleanup"]),
+        "npm" => (get_npm_executable().to_string(), &["cache", "verify"]),
+        "pnpm" => ("pnpm".to_string(), &["store", "prune"]),
+        "yarn" => {
+            if get_yarn_major_version().is_some_and(|major| major >= 2) {
+                return Err(
+                    "Yarn Berry's caches belong to projects, clean them up with `yarn cache clean` in each"
+                        .to_string(),
+                );
+            }
+            ("yarn".to_string(), &["cache", "clean"])
+        }
+        "bun" => ("bun".to_string(), &["pm", "cache", "rm"]),
+        _ if paths::is_installed("cargo-cache") => {
+            ("cargo".to_string(), &["cache", "--autoclean"])
+        }
+        _ => {
+            return Err(
+                "cargo can't clean up its registry cache by itself, `cargo install cargo-cache` adds `cargo cache --autoclean`"
+                    .to_string(),
+            )
+        }
+    };
+    Ok((command, args.iter().map(|arg| arg.to_string()).collect()))
+}
+
+// Exits with an error while there are vulnerable tools, so a CI job can fail on them
+fn audit_installed_tools(fix: bool, args: &Args) -> Result<(), String> {
+    let packages: Vec<inventory::InstalledPackage> = USER_MANAGERS
+        .into_iter()
+        .filter(|manager| audit::get_ecosystem(manager).is_some())
+        .filter(|manager| paths::is_installed(get_manager_executable(manager)))
+        .flat_map(get_installed_packages)
+        .collect();
+    // a crate from git or another registry isn't the one of the same name on crates.io
+    let cargo_installs = cargo::read_cargo_installs();
+    let packages: Vec<&inventory::InstalledPackage> = packages
+        .iter()
+        .filter(|package| {
+            package.package_manager != "cargo"
+                || cargo_installs.iter().any(|install| {
+                    install.crate_name == package.name && cargo::is_from_crates_io(install)
+                })
+        })
+        .collect();
+
+    let vulnerabilities = audit::find_vulnerabilities(&packages)?;
+    let vulnerable: Vec<(&inventory::InstalledPackage, &Vec<String>)> = packages
+        .iter()
+        .copied()
+        .zip(&vulnerabilities)
+        .filter(|(_, ids)| !ids.is_empty())
+        .collect();
+    if vulnerable.is_empty() {
+        println!(
+            "{}",
+            l10n::text("audit-none", &[("count", &packages.len().to_string())])
+        );
+        return Ok(());
+    }
+
+    for (package, ids) in &vulnerable {
+        println!(
+            "{}",
+            l10n::text(
+                "audit-vulnerable",
+                &[
+                    ("package", &package.name),
+                    ("version", &package.version),
+                    ("manager", &package.package_manager)
+                ]
+            )
+        );
+        let advisories = process::map_concurrently(ids, LATEST_VERSION_JOBS, |id| {
+            audit::get_advisory(id, package)
+        });
+        for advisory in advisories {
+            let severity = advisory
+                .severity
+                .map(|severity| format!(" [{}]", severity))
+                .unwrap_or_default();
+            let fixed = advisory
+                .fixed_version
+                .map(|fixed_version| l10n::text("audit-fixed-in", &[("version", &fixed_version)]))
+                .unwrap_or_default();
+            println!(
+                "    {}",
+                l10n::text(
+                    "audit-advisory",
+                    &[
+                        ("id", &advisory.id),
+                        ("severity", &severity),
+                        ("summary", &advisory.summary),
+                        ("fixed", &fixed)
+                    ]
+                )
+            );
+        }
+    }
+
+    if !fix {
+        let id = if vulnerable.len() == 1 {
+            "audit-vulnerable-one"
+        } else {
+            "audit-vulnerable-many"
+        };
+        return Err(l10n::text(id, &[("count", &vulnerable.len().to_string())]));
+    }
+
+    // the fix may well be in a new major version, so this goes to the latest one
+    let mut failed = Vec::new();
+    for (package, _) in &vulnerable {
+        let result = get_update_command(&package.package_manager, &package.name, true)
+            .and_then(|(command, command_args)| {
+                let needs_root = get_root_owned_global_dir(&package.package_manager).is_some()
+                    && !system::is_root();
+                if !needs_root {
+                    return Ok((command, command_args));
+                }
+                let (command, command_args) = get_sudo_command(command, command_args);
+                if args.sudo {
+                    Ok((command, command_args))
+                } else {
+                    Err(l10n::text(
+                        "root-only-packages",
+                        &[
+                            ("manager", &package.package_manager),
+                            ("command", &command),
+                            ("command-args", &command_args.join(" ")),
+                        ],
+                    ))
+                }
+            })
+            .and_then(|(command, command_args)| {
+                println!(
+                    "{}",
+                    l10n::text(
+                        "updating-with-command",
+                        &[
+                            ("package", &package.name),
+                            (
+                                "command",
+                                &format!("{} {}", command, command_args.join(" "))
+                            )
+                        ]
+                    )
+                );
+                let output = runner::run(
+                    &command,
+                    &command_args,
+                    args.timeout.map(Duration::from_secs),
+                    args.idle_timeout.map(Duration::from_secs),
+                )?;
+                match output.status {
+                    runner::RunStatus::Succeeded => Ok(()),
+                    _ => Err(l10n::text(
+                        "command-failed",
+                        &[
+                            ("command", &command),
+                            ("command-args", &command_args.join(" ")),
+                        ],
+                    )),
+                }
+            });
+        if let Err(e) = result {
+            eprintln!("{}", l10n::text("error", &[("message", &e)]));
+            failed.push(package.name.as_str());
+        }
+    }
+    if failed.is_empty() {
+        let id = if vulnerable.len() == 1 {
+            "audit-fixed-one"
+        } else {
+            "audit-fixed"
+        };
+        println!(
+            "{}",
+            l10n::text(id, &[("count", &vulnerable.len().to_string())])
+        );
+        Ok(())
+    } else {
+        Err(l10n::text(
+            "failed-to-update",
+            &[("names", &failed.join(", "))],
+        ))
+    }
+}
+
+// Finding the managers' bin dirs means asking most of them, which is too slow for every press of
+// tab, so the list is cached for a while
+const COMPLETION_TTL_SECS: u64 = 60 * 60;
+
+// The binaries of the packages installed with the user package managers and the ones updated
+// before, one per line
+fn print_completions(prefix: &str) {
+    let mut cache = cache::load("completions");
+    let bin_names = match cache.get("bins", COMPLETION_TTL_SECS) {
+        Some(bin_names) => bin_names.to_string(),
+        None => {
+            let bin_names = get_managed_bin_names().join("\n");
+            cache.set("bins", &bin_names);
+            cache.save();
+            bin_names
+        }
+    };
+    for bin_name in bin_names
+        .lines()
+        .filter(|bin_name| bin_name.starts_with(prefix))
+    {
+        println!("{}", bin_name);
+    }
+}
+
+fn get_managed_bin_names() -> Vec<String> {
+    let mut packages = Vec::new();
+    for manager in USER_MANAGERS
+        .into_iter()
+        .filter(|manager| paths::is_installed(get_manager_executable(manager)))
+    {
+        packages.extend(get_installed_packages(manager));
+    }
+
+    // a global bin dir can be shared with the system, e.g. /usr/bin with a distro's nodejs, so
+    // only the bins the packages declare count
+    let mut bin_names: Vec<String> = packages
+        .into_iter()
+        .flat_map(|package| {
+            get_package_bins(&PackageManager {
+                name: package.package_manager,
+                package_name: package.name,
+            })
+        })
+        .chain(
+            history::read_history()
+                .into_iter()
+                .map(|entry| entry.bin_name)
+                .filter(|bin_name| paths::is_installed(bin_name)),
+        )
+        .collect();
+    bin_names.sort();
+    bin_names.dedup();
+    bin_names
+}
+
+// The user vouches for the binary, so it only has to exist and not belong to a package manager
+// that update-bin already updates it with
+fn adopt_binary(
+    bin_name: &str,
+    update_command: &str,
+    version_command: Option<&str>,
+) -> Result<(), String> {
+    let bin_name = paths::normalize_bin_name(bin_name);
+    process::check_argument(&bin_name, "binary name")?;
+    let bin_path = find_bin_path(&bin_name)?;
+    if get_adopted(&bin_name).is_none() {
+        if let Ok(package_manager) = detect_package_manager(&bin_name, &bin_path) {
+            return Err(l10n::text(
+                "adopt-already-managed",
+                &[
+                    ("bin", &bin_name),
+                    ("manager", &package_manager.name),
+                    ("package", &package_manager.package_name),
+                ],
+            ));
+        }
+    }
+
+    let config_path = config::save_adopted(&bin_name, update_command, version_command)?;
+    println!(
+        "{}",
+        l10n::text(
+            "adopted",
+            &[
+                ("bin", &bin_name),
+                ("config", &config_path.display().to_string()),
+                ("command", update_command)
+            ]
+        )
+    );
+    Ok(())
+}
+
+// (update command, version command)
+fn get_adopted(bin_name: &str) -> Option<(String, Option<String>)> {
+    config::load_config()
+        .ok()?
+        .get_adopted()
+        .into_iter()
+        .find(|(adopted_bin_name, _, _)| adopted_bin_name == bin_name)
+        .map(|(_, update_command, version_command)| (update_command, version_command))
+}
+
+// The first line the version command prints, or what the binary says about itself
+fn get_adopted_version(bin_path: &str, version_command: Option<&str>) -> Result<String, String> {
+    let version_command = match version_command {
+        Some(version_command) => version_command,
+        None => return get_binary_version(bin_path),
+    };
+    let (shell, args) = process::get_shell_command(version_command);
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    get_command_output(shell, &args)
+        .and_then(|output| {
+            output
+                .lines()
+                .map(|line| line.trim().to_string())
+                .find(|line| !line.is_empty())
+        })
+        .ok_or_else(|| {
+            l10n::text(
+                "adopted-version-not-printed",
+                &[("version-command", version_command)],
+            )
+        })
+}
+
+// Installs what the files declare and isn't installed yet, and updates the rest. Versions in
+// .tool-versions are exact, so those are only installed, Go tools are installed at their latest
+// version
+fn sync_manifests(files: &[PathBuf], write: bool, args: &Args) -> Result<(), String> {
+    let files: Vec<PathBuf> = if files.is_empty() {
+        [
+            "Brewfile",
+            ".tool-versions",
+            "package.json",
+            "tools.go",
+            ".tool-deps",
+        ]
+        .into_iter()
+        .map(PathBuf::from)
+        .filter(|path| path.is_file())
+        .collect()
+    } else {
+        files.to_vec()
+    };
+    if files.is_empty() {
+        return Err(
+            "There's no Brewfile, .tool-versions, package.json, tools.go or .tool-deps here, pass the files to sync"
+                .to_string(),
+        );
+    }
+
+    // each with the file declaring it, for --write
+    let mut tools = Vec::new();
+    for path in &files {
+        for tool in manifest::read_manifest(path)? {
+            tools.push((path.as_path(), tool));
+        }
+    }
+    let version_manager = ["mise", "asdf"]
+        .into_iter()
+        .find(|manager| paths::is_installed(manager));
+    let mut installed: Vec<inventory::InstalledPackage> = Vec::new();
+    for manager in ["homebrew", "npm"] {
+        let is_declared = tools
+            .iter()
+            .any(|(_, tool)| tool.package_manager.starts_with(manager));
+        if is_declared && paths::is_installed(get_manager_executable(manager)) {
+            installed.extend(get_installed_packages(manager));
+        }
+    }
+
+    let mut failed = Vec::new();
+    // (file, Go package, version installed)
+    let mut go_versions: Vec<(&Path, &str, String)> = Vec::new();
+    for (path, tool) in &tools {
+        let command = get_sync_command(tool, &installed, version_manager);
+        let (command, command_args) = match command {
+            Ok(command) => command,
+            Err(e) => {
+                eprintln!("{}", l10n::text("error", &[("message", &e)]));
+                failed.push(tool.name.as_str());
+                continue;
+            }
+        };
+        println!(
+            "{}",
+            l10n::text(
+                "syncing",
+                &[
+                    ("package", &tool.name),
+                    (
+                        "command",
+                        &format!("{} {}", command, command_args.join(" "))
+                    )
+                ]
+            )
+        );
+        let output = runner::run(
+            &command,
+            &command_args,
+            args.timeout.map(Duration::from_secs),
+            args.idle_timeout.map(Duration::from_secs),
+        );
+        match output.map(|output| output.status) {
+            Ok(runner::RunStatus::Succeeded) if tool.package_manager == "go" && write => {
+                match go::get_installed_version(&tool.name) {
+                    Some(version) => go_versions.push((path, &tool.name, version)),
+                    None => {
+                        eprintln!(
+                            "{}",
+                            l10n::text(
+                                "error",
+                                &[(
+                                    "message",
+                                    &l10n::text("sync-version-not-found", &[("tool", &tool.name)])
+                                )]
+                            )
+                        );
+                        failed.push(tool.name.as_str());
+                    }
+                }
+            }
+            Ok(runner::RunStatus::Succeeded) => {}
+            Ok(_) => {
+                eprintln!(
+                    "{}",
+                    l10n::text(
+                        "error",
+                        &[(
+                            "message",
+                            &l10n::text(
+                                "command-failed",
+                                &[
+                                    ("command", &command),
+                                    ("command-args", &command_args.join(" "))
+                                ]
+                            )
+                        )]
+                    )
+                );
+                failed.push(tool.name.as_str());
+            }
+            Err(e) => {
+                eprintln!("{}", l10n::text("error", &[("message", &e)]));
+                failed.push(tool.name.as_str());
+            }
+        }
+    }
+
+    for path in files.iter().map(|path| path.as_path()) {
+        let file_versions: Vec<&(&Path, &str, String)> = go_versions
+            .iter()
+            .filter(|(tool_path, _, _)| *tool_path == path)
+            .collect();
+        if file_versions.is_empty() {
+            continue;
+        }
+        let versions: Vec<(String, String)> = file_versions
+            .iter()
+            .map(|(_, name, version)| (name.to_string(), version.clone()))
+            .collect();
+        if let Err(e) = write_go_versions(path, &versions, args) {
+            eprintln!("{}", l10n::text("error", &[("message", &e)]));
+            failed.extend(file_versions.iter().map(|(_, name, _)| *name));
+        }
+    }
+
+    if failed.is_empty() {
+        println!(
+            "{}",
+            l10n::text("synced", &[("count", &tools.len().to_string())])
+        );
+        Ok(())
+    } else {
+        Err(l10n::text(
+            "failed-to-sync",
+            &[("names", &failed.join(", "))],
+        ))
+    }
+}
+
+// Each binary is left alone when it's already recent enough, otherwise updated, or installed
+// with --install-with when it's missing. Fails when any of them still falls short afterwards,
+// for bootstrap scripts to stop at
+fn ensure_versions(
+    constraints: &[String],
+    install_with: Option<&str>,
+    args: &Args,
+) -> Result<(), String> {
+    if let Some(manager) = install_with {
+        if !["homebrew", "npm", "cargo"].contains(&manager) {
+            return Err(l10n::text(
+                "ensure-unknown-manager",
+                &[("manager", manager)],
+            ));
+        }
+    }
+    let constraints = constraints
+        .iter()
+        .map(|constraint| parse_version_constraint(constraint))
+        .collect::<Result<Vec<(&str, Option<&str>)>, String>>()?;
+
+    let mut failed = Vec::new();
+    for (bin_name, min_version) in constraints {
+        let requirement = match min_version {
+            Some(min_version) => format!("{}>={}", bin_name, min_version),
+            None => bin_name.to_string(),
+        };
+        let outcome = match get_installed_bin_version(bin_name) {
+            Some(version) if satisfies_min_version(&version, min_version) => {
+                println!(
+                    "{}",
+                    l10n::text(
+                        "satisfies",
+                        &[
+                            ("bin", bin_name),
+                            ("version", &version),
+                            ("requirement", &requirement)
+                        ]
+                    )
+                );
+                continue;
+            }
+            Some(version) => {
+                println!(
+                    "{}",
+                    l10n::text(
+                        "updating-below-minimum",
+                        &[("bin", bin_name), ("version", &version)]
+                    )
+                );
+                update_binary(bin_name, args)
+            }
+            None => match install_with {
+                Some(manager) => {
+                    println!(
+                        "{}",
+                        l10n::text(
+                            "installing-missing",
+                            &[("bin", bin_name), ("manager", manager)]
+                        )
+                    );
+                    install_binary(bin_name, manager, min_version, args)
+                }
+                None => Err(l10n::text("ensure-not-installed", &[("bin", bin_name)])),
+            },
+        };
+
+        // the manager may only have got it this far, or not know a version that's recent enough
+        let result = outcome.and_then(|_| match get_installed_bin_version(bin_name) {
+            Some(version) if satisfies_min_version(&version, min_version) => Ok(()),
+            Some(version) => Err(l10n::text(
+                "ensure-still-too-old",
+                &[
+                    ("bin", bin_name),
+                    ("version", &version),
+                    ("requirement", &requirement),
+                ],
+            )),
+            None => Err(l10n::text("ensure-still-not-found", &[("bin", bin_name)])),
+        });
+        if let Err(e) = result {
+            eprintln!("{}", l10n::text("error", &[("message", &e)]));
+            failed.push(requirement);
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(l10n::text(
+            "could-not-satisfy",
+            &[("names", &failed.join(", "))],
+        ))
+    }
+}
+
+// `node>=20`, or `node` for any version
+fn parse_version_constraint(constraint: &str) -> Result<(&str, Option<&str>), String> {
+    let (bin_name, min_version) = match constraint.split_once(">=") {
+        Some((bin_name, min_version)) => (bin_name.trim(), Some(min_version.trim())),
+        None => (constraint.trim(), None),
+    };
+    if bin_name.contains(['<', '>', '=', '^', '~']) {
+        return Err(l10n::text(
+            "invalid-constraint",
+            &[("constraint", constraint)],
+        ));
+    }
+    process::check_argument(bin_name, "binary name")?;
+    if let Some(min_version) = min_version {
+        process::check_argument(min_version, "version")?;
+        if version::parse_version(min_version).is_none() {
+            return Err(l10n::text(
+                "invalid-constraint-version",
+                &[("min-version", min_version), ("constraint", constraint)],
+            ));
+        }
+    }
+    Ok((bin_name, min_version))
+}
+
+// The version the binary's manager reports, or the binary itself when there's no telling which
+// manager it came from
+fn get_installed_bin_version(bin_name: &str) -> Option<String> {
+    let bin_path = find_all_bin_paths(bin_name).into_iter().next()?;
+    detect_package_manager(bin_name, &bin_path)
+        .and_then(|package_manager| get_version(&bin_path, &package_manager))
+        .or_else(|_| get_binary_version(&bin_path))
+        .ok()
+}
+
+// A version that can't be parsed can't be shown to be recent enough
+fn satisfies_min_version(version: &str, min_version: Option<&str>) -> bool {
+    match min_version {
+        Some(min_version) => version::compare_versions(version, min_version)
+            .is_some_and(|ordering| ordering != Ordering::Less),
+        None => true,
+    }
+}
+
+// The package named like the binary, at a version no lower than the one asked for
+fn install_binary(
+    bin_name: &str,
+    manager: &str,
+    min_version: Option<&str>,
+    args: &Args,
+) -> Result<(), String> {
+    let (command, command_args) = match manager {
+        "homebrew" => (
+            homebrew::get_brew_command(),
+            vec!["install".to_string(), bin_name.to_string()],
+        ),
+        "npm" => (
+            get_npm_executable().to_string(),
+            vec![
+                "install".to_string(),
+                "-g".to_string(),
+                match min_version {
+                    Some(min_version) => format!("{}@>={}", bin_name, min_version),
+                    None => bin_name.to_string(),
+                },
+            ],
+        ),
+        _ => {
+            let mut command_args = vec!["install".to_string(), bin_name.to_string()];
+            if let Some(min_version) = min_version {
+                command_args.push("--version".to_string());
+                command_args.push(format!(">={}", min_version));
+            }
+            ("cargo".to_string(), command_args)
+        }
+    };
+    let output = runner::run(
+        &command,
+        &command_args,
+        args.timeout.map(Duration::from_secs),
+        args.idle_timeout.map(Duration::from_secs),
+    )?;
+    match output.status {
+        runner::RunStatus::Succeeded => Ok(()),
+        _ => Err(l10n::text(
+            "command-failed",
+            &[
+                ("command", &command),
+                ("command-args", &command_args.join(" ")),
+            ],
+        )),
+    }
+}
+
+// A .tool-deps file holds the versions itself, those of tools.go are in the module's go.mod,
+// which `go get` updates along with go.sum
+fn write_go_versions(
+    path: &Path,
+    versions: &[(String, String)],
+    args: &Args,
+) -> Result<(), String> {
+    if path
+        .file_name()
+        .is_some_and(|file_name| file_name == ".tool-deps")
+    {
+        manifest::write_tool_deps(path, versions)?;
+        println!(
+            "{}",
+            l10n::text("wrote-versions", &[("path", &path.display().to_string())])
+        );
+        return Ok(());
+    }
+
+    let module_dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut command_args = vec![
+        "-C".to_string(),
+        module_dir.to_string_lossy().to_string(),
+        "get".to_string(),
+    ];
+    for (name, version) in versions {
+        process::check_argument(version, "version")?;
+        command_args.push(format!("{}@{}", name, version));
+    }
+    println!(
+        "{}",
+        l10n::text(
+            "updating-go-mod",
+            &[("command", &format!("go {}", command_args.join(" ")))]
+        )
+    );
+    let output = runner::run(
+        "go",
+        &command_args,
+        args.timeout.map(Duration::from_secs),
+        args.idle_timeout.map(Duration::from_secs),
+    )?;
+    match output.status {
+        runner::RunStatus::Succeeded => Ok(()),
+        _ => Err(l10n::text(
+            "go-get-failed",
+            &[("command-args", &command_args.join(" "))],
+        )),
+    }
+}
+
+// The node managers install a package from its tarball as they would from the registry. A
+// crate can't be built offline without all of its dependencies, and Homebrew's bottles are
+// behind a registry login, so those aren't planned
+const PLAN_MANAGERS: [&str; 4] = ["npm", "pnpm", "yarn", "bun"];
+
+fn write_update_plan(
+    file: &Path,
+    inventory_path: Option<&Path>,
+    artifacts: Option<&Path>,
+) -> Result<(), String> {
+    let packages: Vec<(inventory::InstalledPackage, Option<String>)> = match inventory_path {
+        Some(inventory_path) => {
+            let content = std::fs::read_to_string(inventory_path).map_err(|e| {
+                l10n::text(
+                    "failed-to-read",
+                    &[
+                        ("path", &inventory_path.display().to_string()),
+                        ("error", &e.to_string()),
+                    ],
+                )
+            })?;
+            let sbom: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
+                l10n::text(
+                    "invalid-sbom",
+                    &[
+                        ("inventory-path", &inventory_path.display().to_string()),
+                        ("error", &e.to_string()),
+                    ],
+                )
+            })?;
+            let packages: Vec<inventory::InstalledPackage> = inventory::from_cyclonedx(&sbom)
+                .into_iter()
+                .filter(|package| PLAN_MANAGERS.contains(&package.package_manager.as_str()))
+                .collect();
+            let latest_versions =
+                process::map_concurrently(&packages, LATEST_VERSION_JOBS, |package| {
+                    query_latest_version(&package.package_manager, &package.name)
+                });
+            packages.into_iter().zip(latest_versions).collect()
+        }
+        None => get_latest_versions(true, LATEST_VERSION_JOBS)?,
+    };
+
+    let mut updates = Vec::new();
+    let mut skipped = Vec::new();
+    let mut unavailable = Vec::new();
+    for (package, latest_version) in packages {
+        let latest_version = match latest_version {
+            Some(latest_version) => latest_version,
+            None => continue,
+        };
+        if version::compare_versions(&package.version, &latest_version) != Some(Ordering::Less) {
+            continue;
+        }
+        if !PLAN_MANAGERS.contains(&package.package_manager.as_str()) {
+            skipped.push(format!("{} ({})", package.name, package.package_manager));
+            continue;
+        }
+        let details =
+            registry::get_release_details(&package.package_manager, &package.name, &latest_version);
+        let (url, integrity, signatures) = match details {
+            Some(registry::ReleaseDetails {
+                download_url: Some(url),
+                integrity: Some(integrity),
+                signatures,
+                ..
+            }) => (url, integrity, signatures),
+            _ => {
+                unavailable.push(format!("{} {}", package.name, latest_version));
+                continue;
+            }
+        };
+        // one made with a key update-bin trusts, otherwise apply says which key it was
+        let trusted = signatures
+            .iter()
+            .position(|(keyid, _)| signature::find_trusted_key(keyid).is_some())
+            .unwrap_or(0);
+        let signature = signatures
+            .into_iter()
+            .nth(trusted)
+            .map(|(keyid, sig)| signature::RegistrySignature { keyid, sig });
+        updates.push(plan::PlannedUpdate {
+            package_manager: package.package_manager,
+            package_name: package.name,
+            current_version: package.version,
+            version: latest_version,
+            url,
+            integrity,
+            signature,
+        });
+    }
+
+    if !skipped.is_empty() {
+        eprintln!(
+            "{}",
+            l10n::text(
+                "left-out-of-plan",
+                &[
+                    ("managers", &PLAN_MANAGERS.join(", ")),
+                    ("packages", &skipped.join(", "))
+                ]
+            )
+        );
+    }
+    if !unavailable.is_empty() {
+        eprintln!(
+            "{}",
+            l10n::text(
+                "left-out-no-download",
+                &[("packages", &unavailable.join(", "))]
+            )
+        );
+    }
+    if let Some(artifacts) = artifacts {
+        std::fs::create_dir_all(artifacts).map_err(|e| {
+            l10n::text(
+                "failed-to-create",
+                &[
+                    ("path", &artifacts.display().to_string()),
+                    ("error", &e.to_string()),
+                ],
+            )
+        })?;
+        let mut file_names = Vec::new();
+        for update in &updates {
+            let file_name = plan::get_file_name(&update.package_name, &update.version);
+            println!("{}", l10n::text("downloading", &[("file", &file_name)]));
+            let artifact = artifacts.join(&file_name);
+            http::download(&update.url, &artifact)?;
+            verify_artifact(&artifact, update)?;
+            // a signature that doesn't match is found here already, one that can't be checked
+            // for lack of openssl is left to apply
+            signature::verify(
+                &artifact,
+                &get_signed_message(update),
+                update.signature.as_ref(),
+            )?;
+            file_names.push(file_name);
+        }
+        plan::write_sha256sums(artifacts, &file_names)?;
+    }
+    plan::write_plan(file, &updates)?;
+    println!(
+        "{}",
+        l10n::text(
+            "wrote-plan",
+            &[
+                ("count", &updates.len().to_string()),
+                ("path", &file.display().to_string())
+            ]
+        )
+    );
+    Ok(())
+}
+
+fn apply_update_plan(
+    file: &Path,
+    artifacts: &Path,
+    require_signature: bool,
+    args: &Args,
+) -> Result<(), String> {
+    let updates = plan::read_plan(file)?;
+    let mut installed: Vec<inventory::InstalledPackage> = Vec::new();
+    for manager in PLAN_MANAGERS {
+        let is_planned = updates
+            .iter()
+            .any(|update| update.package_manager == manager);
+        if is_planned && paths::is_installed(get_manager_executable(manager)) {
+            installed.extend(get_installed_packages(manager));
+        }
+    }
+
+    let mut failed = Vec::new();
+    let mut applied = 0;
+    for update in &updates {
+        let result = apply_planned_update(update, artifacts, &installed, require_signature, args);
+        match result {
+            Ok(true) => applied += 1,
+            Ok(false) => {}
+            Err(e) => {
+                eprintln!("{}", l10n::text("error", &[("message", &e)]));
+                failed.push(update.package_name.as_str());
+            }
+        }
+    }
+
+    if failed.is_empty() {
+        println!(
+            "{}",
+            l10n::text(
+                "applied",
+                &[
+                    ("count", &applied.to_string()),
+                    ("total", &updates.len().to_string())
+                ]
+            )
+        );
+        Ok(())
+    } else {
+        Err(l10n::text(
+            "failed-to-update",
+            &[("names", &failed.join(", "))],
+        ))
+    }
+}
+
+// The artifact has to hash to what the registry published for the version, and to what the
+// SHA256SUMS next to it lists when there is one. A package that was corrupted or swapped on its
+// way to the offline machine is never installed
+fn verify_artifact(artifact: &Path, update: &plan::PlannedUpdate) -> Result<(), String> {
+    let mismatch = |checksum: &str| {
+        l10n::text(
+            "checksum-mismatch",
+            &[
+                ("artifact", &artifact.display().to_string()),
+                ("checksum", checksum),
+                ("package", &update.package_name),
+                ("version", &update.version),
+                ("url", &update.url),
+            ],
+        )
+    };
+    if !digest::matches_integrity(artifact, &update.integrity)? {
+        return Err(mismatch("the checksum the registry published"));
+    }
+
+    let sha256sums_path = artifact.with_file_name(plan::SHA256SUMS);
+    if let Ok(sha256sums) = std::fs::read_to_string(&sha256sums_path) {
+        let file_name = artifact
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let sha256 = digest::to_hex(&digest::hash_file(artifact, "sha256")?);
+        if plan::find_sha256(&sha256sums, &file_name) != Some(sha256) {
+            return Err(mismatch(&l10n::text(
+                "checksum-in-file",
+                &[("sha256sums-path", &sha256sums_path.display().to_string())],
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn get_signed_message(update: &plan::PlannedUpdate) -> String {
+    signature::get_signed_message(&update.package_name, &update.version, &update.integrity)
+}
+
+// A signature that doesn't match always stops the update, a missing or unchecked one only with
+// --require-signature
+fn check_artifact_signature(
+    artifact: &Path,
+    update: &plan::PlannedUpdate,
+    require_signature: bool,
+) -> Result<(), String> {
+    let verification = signature::verify(
+        artifact,
+        &get_signed_message(update),
+        update.signature.as_ref(),
+    )?;
+    match verification {
+        signature::Verification::Verified => Ok(()),
+        signature::Verification::Unsigned if require_signature => Err(l10n::text(
+            "signature-missing",
+            &[
+                ("package", &update.package_name),
+                ("version", &update.version),
+            ],
+        )),
+        signature::Verification::Unsigned => Ok(()),
+        signature::Verification::Unverifiable(reason) => {
+            let problem = l10n::text(
+                "signature-unverified",
+                &[
+                    ("package", &update.package_name),
+                    ("version", &update.version),
+                    ("reason", &reason),
+                ],
+            );
+            if require_signature {
+                return Err(l10n::text("signature-required", &[("problem", &problem)]));
+            }
+            eprintln!(
+                "{}",
+                l10n::text("warning-message", &[("message", &problem)])
+            );
+            Ok(())
+        }
+    }
+}
+
+// Whether the package was updated, it's left alone when it's already at the planned version
+fn apply_planned_update(
+    update: &plan::PlannedUpdate,
+    artifacts: &Path,
+    installed: &[inventory::InstalledPackage],
+    require_signature: bool,
+    args: &Args,
+) -> Result<bool, String> {
+    if !PLAN_MANAGERS.contains(&update.package_manager.as_str()) {
+        return Err(l10n::text(
+            "offline-unsupported",
+            &[
+                ("package", &update.package_name),
+                ("manager", &update.package_manager),
+            ],
+        ));
+    }
+    process::check_argument(&update.package_name, "package name")?;
+    process::check_argument(&update.version, "version")?;
+    if update.version.contains(['/', '\\']) {
+        return Err(l10n::text(
+            "invalid-version-in-plan",
+            &[("version", &update.version)],
+        ));
+    }
+
+    let installed_version = installed
+        .iter()
+        .find(|package| {
+            package.package_manager == update.package_manager && package.name == update.package_name
+        })
+        .map(|package| package.version.as_str());
+    if let Some(installed_version) = installed_version.filter(|installed_version| {
+        version::compare_versions(installed_version, &update.version) != Some(Ordering::Less)
+    }) {
+        println!(
+            "{}",
+            l10n::text(
+                "already-at",
+                &[
+                    ("package", &update.package_name),
+                    ("version", installed_version)
+                ]
+            )
+        );
+        return Ok(false);
+    }
+
+    let artifact = artifacts.join(plan::get_file_name(&update.package_name, &update.version));
+    if !artifact.is_file() {
+        return Err(l10n::text(
+            "artifact-missing",
+            &[
+                ("artifact", &artifact.display().to_string()),
+                ("url", &update.url),
+            ],
+        ));
+    }
+    verify_artifact(&artifact, update)?;
+    check_artifact_signature(&artifact, update, require_signature)?;
+    // a relative `dir/name.tgz` would be taken for a GitHub repository
+    let artifact = std::path::absolute(&artifact)
+        .map_err(|e| {
+            l10n::text(
+                "failed-to-resolve",
+                &[
+                    ("artifact", &artifact.display().to_string()),
+                    ("error", &e.to_string()),
+                ],
+            )
+        })?
+        .to_string_lossy()
+        .to_string();
+    let (command, command_args) = match update.package_manager.as_str() {
+        "npm" => (get_npm_executable(), vec!["install", "-g", &artifact]),
+        "yarn" => ("yarn", vec!["global", "add", &artifact]),
+        manager => (manager, vec!["add", "-g", &artifact]),
+    };
+    let command_args: Vec<String> = command_args.iter().map(|arg| arg.to_string()).collect();
+    println!(
+        "{}",
+        l10n::text(
+            "updating-from-plan",
+            &[
+                ("package", &update.package_name),
+                (
+                    "change",
+                    &version::format_change(
+                        installed_version.unwrap_or(&update.current_version),
+                        &update.version
+                    )
+                ),
+                (
+                    "command",
+                    &format!("{} {}", command, command_args.join(" "))
+                )
+            ]
+        )
+    );
+    let output = runner::run(
+        command,
+        &command_args,
+        args.timeout.map(Duration::from_secs),
+        args.idle_timeout.map(Duration::from_secs),
+    )?;
+    match output.status {
+        runner::RunStatus::Succeeded => Ok(true),
+        _ => Err(l10n::text(
+            "command-failed-with-output",
+            &[
+                (
+                    "command",
+                    &format!("{} {}", command, command_args.join(" ")),
+                ),
+                ("output", &runner::format_last_lines(&output.lines)),
+            ],
+        )),
+    }
+}
+
+fn print_snapshot() -> Result<(), String> {
+    let mut tools = Vec::new();
+    for manager in USER_MANAGERS {
+        if !paths::is_installed(get_manager_executable(manager)) {
+            continue;
+        }
+        match manager {
+            // tapped formulas are installed again by their full name, which taps them
+            "homebrew" => tools.extend(homebrew::get_installed_packages().into_iter().filter_map(
+                |package| {
+                    Some(snapshot::SnapshotTool {
+                        package_manager: if package.cask {
+                            "homebrew-cask".to_string()
+                        } else {
+                            "homebrew".to_string()
+                        },
+                        name: package.full_name,
+                        version: package.installed_version?,
+                        source: None,
+                        args: if package.head {
+                            vec!["--HEAD".to_string()]
+                        } else {
+                            Vec::new()
+                        },
+                    })
+                },
+            )),
+            "cargo" => tools.extend(cargo::read_cargo_installs().into_iter().map(|install| {
+                snapshot::SnapshotTool {
+                    package_manager: "cargo".to_string(),
+                    args: cargo::get_build_args(&install),
+                    name: install.crate_name,
+                    version: install.version,
+                    source: Some(install.source),
+                }
+            })),
+            // the update command is all there is to know about how it was installed
+            "adopted" => {
+                let config = config::load_config()?;
+                for (bin_name, update_command, version_command) in config.get_adopted() {
+                    let version = get_adopted_version(&bin_name, version_command.as_deref())
+                        .unwrap_or_else(|_| "unknown".to_string());
+                    tools.push(snapshot::SnapshotTool {
+                        package_manager: "adopted".to_string(),
+                        name: bin_name,
+                        version,
+                        source: Some(update_command),
+                        args: Vec::new(),
+                    });
+                }
+            }
+            _ => tools.extend(get_installed_packages(manager).into_iter().map(|package| {
+                snapshot::SnapshotTool {
+                    package_manager: package.package_manager,
+                    name: package.name,
+                    version: package.version,
+                    source: None,
+                    args: Vec::new(),
+                }
+            })),
+        }
+    }
+    print_json(&snapshot::to_json(&tools))
+}
+
+// The tools are installed in the order they were snapshotted, which is USER_MANAGERS' order,
+// so the node and rust Homebrew installs are there before the packages that need them
+fn restore_snapshot(file: &Path, args: &Args) -> Result<(), String> {
+    let tools = snapshot::read_snapshot(file)?;
+    let mut installed: Vec<inventory::InstalledPackage> = Vec::new();
+    let mut listed_managers: Vec<&str> = Vec::new();
+
+    let mut failed = Vec::new();
+    let mut restored = 0;
+    for tool in &tools {
+        let manager = match tool.package_manager.as_str() {
+            "homebrew-cask" => "homebrew",
+            manager => manager,
+        };
+        if !listed_managers.contains(&manager)
+            && paths::is_installed(get_manager_executable(manager))
+        {
+            listed_managers.push(manager);
+            installed.extend(get_installed_packages(manager));
+        }
+        match restore_tool(tool, manager, &installed, args) {
+            Ok(true) => restored += 1,
+            Ok(false) => {}
+            Err(e) => {
+                eprintln!("{}", l10n::text("error", &[("message", &e)]));
+                failed.push(tool.name.as_str());
+            }
+        }
+    }
+
+    if failed.is_empty() {
+        println!(
+            "{}",
+            l10n::text(
+                "restored",
+                &[
+                    ("count", &restored.to_string()),
+                    ("total", &tools.len().to_string())
+                ]
+            )
+        );
+        Ok(())
+    } else {
+        Err(l10n::text(
+            "failed-to-restore",
+            &[("names", &failed.join(", "))],
+        ))
+    }
+}
+
+// Whether the tool was installed, it's left alone when it's already there
+fn restore_tool(
+    tool: &snapshot::SnapshotTool,
+    manager: &str,
+    installed: &[inventory::InstalledPackage],
+    args: &Args,
+) -> Result<bool, String> {
+    if manager == "adopted" {
+        println!(
+            "{}",
+            l10n::text(
+                "restore-adopted",
+                &[
+                    ("package", &tool.name),
+                    ("command", tool.source.as_deref().unwrap_or_default())
+                ]
+            )
+        );
+        return Ok(false);
+    }
+    if !paths::is_installed(get_manager_executable(manager)) {
+        return Err(l10n::text(
+            "restore-manager-not-installed",
+            &[("tool", &tool.name), ("manager", manager)],
+        ));
+    }
+
+    // Homebrew lists a tapped formula by its short name
+    let short_name = match manager {
+        "homebrew" => tool.name.rsplit('/').next().unwrap_or(&tool.name),
+        _ => tool.name.as_str(),
+    };
+    let installed_version = installed
+        .iter()
+        .find(|package| package.package_manager == manager && package.name == short_name)
+        .map(|package| package.version.as_str());
+    if installed_version == Some(tool.version.as_str())
+        || (manager == "homebrew" && installed_version.is_some())
+    {
+        println!(
+            "{}",
+            l10n::text(
+                "already-installed",
+                &[
+                    ("package", &tool.name),
+                    ("version", installed_version.unwrap_or_default())
+                ]
+            )
+        );
+        return Ok(false);
+    }
+
+    let (mut command, mut command_args) = get_restore_command(tool)?;
+    if let Some(global_dir) = get_root_owned_global_dir(manager).filter(|_| !system::is_root()) {
+        (command, command_args) = get_sudo_command(command, command_args);
+        if !args.sudo {
+            return Err(l10n::text(
+                "global-dir-owned-by-root-run-sudo",
+                &[
+                    ("manager", manager),
+                    ("global-dir", &global_dir.display().to_string()),
+                    ("command", &command),
+                    ("command-args", &command_args.join(" ")),
+                ],
+            ));
+        }
+    }
+    // Homebrew only has the latest version of a formula
+    let version = if manager == "homebrew" {
+        "latest"
+    } else {
+        tool.version.as_str()
+    };
+    println!(
+        "{}",
+        l10n::text(
+            "restoring",
+            &[
+                ("package", &tool.name),
+                ("version", version),
+                (
+                    "command",
+                    &format!("{} {}", command, command_args.join(" "))
+                )
+            ]
+        )
+    );
+    let output = runner::run(
+        &command,
+        &command_args,
+        args.timeout.map(Duration::from_secs),
+        args.idle_timeout.map(Duration::from_secs),
+    )?;
+    match output.status {
+        runner::RunStatus::Succeeded => Ok(true),
+        _ => Err(l10n::text(
+            "command-failed-with-output",
+            &[
+                (
+                    "command",
+                    &format!("{} {}", command, command_args.join(" ")),
+                ),
+                ("output", &runner::format_last_lines(&output.lines)),
+            ],
+        )),
+    }
+}
+
+// The snapshot may come from anywhere, so only the options update-bin itself writes into one
+// are passed on
+fn get_restore_command(tool: &snapshot::SnapshotTool) -> Result<(String, Vec<String>), String> {
+    process::check_argument(&tool.name, "tool name")?;
+    process::check_argument(&tool.version, "version")?;
+    let allowed_args: &[&str] = match tool.package_manager.as_str() {
+        "homebrew" => &["--HEAD"],
+        "cargo" => &[
+            "--all-features",
+            "--no-default-features",
+            "--features=",
+            "--profile=",
+        ],
+        _ => &[],
+    };
+    if let Some(arg) = tool.args.iter().find(|arg| {
+        !allowed_args
+            .iter()
+            .any(|allowed| *arg == allowed || (allowed.ends_with('=') && arg.starts_with(allowed)))
+    }) {
+        return Err(l10n::text(
+            "invalid-snapshot-argument",
+            &[("arg", arg), ("tool", &tool.name)],
+        ));
+    }
+
+    match tool.package_manager.as_str() {
+        "homebrew" | "homebrew-cask" => {
+            let mut args = vec!["install".to_string()];
+            if tool.package_manager == "homebrew-cask" {
+                args.push("--cask".to_string());
+            }
+            args.extend(tool.args.iter().cloned());
+            args.push(tool.name.clone());
+            Ok((homebrew::get_brew_command(), args))
+        }
+        "cargo" => {
+            let source = tool
+                .source
+                .as_deref()
+                .ok_or_else(|| l10n::text("snapshot-no-source", &[("tool", &tool.name)]))?;
+            let mut args = vec!["install".to_string()];
+            args.extend(cargo::get_pinned_source_args(source, &tool.version));
+            args.extend(tool.args.iter().cloned());
+            args.push(tool.name.clone());
+            Ok(("cargo".to_string(), args))
+        }
+        manager => get_install_version_command(manager, &tool.name, &tool.version)
+            .ok_or_else(|| l10n::text("cant-restore-packages", &[("manager", manager)])),
+    }
+}
+
+fn get_sync_command(
+    tool: &manifest::DeclaredTool,
+    installed: &[inventory::InstalledPackage],
+    version_manager: Option<&str>,
+) -> Result<(String, Vec<String>), String> {
+    process::check_argument(&tool.name, "tool name")?;
+    if let Some(version) = &tool.version {
+        process::check_argument(version, "version")?;
+    }
+    // tapped formulas are listed as `user/tap/formula` and installed as `formula`
+    let short_name = tool.name.rsplit('/').next().unwrap_or(&tool.name);
+    let is_installed = |package_manager: &str, name: &str| {
+        installed
+            .iter()
+            .any(|package| package.package_manager == package_manager && package.name == name)
+    };
+
+    match tool.package_manager.as_str() {
+        "homebrew" | "homebrew-cask" if is_installed("homebrew", short_name) => {
+            get_update_command("homebrew", short_name, false)
+        }
+        "homebrew" | "homebrew-cask" => {
+            let mut args = vec!["install".to_string()];
+            if tool.package_manager == "homebrew-cask" {
+                args.push("--cask".to_string());
+            }
+            args.push(tool.name.clone());
+            Ok((homebrew::get_brew_command(), args))
+        }
+        "npm" if is_installed("npm", &tool.name) => get_update_command("npm", &tool.name, false),
+        "go" => get_update_command("go", &tool.name, false),
+        "npm" => {
+            let package = match &tool.version {
+                Some(range) => format!("{}@{}", tool.name, range),
+                None => tool.name.clone(),
+            };
+            Ok((
+                get_npm_executable().to_string(),
+                vec!["install".to_string(), "-g".to_string(), package],
+            ))
+        }
+        _ => {
+            let version = tool.version.clone().unwrap_or_default();
+            match version_manager {
+                Some("mise") => Ok((
+                    "mise".to_string(),
+                    vec!["install".to_string(), format!("{}@{}", tool.name, version)],
+                )),
+                Some(manager) => Ok((
+                    manager.to_string(),
+                    vec!["install".to_string(), tool.name.clone(), version],
+                )),
+                None => Err(l10n::text(
+                    "restore-no-version-manager",
+                    &[("tool", &tool.name)],
+                )),
+            }
+        }
+    }
+}
+
+// The installed managers --managers and --skip-managers leave
+fn get_selected_managers(
+    args: &Args,
+    config: &config::Config,
+) -> Result<Vec<&'static str>, String> {
+    for name in args.managers.iter().chain(&args.skip_managers) {
+        if !ALL_MANAGERS.contains(&name.as_str()) {
+            return Err(l10n::text(
+                "unknown-manager",
+                &[("name", name), ("managers", &ALL_MANAGERS.join(", "))],
+            ));
+        }
+    }
+    let mut managers: Vec<&str> = ALL_MANAGERS
+        .into_iter()
+        .filter(|manager| args.managers.is_empty() || args.managers.iter().any(|m| m == manager))
+        .filter(|manager| !args.skip_managers.iter().any(|m| m == manager))
+        .filter(|manager| system::is_available(manager))
+        .filter(|manager| paths::is_installed(get_manager_executable(manager)))
+        .filter(|manager| *manager != "adopted" || !config.get_adopted().is_empty())
+        .collect();
+    // the quickest first by how long their last run took, so that progress shows early. The
+    // ones that never ran keep their order, after the rest
+    let durations = history::get_manager_durations();
+    managers.sort_by_key(|manager| durations.get(*manager).copied().unwrap_or(Duration::MAX));
+    Ok(managers)
+}
+
+// Exit codes of `update-bin step`, which callers like topgrade can rely on
+const STEP_SUCCEEDED: i32 = 0;
+const STEP_FAILED: i32 = 1;
+const STEP_INVALID: i32 = 2;
+const STEP_NOTHING_TO_DO: i32 = 3;
+
+// --all for meta-updaters running update-bin as one of their steps: nothing is asked, and with
+// --json stdout only carries NDJSON events, the managers' own output stays in the events'
+// `output`. Running it again when everything is up to date changes nothing
+fn run_step(json: bool, args: &Args) -> i32 {
+    process::set_non_interactive();
+    let fail = |code: i32, message: &str| {
+        if json {
+            progress::print_event(serde_json::json!({
+                "event": "finished",
+                "status": "error",
+                "exit_code": code,
+                "error": message,
+            }));
+        } else {
+            eprintln!("{}", l10n::text("error", &[("message", message)]));
+        }
+        code
+    };
+    let managers =
+        match config::load_config().and_then(|config| get_selected_managers(args, &config)) {
+            Ok(managers) => managers,
+            Err(e) => return fail(STEP_INVALID, &e),
+        };
+    if managers.is_empty() {
+        return fail(
+            STEP_NOTHING_TO_DO,
+            "None of the selected package managers is installed",
+        );
+    }
+    if !json {
+        return match update_all(args) {
+            Ok(()) => STEP_SUCCEEDED,
+            Err(e) => fail(STEP_FAILED, &e),
+        };
+    }
+
+    progress::print_event(serde_json::json!({
+        "event": "started",
+        "version": 1,
+        "managers": managers,
+        "sudo": managers
+            .iter()
+            .filter(|manager| needs_elevation(manager))
+            .collect::<Vec<&&str>>(),
+    }));
+    let table = progress::LiveTable::with_events(&managers);
+    let indices: Vec<usize> = (0..managers.len()).collect();
+    let results = process::map_concurrently(&indices, args.jobs, |index| {
+        update_with_manager(managers[*index], args, Some((&table, *index)))
+    });
+
+    record_manager_durations(results.iter().map(|(run, _)| run));
+    let mut failed_managers = Vec::new();
+    let mut updated = 0;
+    for (manager, (run, manager_updates)) in managers.iter().zip(results) {
+        if let Some(e) = &run.error {
+            failed_managers.push(*manager);
+            progress::print_event(serde_json::json!({
+                "event": "manager_failed",
+                "manager": manager,
+                "error": e,
+                "output": runner::get_last_lines(&run.lines),
+            }));
+        }
+        for (package, old_version) in manager_updates {
+            updated += 1;
+            progress::print_event(serde_json::json!({
+                "event": "updated",
+                "manager": manager,
+                "package": package.name,
+                "from": old_version,
+                "to": package.version,
+            }));
+        }
+    }
+
+    let exit_code = if failed_managers.is_empty() {
+        STEP_SUCCEEDED
+    } else {
+        STEP_FAILED
+    };
+    progress::print_event(serde_json::json!({
+        "event": "finished",
+        "status": if failed_managers.is_empty() { "succeeded" } else { "failed" },
+        "exit_code": exit_code,
+        "updated": updated,
+        "failed_managers": failed_managers,
+    }));
+    exit_code
+}
+
+// Every package installed with a user package manager, with its latest version when the
+// registry or the manager told it
+fn get_latest_versions(
+    refresh: bool,
+    jobs: usize,
+) -> Result<Vec<(inventory::InstalledPackage, Option<String>)>, String> {
+    let config = config::load_config()?;
+    let ttl_secs = config
+        .get_number("cache_ttl_hours")?
+        .unwrap_or(DEFAULT_CACHE_TTL_HOURS)
+        * 60
+        * 60;
+    let mut cache = cache::load("latest-versions");
+
+    let mut packages = Vec::new();
+    for manager in USER_MANAGERS
+        .into_iter()
+        .filter(|manager| paths::is_installed(get_manager_executable(manager)))
+    {
+        packages.extend(get_installed_packages(manager));
+    }
+
+    let cache_key = |package: &inventory::InstalledPackage| {
+        format!("{}:{}", package.package_manager, package.name)
+    };
+    let uncached_packages: Vec<&inventory::InstalledPackage> = packages
+        .iter()
+        .filter(|package| refresh || cache.get(&cache_key(package), ttl_secs).is_none())
+        .collect();
+    // each lookup is a registry round trip, doing them one by one takes minutes
+    let looked_up_versions = process::map_concurrently(&uncached_packages, jobs, |package| {
+        query_latest_version(&package.package_manager, &package.name)
+    });
+    for (package, latest_version) in uncached_packages.iter().zip(&looked_up_versions) {
+        if let Some(latest_version) = latest_version {
+            cache.set(&cache_key(package), latest_version);
+        }
+    }
+    cache.save();
+
+    Ok(packages
+        .into_iter()
+        .map(|package| {
+            let latest_version = cache
+                .get(&cache_key(&package), ttl_secs)
+                .map(|latest_version| latest_version.to_string());
+            (package, latest_version)
+        })
+        .collect())
+}
+
+// Asks the registry directly, and the package manager only when that fails. Node packages all
+// come from the npm registry, whichever manager installed them. Crates installed from git or
+// a path have no latest version to compare with
+fn query_latest_version(package_manager: &str, package_name: &str) -> Option<String> {
+    process::check_argument(package_name, "package name").ok()?;
+
+    match package_manager {
+        "cargo" => {
+            let install = cargo::find_cargo_install_by_crate(package_name)?;
+            if !cargo::is_from_crates_io(&install) {
+                return None;
+            }
+            registry::get_latest_version("cargo", package_name)
+                .or_else(|| cargo::get_latest_version(package_name))
+        }
+        "homebrew" => registry::get_latest_version("homebrew", package_name)
+            .or_else(|| registry::get_latest_version("homebrew-cask", package_name))
+            .or_else(|| homebrew::get_brew_info(package_name)?.latest_version),
+        // the registry's package of the same name is another one
+        "npm" if get_npm_install_source(package_name).is_some() => None,
+        _ => registry::get_latest_version(package_manager, package_name).or_else(|| {
+            let output =
+                get_command_output(get_npm_executable(), &["view", package_name, "version"])
+                    .or_else(|| get_command_output("pnpm", &["view", package_name, "version"]))?;
+            let version = output.trim();
+            if version.is_empty() {
+                None
+            } else {
+                Some(version.to_string())
+            }
+        }),
+    }
+}
+
+// What the manager has installed globally
+fn get_installed_packages(package_manager: &str) -> Vec<inventory::InstalledPackage> {
+    let packages: Vec<(String, String)> = match package_manager {
+        "homebrew" => homebrew::get_installed_packages()
+            .into_iter()
+            .filter_map(|package| Some((package.name, package.installed_version?)))
+            .collect(),
+        "cargo" => cargo::read_cargo_installs()
+            .into_iter()
+            .map(|install| (install.crate_name, install.version))
+            .collect(),
+        "adopted" => config::load_config()
+            .map(|config| config.get_adopted())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(bin_name, _, version_command)| {
+                let version = get_adopted_version(&bin_name, version_command.as_deref()).ok()?;
+                Some((bin_name, version))
+            })
+            .collect(),
+        _ => get_global_node_packages(package_manager),
+    };
+
+    packages
+        .into_iter()
+        .map(|(name, version)| inventory::InstalledPackage {
+            package_manager: package_manager.to_string(),
+            name,
+            version,
+        })
+        .collect()
+}
+
+// yarn and bun hoist every dependency into their global node_modules, only the packages in
+// the global package.json were installed by the user
+fn get_global_node_packages(package_manager: &str) -> Vec<(String, String)> {
+    let mut packages = Vec::new();
+    for node_modules_dir in get_global_node_modules_dirs(package_manager) {
+        let names: Vec<String> = match package_manager {
+            "yarn" | "bun" => node_modules_dir
+                .parent()
+                .and_then(|global_dir| {
+                    let package_json =
+                        std::fs::read_to_string(global_dir.join("package.json")).ok()?;
+                    let package_json: serde_json::Value =
+                        serde_json::from_str(&package_json).ok()?;
+                    Some(
+                        package_json["dependencies"]
+                            .as_object()?
+                            .keys()
+                            .cloned()
+                            .collect(),
+                    )
+                })
+                .unwrap_or_default(),
+            _ => list_node_modules(&node_modules_dir),
+        };
+
+        for name in names {
+            let package_json_path = get_package_dir(&node_modules_dir, &name).join("package.json");
+            if let Some(version) = read_package_json_version(&package_json_path) {
+                packages.push((name, version));
+            }
+        }
+    }
+    packages
+}
+
+// Package names in a node_modules dir, scoped ones are nested a level deeper
+fn list_node_modules(node_modules_dir: &Path) -> Vec<String> {
+    let list_dir = |dir: &Path| -> Vec<String> {
+        std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .filter(|name| !name.starts_with('.'))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut names = Vec::new();
+    for name in list_dir(node_modules_dir) {
+        if name.starts_with('@') {
+            for scoped_name in list_dir(&node_modules_dir.join(&name)) {
+                names.push(format!("{}/{}", name, scoped_name));
+            }
+        } else {
+            names.push(name);
+        }
+    }
+    names.sort();
+    names
+}
+
+// The content hash and modification time of a file, following symlinks
+fn get_file_fingerprint(path: &str) -> Option<(u64, std::time::SystemTime)> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let content = std::fs::read(path).ok()?;
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    Some((hasher.finish(), modified))
+}
+
+struct PackageManager {
+    name: String,
+    package_name: String,
+}
+
+// The package name is passed to the manager, so make sure it can't be taken for an option
+fn detect_package_manager(bin_name: &str, bin_path: &str) -> Result<PackageManager, String> {
+    let package_manager = find_package_manager(bin_name, bin_path)?;
+    process::check_argument(&package_manager.package_name, "package name")?;
+    Ok(package_manager)
+}
+
+fn find_package_manager(bin_name: &str, bin_path: &str) -> Result<PackageManager, String> {
+    let is_adopted = get_adopted(bin_name).is_some();
+    trail::check("adopted", is_adopted, || {
+        l10n::text("explain-adopted", &[("bin", bin_name)])
+    });
+    if is_adopted {
+        return Ok(PackageManager {
+            name: "adopted".to_string(),
+            package_name: bin_name.to_string(),
+        });
+    }
+
+    // a correction made with `update-bin <bin> --info --all-candidates`
+    let config = config::load_config().ok();
+    let owner = config
+        .as_ref()
+        .and_then(|config| config.get_binary_owner(bin_name))
+        .filter(|(_, path)| {
+            path.is_none_or(|path| paths::same_path(Path::new(path), Path::new(bin_path)))
+        });
+    trail::check("config", owner.is_some(), || {
+        l10n::text("explain-binaries-manager", &[("bin", bin_name)])
+    });
+    if let Some((manager, _)) = owner {
+        let package_name = config
+            .as_ref()
+            .and_then(|config| config.get_package_mapping(manager, bin_name))
+            .unwrap_or(bin_name);
+        return Ok(PackageManager {
+            name: manager.to_string(),
+            package_name: package_name.to_string(),
+        });
+    }
+
+    let shim_manager = version_manager::get_shim_manager(bin_path);
+    trail::check(
+        "version manager",
+        shim_manager.is_some(),
+        || match shim_manager {
+            Some(manager) => l10n::text(
+                "explain-in-shims-dir",
+                &[("bin-path", bin_path), ("manager", manager)],
+            ),
+            None => l10n::text("explain-not-in-shims-dir", &[("bin-path", bin_path)]),
+        },
+    );
+    if let Some(manager) = shim_manager {
+        return detect_version_manager_package(manager, bin_name);
+    }
+
+    // npm and pnpm put .cmd/.ps1 shims on PATH on Windows, the file they launch is what
+    // tells us who installed the binary
+    let shim_target = shim::resolve_shim_target(Path::new(bin_path));
+    trail::check("shim", shim_target.is_some(), || match &shim_target {
+        Some(target_path) => l10n::text(
+            "explain-launches",
+            &[
+                ("bin-path", bin_path),
+                ("target-path", &target_path.display().to_string()),
+            ],
+        ),
+        None => l10n::text("explain-not-windows-shim", &[("bin-path", bin_path)]),
+    });
+    if let Some(target_path) = shim_target {
+        if let Ok(package_manager) =
+            detect_package_manager_from_path(bin_name, &target_path.to_string_lossy())
+        {
+            return Ok(package_manager);
+        }
+    }
+
+    // wrapper scripts that exec the binary they're named after somewhere else belong to whoever
+    // installed that binary, keep the wrapper's own location as the fallback when it's unknown.
+    // Any other script belongs to whoever installed the script
+    let script_target = shim::resolve_script_target(Path::new(bin_path));
+    trail::check(
+        "wrapper script",
+        script_target.is_some(),
+        || match &script_target {
+            Some(target_path) => l10n::text(
+                "explain-script-runs",
+                &[
+                    ("bin-path", bin_path),
+                    ("target-path", &target_path.display().to_string()),
+                ],
+            ),
+            None => l10n::text("explain-not-wrapper-script", &[("bin-path", bin_path)]),
+        },
+    );
+    if let Some(target_path) = script_target {
+        let target_name = target_path
+            .file_stem()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| bin_name.to_string());
+        if let Ok(package_manager) =
+            detect_package_manager_from_path(&target_name, &target_path.to_string_lossy())
+        {
+            return Ok(package_manager);
+        }
+    }
+
+    detect_package_manager_from_path(bin_name, bin_path)
 }
 
-fn main() {
-    let args = Args::parse();
+// Whatever the shim currently runs may still belong to a package manager, like an npm global
+// installed into an asdf-managed node
+fn detect_version_manager_package(manager: &str, bin_name: &str) -> Result<PackageManager, String> {
+    let target = version_manager::resolve_shim(manager, bin_name);
+    trail::check(manager, target.is_some(), || match &target {
+        Some(target) => l10n::text(
+            "explain-shim-runs",
+            &[("bin", bin_name), ("target", &target.display().to_string())],
+        ),
+        None => l10n::text(
+            "explain-shim-target-unknown",
+            &[("manager", manager), ("bin", bin_name)],
+        ),
+    });
 
-    if args.info {
-        match display_info(&args.bin_name) {
-            Ok(_) => {}
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                exit(1);
-            }
+    if manager == "volta" {
+        let package_name = target
+            .as_deref()
+            .and_then(version_manager::get_volta_package)
+            .unwrap_or_else(|| bin_name.to_string());
+        return Ok(PackageManager {
+            name: "volta".to_string(),
+            package_name,
+        });
+    }
+
+    if let Some(target) = &target {
+        if let Ok(package_manager) =
+            detect_package_manager_from_path(bin_name, &target.to_string_lossy())
+        {
+            return Ok(package_manager);
         }
-    } else {
-        match update_binary(&args.bin_name) {
-            Ok(_) => {}
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                exit(1);
+
+        // asdf can only move the pin along with installing, which needs --write-pins
+        if manager == "mise" || manager == "asdf" {
+            if let Some((tool, _)) = version_manager::get_installed_tool(manager, target) {
+                return Ok(PackageManager {
+                    name: manager.to_string(),
+                    package_name: tool,
+                });
             }
         }
     }
+
+    Err(version_manager::explain(
+        manager,
+        bin_name,
+        target.as_deref(),
+    ))
 }
 
-fn display_info(bin_name: &str) -> Result<(), String> {
-    let package_manager = detect_package_manager(bin_name)?;
-    println!("Package name: {}", package_manager.package_name);
-    println!("Package manager: {}", package_manager.name);
-    Ok(())
+// Falls back to a dangling symlink with the name, so it can be reported as broken rather
+// than missing
+fn find_bin_path(bin_name: &str) -> Result<String, String> {
+    find_all_bin_paths(bin_name)
+        .into_iter()
+        .next()
+        .or_else(|| {
+            paths::find_dangling_links(bin_name)
+                .into_iter()
+                .next()
+                .map(|(link, _)| link.to_string_lossy().to_string())
+        })
+        .ok_or_else(|| l10n::text("bin-not-found", &[("bin", bin_name)]))
 }
 
-fn update_binary(bin_name: &str) -> Result<(), String> {
-    let package_manager = detect_package_manager(bin_name)?;
+// Every copy of the binary on PATH, the first one is what actually runs
+fn find_all_bin_paths(bin_name: &str) -> Vec<String> {
+    let mut bin_paths: Vec<String> = paths::find_all_executables(bin_name)
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
 
-    let old_version =
-        get_version(bin_name, &package_manager).unwrap_or_else(|_| "unknown".to_string());
-    println!("Current version: {}", old_version);
+    // only ask where when walking PATH finds nothing, in case it knows about App Execution
+    // Aliases. A `which` would only walk the same PATH, and has no `-a` everywhere
+    if bin_paths.is_empty() && cfg!(windows) {
+        bin_paths = find_all_bin_paths_with_where(bin_name);
+    }
 
-    let (command, args) = get_update_command(&package_manager.name, &package_manager.package_name)?;
+    // the same file can show up more than once, e.g. through /bin and /usr/bin
+    let mut seen_paths = std::collections::HashSet::new();
+    bin_paths
+        .into_iter()
+        .filter(|bin_path| {
+            let resolved_path =
+                std::fs::canonicalize(bin_path).unwrap_or_else(|_| PathBuf::from(bin_path));
+            seen_paths.insert(resolved_path)
+        })
+        .collect()
+}
 
-    println!(
-        "Updating {} with {}",
-        package_manager.package_name, package_manager.name
+fn find_all_bin_paths_with_where(bin_name: &str) -> Vec<String> {
+    let output = match process::query_command("where").arg(bin_name).output() {
+        Ok(output) if output.status.success() => output,
+        _ => return Vec::new(),
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+// A path given with --path, which doesn't have to be on PATH at all
+fn check_bin_path(bin_path: &str) -> Result<String, String> {
+    let path = Path::new(bin_path);
+    if !path.exists() && paths::get_dangling_link_target(path).is_none() {
+        return Err(l10n::text("bin-path-missing", &[("bin-path", bin_path)]));
+    }
+
+    Ok(bin_path.to_string())
+}
+
+// A dangling symlink, a wrapper script whose binary is gone, or a version manager shim for a
+// runtime version that has been uninstalled
+fn find_broken_bin(bin_name: &str, bin_path: &str) -> Option<repair::Repair> {
+    if let Some(manager) = version_manager::get_shim_manager(bin_path) {
+        return version_manager::get_stale_shim(manager, bin_name);
+    }
+
+    let path = Path::new(bin_path);
+    let target =
+        paths::get_dangling_link_target(path).or_else(|| shim::get_missing_script_target(path))?;
+    let problem = l10n::text(
+        "bin-target-gone",
+        &[
+            ("bin-path", bin_path),
+            ("target", &target.display().to_string()),
+        ],
     );
 
-    let mut child = Command::new(&command)
-        .args(&args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to run {}: {}", command, e))?;
+    // where the link pointed says more about who installed it than where the link is
+    let package_manager = detect_package_manager_from_path(bin_name, &target.to_string_lossy())
+        .or_else(|_| detect_package_manager_from_path(bin_name, bin_path));
+    let package_manager = match package_manager {
+        Ok(package_manager) => package_manager,
+        Err(_) => {
+            return Some(repair::Repair {
+                problem: l10n::text(
+                    "broken-bin-unknown-owner",
+                    &[
+                        ("problem", &problem),
+                        ("bin-path", bin_path),
+                        ("bin", bin_name),
+                    ],
+                ),
+                command: None,
+            })
+        }
+    };
+    let package_name = match package_manager.name.as_str() {
+        "npm" | "pnpm" | "yarn" | "bun" => get_node_package_from_path(&target),
+        _ => None,
+    }
+    .unwrap_or(package_manager.package_name);
+    if process::check_argument(&package_name, "package name").is_err() {
+        return Some(repair::Repair {
+            problem,
+            command: None,
+        });
+    }
+
+    let mut command = get_reinstall_command(&package_manager.name, &package_name);
+    if system::is_system_package_manager(&package_manager.name) && !system::is_root() {
+        command = command.map(|(command, command_args)| get_sudo_command(command, command_args));
+    }
+    Some(repair::Repair {
+        problem: l10n::text(
+            "broken-bin-owner",
+            &[
+                ("problem", &problem),
+                ("manager", &package_manager.name),
+                ("package", &package_name),
+            ],
+        ),
+        command,
+    })
+}
+
+// `<prefix>/lib/node_modules/@scope/name/bin/cli.js` -> `@scope/name`
+fn get_node_package_from_path(path: &Path) -> Option<String> {
+    let components: Vec<String> = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+    let index = components.iter().rposition(|name| name == "node_modules")?;
+    let package = components.get(index + 1)?;
+    if package.starts_with('@') {
+        Some(format!("{}/{}", package, components.get(index + 2)?))
+    } else {
+        Some(package.to_string())
+    }
+}
+
+// git and path installs have to be reinstalled from the same source, otherwise cargo would
+// switch them to crates.io
+fn get_cargo_install_command(
+    package_name: &str,
+    install: Option<cargo::CargoInstall>,
+) -> (String, Vec<String>) {
+    let mut args = vec!["install".to_string()];
+    if let Some(install) = install {
+        if let Some(rev) = cargo::get_pinned_rev(&install) {
+            eprintln!(
+                "{}",
+                l10n::text(
+                    "pinned-to-revision",
+                    &[("package", package_name), ("revision", &rev)]
+                )
+            );
+        }
+        args.extend(cargo::get_source_args(&install.source));
+        args.extend(cargo::get_build_args(&install));
+    }
+    args.push(package_name.to_string());
+    ("cargo".to_string(), args)
+}
 
-    if let Some(stdout) = child.stdout.take() {
-        let reader = BufReader::new(stdout);
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                println!("\x1b[2m---> {}\x1b[0m", line);
+// The update command for the binary at that path, which for a `cargo install --root` has to
+// go to the same root, or cargo would install a second copy into CARGO_HOME
+fn get_bin_update_command(
+    bin_path: &str,
+    package_manager: &PackageManager,
+    install_latest: bool,
+) -> Result<(String, Vec<String>), String> {
+    if package_manager.name == "cargo" {
+        if let Some(root) = cargo::find_install_root(Path::new(bin_path)) {
+            let install =
+                cargo::find_cargo_install_by_crate_in(&root, &package_manager.package_name);
+            let (command, mut args) =
+                get_cargo_install_command(&package_manager.package_name, install);
+            args.splice(
+                1..1,
+                ["--root".to_string(), root.to_string_lossy().to_string()],
+            );
+            return Ok((command, args));
+        }
+    }
+    if package_manager.name == "scoop" && scoop::is_global(Path::new(bin_path)) {
+        let (command, mut args) =
+            get_update_command("scoop", &package_manager.package_name, install_latest)?;
+        args.insert(1, "--global".to_string());
+        return Ok((command, args));
+    }
+    // the shim keeps the permissions and the name it was installed with, which a reinstall
+    // without them would drop
+    if package_manager.name == "deno" {
+        if let Some(shim) = deno::read_shim(Path::new(bin_path)) {
+            let (command, mut args) =
+                get_update_command("deno", &package_manager.package_name, install_latest)?;
+            let specifier = args.pop().unwrap_or_default();
+            args.extend(shim.flags);
+            if let Some(name) = Path::new(bin_path).file_stem() {
+                args.push("-n".to_string());
+                args.push(name.to_string_lossy().to_string());
             }
+            args.push(specifier);
+            return Ok((command, args));
         }
     }
+    get_update_command(
+        &package_manager.name,
+        &package_manager.package_name,
+        install_latest,
+    )
+}
 
-    if let Some(stderr) = child.stderr.take() {
-        let reader = BufReader::new(stderr);
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                eprintln!("\x1b[2m---> {}\x1b[0m", line);
+// The channel the binary is on, as chosen with --channel before or told by the installed
+// version, and the one to update it on
+fn get_channels(
+    config: &config::Config,
+    bin_name: &str,
+    requested: Option<&str>,
+    package_manager: &PackageManager,
+    installed_version: &str,
+) -> (String, String) {
+    let current_channel = config
+        .get_binary_channel(bin_name)
+        .map(|channel| channel.to_string())
+        .or_else(|| {
+            channel::detect(
+                &package_manager.name,
+                &package_manager.package_name,
+                installed_version,
+            )
+        })
+        .unwrap_or_else(|| channel::STABLE.to_string());
+    let channel = requested.unwrap_or(&current_channel).to_string();
+    (current_channel, channel)
+}
+
+// The update that keeps the binary on its channel, or moves it to another one. `None` when the
+// usual update already does, like `brew upgrade --fetch-HEAD` for a HEAD build
+fn get_channel_update_command(
+    bin_path: &str,
+    package_manager: &PackageManager,
+    channel: &str,
+    switching: bool,
+) -> Result<Option<(String, Vec<String>)>, String> {
+    if channel == channel::STABLE && !switching {
+        return Ok(None);
+    }
+    let package_name = &package_manager.package_name;
+    match package_manager.name.as_str() {
+        "npm" | "pnpm" | "yarn" | "bun" => {
+            if package_manager.name == "npm" {
+                if let Some(source) = get_npm_install_source(package_name) {
+                    if switching {
+                        return Err(l10n::text(
+                            "channels-unsupported-source",
+                            &[("package", package_name), ("source", &source)],
+                        ));
+                    }
+                    return Ok(None);
+                }
             }
+            // the one that installs `<package>@latest`, with the channel's dist-tag instead
+            let tag = if channel == channel::STABLE {
+                "latest"
+            } else {
+                channel
+            };
+            let (command, mut args) =
+                get_update_command(&package_manager.name, package_name, true)?;
+            if let Some(package) = args.last_mut() {
+                *package = format!("{}@{}", package_name, tag);
+            }
+            Ok(Some((command, args)))
+        }
+        // a plain `cargo install` only looks at stable versions
+        "cargo" if channel != channel::STABLE => {
+            let version =
+                channel::get_latest_version("cargo", package_name, channel).ok_or_else(|| {
+                    l10n::text("crates-prerelease-not-found", &[("package", package_name)])
+                })?;
+            let (command, mut args) = get_bin_update_command(bin_path, package_manager, false)?;
+            channel::add_cargo_version(&mut args, &version);
+            Ok(Some((command, args)))
         }
+        // the build option sticks to the installed keg
+        "homebrew" if switching => Err(l10n::text(
+            if channel == "head" {
+                "homebrew-cant-switch-to-head"
+            } else {
+                "homebrew-cant-switch-to-stable"
+            },
+            &[("package", package_name)],
+        )),
+        _ => Ok(None),
     }
+}
 
-    let status = child
-        .wait()
-        .map_err(|e| format!("Failed to wait for {}: {}", command, e))?;
+// The latest version on the channel, the registry's latest one for stable
+fn query_channel_latest_version(package_manager: &PackageManager, channel: &str) -> Option<String> {
+    if channel == channel::STABLE {
+        return query_latest_version(&package_manager.name, &package_manager.package_name);
+    }
+    channel::get_latest_version(
+        &package_manager.name,
+        &package_manager.package_name,
+        channel,
+    )
+}
 
-    if !status.success() {
-        return Err(format!(
-            "Failed to update {} with {}",
-            package_manager.package_name, package_manager.name
-        ));
+// Updating wouldn't bring back missing files when the manager thinks the installed version is
+// already the latest one
+fn get_reinstall_command(
+    package_manager: &str,
+    package_name: &str,
+) -> Option<(String, Vec<String>)> {
+    let (command, args): (String, &[&str]) = match package_manager {
+        "homebrew" => (homebrew::get_brew_command(), &["install"]),
+        "npm" => (get_npm_executable().to_string(), &["install", "-g"]),
+        "pnpm" | "bun" => (package_manager.to_string(), &["add", "-g"]),
+        "yarn" => ("yarn".to_string(), &["global", "add"]),
+        "cargo" => {
+            let (command, mut args) = get_update_command("cargo", package_name, false).ok()?;
+            args.insert(1, "--force".to_string());
+            return Some((command, args));
+        }
+        _ if system::is_system_package_manager(package_manager) => {
+            return Some(system::get_reinstall_command(package_manager, package_name))
+        }
+        _ => return get_update_command(package_manager, package_name, false).ok(),
+    };
+    let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    args.push(package_name.to_string());
+    Some((command, args))
+}
+
+// When the binary is installed in several places, show who owns each copy and let the user
+// pick which one to update
+fn select_bin_path(bin_name: &str) -> Result<String, String> {
+    let bin_paths = find_all_bin_paths(bin_name);
+    if bin_paths.len() <= 1 {
+        return find_bin_path(bin_name);
     }
 
-    let new_version =
-        get_version(&bin_name, &package_manager).unwrap_or_else(|_| "unknown".to_string());
+    print_installations(bin_name, &bin_paths);
 
-    if old_version != new_version {
-        println!("Updated to version: {}", new_version);
-        println!(
-            "✅ Successfully updated {} from {} to {}",
-            package_manager.package_name, old_version, new_version
+    if !process::can_prompt() {
+        println!("{}", l10n::text("updating-first-installation", &[]));
+        return Ok(bin_paths[0].clone());
+    }
+
+    loop {
+        print!(
+            "{} ",
+            l10n::text(
+                "installations-prompt",
+                &[("count", &bin_paths.len().to_string())]
+            )
         );
+        std::io::stdout().flush().ok();
+
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer).unwrap_or(0) == 0 {
+            return Ok(bin_paths[0].clone());
+        }
+
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Ok(bin_paths[0].clone());
+        }
+        match answer.parse::<usize>() {
+            Ok(index) if index >= 1 && index <= bin_paths.len() => {
+                return Ok(bin_paths[index - 1].clone());
+            }
+            _ => println!(
+                "{}",
+                l10n::text("enter-number", &[("count", &bin_paths.len().to_string())])
+            ),
+        }
+    }
+}
+
+fn print_installations(bin_name: &str, bin_paths: &[String]) {
+    println!(
+        "{}",
+        l10n::text(
+            "found-installations",
+            &[("bin", bin_name), ("count", &bin_paths.len().to_string())]
+        )
+    );
+    for (index, bin_path) in bin_paths.iter().enumerate() {
+        let package_manager = detect_package_manager(bin_name, bin_path).ok();
+        let owner = describe_owner(bin_path, package_manager.as_ref());
+        let runs_first = if index == 0 {
+            format!(" {}", l10n::text("runs-first", &[]))
+        } else {
+            String::new()
+        };
+        println!("  {}. {} - {}{}", index + 1, bin_path, owner, runs_first);
+    }
+}
+
+fn describe_owner(bin_path: &str, package_manager: Option<&PackageManager>) -> String {
+    match package_manager {
+        Some(package_manager) => format!(
+            "{} ({})",
+            package_manager.package_name, package_manager.name
+        ),
+        None => match version_manager::get_shim_manager(bin_path) {
+            Some(manager) => l10n::text("owner-shim", &[("manager", manager)]),
+            None => l10n::text("owner-unknown", &[]),
+        },
+    }
+}
+
+// Lists each binary that more than one package provides, in PATH order so it's clear which copy
+// shadows the others, and offers to uninstall one of them with its manager. Copies that are
+// the same package, like a formula linked into two dirs, aren't a conflict
+fn dedupe_installations(bin_names: &[String]) -> Result<(), String> {
+    let bin_names = if bin_names.is_empty() {
+        paths::find_shadowed_names()
     } else {
+        bin_names.to_vec()
+    };
+
+    let mut conflicts = 0;
+    for bin_name in &bin_names {
+        let bin_paths = find_all_bin_paths(bin_name);
+        if bin_paths.len() < 2 {
+            continue;
+        }
+        let owners: Vec<Option<PackageManager>> = bin_paths
+            .iter()
+            .map(|bin_path| detect_package_manager(bin_name, bin_path).ok())
+            .collect();
+        let first_owner = owners[0]
+            .as_ref()
+            .map(|owner| (&owner.name, &owner.package_name));
+        if first_owner.is_some()
+            && owners.iter().all(|owner| {
+                owner
+                    .as_ref()
+                    .map(|owner| (&owner.name, &owner.package_name))
+                    == first_owner
+            })
+        {
+            continue;
+        }
+
+        conflicts += 1;
         println!(
-            "ℹ️  {} is already up to date ({})",
-            package_manager.package_name, old_version
+            "{}",
+            l10n::text(
+                "installed-times",
+                &[("bin", bin_name), ("count", &bin_paths.len().to_string())]
+            )
         );
+        for (index, (bin_path, owner)) in bin_paths.iter().zip(&owners).enumerate() {
+            let shadowing = if index == 0 {
+                format!(" {}", l10n::text("runs-first", &[]))
+            } else {
+                format!(", {}", l10n::text("shadowed-by-first", &[]))
+            };
+            println!(
+                "  {}. {} - {}{}",
+                index + 1,
+                bin_path,
+                describe_owner(bin_path, owner.as_ref()),
+                shadowing
+            );
+        }
+        offer_uninstall(bin_name, &bin_paths, &owners)?;
     }
 
+    if conflicts == 0 {
+        println!("{}", l10n::text("no-duplicates", &[]));
+    }
     Ok(())
 }
 
-struct PackageManager {
-    name: String,
-    package_name: String,
-}
+// With nobody to ask, only says how each copy would be uninstalled
+fn offer_uninstall(
+    bin_name: &str,
+    bin_paths: &[String],
+    owners: &[Option<PackageManager>],
+) -> Result<(), String> {
+    let commands: Vec<Option<(String, Vec<String>)>> = owners
+        .iter()
+        .map(|owner| {
+            let owner = owner.as_ref()?;
+            let (command, command_args) = get_uninstall_command(&owner.name, &owner.package_name)?;
+            Some(if needs_elevation(&owner.name) {
+                get_sudo_command(command, command_args)
+            } else {
+                (command, command_args)
+            })
+        })
+        .collect();
 
-fn detect_package_manager(bin_name: &str) -> Result<PackageManager, String> {
-    if let Ok(output) = Command::new("which").arg(bin_name).output() {
-        if !output.status.success() {
-            return Err(format!("Binary '{}' not found", bin_name));
+    if !process::can_prompt() {
+        for (index, command) in commands.iter().enumerate() {
+            if let Some((command, command_args)) = command {
+                println!(
+                    "  {}",
+                    l10n::text(
+                        "uninstall-with",
+                        &[
+                            ("number", &(index + 1).to_string()),
+                            (
+                                "command",
+                                &format!("{} {}", command, command_args.join(" "))
+                            )
+                        ]
+                    )
+                );
+            }
         }
+        return Ok(());
+    }
 
-        let bin_path_raw = String::from_utf8_lossy(&output.stdout);
-        let bin_path = bin_path_raw.trim();
+    let index = loop {
+        print!(
+            "{} ",
+            l10n::text(
+                "uninstall-prompt",
+                &[("count", &bin_paths.len().to_string())]
+            )
+        );
+        std::io::stdout().flush().ok();
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer).unwrap_or(0) == 0 {
+            return Ok(());
+        }
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Ok(());
+        }
+        match answer.parse::<usize>() {
+            Ok(index) if index >= 1 && index <= bin_paths.len() => break index - 1,
+            _ => println!(
+                "{}",
+                l10n::text("enter-number", &[("count", &bin_paths.len().to_string())])
+            ),
+        }
+    };
 
-        if bin_path.contains("/opt/homebrew/") || bin_path.contains("/usr/local/") {
-            return Ok(PackageManager {
-                name: "homebrew".to_string(),
-                package_name: map_bin_name_to_homebrew_package_name(bin_name),
-            });
+    let (command, command_args) = match &commands[index] {
+        Some(command) => command,
+        None => {
+            println!(
+                "{}",
+                l10n::text(
+                    "cannot-uninstall",
+                    &[
+                        ("path", &bin_paths[index]),
+                        (
+                            "owner",
+                            &describe_owner(&bin_paths[index], owners[index].as_ref())
+                        )
+                    ]
+                )
+            );
+            return Ok(());
         }
+    };
+    println!(
+        "{}",
+        l10n::text(
+            "running",
+            &[(
+                "command",
+                &format!("{} {}", command, command_args.join(" "))
+            )]
+        )
+    );
+    let output = runner::run(command, command_args, None, None)?;
+    if !matches!(output.status, runner::RunStatus::Succeeded) {
+        return Err(l10n::text(
+            "command-failed",
+            &[
+                ("command", command),
+                ("command-args", &command_args.join(" ")),
+            ],
+        ));
+    }
+    match find_all_bin_paths(bin_name).first() {
+        Some(bin_path) => println!(
+            "{}",
+            l10n::text("now-runs", &[("bin", bin_name), ("path", bin_path)])
+        ),
+        None => println!(
+            "{}",
+            l10n::text("not-on-path-anymore", &[("bin", bin_name)])
+        ),
+    }
+    Ok(())
+}
 
-        if bin_path.contains("/.bun/") {
-            return Ok(PackageManager {
-                name: "bun".to_string(),
-                package_name: map_bin_name_to_bun_package_name(bin_name),
-            });
+// What removes a package again, for the managers that can. System packages need sudo
+fn get_uninstall_command(
+    package_manager: &str,
+    package_name: &str,
+) -> Option<(String, Vec<String>)> {
+    let (executable, args): (String, &[&str]) = match package_manager {
+        "homebrew" => (homebrew::get_brew_command(), &["uninstall"]),
+        "npm" => (get_npm_executable().to_string(), &["uninstall", "-g"]),
+        "pnpm" | "bun" => (package_manager.to_string(), &["remove", "-g"]),
+        "yarn" => ("yarn".to_string(), &["global", "remove"]),
+        "cargo" => ("cargo".to_string(), &["uninstall"]),
+        "uv" => ("uv".to_string(), &["tool", "uninstall"]),
+        "winget" => ("winget".to_string(), &["uninstall", "--exact", "--id"]),
+        "scoop" => (scoop::get_executable().to_string(), &["uninstall"]),
+        "chocolatey" => ("choco".to_string(), &["uninstall", "-y"]),
+        "apt" => ("apt-get".to_string(), &["remove"]),
+        "dnf" | "yum" | "zypper" => (package_manager.to_string(), &["remove"]),
+        "pacman" => ("pacman".to_string(), &["-R"]),
+        "apk" => ("apk".to_string(), &["del"]),
+        "pkg" => ("pkg".to_string(), &["delete"]),
+        "pkg_add" => ("pkg_delete".to_string(), &[]),
+        "ips" => ("pkg".to_string(), &["uninstall"]),
+        _ => return None,
+    };
+    let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    args.push(package_name.to_string());
+    Some((executable, args))
+}
+
+fn detect_package_manager_from_path(
+    bin_name: &str,
+    bin_path: &str,
+) -> Result<PackageManager, String> {
+    // container-backed bins live wherever their wrapper was put, usually /usr/local/bin
+    let whalebrew_image = container::get_whalebrew_image(bin_path);
+    trail::check("whalebrew", whalebrew_image.is_some(), || {
+        l10n::text(
+            "explain-read-for-whalebrew-package",
+            &[("bin-path", bin_path)],
+        )
+    });
+    if let Some(image) = whalebrew_image {
+        return Ok(PackageManager {
+            name: "whalebrew".to_string(),
+            package_name: image,
+        });
+    }
+
+    let docker_image = container::get_docker_run_image(bin_path);
+    trail::check("docker", docker_image.is_some(), || {
+        l10n::text("explain-read-for-docker-run", &[("bin-path", bin_path)])
+    });
+    if let Some(image) = docker_image {
+        return Ok(PackageManager {
+            name: "docker".to_string(),
+            package_name: image,
+        });
+    }
+
+    let winget_package = winget::get_package_from_path(bin_path);
+    trail::check("winget", winget_package.is_some(), || {
+        l10n::text(
+            "explain-compared-with-winget-links",
+            &[("bin-path", bin_path)],
+        )
+    });
+    if let Some(package_id) = winget_package {
+        return Ok(PackageManager {
+            name: "winget".to_string(),
+            package_name: package_id,
+        });
+    }
+
+    // the shims dir belongs to scoop alone, the shim says which app it runs
+    let scoop_app = scoop::get_app_from_path(bin_path);
+    trail::check("scoop", scoop_app.is_some(), || match &scoop_app {
+        Some(app) => l10n::text(
+            "explain-runs-scoop-app",
+            &[("bin-path", bin_path), ("app", app)],
+        ),
+        None => l10n::text("explain-isnt-in-scoop-shims", &[("bin-path", bin_path)]),
+    });
+    if let Some(app) = scoop_app {
+        return Ok(PackageManager {
+            name: "scoop".to_string(),
+            package_name: app,
+        });
+    }
+
+    let chocolatey_package = chocolatey::get_package_from_path(bin_path);
+    trail::check("chocolatey", chocolatey_package.is_some(), || {
+        l10n::text(
+            "explain-compared-with-chocolatey-bin",
+            &[
+                ("bin-path", bin_path),
+                (
+                    "install-dir",
+                    &chocolatey::get_install_dir()
+                        .join("bin")
+                        .display()
+                        .to_string(),
+                ),
+            ],
+        )
+    });
+    if let Some(package_name) = chocolatey_package {
+        return Ok(PackageManager {
+            name: "chocolatey".to_string(),
+            package_name,
+        });
+    }
+
+    // keg-only formulas are only reachable through their Cellar/opt paths
+    let formula = homebrew::get_formula_from_path(bin_path);
+    trail::check("homebrew", formula.is_some(), || match &formula {
+        Some(formula) => l10n::text(
+            "explain-resolves-into-cellar",
+            &[("bin-path", bin_path), ("formula", formula)],
+        ),
+        None => l10n::text(
+            "explain-doesnt-resolve-into-homebrew",
+            &[("bin-path", bin_path)],
+        ),
+    });
+    if let Some(formula) = formula {
+        return Ok(PackageManager {
+            name: "homebrew".to_string(),
+            package_name: formula,
+        });
+    }
+
+    let path = Path::new(bin_path);
+
+    // /usr/local is only Homebrew's on machines that actually have it
+    let is_in_brew_prefix = homebrew::is_in_brew_prefix(path);
+    trail::check(
+        "homebrew prefix",
+        is_in_brew_prefix,
+        || match homebrew::get_brew_executable().and(homebrew::get_brew_prefix()) {
+            Some(prefix) => l10n::text(
+                "explain-compared-with-homebrew-prefix",
+                &[("bin-path", bin_path), ("prefix", &prefix)],
+            ),
+            None => l10n::text("explain-homebrew-not-installed", &[]),
+        },
+    );
+    if is_in_brew_prefix {
+        return Ok(PackageManager {
+            name: "homebrew".to_string(),
+            package_name: map_bin_name_to_homebrew_package_name(bin_name),
+        });
+    }
+
+    let bun_bin_dir = if paths::is_installed("bun") {
+        get_bun_global_bin_dir()
+    } else {
+        None
+    };
+    let is_bun_global_bin = paths::has_components(path, &[".bun"])
+        || bun_bin_dir
+            .as_ref()
+            .is_some_and(|bin_dir| paths::starts_with(path, bin_dir));
+    trail::check("bun", is_bun_global_bin, || match &bun_bin_dir {
+        Some(bin_dir) => l10n::text(
+            "explain-compared-with-bun",
+            &[
+                ("bin-path", bin_path),
+                ("bin-dir", &bin_dir.display().to_string()),
+            ],
+        ),
+        None => l10n::text("explain-isnt-in-bun", &[("bin-path", bin_path)]),
+    });
+    if is_bun_global_bin {
+        return Ok(PackageManager {
+            name: "bun".to_string(),
+            package_name: map_bin_name_to_bun_package_name(bin_name),
+        });
+    }
+
+    let cargo_bin_dir = cargo::get_cargo_home().join("bin");
+    let is_cargo_bin =
+        paths::has_components(path, &[".cargo", "bin"]) || paths::starts_with(path, &cargo_bin_dir);
+    trail::check("cargo", is_cargo_bin, || {
+        l10n::text(
+            "explain-compared-with-cargo-bin",
+            &[
+                ("bin-path", bin_path),
+                ("cargo-bin-dir", &cargo_bin_dir.display().to_string()),
+            ],
+        )
+    });
+    if is_cargo_bin {
+        // the crate name can differ from the bin name, e.g. ripgrep installs rg
+        let install = cargo::find_cargo_install(bin_name);
+        trail::check("cargo", install.is_some(), || match &install {
+            Some(install) => l10n::text(
+                "explain-crates2-json-lists",
+                &[("bin", bin_name), ("crate", &install.crate_name)],
+            ),
+            None => l10n::text("explain-crates2-json-doesnt-list", &[("bin", bin_name)]),
+        });
+        let package_name = install
+            .map(|install| install.crate_name)
+            .unwrap_or_else(|| bin_name.to_string());
+        return Ok(PackageManager {
+            name: "cargo".to_string(),
+            package_name,
+        });
+    }
+
+    // `cargo install --root ./.tools` keeps its own .crates2.json next to the bin dir
+    let cargo_root = cargo::find_install_root(path);
+    let root_install = cargo_root
+        .as_ref()
+        .and_then(|root| cargo::find_cargo_install_in(root, bin_name));
+    trail::check("cargo --root", root_install.is_some(), || {
+        match (&root_install, &cargo_root) {
+            (Some(install), Some(root)) => l10n::text(
+                "explain-crates2-json-in-root-lists",
+                &[
+                    ("root", &root.display().to_string()),
+                    ("bin", bin_name),
+                    ("crate", &install.crate_name),
+                ],
+            ),
+            (None, Some(root)) => l10n::text(
+                "explain-crates2-json-in-root-doesnt-list",
+                &[("root", &root.display().to_string()), ("bin", bin_name)],
+            ),
+            _ => l10n::text("explain-isnt-in-bin-dir", &[("bin-path", bin_path)]),
         }
+    });
+    if let Some(install) = root_install {
+        return Ok(PackageManager {
+            name: "cargo".to_string(),
+            package_name: install.crate_name,
+        });
+    }
 
-        if bin_path.contains("/.cargo/bin/") {
-            return Ok(PackageManager {
-                name: "cargo".to_string(),
-                package_name: bin_name.to_string(),
-            });
+    // the package comes from the binary itself, `go install` records where it was built from
+    let go_bin_dir = if paths::is_installed("go") {
+        go::get_bin_dir()
+    } else {
+        None
+    };
+    let go_package = go_bin_dir
+        .as_ref()
+        .filter(|bin_dir| paths::starts_with(path, bin_dir))
+        .and_then(|_| go::get_package_path(path));
+    trail::check("go", go_package.is_some(), || {
+        match (&go_package, &go_bin_dir) {
+            (Some(package_path), _) => l10n::text(
+                "explain-was-built-from",
+                &[("bin-path", bin_path), ("package-path", package_path)],
+            ),
+            (None, Some(bin_dir)) => l10n::text(
+                "explain-compared-with-go-bin",
+                &[
+                    ("bin-path", bin_path),
+                    ("bin-dir", &bin_dir.display().to_string()),
+                ],
+            ),
+            (None, None) => l10n::text("explain-go-not-installed", &[]),
         }
+    });
+    if let Some(package_path) = go_package {
+        return Ok(PackageManager {
+            name: "go".to_string(),
+            package_name: package_path,
+        });
+    }
 
-        // check if installed by pnpm
-        let global_bin_dir = Command::new("pnpm")
-            .args(&["bin", "-g"])
-            .output()
-            .ok()
-            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
-        if let Some(dir) = global_bin_dir {
-            if bin_path.contains(&dir) {
-                return Ok(PackageManager {
-                    name: "pnpm".to_string(),
-                    package_name: map_bin_name_to_pnpm_package_name(bin_name),
-                });
-            }
+    // the shim runs the module it was installed from, which is what gets installed again
+    let deno_bin_dir = deno::get_bin_dir();
+    let deno_shim = deno_bin_dir
+        .as_ref()
+        .filter(|bin_dir| paths::starts_with(path, bin_dir))
+        .and_then(|_| deno::read_shim(path));
+    trail::check("deno", deno_shim.is_some(), || {
+        match (&deno_shim, &deno_bin_dir) {
+            (Some(shim), _) => l10n::text(
+                "explain-is-deno-shim",
+                &[("bin-path", bin_path), ("specifier", &shim.specifier)],
+            ),
+            (None, Some(bin_dir)) => l10n::text(
+                "explain-compared-with-deno-bin",
+                &[
+                    ("bin-path", bin_path),
+                    ("bin-dir", &bin_dir.display().to_string()),
+                ],
+            ),
+            (None, None) => l10n::text("explain-deno-no-bin-dir", &[]),
         }
+    });
+    if let Some(shim) = deno_shim {
+        return Ok(PackageManager {
+            name: "deno".to_string(),
+            package_name: shim.specifier,
+        });
+    }
 
-        // get npm binary path by running `which npm` and get its directory
-        let npm_bin_path = Command::new("which")
-            .arg("npm")
-            .output()
-            .ok()
-            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
-        let npm_bin_dir = npm_bin_path.as_ref().map(|path| {
-            let mut parts: Vec<&str> = path.split('/').collect();
-            parts.pop();
-            parts.join("/")
+    // the tool's name is that of its environment, which isn't always the bin's
+    let uv_tool = uv::get_tool_from_path(bin_path);
+    trail::check("uv", uv_tool.is_some(), || {
+        match (&uv_tool, uv::get_tool_dir()) {
+            (Some(tool), _) => l10n::text(
+                "explain-belongs-to-uv-tool",
+                &[("bin-path", bin_path), ("tool", tool)],
+            ),
+            (None, Some(tool_dir)) => l10n::text(
+                "explain-doesnt-resolve-into-uv",
+                &[
+                    ("bin-path", bin_path),
+                    ("tool-dir", &tool_dir.display().to_string()),
+                ],
+            ),
+            (None, None) => l10n::text("explain-uv-no-tool-dir", &[]),
+        }
+    });
+    if let Some(tool) = uv_tool {
+        return Ok(PackageManager {
+            name: "uv".to_string(),
+            package_name: tool,
         });
+    }
 
-        if let Some(dir) = npm_bin_dir {
-            if bin_path.contains(&dir) {
-                let global_node_modules_dir = Path::new(&dir)
-                    .parent()
-                    .unwrap()
-                    .join("lib")
-                    .join("node_modules")
-                    .to_string_lossy()
-                    .to_string();
+    // check if installed by pnpm
+    let pnpm_bin_dir = get_pnpm_global_bin_dir();
+    let is_pnpm_bin = pnpm_bin_dir
+        .as_ref()
+        .is_some_and(|dir| paths::starts_with(path, dir));
+    trail::check("pnpm", is_pnpm_bin, || match &pnpm_bin_dir {
+        Some(dir) => l10n::text(
+            "explain-compared-with-pnpm-global",
+            &[("bin-path", bin_path), ("dir", &dir.display().to_string())],
+        ),
+        None => l10n::text("explain-pnpm-no-bin-dir", &[]),
+    });
+    if is_pnpm_bin {
+        return Ok(PackageManager {
+            name: "pnpm".to_string(),
+            package_name: map_bin_name_to_pnpm_package_name(bin_name),
+        });
+    }
 
+    // check if installed by npm
+    let npm_global_dirs = get_npm_global_dirs();
+    if let Some((npm_bin_dir, global_node_modules_dir)) = &npm_global_dirs {
+        if paths::starts_with(path, npm_bin_dir) {
+            // with a distro's nodejs the prefix is /usr, so leave the bins npm didn't install
+            // to the distro's package manager
+            let package_name = map_bin_name_to_npm_package_name(bin_name, global_node_modules_dir);
+            let is_npm_bin =
+                package_name.is_some() || system::get_package_from_path(bin_path).is_none();
+            trail::check("npm", is_npm_bin, || match &package_name {
+                Some(package_name) => l10n::text(
+                    "explain-npm-package-provides",
+                    &[
+                        ("bin-path", bin_path),
+                        ("npm-bin-dir", &npm_bin_dir.display().to_string()),
+                        ("package", package_name),
+                        (
+                            "global-node-modules-dir",
+                            &global_node_modules_dir.display().to_string(),
+                        ),
+                    ],
+                ),
+                None if is_npm_bin => l10n::text(
+                    "explain-npm-no-package-declares",
+                    &[
+                        ("bin-path", bin_path),
+                        ("npm-bin-dir", &npm_bin_dir.display().to_string()),
+                        (
+                            "global-node-modules-dir",
+                            &global_node_modules_dir.display().to_string(),
+                        ),
+                    ],
+                ),
+                None => l10n::text(
+                    "explain-npm-system-package-owns",
+                    &[
+                        ("bin-path", bin_path),
+                        ("npm-bin-dir", &npm_bin_dir.display().to_string()),
+                        (
+                            "global-node-modules-dir",
+                            &global_node_modules_dir.display().to_string(),
+                        ),
+                    ],
+                ),
+            });
+            if is_npm_bin {
                 return Ok(PackageManager {
                     name: "npm".to_string(),
-                    package_name: map_bin_name_to_npm_package_name(
-                        bin_name,
-                        &global_node_modules_dir,
-                    ),
+                    package_name: package_name.unwrap_or_else(|| bin_name.to_string()),
                 });
             }
+        } else {
+            trail::check("npm", false, || {
+                l10n::text(
+                    "explain-compared-with-npm-global",
+                    &[
+                        ("bin-path", bin_path),
+                        ("npm-bin-dir", &npm_bin_dir.display().to_string()),
+                    ],
+                )
+            });
         }
+    } else {
+        trail::check("npm", false, || {
+            l10n::text("explain-npm-no-global-dir", &[])
+        });
+    }
 
-        // check if installed by yarn
-        let yarn_bin_dir = Command::new("yarn")
-            .args(&["global", "bin"])
-            .output()
-            .ok()
-            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
-        if let Some(dir) = yarn_bin_dir {
-            if bin_path.contains(&dir) {
-                return Ok(PackageManager {
-                    name: "yarn".to_string(),
-                    package_name: map_bin_name_to_yarn_package_name(bin_name),
-                });
-            }
-        }
+    // check if installed by yarn
+    let yarn_bin_dir = get_yarn_global_bin_dir();
+    let is_yarn_bin = yarn_bin_dir
+        .as_ref()
+        .is_some_and(|dir| paths::starts_with(path, dir));
+    trail::check("yarn", is_yarn_bin, || match &yarn_bin_dir {
+        Some(dir) => l10n::text(
+            "explain-compared-with-yarn-global",
+            &[("bin-path", bin_path), ("dir", &dir.display().to_string())],
+        ),
+        None => l10n::text("explain-yarn-no-bin-dir", &[]),
+    });
+    if is_yarn_bin {
+        return Ok(PackageManager {
+            name: "yarn".to_string(),
+            package_name: map_bin_name_to_yarn_package_name(bin_name),
+        });
     }
 
-    Err(format!(
-        "Could not detect package manager for '{}'",
-        bin_name
-    ))
+    // anything else in a system dir most likely came from the distro
+    let system_package = system::get_package_from_path(bin_path);
+    trail::check(
+        "system package",
+        system_package.is_some(),
+        || match &system_package {
+            Some((package_manager, package_name)) => l10n::text(
+                "explain-owns",
+                &[
+                    ("manager", package_manager),
+                    ("bin-path", bin_path),
+                    ("package", package_name),
+                ],
+            ),
+            None => l10n::text(
+                "explain-no-installed-package-database",
+                &[("bin-path", bin_path)],
+            ),
+        },
+    );
+    if let Some((package_manager, package_name)) = system_package {
+        return Ok(PackageManager {
+            name: package_manager,
+            package_name,
+        });
+    }
+
+    let error = l10n::text("detect-failed", &[("bin", bin_name)]);
+    if trail::is_recording() {
+        Err(error)
+    } else {
+        Err(l10n::text(
+            "detect-failed-explain-hint",
+            &[("error", &error), ("bin", bin_name)],
+        ))
+    }
+}
+
+// `update` by default, which is what the managers themselves do
+fn get_strategy<'a>(config: &'a config::Config, package_manager: &str) -> Result<&'a str, String> {
+    match config.get_manager_setting(package_manager, "strategy") {
+        None => Ok("update"),
+        Some(strategy) if strategy == "update" || strategy == "latest" => Ok(strategy),
+        Some(strategy) => Err(l10n::text(
+            "invalid-strategy",
+            &[("strategy", strategy), ("manager", package_manager)],
+        )),
+    }
 }
 
+// With install_latest, node managers install `<package>@latest` rather than updating within
+// the semver range the package was installed with
 fn get_update_command(
     package_manager: &str,
     package_name: &str,
+    install_latest: bool,
 ) -> Result<(String, Vec<String>), String> {
+    let latest_package = format!("{}@latest", package_name);
     match package_manager {
-        "homebrew" => Ok((
-            "brew".to_string(),
-            vec!["upgrade".to_string(), package_name.to_string()],
+        "adopted" => {
+            let (update_command, _) = get_adopted(package_name)
+                .ok_or_else(|| l10n::text("adopted-no-longer", &[("package", package_name)]))?;
+            let (shell, args) = process::get_shell_command(&update_command);
+            Ok((shell.to_string(), args))
+        }
+        "homebrew" => {
+            let mut args = vec!["upgrade".to_string()];
+            let mut formula = package_name.to_string();
+            if let Some(package) = homebrew::get_brew_info(package_name) {
+                // `brew info` resolves aliases and renames, but `brew upgrade` errors on them
+                if package.name != package_name {
+                    println!(
+                        "{}",
+                        l10n::text(
+                            get_brew_rename_message(&package, package_name),
+                            &[("name", package_name), ("formula", &package.name)]
+                        )
+                    );
+                    formula = package.name.clone();
+                }
+                if package.pinned {
+                    return Err(l10n::text("homebrew-pinned", &[("package", &package.name)]));
+                }
+                if package.cask {
+                    args.push("--cask".to_string());
+                }
+                if package.head {
+                    args.push("--fetch-HEAD".to_string());
+                }
+            }
+            args.push(formula);
+            Ok((homebrew::get_brew_command(), args))
+        }
+        "bun" | "npm" | "pnpm" => {
+            // `npm update -g` only looks at the registry, and would leave it as it is
+            if package_manager == "npm" {
+                if let Some(source) = get_npm_install_source(package_name) {
+                    return Ok((
+                        get_npm_executable().to_string(),
+                        vec!["install".to_string(), "-g".to_string(), source],
+                    ));
+                }
+            }
+            let executable = match package_manager {
+                "npm" => get_npm_executable(),
+                manager => manager,
+            };
+            let args = if !install_latest {
+                vec!["update", "-g", package_name]
+            } else if package_manager == "npm" {
+                vec!["install", "-g", &latest_package]
+            } else {
+                vec!["add", "-g", &latest_package]
+            };
+            Ok((
+                executable.to_string(),
+                args.iter().map(|arg| arg.to_string()).collect(),
+            ))
+        }
+        "cargo" => Ok(get_cargo_install_command(
+            package_name,
+            cargo::find_cargo_install_by_crate(package_name),
         )),
-        "bun" => Ok((
-            "bun".to_string(),
+        "yarn" => {
+            if let Some(major) = get_yarn_major_version().filter(|major| *major >= 2) {
+                return Err(l10n::text(
+                    "yarn-berry-no-global-upgrade",
+                    &[("major", &major.to_string()), ("package", package_name)],
+                ));
+            }
+
+            let args = if install_latest {
+                vec!["global", "add", &latest_package]
+            } else {
+                vec!["global", "upgrade", package_name]
+            };
+            Ok((
+                "yarn".to_string(),
+                args.iter().map(|arg| arg.to_string()).collect(),
+            ))
+        }
+        "winget" => Ok((
+            "winget".to_string(),
             vec![
-                "update".to_string(),
-                "-g".to_string(),
+                "upgrade".to_string(),
+                "--id".to_string(),
                 package_name.to_string(),
+                "--exact".to_string(),
+                "--accept-source-agreements".to_string(),
+                "--disable-interactivity".to_string(),
             ],
         )),
-        "npm" => Ok((
-            "npm".to_string(),
+        // whalebrew has no upgrade command, its packages update by pulling the image again
+        _ if system::is_system_package_manager(package_manager) => {
+            Ok(system::get_update_command(package_manager, package_name))
+        }
+        // `volta install` always fetches the latest version
+        "volta" => Ok((
+            "volta".to_string(),
+            vec!["install".to_string(), package_name.to_string()],
+        )),
+        "mise" => Ok((
+            "mise".to_string(),
+            vec!["upgrade".to_string(), package_name.to_string()],
+        )),
+        // the pin is moved once it's installed
+        "asdf" => Ok((
+            "asdf".to_string(),
             vec![
-                "update".to_string(),
-                "-g".to_string(),
+                "install".to_string(),
                 package_name.to_string(),
+                version_manager::get_asdf_latest_version(package_name)?,
             ],
         )),
-        "pnpm" => Ok((
-            "pnpm".to_string(),
+        "whalebrew" | "docker" => Ok((
+            "docker".to_string(),
+            vec!["pull".to_string(), package_name.to_string()],
+        )),
+        "go" => Ok((
+            "go".to_string(),
+            vec!["install".to_string(), format!("{}@latest", package_name)],
+        )),
+        "uv" => Ok((
+            "uv".to_string(),
             vec![
-                "update".to_string(),
-                "-g".to_string(),
+                "tool".to_string(),
+                "upgrade".to_string(),
                 package_name.to_string(),
             ],
         )),
-        "cargo" => Ok((
-            "cargo".to_string(),
-            vec!["install".to_string(), package_name.to_string()],
-        )),
-        "yarn" => Ok((
-            "yarn".to_string(),
+        "chocolatey" => Ok((
+            "choco".to_string(),
             vec![
-                "global".to_string(),
                 "upgrade".to_string(),
                 package_name.to_string(),
+                "-y".to_string(),
             ],
         )),
-        _ => Err(format!("Unsupported package manager: {}", package_manager)),
+        "scoop" => Ok((
+            scoop::get_executable().to_string(),
+            vec!["update".to_string(), package_name.to_string()],
+        )),
+        // the package is the module's specifier, --reload fetches it again instead of reusing
+        // the cached one
+        "deno" => {
+            let specifier = if install_latest {
+                deno::unpin(package_name)
+            } else {
+                package_name.to_string()
+            };
+            Ok((
+                "deno".to_string(),
+                ["install", "-g", "-f", "--reload", &specifier]
+                    .iter()
+                    .map(|arg| arg.to_string())
+                    .collect(),
+            ))
+        }
+        _ => Err(l10n::text(
+            "unsupported-package-manager",
+            &[("manager", package_manager)],
+        )),
     }
 }
 
-fn get_version(bin_name: &str, package_manager: &PackageManager) -> Result<String, String> {
-    match package_manager.name.to_string().as_str() {
-        "homebrew" => get_homebrew_version(bin_name),
-        "bun" | "npm" | "pnpm" => get_node_package_version(bin_name, package_manager),
-        "cargo" => get_cargo_version(bin_name),
-        _ => get_binary_version(bin_name),
-    }
+// Installs the given version again, to undo an update that broke the binary. Homebrew only
+// keeps the latest formula, and distro repositories rarely keep the old package
+fn get_install_version_command(
+    package_manager: &str,
+    package_name: &str,
+    version: &str,
+) -> Option<(String, Vec<String>)> {
+    let package = format!("{}@{}", package_name, version);
+    let (executable, args) = match package_manager {
+        "npm" => (
+            get_npm_executable().to_string(),
+            vec!["install", "-g", &package],
+        ),
+        "pnpm" | "bun" => (package_manager.to_string(), vec!["add", "-g", &package]),
+        "yarn" => ("yarn".to_string(), vec!["global", "add", &package]),
+        "cargo" => {
+            let install = cargo::find_cargo_install_by_crate(package_name)?;
+            // a git install's version is whatever the checkout said, not one to ask for
+            if !cargo::is_from_crates_io(&install) {
+                return None;
+            }
+            let mut args = vec!["install".to_string(), "--force".to_string()];
+            args.extend(cargo::get_build_args(&install));
+            args.push(format!("--version={}", version));
+            args.push(package_name.to_string());
+            return Some(("cargo".to_string(), args));
+        }
+        _ => return None,
+    };
+    Some((executable, args.iter().map(|arg| arg.to_string()).collect()))
 }
 
-fn get_homebrew_version(bin_name: &str) -> Result<String, String> {
-    let output = Command::new("brew")
-        .args(&["list", "--versions", bin_name])
-        .output()
-        .map_err(|e| format!("Failed to get brew version: {}", e))?;
-
-    if !output.status.success() {
-        return Err("Package not found in homebrew".to_string());
+fn get_brew_rename_message(package: &homebrew::BrewPackage, name: &str) -> &'static str {
+    if package.oldnames.iter().any(|oldname| oldname == name) {
+        "brew-renamed"
+    } else if package.aliases.iter().any(|alias| alias == name) {
+        "brew-alias"
+    } else {
+        "brew-known-as"
     }
+}
 
-    let version_line = String::from_utf8_lossy(&output.stdout);
-    let version = version_line
-        .trim()
-        .split_whitespace()
-        .nth(1)
-        .unwrap_or("unknown")
-        .to_string();
+fn get_version(bin_path: &str, package_manager: &PackageManager) -> Result<String, String> {
+    match package_manager.name.to_string().as_str() {
+        "homebrew" => get_homebrew_version(&package_manager.package_name),
+        "adopted" => {
+            let version_command = get_adopted(&package_manager.package_name)
+                .and_then(|(_, version_command)| version_command);
+            get_adopted_version(bin_path, version_command.as_deref())
+        }
+        "bun" | "npm" | "pnpm" | "yarn" => get_node_package_version(bin_path, package_manager),
+        "cargo" => get_cargo_version(bin_path, package_manager),
+        "go" => go::get_module_version(Path::new(bin_path))
+            .map_or_else(|| get_binary_version(bin_path), Ok),
+        "uv" => uv::get_installed_version(&package_manager.package_name)
+            .map_or_else(|| get_binary_version(bin_path), Ok),
+        "chocolatey" => chocolatey::get_installed_version(&package_manager.package_name)
+            .map_or_else(|| get_binary_version(bin_path), Ok),
+        "scoop" => scoop::get_installed_version(&package_manager.package_name)
+            .map_or_else(|| get_binary_version(bin_path), Ok),
+        "deno" => deno::get_pinned_version(&package_manager.package_name)
+            .map_or_else(|| get_binary_version(bin_path), Ok),
+        // running the wrapper would start a container just to print a version
+        manager if system::is_system_package_manager(manager) => {
+            system::get_installed_version(manager, &package_manager.package_name)
+                .map_or_else(|| get_binary_version(bin_path), Ok)
+        }
+        "whalebrew" | "docker" => container::get_image_id(&package_manager.package_name)
+            .ok_or_else(|| {
+                l10n::text(
+                    "docker-inspect-failed",
+                    &[("package", &package_manager.package_name)],
+                )
+            }),
+        _ => get_binary_version(bin_path),
+    }
+}
 
-    Ok(version)
+fn get_homebrew_version(formula: &str) -> Result<String, String> {
+    homebrew::get_brew_info(formula)
+        .and_then(|package| package.installed_version)
+        .ok_or_else(|| l10n::text("homebrew-package-not-found", &[]))
 }
 
 fn get_node_package_version(
-    bin_name: &str,
+    bin_path: &str,
     package_manager: &PackageManager,
 ) -> Result<String, String> {
-    let output = Command::new(&package_manager.name)
-        .args(&["list", "-g", "--depth=0"])
-        .output()
-        .map_err(|e| format!("Failed to get {} version: {}", package_manager.name, e))?;
+    let package_name = &package_manager.package_name;
+    let version = match package_manager.name.as_str() {
+        "npm" => get_command_output(get_npm_executable(), &["list", "-g", "--depth=0", "--json"])
+            .and_then(|output| parse_npm_list_version(&output, package_name)),
+        "pnpm" => get_command_output("pnpm", &["list", "-g", "--depth=0", "--json"])
+            .and_then(|output| parse_pnpm_list_version(&output, package_name)),
+        // bun has no JSON output for its global packages
+        "bun" => get_command_output("bun", &["pm", "ls", "-g"])
+            .and_then(|output| parse_bun_list_version(&output, package_name)),
+        // yarn's global list is human readable even with --json, the installed
+        // package.json is more reliable
+        "yarn" => get_yarn_global_dir().and_then(|global_dir| {
+            read_package_json_version(
+                &get_package_dir(&global_dir.join("node_modules"), package_name)
+                    .join("package.json"),
+            )
+        }),
+        _ => None,
+    };
 
-    if !output.status.success() {
-        return get_binary_version(bin_name);
+    match version {
+        Some(version) => Ok(version),
+        None => get_binary_version(bin_path),
     }
+}
 
-    let list_output = String::from_utf8_lossy(&output.stdout);
-    for line in list_output.lines() {
-        if line.contains(&format!("{}@", package_manager.package_name)) {
-            let version = line
-                .split(&format!("{}@", package_manager.package_name))
-                .nth(1)
-                .unwrap_or("unknown")
-                .trim()
-                .to_string();
-            return Ok(version);
-        }
+// stdout of a command that exited successfully
+fn get_command_output(command: &str, args: &[&str]) -> Option<String> {
+    if !paths::is_installed(command) {
+        return None;
     }
 
-    get_binary_version(bin_name)
+    let output = process::query_command(command)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+    trail::command(command, args, output.is_some());
+    output.map(|output| String::from_utf8_lossy(&output.stdout).to_string())
 }
 
-fn get_cargo_version(bin_name: &str) -> Result<String, String> {
-    let output = Command::new("cargo")
-        .args(&["install", "--list"])
-        .output()
-        .map_err(|e| format!("Failed to get cargo version: {}", e))?;
+// `npm list -g --json` looks like {"dependencies": {"<package>": {"version": "1.0.0"}}}
+fn parse_npm_list_version(json: &str, package_name: &str) -> Option<String> {
+    let list: serde_json::Value = serde_json::from_str(json).ok()?;
+    list["dependencies"][package_name]["version"]
+        .as_str()
+        .map(|version| version.to_string())
+}
 
-    if !output.status.success() {
-        return get_binary_version(bin_name);
+// `pnpm list -g --json` is an array of projects with their dependencies, older versions
+// print a single project object instead
+fn parse_pnpm_list_version(json: &str, package_name: &str) -> Option<String> {
+    get_pnpm_list_projects(json).iter().find_map(|project| {
+        project["dependencies"][package_name]["version"]
+            .as_str()
+            .map(|version| version.to_string())
+    })
+}
+
+// The projects of `pnpm list -g --json` in either shape, none when it isn't JSON
+fn get_pnpm_list_projects(json: &str) -> Vec<serde_json::Value> {
+    match serde_json::from_str(json).unwrap_or_default() {
+        serde_json::Value::Array(projects) => projects,
+        serde_json::Value::Null => Vec::new(),
+        project => vec![project],
     }
+}
 
-    let list_output = String::from_utf8_lossy(&output.stdout);
-    for line in list_output.lines() {
-        if line.starts_with(&format!("{} ", bin_name)) {
-            let version = line
-                .split_whitespace()
-                .nth(1)
-                .and_then(|v| v.strip_prefix("v"))
-                .unwrap_or("unknown")
-                .trim_end_matches(':')
-                .to_string();
-            return Ok(version);
+// `bun pm ls -g` prints a tree with one `├── <package>@<version>` line per package
+fn parse_bun_list_version(output: &str, package_name: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let package = line.split_whitespace().last()?;
+        // the leading @ of scoped packages isn't the version separator
+        let separator = package.rfind('@').filter(|separator| *separator > 0)?;
+        if &package[..separator] == package_name {
+            Some(package[separator + 1..].to_string())
+        } else {
+            None
         }
+    })
+}
+
+fn read_package_json_version(package_json_path: &Path) -> Option<String> {
+    let package_json = std::fs::read_to_string(package_json_path).ok()?;
+    let package_json: serde_json::Value = serde_json::from_str(&package_json).ok()?;
+    package_json["version"]
+        .as_str()
+        .map(|version| version.to_string())
+}
+
+fn get_cargo_version(bin_path: &str, package_manager: &PackageManager) -> Result<String, String> {
+    let install = match cargo::find_install_root(Path::new(bin_path)) {
+        Some(root) => cargo::find_cargo_install_by_crate_in(&root, &package_manager.package_name),
+        None => cargo::find_cargo_install_by_crate(&package_manager.package_name),
+    };
+    if let Some(install) = install {
+        return Ok(install.version);
     }
 
-    get_binary_version(bin_name)
+    let version = get_command_output("cargo", &["install", "--list"])
+        .and_then(|output| parse_cargo_list_version(&output, &package_manager.package_name));
+
+    match version {
+        Some(version) => Ok(version),
+        None => get_binary_version(bin_path),
+    }
+}
+
+// `cargo install --list` has no JSON output, it prints `<crate> v<version>:` lines, or
+// `<crate> v<version> (<source>):` for git and path installs, followed by the indented bins
+fn parse_cargo_list_version(output: &str, crate_name: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        if parts.next()? != crate_name {
+            return None;
+        }
+        parts
+            .next()?
+            .trim_end_matches(':')
+            .strip_prefix('v')
+            .map(|version| version.to_string())
+    })
 }
 
-fn get_binary_version(bin_name: &str) -> Result<String, String> {
+fn get_binary_version(bin_path: &str) -> Result<String, String> {
     let version_flags = ["--version", "-v", "-V", "version"];
 
     for flag in &version_flags {
-        if let Ok(output) = Command::new(bin_name).arg(flag).output() {
+        if let Ok(output) = process::query_command(bin_path).arg(flag).output() {
             if output.status.success() {
                 let version_output = String::from_utf8_lossy(&output.stdout);
                 let version = version_output
@@ -368,14 +6636,101 @@ fn get_binary_version(bin_name: &str) -> Result<String, String> {
         }
     }
 
-    Err("Could not determine version".to_string())
+    Err(l10n::text("version-unknown", &[]))
+}
+
+// npm is a npm.cmd batch file on Windows, which Command doesn't resolve by itself
+fn get_npm_executable() -> &'static str {
+    if cfg!(windows) {
+        "npm.cmd"
+    } else {
+        "npm"
+    }
+}
+
+// Where a global package was installed from when that isn't the registry, like
+// `git+ssh://git@github.com/user/repo.git` for `npm i -g github:user/repo` or a tarball URL.
+// npm records it as `resolved` in the global node_modules' hidden lockfile, older versions as
+// `_resolved` in the package's package.json
+fn get_npm_install_source(package_name: &str) -> Option<String> {
+    let (_, global_node_modules_dir) = get_npm_global_dirs()?;
+    let lockfile = std::fs::read_to_string(global_node_modules_dir.join(".package-lock.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .unwrap_or_default();
+    let resolved = lockfile["packages"][format!("node_modules/{}", package_name)]["resolved"]
+        .as_str()
+        .map(|resolved| resolved.to_string())
+        .or_else(|| {
+            let package_json_path =
+                get_package_dir(&global_node_modules_dir, package_name).join("package.json");
+            let package_json = std::fs::read_to_string(package_json_path).ok()?;
+            let package_json: serde_json::Value = serde_json::from_str(&package_json).ok()?;
+            package_json["_resolved"]
+                .as_str()
+                .map(|resolved| resolved.to_string())
+        })?;
+    get_custom_npm_source(&resolved)
+}
+
+// A git or tarball source to install from again, `None` for the registry's own tarballs
+// (`<registry>/<name>/-/<name>-<version>.tgz`). A git source keeps the commit or tag it was
+// installed at, following the default branch instead would be a different package. A local
+// tarball, like the ones `update-bin apply` installs, was a one-off, the registry is where the
+// package comes from afterwards
+fn get_custom_npm_source(resolved: &str) -> Option<String> {
+    if resolved.starts_with("git") {
+        return Some(resolved.to_string());
+    }
+    let is_tarball = resolved.ends_with(".tgz") || resolved.ends_with(".tar.gz");
+    if resolved.starts_with("file:") {
+        return if is_tarball {
+            None
+        } else {
+            Some(resolved.to_string())
+        };
+    }
+    if resolved.starts_with("http") && is_tarball && !resolved.contains("/-/") {
+        return Some(resolved.to_string());
+    }
+    None
+}
+
+// Returns npm's global bin dir and global node_modules dir. They live in <prefix>/bin and
+// <prefix>/lib/node_modules, except on Windows where the prefix (usually %APPDATA%\npm)
+// holds the bins directly and node_modules sits right under it
+fn get_npm_global_dirs() -> Option<(PathBuf, PathBuf)> {
+    if !paths::is_installed(get_npm_executable()) {
+        return None;
+    }
+
+    let output = process::query_command(get_npm_executable())
+        .args(["prefix", "-g"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    let prefix = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if prefix.is_empty() {
+        return None;
+    }
+
+    let prefix = PathBuf::from(prefix);
+    if cfg!(windows) {
+        Some((prefix.clone(), prefix.join("node_modules")))
+    } else {
+        Some((prefix.join("bin"), prefix.join("lib").join("node_modules")))
+    }
 }
 
 // an npm package can be installed as another name other than its package name to the bin directory
 // so we need to scan all packages and use the "bin" field (string or object) to determine the actual package name by the bin name
-fn map_bin_name_to_npm_package_name(bin_name: &str, global_node_modules_dir: &str) -> String {
-    let global_json_content = Command::new("npm")
-        .args(&["list", "-g", "--json", "--depth=0"])
+fn map_bin_name_to_npm_package_name(
+    bin_name: &str,
+    global_node_modules_dir: &Path,
+) -> Option<String> {
+    let global_json_content = process::query_command(get_npm_executable())
+        .args(["list", "-g", "--json", "--depth=0"])
         .output()
         .ok()
         .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
@@ -387,126 +6742,354 @@ fn map_bin_name_to_npm_package_name(bin_name: &str, global_node_modules_dir: &st
         let packages = global_json["dependencies"]
             .as_object()
             .unwrap_or(&empty_map);
-        for (package_name, _) in packages {
-            let package_json_path =
-                format!("{}/{}/package.json", global_node_modules_dir, package_name);
-            let package_json = std::fs::read_to_string(package_json_path).unwrap_or_default();
-            let package_json: serde_json::Value =
-                serde_json::from_str(&package_json).unwrap_or_default();
-            if let Some(bin) = package_json.get("bin") {
-                if bin.is_string() && bin.as_str() == Some(bin_name) {
-                    return package_name.to_string();
-                }
-                if bin.is_object() {
-                    for (bin_name_in_json, _) in bin.as_object().unwrap() {
-                        if bin_name_in_json == bin_name {
-                            return package_name.to_string();
-                        }
-                    }
-                }
+        let candidates = packages
+            .keys()
+            .filter_map(|package_name| {
+                get_node_candidate(
+                    package_name,
+                    &get_package_dir(global_node_modules_dir, package_name),
+                    bin_name,
+                )
+            })
+            .collect();
+        return mapping::choose_package("npm", bin_name, candidates);
+    }
+
+    None
+}
+
+fn get_pnpm_major_version() -> Option<u64> {
+    if !paths::is_installed("pnpm") {
+        return None;
+    }
+
+    get_command_output("pnpm", &["--version"])?
+        .trim()
+        .split('.')
+        .next()?
+        .parse()
+        .ok()
+}
+
+// pnpm's global bin dir is governed by PNPM_HOME, so prefer it over `pnpm bin -g`.
+// pnpm v7 and v8 fall back to the dir node lives in when PNPM_HOME isn't set, while v9 errors
+// out, so look for the default PNPM_HOME that `pnpm setup` would have configured instead
+fn get_pnpm_global_bin_dir() -> Option<PathBuf> {
+    if let Some(pnpm_home) = std::env::var_os("PNPM_HOME") {
+        if !pnpm_home.is_empty() {
+            return Some(PathBuf::from(pnpm_home));
+        }
+    }
+
+    if get_pnpm_major_version()? >= 9 {
+        return get_pnpm_default_home().filter(|pnpm_home| pnpm_home.is_dir());
+    }
+
+    let output = process::query_command("pnpm")
+        .args(["bin", "-g"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    paths::path_from_output(&output.stdout)
+}
+
+// the default PNPM_HOME used by `pnpm setup`
+fn get_pnpm_default_home() -> Option<PathBuf> {
+    if cfg!(windows) {
+        return std::env::var_os("LOCALAPPDATA")
+            .map(|local_app_data| PathBuf::from(local_app_data).join("pnpm"));
+    }
+
+    if cfg!(target_os = "macos") {
+        return paths::get_home_dir().map(|home| home.join("Library").join("pnpm"));
+    }
+
+    std::env::var_os("XDG_DATA_HOME")
+        .filter(|data_home| !data_home.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| paths::get_home_dir().map(|home| home.join(".local").join("share")))
+        .map(|data_home| data_home.join("pnpm"))
+}
+
+// The global node_modules dirs, `<pnpm home>/global/<layout version>/node_modules` where the
+// layout version changed between pnpm majors, so every layout present is returned
+fn get_pnpm_global_node_modules_dirs() -> Vec<PathBuf> {
+    let mut node_modules_dirs = Vec::new();
+
+    if let Some(root) = get_command_output("pnpm", &["root", "-g"])
+        .and_then(|output| paths::path_from_output(output.as_bytes()))
+    {
+        node_modules_dirs.push(root);
+    }
+
+    if let Some(bin_dir) = get_pnpm_global_bin_dir() {
+        for node_modules_dir in get_pnpm_layout_node_modules_dirs(&bin_dir) {
+            if !node_modules_dirs.contains(&node_modules_dir) {
+                node_modules_dirs.push(node_modules_dir);
             }
         }
     }
 
-    bin_name.to_string()
+    node_modules_dirs
+}
+
+// `<pnpm home>/global/<layout version>/node_modules` for each layout version installed to
+fn get_pnpm_layout_node_modules_dirs(pnpm_home: &Path) -> Vec<PathBuf> {
+    let mut node_modules_dirs: Vec<PathBuf> = std::fs::read_dir(pnpm_home.join("global"))
+        .map(|layouts| {
+            layouts
+                .flatten()
+                .map(|layout| layout.path().join("node_modules"))
+                .filter(|node_modules_dir| node_modules_dir.is_dir())
+                .collect()
+        })
+        .unwrap_or_default();
+    // read_dir's order is up to the filesystem
+    node_modules_dirs.sort();
+    node_modules_dirs
 }
 
 // Similar to map_bin_name_to_npm_package_name but for pnpm
 fn map_bin_name_to_pnpm_package_name(bin_name: &str) -> String {
-    let global_json_content = Command::new("pnpm")
-        .args(&["list", "-g", "--json"])
+    let global_json_content = process::query_command("pnpm")
+        .args(["list", "-g", "--json"])
         .output()
         .ok()
+        .filter(|output| output.status.success())
         .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
     if let Some(global_json_content) = global_json_content {
-        let global_json: serde_json::Value =
-            serde_json::from_str(&global_json_content).unwrap_or_default();
-
-        if let Some(global_array) = global_json.as_array() {
-            for global_object in global_array {
-                let empty_map = serde_json::Map::new();
-                let packages = global_object["dependencies"]
-                    .as_object()
-                    .unwrap_or(&empty_map);
-                for (package_name, package_info) in packages {
-                    // Use the path from the package info to find package.json
-                    if let Some(package_path) = package_info["path"].as_str() {
-                        let package_json_path = format!("{}/package.json", package_path);
-                        let package_json =
-                            std::fs::read_to_string(package_json_path).unwrap_or_default();
-                        let package_json: serde_json::Value =
-                            serde_json::from_str(&package_json).unwrap_or_default();
-                        if let Some(bin) = package_json.get("bin") {
-                            if bin.is_string() && bin.as_str() == Some(bin_name) {
-                                return package_name.to_string();
-                            }
-                            if bin.is_object() {
-                                for (bin_name_in_json, _) in bin.as_object().unwrap() {
-                                    if bin_name_in_json == bin_name {
-                                        return package_name.to_string();
-                                    }
-                                }
-                            }
-                        }
-                    }
+        let mut candidates = Vec::new();
+        for global_object in get_pnpm_list_projects(&global_json_content) {
+            let empty_map = serde_json::Map::new();
+            let packages = global_object["dependencies"]
+                .as_object()
+                .unwrap_or(&empty_map);
+            for (package_name, package_info) in packages {
+                // Use the path from the package info to find package.json
+                if let Some(package_path) = package_info["path"].as_str() {
+                    candidates.extend(get_node_candidate(
+                        package_name,
+                        Path::new(package_path),
+                        bin_name,
+                    ));
                 }
             }
         }
+        if let Some(package_name) = mapping::choose_package("pnpm", bin_name, candidates) {
+            return package_name;
+        }
     }
 
-    bin_name.to_string()
+    // `pnpm list -g` fails on pnpm v9 without PNPM_HOME, so fall back to scanning the
+    // global dirs directly. Their node_modules entries are symlinks into the
+    // content-addressed `.pnpm` store, which reading package.json follows transparently
+    let candidates = get_pnpm_global_node_modules_dirs()
+        .iter()
+        .flat_map(|node_modules_dir| find_packages_providing_bin(node_modules_dir, bin_name))
+        .collect();
+    mapping::choose_package("pnpm", bin_name, candidates).unwrap_or_else(|| bin_name.to_string())
 }
 
-// Similar to map_bin_name_to_npm_package_name but for yarn
-fn map_bin_name_to_yarn_package_name(bin_name: &str) -> String {
-    let yarn_global_dir = Command::new("yarn")
-        .args(&["global", "dir"])
-        .output()
-        .ok()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
-    if let Some(global_dir) = yarn_global_dir {
-        let package_json_path = format!("{}/package.json", global_dir);
-        let package_json_content = std::fs::read_to_string(package_json_path).unwrap_or_default();
-        let package_json: serde_json::Value =
-            serde_json::from_str(&package_json_content).unwrap_or_default();
+// Scan a node_modules directory (including @scope sub directories) for the package whose
+// package.json "bin" field provides the given bin name
+fn find_packages_providing_bin(node_modules_dir: &Path, bin_name: &str) -> Vec<mapping::Candidate> {
+    let mut candidates = Vec::new();
+    let entries = match std::fs::read_dir(node_modules_dir) {
+        Ok(entries) => entries,
+        Err(_) => return candidates,
+    };
+    for entry in entries.flatten() {
+        let entry_name = entry.file_name().to_string_lossy().to_string();
+        if entry_name.starts_with('.') {
+            continue;
+        }
 
-        let empty_map = serde_json::Map::new();
-        let packages = package_json["dependencies"]
-            .as_object()
-            .unwrap_or(&empty_map);
-        for (package_name, _) in packages {
-            let node_modules_dir = format!("{}/node_modules", global_dir);
-            let package_json_path = format!("{}/{}/package.json", node_modules_dir, package_name);
-            let package_json = std::fs::read_to_string(package_json_path).unwrap_or_default();
-            let package_json: serde_json::Value =
-                serde_json::from_str(&package_json).unwrap_or_default();
-            if let Some(bin) = package_json.get("bin") {
-                if bin.is_string() && bin.as_str() == Some(bin_name) {
-                    return package_name.to_string();
-                }
-                if bin.is_object() {
-                    for (bin_name_in_json, _) in bin.as_object().unwrap() {
-                        if bin_name_in_json == bin_name {
-                            return package_name.to_string();
-                        }
-                    }
+        if entry_name.starts_with('@') {
+            if let Ok(scoped_entries) = std::fs::read_dir(entry.path()) {
+                for scoped_entry in scoped_entries.flatten() {
+                    let package_name = format!(
+                        "{}/{}",
+                        entry_name,
+                        scoped_entry.file_name().to_string_lossy()
+                    );
+                    candidates.extend(get_node_candidate(
+                        &package_name,
+                        &scoped_entry.path(),
+                        bin_name,
+                    ));
                 }
             }
+            continue;
         }
+
+        candidates.extend(get_node_candidate(&entry_name, &entry.path(), bin_name));
     }
 
-    bin_name.to_string()
+    candidates
 }
 
-// Similar to map_bin_name_to_npm_package_name but for bun
-fn map_bin_name_to_bun_package_name(bin_name: &str) -> String {
-    // Bun's global directory is typically ~/.bun/install/global
-    let bun_global_dir = std::env::var("HOME")
-        .map(|home| format!("{}/.bun/install/global", home))
-        .unwrap_or_else(|_| "~/.bun/install/global".to_string());
-    
-    let package_json_path = format!("{}/package.json", bun_global_dir);
-    let package_json_content = std::fs::read_to_string(package_json_path).unwrap_or_default();
+// The package in `package_dir`, if it has the bin
+fn get_node_candidate(
+    package_name: &str,
+    package_dir: &Path,
+    bin_name: &str,
+) -> Option<mapping::Candidate> {
+    let package_json_path = package_dir.join("package.json");
+    if !package_json_provides_bin(&package_json_path, bin_name) {
+        return None;
+    }
+    Some(mapping::Candidate {
+        package_name: package_name.to_string(),
+        version: read_package_json_version(&package_json_path),
+        path: package_dir.to_path_buf(),
+    })
+}
+
+// Every bin the package installs, which all change along with the one being updated
+fn get_package_bins(package_manager: &PackageManager) -> Vec<String> {
+    let package_name = &package_manager.package_name;
+    match package_manager.name.as_str() {
+        "homebrew" => return homebrew::get_formula_bins(package_name),
+        "adopted" => return vec![package_name.to_string()],
+        "cargo" => {
+            return cargo::find_cargo_install_by_crate(package_name)
+                .map(|install| {
+                    install
+                        .bins
+                        .iter()
+                        .map(|bin| paths::normalize_bin_name(bin))
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+        _ => {}
+    }
+
+    let package_json = match read_global_package_json(package_manager) {
+        Some(package_json) => package_json,
+        None => return Vec::new(),
+    };
+    match &package_json["bin"] {
+        serde_json::Value::Object(bins) => {
+            let mut bins: Vec<String> = bins.keys().cloned().collect();
+            bins.sort();
+            bins
+        }
+        serde_json::Value::String(_) => package_name
+            .rsplit('/')
+            .next()
+            .map(|name| vec![name.to_string()])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+fn get_global_node_modules_dirs(package_manager: &str) -> Vec<PathBuf> {
+    match package_manager {
+        "npm" => get_npm_global_dirs()
+            .map(|(_, node_modules_dir)| vec![node_modules_dir])
+            .unwrap_or_default(),
+        "pnpm" => get_pnpm_global_node_modules_dirs(),
+        "yarn" => get_yarn_global_dir()
+            .map(|global_dir| vec![global_dir.join("node_modules")])
+            .unwrap_or_default(),
+        "bun" => get_bun_global_dir()
+            .map(|global_dir| vec![global_dir.join("node_modules")])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+// The package.json of a globally installed node package
+fn read_global_package_json(package_manager: &PackageManager) -> Option<serde_json::Value> {
+    get_global_node_modules_dirs(&package_manager.name)
+        .iter()
+        .find_map(|dir| {
+            let package_json_path =
+                get_package_dir(dir, &package_manager.package_name).join("package.json");
+            let package_json = std::fs::read_to_string(package_json_path).ok()?;
+            serde_json::from_str(&package_json).ok()
+        })
+}
+
+// Where the package is developed, to link to its release notes
+fn get_repository_url(package_manager: &PackageManager) -> Option<String> {
+    match package_manager.name.as_str() {
+        "homebrew" => homebrew::get_brew_info(&package_manager.package_name)
+            .and_then(|package| homebrew::get_repository_url(&package)),
+        "cargo" => cargo::find_cargo_install_by_crate(&package_manager.package_name)
+            .and_then(|install| cargo::get_repository_url(&install)),
+        _ => {
+            // `"repository": "owner/repo"` or `"repository": { "url": "git+https://..." }`
+            let package_json = read_global_package_json(package_manager)?;
+            let repository = &package_json["repository"];
+            repository
+                .as_str()
+                .or_else(|| repository["url"].as_str())
+                .or_else(|| package_json["homepage"].as_str())
+                .map(|url| url.to_string())
+        }
+    }
+}
+
+fn print_sibling_bins(bin_name: &str, package_manager: &PackageManager, version: &str) {
+    let bins = get_package_bins(package_manager);
+    let siblings: Vec<&String> = bins.iter().filter(|bin| *bin != bin_name).collect();
+    if siblings.is_empty() {
+        return;
+    }
+
+    println!(
+        "{}",
+        l10n::text(
+            "also-provides",
+            &[
+                ("package", &package_manager.package_name),
+                ("version", version)
+            ]
+        )
+    );
+    for sibling in siblings {
+        println!("  - {}", sibling);
+    }
+}
+
+// `@scope/name` packages are two dirs deep in node_modules, join them one at a time so the
+// path has the platform's separators
+fn get_package_dir(node_modules_dir: &Path, package_name: &str) -> PathBuf {
+    package_name
+        .split('/')
+        .fold(node_modules_dir.to_path_buf(), |dir, part| dir.join(part))
+}
+
+fn package_json_provides_bin(package_json_path: &Path, bin_name: &str) -> bool {
+    let package_json = std::fs::read_to_string(package_json_path).unwrap_or_default();
+    let package_json: serde_json::Value = serde_json::from_str(&package_json).unwrap_or_default();
+    match package_json.get("bin") {
+        Some(serde_json::Value::String(_)) => {
+            // a string "bin" field installs the bin under the package's own name, without
+            // the scope for scoped packages
+            package_json["name"]
+                .as_str()
+                .map(|name| name.rsplit('/').next() == Some(bin_name))
+                .unwrap_or(false)
+        }
+        Some(serde_json::Value::Object(bins)) => bins.contains_key(bin_name),
+        _ => false,
+    }
+}
+
+// Packages installed globally are the dependencies of a package.json in the global dir,
+// with the packages themselves in its node_modules
+fn find_global_dependencies_providing_bin(
+    global_dir: &Path,
+    bin_name: &str,
+) -> Vec<mapping::Candidate> {
+    let package_json_content =
+        std::fs::read_to_string(global_dir.join("package.json")).unwrap_or_default();
     let package_json: serde_json::Value =
         serde_json::from_str(&package_json_content).unwrap_or_default();
 
@@ -514,69 +7097,365 @@ fn map_bin_name_to_bun_package_name(bin_name: &str) -> String {
     let packages = package_json["dependencies"]
         .as_object()
         .unwrap_or(&empty_map);
-    for (package_name, _) in packages {
-        let node_modules_dir = format!("{}/node_modules", bun_global_dir);
-        let package_json_path = format!("{}/{}/package.json", node_modules_dir, package_name);
-        let package_json = std::fs::read_to_string(package_json_path).unwrap_or_default();
-        let package_json: serde_json::Value =
-            serde_json::from_str(&package_json).unwrap_or_default();
-        if let Some(bin) = package_json.get("bin") {
-            if bin.is_string() && bin.as_str() == Some(bin_name) {
-                return package_name.to_string();
-            }
-            if bin.is_object() {
-                for (bin_name_in_json, _) in bin.as_object().unwrap() {
-                    if bin_name_in_json == bin_name {
-                        return package_name.to_string();
-                    }
-                }
-            }
-        }
+    let node_modules_dir = global_dir.join("node_modules");
+    packages
+        .keys()
+        .filter_map(|package_name| {
+            get_node_candidate(
+                package_name,
+                &get_package_dir(&node_modules_dir, package_name),
+                bin_name,
+            )
+        })
+        .collect()
+}
+
+fn get_yarn_major_version() -> Option<u64> {
+    if !paths::is_installed("yarn") {
+        return None;
+    }
+
+    let output = process::query_command("yarn")
+        .arg("--version")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .split('.')
+        .next()?
+        .parse()
+        .ok()
+}
+
+// Yarn Berry (v2+) has no `yarn global`, but binaries installed earlier with Yarn Classic
+// are still in its default global bin dir
+fn get_yarn_global_bin_dir() -> Option<PathBuf> {
+    if get_yarn_major_version()? >= 2 {
+        return paths::get_home_dir().map(|home| home.join(".yarn").join("bin"));
     }
 
-    bin_name.to_string()
+    let output = process::query_command("yarn")
+        .args(["global", "bin"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    paths::path_from_output(&output.stdout)
 }
 
-// Similar to map_bin_name_to_npm_package_name but for homebrew
-fn map_bin_name_to_homebrew_package_name(bin_name: &str) -> String {
-    // Get all installed packages in one call
-    let installed_packages = Command::new("brew")
-        .args(&["list", "--formula"])
+fn get_yarn_global_dir() -> Option<PathBuf> {
+    if get_yarn_major_version()? >= 2 {
+        return paths::get_home_dir().map(|home| home.join(".config").join("yarn").join("global"));
+    }
+
+    let output = process::query_command("yarn")
+        .args(["global", "dir"])
         .output()
         .ok()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
-    
-    if let Some(installed_list) = installed_packages {
-        // Convert to a set for O(1) lookup
-        let installed_set: std::collections::HashSet<&str> = installed_list.lines()
-            .map(|line| line.trim())
-            .filter(|line| !line.is_empty())
-            .collect();
-        
-        // Use `brew which-formula` to find which packages provide the binary
-        let candidates = Command::new("brew")
-            .args(&["which-formula", bin_name])
-            .output()
-            .ok()
-            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
-        
-        if let Some(candidates) = candidates {
-            if !candidates.is_empty() && !candidates.contains("Error") {
-                // Find the first candidate that is installed
-                for candidate in candidates.lines() {
-                    let candidate = candidate.trim();
-                    if candidate.is_empty() {
-                        continue;
-                    }
-                    
-                    if installed_set.contains(candidate) {
-                        return candidate.to_string();
-                    }
-                }
-            }
-        }
+        .filter(|output| output.status.success())?;
+    paths::path_from_output(&output.stdout)
+}
+
+// Similar to map_bin_name_to_npm_package_name but for yarn
+fn map_bin_name_to_yarn_package_name(bin_name: &str) -> String {
+    let candidates = get_yarn_global_dir()
+        .map(|global_dir| find_global_dependencies_providing_bin(&global_dir, bin_name))
+        .unwrap_or_default();
+    mapping::choose_package("yarn", bin_name, candidates).unwrap_or_else(|| bin_name.to_string())
+}
+
+// `npm link`, `pnpm link --global` and `yarn link` expose a development checkout's bins
+// globally, so the bin resolves to somewhere outside the manager's global node_modules.
+// Returns the checkout the bin is linked to
+fn get_linked_package_dir(bin_path: &str, package_manager: &str) -> Option<PathBuf> {
+    let global_node_modules_dirs = match package_manager {
+        "npm" => get_npm_global_dirs()
+            .map(|(_, node_modules_dir)| vec![node_modules_dir])
+            .unwrap_or_default(),
+        "pnpm" => get_pnpm_global_node_modules_dirs(),
+        "yarn" => get_yarn_global_dir()
+            .map(|global_dir| vec![global_dir.join("node_modules")])
+            .unwrap_or_default(),
+        _ => return None,
+    };
+    let global_node_modules_dirs: Vec<PathBuf> = global_node_modules_dirs
+        .iter()
+        .filter_map(|dir| std::fs::canonicalize(dir).ok())
+        .collect();
+    if global_node_modules_dirs.is_empty() {
+        return None;
+    }
+
+    // Windows shims aren't symlinks, follow them to the script they run
+    let bin_path = Path::new(bin_path);
+    let target = shim::resolve_shim_target(bin_path).unwrap_or_else(|| bin_path.to_path_buf());
+    let target = std::fs::canonicalize(target).ok()?;
+    if global_node_modules_dirs
+        .iter()
+        .any(|dir| paths::starts_with(&target, dir))
+    {
+        return None;
+    }
+
+    // only a bin that belongs to a package checkout counts as linked
+    target
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join("package.json").is_file())
+        .map(|dir| dir.to_path_buf())
+}
+
+// With node from distro packages npm's prefix is /usr, which a regular user can't write to
+fn get_root_owned_global_dir(package_manager: &str) -> Option<PathBuf> {
+    if package_manager != "npm" || cfg!(windows) {
+        return None;
+    }
+
+    get_npm_global_dirs()
+        .map(|(_, node_modules_dir)| node_modules_dir)
+        .filter(|node_modules_dir| {
+            node_modules_dir.is_dir() && !paths::is_writable_dir(node_modules_dir)
+        })
+}
+
+fn get_unlink_command(package_manager: &str, package_name: &str) -> String {
+    match package_manager {
+        "npm" => format!("npm unlink -g {}", package_name),
+        "pnpm" => format!("pnpm remove -g {}", package_name),
+        _ => l10n::text("yarn-unlink-in-checkout", &[]),
+    }
+}
+
+// Similar to map_bin_name_to_npm_package_name but for bun
+fn map_bin_name_to_bun_package_name(bin_name: &str) -> String {
+    let candidates = get_bun_global_dir()
+        .map(|global_dir| find_global_dependencies_providing_bin(&global_dir, bin_name))
+        .unwrap_or_default();
+    mapping::choose_package("bun", bin_name, candidates).unwrap_or_else(|| bin_name.to_string())
+}
+
+// BUN_INSTALL (~/.bun by default, %USERPROFILE%\.bun on Windows) is where bun keeps itself
+// and, unless configured otherwise, its global packages
+fn get_bun_install_dir() -> Option<PathBuf> {
+    std::env::var_os("BUN_INSTALL")
+        .filter(|install_dir| !install_dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| paths::get_home_dir().map(|home| home.join(".bun")))
+}
+
+// The dir global packages' bins are linked into, which bunfig.toml can move
+fn get_bun_global_bin_dir() -> Option<PathBuf> {
+    if let Some(bin_dir) = std::env::var_os("BUN_INSTALL_BIN").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(bin_dir));
+    }
+
+    get_command_output("bun", &["pm", "bin", "-g"])
+        .and_then(|output| paths::path_from_output(output.as_bytes()))
+        .or_else(|| get_bun_install_dir().map(|install_dir| install_dir.join("bin")))
+}
+
+// The dir holding the package.json and node_modules of global packages. `bun pm ls -g` starts
+// with it, e.g. `/home/user/.bun/install/global node_modules (3)`
+fn get_bun_global_dir() -> Option<PathBuf> {
+    if let Some(global_dir) =
+        std::env::var_os("BUN_INSTALL_GLOBAL_DIR").filter(|dir| !dir.is_empty())
+    {
+        return Some(PathBuf::from(global_dir));
     }
-    
+
+    get_command_output("bun", &["pm", "ls", "-g"])
+        .and_then(|output| {
+            let first_line = output.lines().next()?.trim().to_string();
+            let global_dir = first_line.rsplit_once(" node_modules")?.0;
+            Some(PathBuf::from(global_dir))
+        })
+        .filter(|global_dir| global_dir.is_dir())
+        .or_else(|| {
+            get_bun_install_dir().map(|install_dir| install_dir.join("install").join("global"))
+        })
+}
+
+// Similar to map_bin_name_to_npm_package_name but for homebrew
+fn map_bin_name_to_homebrew_package_name(bin_name: &str) -> String {
     // If we can't find the package that provides the binary, fall back to the bin name
-    bin_name.to_string()
-}
\ No newline at end of file
+    mapping::choose_package(
+        "homebrew",
+        bin_name,
+        homebrew::find_formulas_providing_bin(bin_name),
+    )
+    .unwrap_or_else(|| bin_name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_npm_list_version_fixtures() {
+        let v6 = include_str!("../tests/fixtures/npm-ls-v6.json");
+        assert_eq!(
+            parse_npm_list_version(v6, "typescript"),
+            Some("4.9.5".to_string())
+        );
+        assert_eq!(parse_npm_list_version(v6, "prettier"), None);
+
+        let v10 = include_str!("../tests/fixtures/npm-ls-v10.json");
+        assert_eq!(
+            parse_npm_list_version(v10, "typescript"),
+            Some("5.4.5".to_string())
+        );
+        assert_eq!(
+            parse_npm_list_version(v10, "@biomejs/biome"),
+            Some("1.7.3".to_string())
+        );
+        assert_eq!(parse_npm_list_version("", "typescript"), None);
+    }
+
+    #[test]
+    fn parse_pnpm_list_version_fixtures() {
+        let single_project = include_str!("../tests/fixtures/pnpm-ls-v6.json");
+        assert_eq!(
+            parse_pnpm_list_version(single_project, "typescript"),
+            Some("4.5.5".to_string())
+        );
+
+        let projects = include_str!("../tests/fixtures/pnpm-ls-v8.json");
+        assert_eq!(
+            parse_pnpm_list_version(projects, "typescript"),
+            Some("5.2.2".to_string())
+        );
+        assert_eq!(
+            parse_pnpm_list_version(projects, "@biomejs/biome"),
+            Some("1.7.3".to_string())
+        );
+        assert_eq!(parse_pnpm_list_version(projects, "prettier"), None);
+    }
+
+    #[test]
+    fn parse_pnpm_list_version_across_majors() {
+        let v7 = include_str!("../tests/fixtures/pnpm-ls-v7.json");
+        assert_eq!(
+            parse_pnpm_list_version(v7, "typescript"),
+            Some("4.9.5".to_string())
+        );
+        let v8 = include_str!("../tests/fixtures/pnpm-ls-v8.json");
+        assert_eq!(
+            parse_pnpm_list_version(v8, "typescript"),
+            Some("5.2.2".to_string())
+        );
+        let v9 = include_str!("../tests/fixtures/pnpm-ls-v9.json");
+        assert_eq!(
+            parse_pnpm_list_version(v9, "typescript"),
+            Some("5.5.4".to_string())
+        );
+        assert_eq!(
+            parse_pnpm_list_version(v9, "@biomejs/biome"),
+            Some("1.8.3".to_string())
+        );
+        assert!(get_pnpm_list_projects("").is_empty());
+    }
+
+    #[test]
+    fn pnpm_global_layouts() {
+        let pnpm_home =
+            std::env::temp_dir().join(format!("update-bin-test-pnpm-{}", std::process::id()));
+        let package_dir = pnpm_home
+            .join("global")
+            .join("5")
+            .join("node_modules")
+            .join("@biomejs")
+            .join("biome");
+        std::fs::create_dir_all(&package_dir).unwrap();
+        std::fs::write(
+            package_dir.join("package.json"),
+            r#"{"name": "@biomejs/biome", "bin": {"biome": "bin/biome"}}"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(pnpm_home.join("global").join("3").join("node_modules")).unwrap();
+        // a layout dir without node_modules isn't one
+        std::fs::create_dir_all(pnpm_home.join("global").join("4")).unwrap();
+
+        let node_modules_dirs = get_pnpm_layout_node_modules_dirs(&pnpm_home);
+        assert_eq!(
+            node_modules_dirs,
+            vec![
+                pnpm_home.join("global").join("3").join("node_modules"),
+                pnpm_home.join("global").join("5").join("node_modules"),
+            ]
+        );
+        let packages: Vec<String> = node_modules_dirs
+            .iter()
+            .flat_map(|node_modules_dir| find_packages_providing_bin(node_modules_dir, "biome"))
+            .map(|candidate| candidate.package_name)
+            .collect();
+        assert_eq!(packages, vec!["@biomejs/biome".to_string()]);
+
+        std::fs::remove_dir_all(&pnpm_home).unwrap();
+        assert!(get_pnpm_layout_node_modules_dirs(&pnpm_home).is_empty());
+    }
+
+    #[test]
+    fn parse_bun_list_version_fixture() {
+        let output = include_str!("../tests/fixtures/bun-pm-ls.txt");
+        assert_eq!(
+            parse_bun_list_version(output, "prettier"),
+            Some("3.2.5".to_string())
+        );
+        assert_eq!(
+            parse_bun_list_version(output, "typescript"),
+            Some("5.4.5".to_string())
+        );
+        assert_eq!(
+            parse_bun_list_version(output, "@biomejs/biome"),
+            Some("1.7.3".to_string())
+        );
+        assert_eq!(parse_bun_list_version(output, "biome"), None);
+    }
+
+    #[test]
+    fn parse_cargo_list_version_fixture() {
+        let output = include_str!("../tests/fixtures/cargo-install-list.txt");
+        assert_eq!(
+            parse_cargo_list_version(output, "ripgrep"),
+            Some("14.1.0".to_string())
+        );
+        assert_eq!(
+            parse_cargo_list_version(output, "mytool"),
+            Some("0.1.0".to_string())
+        );
+        assert_eq!(
+            parse_cargo_list_version(output, "zellij"),
+            Some("0.41.0-pre".to_string())
+        );
+        // bins aren't crates
+        assert_eq!(parse_cargo_list_version(output, "rg"), None);
+        assert_eq!(parse_cargo_list_version(output, "cargo"), None);
+    }
+
+    #[test]
+    fn custom_npm_sources() {
+        let commit =
+            "git+ssh://git@github.com/user/repo.git#0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(get_custom_npm_source(commit).as_deref(), Some(commit));
+        let tag = "git+https://github.com/user/repo.git#v1.2.0";
+        assert_eq!(get_custom_npm_source(tag).as_deref(), Some(tag));
+        let tarball = "https://example.com/releases/tool-1.2.0.tgz";
+        assert_eq!(get_custom_npm_source(tarball).as_deref(), Some(tarball));
+        assert_eq!(
+            get_custom_npm_source("file:../checkout").as_deref(),
+            Some("file:../checkout")
+        );
+        // installed by `update-bin apply`
+        assert_eq!(
+            get_custom_npm_source("file:/tmp/packages/tool-1.2.0.tgz"),
+            None
+        );
+        assert_eq!(
+            get_custom_npm_source("https://registry.npmjs.org/tool/-/tool-1.2.0.tgz"),
+            None
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use crate::l10n;
+use std::path::Path;
+
+// A tool declared in a file the user already keeps, which `update-bin sync` installs and
+// keeps updated
+pub struct DeclaredTool {
+    // `homebrew`, `homebrew-cask`, `npm`, `go`, or `tool-versions` for whichever of mise and
+    // asdf is installed
+    pub package_manager: String,
+    pub name: String,
+    // the version or range the file asks for, if any
+    pub version: Option<String>,
+}
+
+// The file's format is told by its name: a Brewfile, a .tool-versions file, the
+// package.json of a workspace that only exists to install tools, or a Go module's tools.go
+// or .tool-deps
+pub fn read_manifest(path: &Path) -> Result<Vec<DeclaredTool>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        l10n::text(
+            "failed-to-read",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })?;
+    let file_name = path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match file_name.as_str() {
+        "package.json" => parse_package_json(&content).map_err(|e| {
+            l10n::text(
+                "invalid-package-json",
+                &[("path", &path.display().to_string()), ("error", &e)],
+            )
+        }),
+        ".tool-versions" => Ok(parse_tool_versions(&content)),
+        "tools.go" => Ok(parse_tools_go(&content)),
+        ".tool-deps" => Ok(parse_tool_deps(&content)),
+        _ if file_name.starts_with("Brewfile") => Ok(parse_brewfile(&content)),
+        _ => Err(l10n::text(
+            "manifest-unknown",
+            &[("path", &path.display().to_string())],
+        )),
+    }
+}
+
+// `brew "ripgrep"` and `cask "firefox"` lines, a Brewfile is Ruby but these are all that
+// matter here. Taps, mas and vscode entries are left to `brew bundle`
+fn parse_brewfile(content: &str) -> Vec<DeclaredTool> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (kind, rest) = line.split_once(char::is_whitespace)?;
+            let package_manager = match kind {
+                "brew" => "homebrew",
+                "cask" => "homebrew-cask",
+                _ => return None,
+            };
+            let rest = rest.trim_start();
+            let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            let name = rest[1..].split(quote).next()?;
+            Some(DeclaredTool {
+                package_manager: package_manager.to_string(),
+                name: name.to_string(),
+                version: None,
+            })
+        })
+        .collect()
+}
+
+// `nodejs 20.11.0` per line, the first version is the one that's used
+fn parse_tool_versions(content: &str) -> Vec<DeclaredTool> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?;
+            let version = parts.next()?;
+            Some(DeclaredTool {
+                package_manager: "tool-versions".to_string(),
+                name: name.to_string(),
+                version: Some(version.to_string()),
+            })
+        })
+        .collect()
+}
+
+fn parse_package_json(content: &str) -> Result<Vec<DeclaredTool>, String> {
+    let package_json: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let empty_map = serde_json::Map::new();
+    let dependencies = package_json["dependencies"]
+        .as_object()
+        .unwrap_or(&empty_map);
+    Ok(dependencies
+        .iter()
+        .map(|(name, range)| DeclaredTool {
+            package_manager: "npm".to_string(),
+            name: name.to_string(),
+            version: range.as_str().map(|range| range.to_string()),
+        })
+        .collect())
+}
+
+// The blank imports of a `//go:build tools` file, `_ "golang.org/x/tools/cmd/stringer"`, in
+// an import block or on their own. Their versions are the module's, in go.mod
+fn parse_tools_go(content: &str) -> Vec<DeclaredTool> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let line = line.strip_prefix("import").unwrap_or(line).trim_start();
+            let path = line.strip_prefix('_')?.trim_start().strip_prefix('"')?;
+            let path = path.split('"').next()?;
+            Some(DeclaredTool {
+                package_manager: "go".to_string(),
+                name: path.to_string(),
+                version: None,
+            })
+        })
+        .collect()
+}
+
+// `golang.org/x/tools/cmd/stringer@v0.21.0` per line, the version is optional
+fn parse_tool_deps(content: &str) -> Vec<DeclaredTool> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (name, version) = match line.split_once('@') {
+                Some((name, version)) => (name, Some(version.to_string())),
+                None => (line, None),
+            };
+            DeclaredTool {
+                package_manager: "go".to_string(),
+                name: name.to_string(),
+                version,
+            }
+        })
+        .collect()
+}
+
+// Sets the versions of the tools in a .tool-deps file, keeping the lines as they were otherwise
+pub fn write_tool_deps(path: &Path, versions: &[(String, String)]) -> Result<(), String> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        l10n::text(
+            "failed-to-read",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })?;
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        let (declaration, comment) = match line.find('#') {
+            Some(index) => line.split_at(index),
+            None => (line, ""),
+        };
+        let name = declaration.trim().split('@').next().unwrap_or_default();
+        match versions.iter().find(|(tool, _)| tool == name) {
+            Some((_, version)) if !name.is_empty() => {
+                let comment = if comment.is_empty() {
+                    String::new()
+                } else {
+                    format!(" {}", comment)
+                };
+                lines.push(format!("{}@{}{}", name, version, comment));
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    let mut content = lines.join("\n");
+    content.push('\n');
+    std::fs::write(path, content).map_err(|e| {
+        l10n::text(
+            "failed-to-write",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn declared(tools: Vec<DeclaredTool>) -> Vec<(String, String, Option<String>)> {
+        tools
+            .into_iter()
+            .map(|tool| (tool.package_manager, tool.name, tool.version))
+            .collect()
+    }
+
+    fn tool(manager: &str, name: &str, version: Option<&str>) -> (String, String, Option<String>) {
+        (
+            manager.to_string(),
+            name.to_string(),
+            version.map(|version| version.to_string()),
+        )
+    }
+
+    #[test]
+    fn brewfile() {
+        let content = "\
+tap \"homebrew/bundle\"
+brew \"ripgrep\"
+brew 'fd', args: [\"HEAD\"]
+  cask \"firefox\"
+mas \"Xcode\", id: 497799835
+brew ripgrep
+";
+        assert_eq!(
+            declared(parse_brewfile(content)),
+            [
+                tool("homebrew", "ripgrep", None),
+                tool("homebrew", "fd", None),
+                tool("homebrew-cask", "firefox", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn tool_versions() {
+        let content = "\
+# the first version is the one that's used
+nodejs 20.11.0 18.19.0
+python 3.12.1 # a comment
+golang
+";
+        assert_eq!(
+            declared(parse_tool_versions(content)),
+            [
+                tool("tool-versions", "nodejs", Some("20.11.0")),
+                tool("tool-versions", "python", Some("3.12.1")),
+            ]
+        );
+    }
+
+    #[test]
+    fn package_json() {
+        let content = r#"{"name": "tools", "dependencies": {"prettier": "^3.0.0", "odd": 3}}"#;
+        assert_eq!(
+            declared(parse_package_json(content).unwrap()),
+            [
+                tool("npm", "odd", None),
+                tool("npm", "prettier", Some("^3.0.0")),
+            ]
+        );
+        assert!(declared(parse_package_json("{}").unwrap()).is_empty());
+        assert!(parse_package_json("not json").is_err());
+    }
+
+    #[test]
+    fn tools_go() {
+        let content = "\
+//go:build tools
+
+package tools
+
+import (
+\t_ \"golang.org/x/tools/cmd/stringer\"
+\t\"fmt\"
+)
+
+import _ \"github.com/golangci/golangci-lint/cmd/golangci-lint\"
+";
+        assert_eq!(
+            declared(parse_tools_go(content)),
+            [
+                tool("go", "golang.org/x/tools/cmd/stringer", None),
+                tool(
+                    "go",
+                    "github.com/golangci/golangci-lint/cmd/golangci-lint",
+                    None
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn tool_deps() {
+        let content = "\
+# tools
+golang.org/x/tools/cmd/stringer@v0.21.0
+mvdan.cc/gofumpt  # latest
+
+";
+        assert_eq!(
+            declared(parse_tool_deps(content)),
+            [
+                tool("go", "golang.org/x/tools/cmd/stringer", Some("v0.21.0")),
+                tool("go", "mvdan.cc/gofumpt", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_tool_deps() {
+        let path = std::env::temp_dir().join(format!(
+            "update-bin-test-manifest-{}.tool-deps",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            "# tools\ngolang.org/x/tools/cmd/stringer@v0.20.0\nmvdan.cc/gofumpt # formatter\nother/tool@v1\n",
+        )
+        .unwrap();
+        let result = write_tool_deps(
+            &path,
+            &[
+                (
+                    "golang.org/x/tools/cmd/stringer".to_string(),
+                    "v0.21.0".to_string(),
+                ),
+                ("mvdan.cc/gofumpt".to_string(), "v0.6.0".to_string()),
+            ],
+        );
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            content,
+            "# tools\ngolang.org/x/tools/cmd/stringer@v0.21.0\nmvdan.cc/gofumpt@v0.6.0 # formatter\nother/tool@v1\n"
+        );
+    }
+}
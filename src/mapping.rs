@@ -0,0 +1,106 @@
+use crate::config;
+use crate::l10n;
+use crate::process;
+use std::io::Write;
+use std::path::PathBuf;
+
+// An installed package that provides the binary being updated
+pub struct Candidate {
+    pub package_name: String,
+    pub version: Option<String>,
+    pub path: PathBuf,
+}
+
+// Two npm packages can both have a `tsc` bin, and conflicting formulas can ship the same one.
+// The package picked in the config wins, otherwise the user picks one and it's saved there for
+// next time. Without a terminal to ask on it's the first one, as before
+pub fn choose_package(manager: &str, bin_name: &str, candidates: Vec<Candidate>) -> Option<String> {
+    if candidates.len() <= 1 {
+        return candidates
+            .into_iter()
+            .next()
+            .map(|candidate| candidate.package_name);
+    }
+    if let Ok(config) = config::load_config() {
+        if let Some(package_name) = config.get_package_mapping(manager, bin_name) {
+            if candidates
+                .iter()
+                .any(|candidate| candidate.package_name == package_name)
+            {
+                return Some(package_name.to_string());
+            }
+        }
+    }
+
+    println!(
+        "{}",
+        l10n::text(
+            "found-providing-packages",
+            &[
+                ("count", &candidates.len().to_string()),
+                ("manager", manager),
+                ("bin", bin_name)
+            ]
+        )
+    );
+    for (index, candidate) in candidates.iter().enumerate() {
+        println!(
+            "  {}. {} {} - {}",
+            index + 1,
+            candidate.package_name,
+            candidate
+                .version
+                .clone()
+                .unwrap_or_else(|| format!("({})", l10n::text("unknown-version", &[]))),
+            candidate.path.display()
+        );
+    }
+
+    if !process::can_prompt() {
+        println!(
+            "{}",
+            l10n::text(
+                "using-first-package",
+                &[("manager", manager), ("bin", bin_name)]
+            )
+        );
+        return Some(candidates[0].package_name.clone());
+    }
+
+    let index = loop {
+        print!(
+            "{} ",
+            l10n::text(
+                "package-prompt",
+                &[("bin", bin_name), ("count", &candidates.len().to_string())]
+            )
+        );
+        std::io::stdout().flush().ok();
+
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer).unwrap_or(0) == 0 {
+            break 0;
+        }
+        let answer = answer.trim();
+        if answer.is_empty() {
+            break 0;
+        }
+        match answer.parse::<usize>() {
+            Ok(index) if index >= 1 && index <= candidates.len() => break index - 1,
+            _ => println!(
+                "{}",
+                l10n::text("enter-number", &[("count", &candidates.len().to_string())])
+            ),
+        }
+    };
+
+    let package_name = candidates[index].package_name.clone();
+    match config::save_package_mapping(manager, bin_name, &package_name) {
+        Ok(path) => println!(
+            "{}",
+            l10n::text("choice-saved", &[("path", &path.display().to_string())])
+        ),
+        Err(e) => eprintln!("{}", l10n::text("choice-save-failed", &[("error", &e)])),
+    }
+    Some(package_name)
+}
@@ -0,0 +1,142 @@
+use crate::history;
+use crate::inventory::InstalledPackage;
+use crate::l10n;
+use crate::version;
+use std::cmp::Ordering;
+use std::path::Path;
+
+// The outcome of an --all run, for node_exporter's textfile collector
+pub struct RunMetrics<'a> {
+    pub updated: usize,
+    pub failed_managers: Vec<&'a str>,
+    pub managers: Vec<&'a str>,
+    // every installed package with its latest version, when that's known
+    pub packages: &'a [(InstalledPackage, Option<String>)],
+}
+
+// The collector can read the file at any moment, so it's written next to it and renamed into
+// place. node_exporter only picks up `.prom` files, the temporary one isn't read
+pub fn write_metrics(path: &Path, metrics: &RunMetrics) -> Result<(), String> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    std::fs::write(&temp_path, to_prometheus(metrics))
+        .and_then(|_| std::fs::rename(&temp_path, path))
+        .map_err(|e| {
+            l10n::text(
+                "failed-to-write-metrics",
+                &[
+                    ("path", &path.display().to_string()),
+                    ("error", &e.to_string()),
+                ],
+            )
+        })
+}
+
+fn to_prometheus(metrics: &RunMetrics) -> String {
+    let is_outdated = |package: &InstalledPackage, latest_version: &Option<String>| {
+        latest_version.as_ref().is_some_and(|latest_version| {
+            version::compare_versions(&package.version, latest_version) == Some(Ordering::Less)
+        })
+    };
+    let outdated = metrics
+        .packages
+        .iter()
+        .filter(|(package, latest_version)| is_outdated(package, latest_version))
+        .count();
+
+    let mut text = String::new();
+    let mut gauge = |name: &str, help: &str, samples: Vec<(String, u64)>| {
+        text.push_str(&format!(
+            "# HELP {} {}\n# TYPE {} gauge\n",
+            name, help, name
+        ));
+        for (labels, value) in samples {
+            text.push_str(&format!("{}{} {}\n", name, labels, value));
+        }
+    };
+    gauge(
+        "update_bin_last_run_timestamp",
+        "When the last --all run finished, in seconds since the Unix epoch.",
+        vec![(String::new(), history::now())],
+    );
+    gauge(
+        "update_bin_last_run_success",
+        "Whether every package manager of the last --all run succeeded.",
+        vec![(String::new(), u64::from(metrics.failed_managers.is_empty()))],
+    );
+    gauge(
+        "update_bin_updated_total",
+        "Packages updated by the last --all run.",
+        vec![(String::new(), metrics.updated as u64)],
+    );
+    gauge(
+        "update_bin_outdated_total",
+        "Installed packages that still have a newer version.",
+        vec![(String::new(), outdated as u64)],
+    );
+    gauge(
+        "update_bin_manager_failed",
+        "Whether the package manager failed in the last --all run.",
+        metrics
+            .managers
+            .iter()
+            .map(|manager| {
+                (
+                    format_labels(&[("manager", manager)]),
+                    u64::from(metrics.failed_managers.contains(manager)),
+                )
+            })
+            .collect(),
+    );
+    gauge(
+        "update_bin_tool_info",
+        "An installed package and its version.",
+        metrics
+            .packages
+            .iter()
+            .map(|(package, _)| {
+                (
+                    format_labels(&[
+                        ("bin", &package.name),
+                        ("manager", &package.package_manager),
+                        ("version", &package.version),
+                    ]),
+                    1,
+                )
+            })
+            .collect(),
+    );
+    gauge(
+        "update_bin_tool_outdated",
+        "Whether the installed package has a newer version.",
+        metrics
+            .packages
+            .iter()
+            .map(|(package, latest_version)| {
+                (
+                    format_labels(&[
+                        ("bin", &package.name),
+                        ("manager", &package.package_manager),
+                    ]),
+                    u64::from(is_outdated(package, latest_version)),
+                )
+            })
+            .collect(),
+    );
+    text
+}
+
+// `{bin="ripgrep",manager="cargo"}`
+fn format_labels(labels: &[(&str, &str)]) -> String {
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", name, value)
+        })
+        .collect();
+    format!("{{{}}}", labels.join(","))
+}
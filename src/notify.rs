@@ -0,0 +1,27 @@
+use crate::http;
+use crate::l10n;
+
+// Post the outcome of an unattended run to a webhook. Slack wants `text` and Discord wants
+// `content`, anything else gets the whole report as JSON
+pub fn send_webhook(url: &str, summary: &str, details: &[String], report: serde_json::Value) {
+    let message = if details.is_empty() {
+        summary.to_string()
+    } else {
+        format!("{}\n{}", summary, details.join("\n"))
+    };
+    let payload = if url.contains("hooks.slack.com") {
+        serde_json::json!({ "text": message })
+    } else if url.contains("discord.com/api/webhooks")
+        || url.contains("discordapp.com/api/webhooks")
+    {
+        serde_json::json!({ "content": message })
+    } else {
+        let mut payload = report;
+        payload["text"] = serde_json::Value::String(message);
+        payload
+    };
+
+    if let Err(e) = http::post_json(url, &payload.to_string()) {
+        eprintln!("{}", l10n::text("webhook-failed", &[("error", &e)]));
+    }
+}
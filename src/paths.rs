@@ -0,0 +1,344 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+pub fn get_home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .or_else(get_passwd_home_dir)
+}
+
+// Services and cron jobs on the BSDs and illumos can run without HOME, the user's entry in
+// /etc/passwd still has it: `name:password:uid:gid:gecos:home:shell`
+fn get_passwd_home_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        return None;
+    }
+    let user = std::env::var("LOGNAME")
+        .or_else(|_| std::env::var("USER"))
+        .ok()
+        .filter(|user| !user.is_empty())?;
+    let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+    passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        (fields.len() >= 7 && fields[0] == user && !fields[5].is_empty())
+            .then(|| PathBuf::from(fields[5]))
+    })
+}
+
+// Whether the path contains the given components next to each other, e.g. [".cargo", "bin"],
+// compared component by component so `/home/a.cargo/bin` doesn't count
+pub fn has_components(path: &Path, sequence: &[&str]) -> bool {
+    let names: Vec<&std::ffi::OsStr> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name),
+            _ => None,
+        })
+        .collect();
+
+    names.windows(sequence.len()).any(|window| {
+        window
+            .iter()
+            .zip(sequence)
+            .all(|(name, expected)| names_equal(name, std::ffi::OsStr::new(expected)))
+    })
+}
+
+// Path::starts_with, but ignoring case where the filesystem does (C:\Users\X\.Cargo\Bin)
+pub fn starts_with(path: &Path, prefix: &Path) -> bool {
+    let mut components = path.components();
+    prefix.components().all(|prefix_component| {
+        components
+            .next()
+            .map(|component| names_equal(component.as_os_str(), prefix_component.as_os_str()))
+            .unwrap_or(false)
+    })
+}
+
+pub fn same_path(a: &Path, b: &Path) -> bool {
+    a.components().count() == b.components().count() && starts_with(a, b)
+}
+
+// Windows and macOS filesystems are case-insensitive by default
+pub fn names_equal(a: &std::ffi::OsStr, b: &std::ffi::OsStr) -> bool {
+    if cfg!(any(windows, target_os = "macos")) {
+        a.to_string_lossy().to_lowercase() == b.to_string_lossy().to_lowercase()
+    } else {
+        a == b
+    }
+}
+
+// A path printed by a command, e.g. `pnpm bin -g`
+pub fn path_from_output(stdout: &[u8]) -> Option<PathBuf> {
+    let path = String::from_utf8_lossy(stdout).trim().to_string();
+    if path.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
+// Look a program up on PATH without spawning anything, honoring PATHEXT on Windows
+pub fn find_executable(program: &str) -> Option<PathBuf> {
+    find_all_executables(program).into_iter().next()
+}
+
+// Every match on PATH in the order they'd be found, the first one is what runs
+pub fn find_all_executables(program: &str) -> Vec<PathBuf> {
+    let path_var = match std::env::var_os("PATH") {
+        Some(path_var) => path_var,
+        None => return Vec::new(),
+    };
+    let extensions = get_executable_extensions();
+
+    let mut executables = Vec::new();
+    for dir in std::env::split_paths(&path_var) {
+        // empty entries mean the current dir, which a shell would only search on Windows
+        if dir.as_os_str().is_empty() {
+            continue;
+        }
+        for extension in &extensions {
+            let candidate = dir.join(format!("{}{}", program, extension));
+            if is_executable(&candidate) {
+                executables.push(candidate);
+            }
+        }
+    }
+    executables
+}
+
+// The names of the executables found in more than one PATH dir, sorted. A dir that's on PATH
+// twice, or twice under different names like /bin and /usr/bin with a merged /usr, counts once
+pub fn find_shadowed_names() -> Vec<String> {
+    let path_var = match std::env::var_os("PATH") {
+        Some(path_var) => path_var,
+        None => return Vec::new(),
+    };
+
+    let mut seen_dirs = HashSet::new();
+    let mut dir_counts: HashMap<String, usize> = HashMap::new();
+    for dir in std::env::split_paths(&path_var) {
+        if dir.as_os_str().is_empty() {
+            continue;
+        }
+        let resolved_dir = std::fs::canonicalize(&dir).unwrap_or_else(|_| dir.clone());
+        if !seen_dirs.insert(resolved_dir) {
+            continue;
+        }
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        // on Windows every file passes as executable, only the PATHEXT ones are programs
+        let names: HashSet<String> = entries
+            .flatten()
+            .filter(|entry| is_executable(&entry.path()))
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let bin_name = normalize_bin_name(&name);
+                (!cfg!(windows) || bin_name != name).then_some(bin_name)
+            })
+            .collect();
+        for name in names {
+            *dir_counts.entry(name).or_insert(0) += 1;
+        }
+    }
+    let mut names: Vec<String> = dir_counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    names
+}
+
+// Whether a package manager is installed at all, so that detection can skip the ones that
+// aren't instead of waiting for the OS to fail spawning them. Cached for the whole run
+pub fn is_installed(program: &str) -> bool {
+    static INSTALLED: OnceLock<Mutex<HashMap<String, bool>>> = OnceLock::new();
+
+    let mut installed = INSTALLED
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    *installed
+        .entry(program.to_string())
+        .or_insert_with(|| find_executable(program).is_some())
+}
+
+// `rg.exe`, `rg.cmd` and `rg` are the same binary on Windows, and package metadata (npm's bin
+// field, cargo's bin list) only ever has the bare name
+pub fn normalize_bin_name(bin_name: &str) -> String {
+    if !cfg!(windows) {
+        return bin_name.to_string();
+    }
+    strip_executable_extension(bin_name, &get_executable_extensions())
+}
+
+// `rg.exe` -> `rg` when `.exe` is one of the extensions (PATHEXT's, lowercased), and `.ps1`,
+// which PowerShell runs without being in PATHEXT
+fn strip_executable_extension(bin_name: &str, extensions: &[String]) -> String {
+    let path = Path::new(bin_name);
+    let is_executable_extension = path
+        .extension()
+        .map(|extension| {
+            let extension = extension.to_string_lossy().to_lowercase();
+            extension == "ps1" || extensions.contains(&format!(".{}", extension))
+        })
+        .unwrap_or(false);
+    match path.file_stem() {
+        Some(stem) if is_executable_extension => stem.to_string_lossy().to_string(),
+        _ => bin_name.to_string(),
+    }
+}
+
+fn get_executable_extensions() -> Vec<String> {
+    if !cfg!(windows) {
+        return vec![String::new()];
+    }
+
+    // a name that already has an extension is looked up as is first
+    let mut extensions = vec![String::new()];
+    let path_ext = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    extensions.extend(
+        path_ext
+            .split(';')
+            .filter(|extension| !extension.is_empty())
+            .map(|extension| extension.to_lowercase()),
+    );
+    extensions
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+// `$XDG_CACHE_HOME/update-bin` (~/.cache by default), `%LOCALAPPDATA%\update-bin\cache` on
+// Windows
+pub fn get_cache_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        return std::env::var_os("LOCALAPPDATA").map(|local_app_data| {
+            PathBuf::from(local_app_data)
+                .join("update-bin")
+                .join("cache")
+        });
+    }
+
+    std::env::var_os("XDG_CACHE_HOME")
+        .filter(|cache_home| !cache_home.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| get_home_dir().map(|home| home.join(".cache")))
+        .map(|cache_home| cache_home.join("update-bin"))
+}
+
+// Permission bits don't account for ACLs or read-only mounts, actually creating a file does
+pub fn is_writable_dir(dir: &Path) -> bool {
+    let probe = dir.join(format!(".update-bin-{}", std::process::id()));
+    match std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+    {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+// Symlinks on PATH whose target is gone, like the links Homebrew leaves behind when a keg is
+// deleted by hand, as (link, target)
+pub fn find_dangling_links(program: &str) -> Vec<(PathBuf, PathBuf)> {
+    let path_var = match std::env::var_os("PATH") {
+        Some(path_var) => path_var,
+        None => return Vec::new(),
+    };
+    let extensions = get_executable_extensions();
+
+    let mut links = Vec::new();
+    for dir in std::env::split_paths(&path_var) {
+        if dir.as_os_str().is_empty() {
+            continue;
+        }
+        for extension in &extensions {
+            let candidate = dir.join(format!("{}{}", program, extension));
+            if let Some(target) = get_dangling_link_target(&candidate) {
+                links.push((candidate, target));
+            }
+        }
+    }
+    links
+}
+
+// The missing target of a symlink, resolved against the link's own dir without touching the
+// filesystem since there is nothing left to canonicalize
+pub fn get_dangling_link_target(path: &Path) -> Option<PathBuf> {
+    let metadata = std::fs::symlink_metadata(path).ok()?;
+    if !metadata.file_type().is_symlink() || path.exists() {
+        return None;
+    }
+
+    let target = std::fs::read_link(path).ok()?;
+    let mut resolved_target = path.parent()?.to_path_buf();
+    for component in target.components() {
+        match component {
+            Component::ParentDir => {
+                resolved_target.pop();
+            }
+            Component::CurDir => {}
+            component => resolved_target.push(component),
+        }
+    }
+    Some(resolved_target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path_ext() -> Vec<String> {
+        [".com", ".exe", ".bat", ".cmd"]
+            .iter()
+            .map(|extension| extension.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn strip_executable_extension_strips_windows_extensions() {
+        assert_eq!(strip_executable_extension("rg.exe", &path_ext()), "rg");
+        assert_eq!(strip_executable_extension("tsc.cmd", &path_ext()), "tsc");
+        assert_eq!(
+            strip_executable_extension("build.bat", &path_ext()),
+            "build"
+        );
+        assert_eq!(strip_executable_extension("pnpm.ps1", &path_ext()), "pnpm");
+        assert_eq!(strip_executable_extension("RG.EXE", &path_ext()), "RG");
+    }
+
+    #[test]
+    fn strip_executable_extension_keeps_other_names() {
+        assert_eq!(strip_executable_extension("rg", &path_ext()), "rg");
+        assert_eq!(
+            strip_executable_extension("node.js", &path_ext()),
+            "node.js"
+        );
+        assert_eq!(
+            strip_executable_extension("rg.exe", &[String::new()]),
+            "rg.exe"
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use crate::digest;
+use crate::inventory;
+use crate::l10n;
+use crate::signature::RegistrySignature;
+use std::path::Path;
+use std::time::SystemTime;
+
+// Bumped when the format changes, so an older update-bin refuses a plan it would misread
+const PLAN_VERSION: u64 = 3;
+
+// An update to make on a machine without network access, written by `update-bin plan` on one
+// that has it and made by `update-bin apply`
+pub struct PlannedUpdate {
+    pub package_manager: String,
+    pub package_name: String,
+    pub current_version: String,
+    pub version: String,
+    pub url: String,
+    // what the registry says the tarball hashes to, checked before it's installed
+    pub integrity: String,
+    // the registry's signature of the integrity, for registries that sign
+    pub signature: Option<RegistrySignature>,
+}
+
+pub fn write_plan(path: &Path, updates: &[PlannedUpdate]) -> Result<(), String> {
+    let updates: Vec<serde_json::Value> = updates
+        .iter()
+        .map(|update| {
+            serde_json::json!({
+                "package_manager": update.package_manager,
+                "package_name": update.package_name,
+                "current_version": update.current_version,
+                "version": update.version,
+                "url": update.url,
+                "integrity": update.integrity,
+                "signature": update.signature.as_ref().map(|signature| serde_json::json!({
+                    "keyid": signature.keyid,
+                    "sig": signature.sig,
+                })),
+                // what `apply` looks for in the artifacts directory, for fetching them by hand
+                "file_name": get_file_name(&update.package_name, &update.version),
+            })
+        })
+        .collect();
+    let plan = serde_json::json!({
+        "version": PLAN_VERSION,
+        "created_at": inventory::format_timestamp(SystemTime::now()),
+        "updates": updates,
+    });
+    let content = serde_json::to_string_pretty(&plan).map_err(|e| e.to_string())?;
+    std::fs::write(path, content + "\n").map_err(|e| {
+        l10n::text(
+            "failed-to-write-plan",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })
+}
+
+pub fn read_plan(path: &Path) -> Result<Vec<PlannedUpdate>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        l10n::text(
+            "failed-to-read",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })?;
+    let plan: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
+        l10n::text(
+            "invalid-plan",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })?;
+    if plan["version"].as_u64() != Some(PLAN_VERSION) {
+        return Err(l10n::text(
+            "plan-version-unknown",
+            &[("path", &path.display().to_string())],
+        ));
+    }
+
+    let empty_updates = Vec::new();
+    plan["updates"]
+        .as_array()
+        .unwrap_or(&empty_updates)
+        .iter()
+        .map(|update| {
+            let get = |key: &str| {
+                update[key]
+                    .as_str()
+                    .map(|value| value.to_string())
+                    .ok_or_else(|| {
+                        l10n::text(
+                            "invalid-plan-missing",
+                            &[("path", &path.display().to_string()), ("key", key)],
+                        )
+                    })
+            };
+            Ok(PlannedUpdate {
+                package_manager: get("package_manager")?,
+                package_name: get("package_name")?,
+                current_version: get("current_version")?,
+                version: get("version")?,
+                url: get("url")?,
+                integrity: get("integrity")?,
+                signature: update["signature"].as_object().and_then(|signature| {
+                    let get = |key: &str| Some(signature.get(key)?.as_str()?.to_string());
+                    Some(RegistrySignature {
+                        keyid: get("keyid")?,
+                        sig: get("sig")?,
+                    })
+                }),
+            })
+        })
+        .collect()
+}
+
+// `@types/node` 20.1.0 -> `types-node-20.1.0.tgz`, what `npm pack` names it. Names and
+// versions are checked before they get here, so the name can't leave the directory
+pub fn get_file_name(package_name: &str, version: &str) -> String {
+    format!(
+        "{}-{}.tgz",
+        package_name.trim_start_matches('@').replace('/', "-"),
+        version
+    )
+}
+
+// `<sha256>  <file name>` lines, what `sha256sum` writes, so the artifacts can be checked by hand
+// too after they're copied
+pub const SHA256SUMS: &str = "SHA256SUMS";
+
+pub fn write_sha256sums(artifacts: &Path, file_names: &[String]) -> Result<(), String> {
+    let mut lines = Vec::new();
+    for file_name in file_names {
+        let path = artifacts.join(file_name);
+        lines.push(format!(
+            "{}  {}\n",
+            digest::to_hex(&digest::hash_file(&path, "sha256")?),
+            file_name
+        ));
+    }
+    let path = artifacts.join(SHA256SUMS);
+    std::fs::write(&path, lines.concat()).map_err(|e| {
+        l10n::text(
+            "failed-to-write",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })
+}
+
+// The SHA-256 the artifacts' SHA256SUMS lists for the file, `*` marks binary mode in its lines
+pub fn find_sha256(sha256sums: &str, file_name: &str) -> Option<String> {
+    sha256sums.lines().find_map(|line| {
+        let (hash, name) = line.trim().split_once(char::is_whitespace)?;
+        let name = name.trim_start();
+        let name = name.strip_prefix('*').unwrap_or(name);
+        (name == file_name).then(|| hash.to_ascii_lowercase())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_sha256_in_sha256sums() {
+        let sha256sums = "\
+ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  cowsay-1.6.0.tgz
+E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855 *types-node-20.1.0.tgz
+";
+        assert_eq!(
+            find_sha256(sha256sums, "cowsay-1.6.0.tgz").as_deref(),
+            Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(
+            find_sha256(sha256sums, "types-node-20.1.0.tgz").as_deref(),
+            Some("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+        assert_eq!(find_sha256(sha256sums, "cowsay-1.6.tgz"), None);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::l10n;
+use crate::process;
+use crate::version::{self, Change};
+use std::cmp::Ordering;
+use std::io::Write;
+
+// The `auto` policies, from applying nothing by itself to applying every update
+pub const POLICIES: [&str; 4] = ["none", "patch", "minor", "major"];
+
+// Why an update goes beyond what the policy applies by itself, `None` when it doesn't. An
+// update whose size can't be told is never applied by itself, unless every update is
+pub fn check(policy: &str, old_version: &str, latest_version: Option<&str>) -> Option<String> {
+    if policy == "major" {
+        return None;
+    }
+    let latest_version = match latest_version {
+        Some(latest_version) => latest_version,
+        None => return Some(l10n::text("policy-latest-unknown", &[])),
+    };
+    // already at the latest version, or ahead of it
+    if version::compare_versions(old_version, latest_version)
+        .is_some_and(|ordering| ordering != Ordering::Less)
+    {
+        return None;
+    }
+    let (change, id) = match version::get_change(old_version, latest_version) {
+        Some((Change::Major, _)) => (3, "policy-major-update"),
+        Some((Change::Minor, _)) => (2, "policy-minor-update"),
+        Some((Change::Patch, _)) => (1, "policy-patch-update"),
+        None => {
+            return Some(l10n::text(
+                "policy-cant-compare",
+                &[("old-version", old_version)],
+            ))
+        }
+    };
+    let allowed = POLICIES
+        .iter()
+        .position(|allowed| *allowed == policy)
+        .unwrap_or(0);
+    if change <= allowed {
+        return None;
+    }
+    Some(l10n::text(
+        id,
+        &[
+            ("old-version", old_version),
+            ("latest-version", latest_version),
+        ],
+    ))
+}
+
+// On a terminal the user decides, a scheduled run with nobody to ask holds the update back
+pub fn confirm(package_name: &str, policy: &str, reason: &str) -> bool {
+    let args = [
+        ("package", package_name),
+        ("policy", policy),
+        ("reason", reason),
+    ];
+    if !process::can_prompt() {
+        println!("{}", l10n::text("policy-skipping", &args));
+        return false;
+    }
+
+    println!("{}", l10n::text("policy-held-back", &args));
+    print!("{} ", l10n::text("update-anyway-prompt", &[]));
+    std::io::stdout().flush().ok();
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).ok();
+    matches!(answer.trim(), "y" | "Y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_updates_up_to_the_policy() {
+        assert_eq!(check("patch", "1.2.3", Some("1.2.4")), None);
+        assert_eq!(check("minor", "1.2.3", Some("1.3.0")), None);
+        assert_eq!(check("minor", "1.2.3", Some("1.2.4")), None);
+        assert_eq!(check("major", "1.2.3", Some("2.0.0")), None);
+        // already there, or ahead
+        assert_eq!(check("none", "1.2.3", Some("1.2.3")), None);
+        assert_eq!(check("none", "1.3.0", Some("1.2.3")), None);
+    }
+
+    #[test]
+    fn holds_back_bigger_updates() {
+        let reason = |id: &str, latest_version: &str| {
+            Some(l10n::text(
+                id,
+                &[("old-version", "1.2.3"), ("latest-version", latest_version)],
+            ))
+        };
+        assert_eq!(
+            check("none", "1.2.3", Some("1.2.4")),
+            reason("policy-patch-update", "1.2.4")
+        );
+        assert_eq!(
+            check("patch", "1.2.3", Some("1.3.0")),
+            reason("policy-minor-update", "1.3.0")
+        );
+        assert_eq!(
+            check("minor", "1.2.3", Some("2.0.0")),
+            reason("policy-major-update", "2.0.0")
+        );
+        // an unknown policy applies nothing by itself
+        assert_eq!(
+            check("sometimes", "1.2.3", Some("1.2.4")),
+            reason("policy-patch-update", "1.2.4")
+        );
+    }
+
+    #[test]
+    fn holds_back_updates_of_unknown_size() {
+        assert_eq!(
+            check("minor", "1.2.3", None),
+            Some(l10n::text("policy-latest-unknown", &[]))
+        );
+        assert_eq!(
+            check("minor", "nightly", Some("1.2.3")),
+            Some(l10n::text(
+                "policy-cant-compare",
+                &[("old-version", "nightly")]
+            ))
+        );
+        assert_eq!(check("major", "nightly", None), None);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::homebrew;
+use crate::http;
+use crate::l10n;
+use crate::process;
+use crate::registry;
+use crate::version;
+use std::cmp::Ordering;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+// Versions below these are too old to update packages reliably, along with how to update
+// the manager itself
+const MINIMUM_VERSIONS: [(&str, &str, &str); 4] = [
+    ("npm", "7.0.0", "npm install -g npm"),
+    ("pnpm", "7.0.0", "pnpm self-update"),
+    ("yarn", "1.22.0", "npm install -g yarn"),
+    ("bun", "1.0.0", "bun upgrade"),
+];
+
+// The registry each manager downloads from, used to tell whether it's reachable at all
+const REGISTRIES: [(&str, &str); 14] = [
+    ("cargo", "https://index.crates.io"),
+    ("npm", "https://registry.npmjs.org"),
+    ("pnpm", "https://registry.npmjs.org"),
+    ("bun", "https://registry.npmjs.org"),
+    ("volta", "https://registry.npmjs.org"),
+    ("yarn", "https://registry.yarnpkg.com"),
+    ("homebrew", "https://formulae.brew.sh"),
+    ("uv", "https://pypi.org"),
+    ("go", "https://proxy.golang.org"),
+    ("deno", "https://jsr.io"),
+    ("winget", "https://cdn.winget.microsoft.com"),
+    ("chocolatey", "https://community.chocolatey.org"),
+    ("docker", "https://registry-1.docker.io"),
+    ("whalebrew", "https://registry-1.docker.io"),
+];
+
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Homebrew's formula index is considered stale after this long without `brew update`
+const BREW_INDEX_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+// Make sure the package manager itself works before asking it to update anything, so the user
+// gets a suggestion instead of a confusing error halfway through the update
+pub fn check_package_manager(
+    package_manager: &str,
+    executable: &str,
+    brew_update: bool,
+) -> Result<(), String> {
+    // go has a subcommand for it, and fails on the flag
+    let version_arg = if package_manager == "go" {
+        "version"
+    } else {
+        "--version"
+    };
+    let output = process::query_command(executable)
+        .arg(version_arg)
+        .output()
+        .map_err(|e| {
+            l10n::text(
+                "manager-not-working",
+                &[("manager", package_manager), ("error", &e.to_string())],
+            )
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .map(|line| line.trim())
+            .find(|line| !line.is_empty())
+            .unwrap_or("it exited with an error");
+        return Err(l10n::text(
+            "manager-version-failed",
+            &[
+                ("manager", package_manager),
+                ("executable", executable),
+                ("version-arg", version_arg),
+                ("reason", reason),
+            ],
+        ));
+    }
+
+    let installed_version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    for (name, minimum_version, update_command) in MINIMUM_VERSIONS {
+        if name == package_manager
+            && version::compare_versions(&installed_version, minimum_version)
+                == Some(Ordering::Less)
+        {
+            eprintln!(
+                "{}",
+                l10n::text(
+                    "manager-outdated",
+                    &[
+                        ("manager", package_manager),
+                        ("version", &installed_version),
+                        ("command", update_command)
+                    ]
+                )
+            );
+        }
+    }
+
+    if package_manager == "homebrew" {
+        check_brew_index(brew_update)?;
+    }
+
+    Ok(())
+}
+
+// `brew upgrade` refreshes the formula index by itself unless HOMEBREW_NO_AUTO_UPDATE is set,
+// in which case a stale index means upgrading to an outdated version (or not at all)
+fn check_brew_index(brew_update: bool) -> Result<(), String> {
+    if std::env::var_os("HOMEBREW_NO_AUTO_UPDATE").is_none() && !brew_update {
+        return Ok(());
+    }
+
+    if brew_update {
+        println!("{}", l10n::text("running", &[("command", "brew update")]));
+        let status = Command::new(homebrew::get_brew_command())
+            .arg("update")
+            .status()
+            .map_err(|e| l10n::text("failed-to-run-brew-update", &[("error", &e.to_string())]))?;
+        if !status.success() {
+            return Err(
+                "brew update failed, run `brew doctor` to find out what's wrong with Homebrew"
+                    .to_string(),
+            );
+        }
+    } else if get_brew_index_age()
+        .map(|age| age > BREW_INDEX_MAX_AGE)
+        .unwrap_or(true)
+    {
+        eprintln!("{}", l10n::text("brew-index-stale", &[]));
+    }
+
+    Ok(())
+}
+
+// Homebrew 4+ keeps the formula index as a JSON API cache, older versions as a git checkout
+fn get_brew_index_age() -> Option<Duration> {
+    let cache_dir = process::query_command(homebrew::get_brew_command())
+        .arg("--cache")
+        .output()
+        .ok()
+        .and_then(|output| crate::paths::path_from_output(&output.stdout));
+    let repository_dir = process::query_command(homebrew::get_brew_command())
+        .arg("--repository")
+        .output()
+        .ok()
+        .and_then(|output| crate::paths::path_from_output(&output.stdout));
+
+    let index_files = [
+        cache_dir.map(|dir| dir.join("api").join("formula.jws.json")),
+        repository_dir.map(|dir| dir.join(".git").join("FETCH_HEAD")),
+    ];
+
+    index_files
+        .into_iter()
+        .flatten()
+        .filter_map(|path| std::fs::metadata(path).ok()?.modified().ok())
+        .max()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+}
+
+// Connect to the manager's registry (or the proxy in between) before starting an update
+// that would otherwise hang for minutes when offline
+pub fn check_network(package_manager: &str) -> Result<(), String> {
+    let registry = match get_registry_url(package_manager) {
+        Some(registry) => registry,
+        None => return Ok(()),
+    };
+    let proxy = http::get_proxy(&registry);
+    let url = proxy.as_deref().unwrap_or(&registry);
+
+    let (host, port) = match http::get_host_and_port(url) {
+        Some(address) => address,
+        None => return Ok(()),
+    };
+    let unreachable = |reason: String| {
+        l10n::text(
+            "network-unreachable",
+            &[
+                ("host", &host),
+                ("manager", package_manager),
+                ("reason", &reason),
+            ],
+        )
+    };
+
+    let addresses = (host.as_str(), port)
+        .to_socket_addrs()
+        .map_err(|e| unreachable(e.to_string()))?;
+    let mut last_error = l10n::text("no-address-found", &[]);
+    for address in addresses {
+        match TcpStream::connect_timeout(&address, NETWORK_TIMEOUT) {
+            Ok(_) => return Ok(()),
+            Err(e) => last_error = e.to_string(),
+        }
+    }
+
+    Err(unreachable(last_error))
+}
+
+// npm, pnpm and yarn all read the registry from npm's config environment variables too. A
+// registry from the config is the one they're pointed at, except for Homebrew's bottle domain,
+// which it only downloads from after asking formulae.brew.sh
+fn get_registry_url(package_manager: &str) -> Option<String> {
+    if package_manager != "homebrew" {
+        if let Some(registry) = registry::get_configured_registry(package_manager) {
+            return Some(registry.trim_start_matches("sparse+").to_string());
+        }
+    }
+    let registry = REGISTRIES
+        .iter()
+        .find(|(name, _)| *name == package_manager)
+        .map(|(_, registry)| registry.to_string())?;
+
+    if registry.contains("npmjs") || registry.contains("yarnpkg") {
+        if let Some(custom_registry) = ["npm_config_registry", "NPM_CONFIG_REGISTRY"]
+            .iter()
+            .find_map(|variable| std::env::var(variable).ok())
+            .filter(|custom_registry| !custom_registry.is_empty())
+        {
+            return Some(custom_registry);
+        }
+    }
+
+    Some(registry)
+}
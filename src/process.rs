@@ -0,0 +1,97 @@
+use crate::l10n;
+use crate::registry;
+use std::ffi::OsStr;
+use std::io::IsTerminal;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Mutex;
+
+// A command whose output we parse. It runs in the C locale so that its messages, number
+// formats and dates are the same on every machine. The update command itself keeps the
+// user's locale since its output is shown as is. Both go to the configured registry
+pub fn query_command<S: AsRef<OsStr>>(program: S) -> Command {
+    let (env, leading_args) = registry::get_registry_overrides(&program.as_ref().to_string_lossy());
+    let mut command = Command::new(program);
+    command
+        .env("LC_ALL", "C")
+        .env("LANG", "C")
+        .env("LANGUAGE", "C")
+        .envs(env)
+        .args(leading_args);
+    command
+}
+
+// Set for callers like `update-bin step` that can't answer a prompt even from a terminal
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_non_interactive() {
+    NON_INTERACTIVE.store(true, AtomicOrdering::Relaxed);
+}
+
+// Whether there's someone to ask, otherwise a prompt takes its default or says what to run
+pub fn can_prompt() -> bool {
+    !NON_INTERACTIVE.load(AtomicOrdering::Relaxed) && std::io::stdin().is_terminal()
+}
+
+// Names that end up as arguments of a command come from the user, from package.json files
+// and from other commands' output. A name starting with a dash would be taken as an option
+// (`--registry=https://evil.example`), so only plain names are let through
+pub fn check_argument(value: &str, what: &str) -> Result<(), String> {
+    if value.is_empty() {
+        return Err(l10n::text("argument-empty", &[("what", what)]));
+    }
+    if value.starts_with('-') {
+        return Err(l10n::text(
+            "argument-looks-like-option",
+            &[("what", what), ("value", value)],
+        ));
+    }
+    if value.chars().any(|c| c.is_control() || c.is_whitespace()) {
+        return Err(l10n::text(
+            "argument-has-whitespace",
+            &[("what", what), ("value", &format!("{:?}", value))],
+        ));
+    }
+    Ok(())
+}
+
+// Run `query` on every item with at most `jobs` of them in flight, for commands that spend
+// most of their time waiting on the network. Results are in the order of the items
+pub fn map_concurrently<T: Sync, R: Send>(
+    items: &[T],
+    jobs: usize,
+    query: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let next_index = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next_index.fetch_add(1, AtomicOrdering::Relaxed);
+                let item = match items.get(index) {
+                    Some(item) => item,
+                    None => break,
+                };
+                let result = query(item);
+                results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+// Adopted binaries' and health checks' commands are written for the user's shell
+pub fn get_shell_command(command: &str) -> (&'static str, Vec<String>) {
+    if cfg!(windows) {
+        ("cmd", vec!["/C".to_string(), command.to_string()])
+    } else {
+        ("sh", vec!["-c".to_string(), command.to_string()])
+    }
+}
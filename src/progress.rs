@@ -0,0 +1,333 @@
+use crate::l10n;
+use crate::style;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const ASCII_SPINNER: [&str; 4] = ["|", "/", "-", "\\"];
+
+// Longer phases are cut so that a row never wraps, which would break redrawing in place
+const PHASE_WIDTH: usize = 48;
+
+enum State {
+    Waiting,
+    Running(Instant),
+    Finished(Duration, bool),
+}
+
+struct Row {
+    name: String,
+    phase: String,
+    state: State,
+}
+
+// A row per task that runs at the same time as the others, with a spinner, what it's doing
+// and for how long. On a terminal it's redrawn in place, anywhere else and in plain mode every
+// change is a line
+pub struct LiveTable {
+    rows: Mutex<Vec<Row>>,
+    is_terminal: bool,
+    // a JSON object per change instead, for `update-bin step --json`
+    events: bool,
+    // whether there's a drawn table to go back up over
+    drawn: AtomicBool,
+}
+
+impl LiveTable {
+    pub fn new(names: &[&str]) -> LiveTable {
+        LiveTable {
+            rows: Mutex::new(
+                names
+                    .iter()
+                    .map(|name| Row {
+                        name: name.to_string(),
+                        phase: "waiting".to_string(),
+                        state: State::Waiting,
+                    })
+                    .collect(),
+            ),
+            is_terminal: std::io::stdout().is_terminal() && !style::is_plain(),
+            events: false,
+            drawn: AtomicBool::new(false),
+        }
+    }
+
+    pub fn with_events(names: &[&str]) -> LiveTable {
+        LiveTable {
+            is_terminal: false,
+            events: true,
+            ..LiveTable::new(names)
+        }
+    }
+
+    pub fn set_phase(&self, index: usize, phase: &str) {
+        let mut rows = self.rows.lock().unwrap_or_else(|e| e.into_inner());
+        let row = &mut rows[index];
+        if let State::Waiting = row.state {
+            row.state = State::Running(Instant::now());
+        }
+        row.phase = phase.to_string();
+        if self.events {
+            print_event(serde_json::json!({
+                "event": "phase",
+                "manager": row.name,
+                "phase": phase,
+            }));
+        } else if !self.is_terminal {
+            println!("{}: {}", row.name, phase);
+        }
+    }
+
+    pub fn finish(&self, index: usize, succeeded: bool) {
+        let mut rows = self.rows.lock().unwrap_or_else(|e| e.into_inner());
+        let row = &mut rows[index];
+        let elapsed = match row.state {
+            State::Running(started) => started.elapsed(),
+            _ => Duration::ZERO,
+        };
+        row.state = State::Finished(elapsed, succeeded);
+        row.phase = l10n::text(
+            if succeeded {
+                "phase-done"
+            } else {
+                "phase-failed"
+            },
+            &[],
+        );
+        if self.events {
+            print_event(serde_json::json!({
+                "event": "manager_finished",
+                "manager": row.name,
+                "status": if succeeded { "succeeded" } else { "failed" },
+                "duration_secs": elapsed.as_secs_f64(),
+            }));
+        } else if !self.is_terminal {
+            println!(
+                "{}",
+                l10n::text(
+                    "manager-finished",
+                    &[
+                        ("manager", &row.name),
+                        ("phase", &row.phase),
+                        ("seconds", &format!("{:.1}", elapsed.as_secs_f64()))
+                    ]
+                )
+            );
+        }
+    }
+
+    // Redraws the table every 100ms while `task` runs, and once more when it's done
+    pub fn show_while<R>(&self, task: impl FnOnce() -> R) -> R {
+        if !self.is_terminal {
+            return task();
+        }
+        let done = AtomicBool::new(false);
+        let result = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                let mut frame = 0;
+                while !done.load(AtomicOrdering::Relaxed) {
+                    self.draw(frame);
+                    frame += 1;
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+            });
+            let result = task();
+            done.store(true, AtomicOrdering::Relaxed);
+            result
+        });
+        self.draw(0);
+        result
+    }
+
+    fn draw(&self, frame: usize) {
+        let rows = self.rows.lock().unwrap_or_else(|e| e.into_inner());
+        let name_width = rows.iter().map(|row| row.name.len()).max().unwrap_or(0);
+        let mut text = String::new();
+        if self.drawn.swap(true, AtomicOrdering::Relaxed) {
+            // back to the first row
+            text.push_str(&format!("\x1b[{}A", rows.len()));
+        }
+        let spinner: &[&str] = if style::is_ascii_table() {
+            &ASCII_SPINNER
+        } else {
+            &SPINNER
+        };
+        for row in rows.iter() {
+            let (symbol, elapsed) = match row.state {
+                State::Waiting => (" ".to_string(), String::new()),
+                State::Running(started) => (
+                    spinner[frame % spinner.len()].to_string(),
+                    format!("{:.1}s", started.elapsed().as_secs_f64()),
+                ),
+                State::Finished(elapsed, true) => (
+                    style::paint(&style::table_symbol(style::SUCCESS), style::SUCCESS),
+                    format!("{:.1}s", elapsed.as_secs_f64()),
+                ),
+                State::Finished(elapsed, false) => (
+                    style::paint(&style::table_symbol(style::FAILURE), style::FAILURE),
+                    format!("{:.1}s", elapsed.as_secs_f64()),
+                ),
+            };
+            let phase: String = row.phase.chars().take(PHASE_WIDTH).collect();
+            // \x1b[2K clears what a longer phase left behind
+            text.push_str(&format!(
+                "\x1b[2K{} {:name_width$}  {:PHASE_WIDTH$}  {}\n",
+                symbol, row.name, phase, elapsed
+            ));
+        }
+        let mut stdout = std::io::stdout().lock();
+        let _ = stdout.write_all(text.as_bytes());
+        let _ = stdout.flush();
+    }
+}
+
+// A line of NDJSON on stdout, whole even when several threads print at once
+pub fn print_event(event: serde_json::Value) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", event);
+    let _ = stdout.flush();
+}
+
+// Takes the terminal's progress indicator back to nothing
+const CLEAR_TERMINAL_PROGRESS: &str = "\x1b]9;4;0;0\x1b\\";
+
+// Overall progress shown by the terminal itself, in Windows Terminal's taskbar button or
+// iTerm2's tab, through the OSC 9;4 sequence ConEmu introduced. Cleared when dropped, and when
+// update-bin is interrupted, so the terminal doesn't keep showing an update that's gone
+pub struct TerminalProgress {
+    enabled: bool,
+    total: usize,
+    finished: AtomicUsize,
+    failed: AtomicBool,
+}
+
+impl TerminalProgress {
+    pub fn start(total: usize) -> TerminalProgress {
+        let progress = TerminalProgress {
+            enabled: supports_terminal_progress(),
+            total,
+            finished: AtomicUsize::new(0),
+            failed: AtomicBool::new(false),
+        };
+        if progress.enabled {
+            interrupt::clear_terminal_progress_on_interrupt();
+            progress.show(0, false);
+        }
+        progress
+    }
+
+    // A failure turns the rest of the run red (the error state), it still counts as done
+    pub fn finish_one(&self, succeeded: bool) {
+        if !succeeded {
+            self.failed.store(true, AtomicOrdering::Relaxed);
+        }
+        let finished = self.finished.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+        if self.enabled {
+            self.show(finished, self.failed.load(AtomicOrdering::Relaxed));
+        }
+    }
+
+    fn show(&self, finished: usize, failed: bool) {
+        let percent = finished * 100 / self.total.max(1);
+        write_to_terminal(&format!(
+            "\x1b]9;4;{};{}\x1b\\",
+            if failed { 2 } else { 1 },
+            percent.min(100)
+        ));
+    }
+}
+
+impl Drop for TerminalProgress {
+    fn drop(&mut self) {
+        if self.enabled {
+            write_to_terminal(CLEAR_TERMINAL_PROGRESS);
+        }
+    }
+}
+
+// Terminals that don't know the sequence would print it, so only the ones that do get it:
+// Windows Terminal, ConEmu, iTerm2, WezTerm and Ghostty
+fn supports_terminal_progress() -> bool {
+    if !std::io::stderr().is_terminal() || style::is_plain() {
+        return false;
+    }
+    std::env::var_os("WT_SESSION").is_some()
+        || std::env::var("ConEmuANSI").is_ok_and(|ansi| ansi == "ON")
+        || std::env::var("TERM_PROGRAM")
+            .is_ok_and(|program| ["iTerm.app", "WezTerm", "ghostty"].contains(&program.as_str()))
+}
+
+// On stderr, which stays the terminal when the output is piped somewhere
+fn write_to_terminal(sequence: &str) {
+    let mut stderr = std::io::stderr().lock();
+    let _ = stderr.write_all(sequence.as_bytes());
+    let _ = stderr.flush();
+}
+
+// Ctrl+C ends update-bin without unwinding, so nothing would be dropped. The handler only
+// clears the progress and then ends it the way the default handler would have
+#[cfg(unix)]
+mod interrupt {
+    use super::CLEAR_TERMINAL_PROGRESS;
+
+    const SIGINT: i32 = 2;
+    const SIGTERM: i32 = 15;
+
+    extern "C" {
+        fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+        fn write(fd: i32, buf: *const u8, count: usize) -> isize;
+        fn _exit(status: i32) -> !;
+    }
+
+    // Only async-signal-safe calls in here
+    extern "C" fn on_signal(signum: i32) {
+        unsafe {
+            write(
+                2,
+                CLEAR_TERMINAL_PROGRESS.as_ptr(),
+                CLEAR_TERMINAL_PROGRESS.len(),
+            );
+            _exit(128 + signum);
+        }
+    }
+
+    pub fn clear_terminal_progress_on_interrupt() {
+        unsafe {
+            signal(SIGINT, on_signal);
+            signal(SIGTERM, on_signal);
+        }
+    }
+}
+
+#[cfg(windows)]
+mod interrupt {
+    use super::{write_to_terminal, CLEAR_TERMINAL_PROGRESS};
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetConsoleCtrlHandler(
+            handler: Option<unsafe extern "system" fn(u32) -> i32>,
+            add: i32,
+        ) -> i32;
+    }
+
+    // Runs on a thread of its own. Returning false passes the event on to the default
+    // handler, which ends the process
+    unsafe extern "system" fn on_ctrl(_event: u32) -> i32 {
+        write_to_terminal(CLEAR_TERMINAL_PROGRESS);
+        0
+    }
+
+    pub fn clear_terminal_progress_on_interrupt() {
+        unsafe {
+            SetConsoleCtrlHandler(Some(on_ctrl), 1);
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod interrupt {
+    pub fn clear_terminal_progress_on_interrupt() {}
+}
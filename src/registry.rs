@@ -0,0 +1,300 @@
+use crate::config;
+use crate::deno;
+use crate::digest;
+use crate::http;
+use std::sync::OnceLock;
+
+// The latest version straight from the package's registry, which is a lot quicker than
+// starting the package manager to ask it. `None` when the registry can't be reached, so the
+// caller can still ask the manager
+pub fn get_latest_version(package_manager: &str, package_name: &str) -> Option<String> {
+    let (url, pointer) = match package_manager {
+        "cargo" => (
+            format!("https://crates.io/api/v1/crates/{}", package_name),
+            "/crate/max_stable_version",
+        ),
+        "homebrew" => (
+            format!("https://formulae.brew.sh/api/formula/{}.json", package_name),
+            "/versions/stable",
+        ),
+        "homebrew-cask" => (
+            format!("https://formulae.brew.sh/api/cask/{}.json", package_name),
+            "/version",
+        ),
+        // scoped packages are fetched as `@scope%2Fname`
+        "npm" | "pnpm" | "yarn" | "bun" => (
+            format!(
+                "{}/{}/latest",
+                get_npm_registry(),
+                package_name.replace('/', "%2F")
+            ),
+            "/version",
+        ),
+        "uv" => (
+            format!("https://pypi.org/pypi/{}/json", package_name),
+            "/info/version",
+        ),
+        // only jsr: and npm: specifiers name a package with versions to look up
+        "deno" => match deno::get_registry_package(package_name)? {
+            ("jsr", name) => (format!("https://jsr.io/{}/meta.json", name), "/latest"),
+            (_, name) => return get_latest_version("npm", &name),
+        },
+        _ => return None,
+    };
+
+    http::get_json(&url)?
+        .pointer(pointer)?
+        .as_str()
+        .filter(|version| !version.is_empty())
+        .map(|version| version.to_string())
+}
+
+// A node package's dist-tags and the version each points to, e.g.
+// `{ "latest": "5.4.5", "next": "5.5.0-beta" }`
+pub fn get_dist_tags(package_name: &str) -> Option<Vec<(String, String)>> {
+    let url = format!(
+        "{}/-/package/{}/dist-tags",
+        get_npm_registry(),
+        package_name.replace('/', "%2F")
+    );
+    let dist_tags = http::get_json(&url)?;
+    Some(
+        dist_tags
+            .as_object()?
+            .iter()
+            .filter_map(|(tag, version)| Some((tag.to_string(), version.as_str()?.to_string())))
+            .collect(),
+    )
+}
+
+// The newest version of a crate, prereleases included
+pub fn get_latest_prerelease(crate_name: &str) -> Option<String> {
+    http::get_json(&format!("https://crates.io/api/v1/crates/{}", crate_name))?
+        .pointer("/crate/max_version")?
+        .as_str()
+        .filter(|version| !version.is_empty())
+        .map(|version| version.to_string())
+}
+
+// What a release weighs and what it needs to run or build, for --dry-run
+pub struct ReleaseDetails {
+    // the .crate file for a crate, everything in the tarball for a node package
+    pub size: Option<u64>,
+    // the crate's minimum Rust version, or the node package's `engines.node` range
+    pub requires: Option<String>,
+    // the node package's tarball, a crate alone can't be built without its dependencies
+    pub download_url: Option<String>,
+    // the node package's tarball as a Subresource Integrity string, `sha512-<base64>`
+    pub integrity: Option<String>,
+    // (keyid, base64 signature) pairs the registry signed the node package's integrity with
+    pub signatures: Vec<(String, String)>,
+}
+
+pub fn get_release_details(
+    package_manager: &str,
+    package_name: &str,
+    version: &str,
+) -> Option<ReleaseDetails> {
+    let (url, size_pointer, requires_pointer) = match package_manager {
+        "cargo" => (
+            format!(
+                "https://crates.io/api/v1/crates/{}/{}",
+                package_name, version
+            ),
+            "/version/crate_size",
+            "/version/rust_version",
+        ),
+        "npm" | "pnpm" | "yarn" | "bun" => (
+            format!(
+                "{}/{}/{}",
+                get_npm_registry(),
+                package_name.replace('/', "%2F"),
+                version
+            ),
+            "/dist/unpackedSize",
+            "/engines/node",
+        ),
+        _ => return None,
+    };
+
+    let release = http::get_json(&url)?;
+    Some(ReleaseDetails {
+        size: release.pointer(size_pointer).and_then(|size| size.as_u64()),
+        requires: release
+            .pointer(requires_pointer)
+            .and_then(|requires| requires.as_str())
+            .filter(|requires| !requires.is_empty())
+            .map(|requires| requires.to_string()),
+        download_url: release
+            .pointer("/dist/tarball")
+            .and_then(|url| url.as_str())
+            .map(|url| url.to_string()),
+        // packages published before npm 5 only have the tarball's SHA-1, in hex
+        integrity: release
+            .pointer("/dist/integrity")
+            .and_then(|integrity| integrity.as_str())
+            .map(|integrity| integrity.to_string())
+            .or_else(|| {
+                let shasum = release.pointer("/dist/shasum")?.as_str()?;
+                Some(format!(
+                    "sha1-{}",
+                    digest::to_base64(&digest::from_hex(shasum)?)
+                ))
+            }),
+        signatures: release
+            .pointer("/dist/signatures")
+            .and_then(|signatures| signatures.as_array())
+            .map(|signatures| {
+                signatures
+                    .iter()
+                    .filter_map(|signature| {
+                        Some((
+                            signature["keyid"].as_str()?.to_string(),
+                            signature["sig"].as_str()?.to_string(),
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    })
+}
+
+// npm reads the registry from its config, which it exports to scripts and which can also be
+// set in the environment. update-bin's own config comes first, it's what npm is pointed at
+fn get_npm_registry() -> String {
+    get_configured_registry("npm")
+        .or_else(|| std::env::var("npm_config_registry").ok())
+        .or_else(|| std::env::var("NPM_CONFIG_REGISTRY").ok())
+        .filter(|registry| !registry.is_empty())
+        .unwrap_or_else(|| "https://registry.npmjs.org".to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+// The config's `managers.<manager>.registry` for the manager a program belongs to, as the
+// environment variables and leading arguments that point it there. pnpm, yarn and bun fall back
+// to npm's, since they all download from the npm registry
+pub fn get_registry_overrides(program: &str) -> (Vec<(&'static str, String)>, Vec<String>) {
+    let name = std::path::Path::new(program)
+        .file_stem()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let package_manager = match name.as_str() {
+        "npm" | "npx" => "npm",
+        "pnpm" | "yarn" | "bun" | "cargo" | "uv" => name.as_str(),
+        "brew" => "homebrew",
+        "pip" | "pip3" | "pipx" => "pip",
+        _ if name.starts_with("python") => "pip",
+        _ => return (Vec::new(), Vec::new()),
+    };
+    let registry = match get_configured_registry(package_manager) {
+        Some(registry) => registry,
+        None => return (Vec::new(), Vec::new()),
+    };
+
+    match package_manager {
+        // Yarn Berry ignores npm's config and reads its own
+        "yarn" => (
+            vec![
+                ("npm_config_registry", registry.clone()),
+                ("YARN_NPM_REGISTRY_SERVER", registry),
+            ],
+            Vec::new(),
+        ),
+        "bun" => (vec![("NPM_CONFIG_REGISTRY", registry)], Vec::new()),
+        "homebrew" => (vec![("HOMEBREW_BOTTLE_DOMAIN", registry)], Vec::new()),
+        "pip" => (vec![("PIP_INDEX_URL", registry)], Vec::new()),
+        // uv before 0.4.23 only knows UV_INDEX_URL
+        "uv" => (
+            vec![
+                ("UV_DEFAULT_INDEX", registry.clone()),
+                ("UV_INDEX_URL", registry),
+            ],
+            Vec::new(),
+        ),
+        // Cargo can't replace a source from the environment, only from its config, which
+        // `--config` adds to. `sparse+https://...` for a sparse mirror
+        "cargo" => {
+            let registry = registry.replace('\\', "\\\\").replace('"', "\\\"");
+            (
+                Vec::new(),
+                vec![
+                    "--config".to_string(),
+                    "source.crates-io.replace-with=\"update-bin-mirror\"".to_string(),
+                    "--config".to_string(),
+                    format!("source.update-bin-mirror.registry=\"{}\"", registry),
+                ],
+            )
+        }
+        _ => (vec![("npm_config_registry", registry)], Vec::new()),
+    }
+}
+
+// The command line running `command args` against the configured registry. sudo and doas
+// reset the environment, so behind them the variables are passed with `env`
+pub fn with_registry_overrides(
+    command: &str,
+    args: &[String],
+) -> (Vec<(&'static str, String)>, Vec<String>) {
+    let is_sudo = ["sudo", "doas"]
+        .iter()
+        .any(|sudo| std::path::Path::new(command).file_stem() == Some(std::ffi::OsStr::new(sudo)));
+    if !is_sudo {
+        let (env, leading_args) = get_registry_overrides(command);
+        return (env, leading_args.into_iter().chain(args.to_vec()).collect());
+    }
+    // sudo's own options, like `-n`, come before the program
+    let options = args.iter().take_while(|arg| arg.starts_with('-')).count();
+    let (sudo_options, args_after_options) = args.split_at(options);
+    let (program, program_args) = match args_after_options.split_first() {
+        Some((program, program_args)) => (program.as_str(), program_args),
+        None => return (Vec::new(), args.to_vec()),
+    };
+
+    let (env, leading_args) = get_registry_overrides(program);
+    if env.is_empty() && leading_args.is_empty() {
+        return (Vec::new(), args.to_vec());
+    }
+    let mut sudo_args = sudo_options.to_vec();
+    sudo_args.push("env".to_string());
+    sudo_args.extend(
+        env.iter()
+            .map(|(variable, value)| format!("{}={}", variable, value)),
+    );
+    sudo_args.push(program.to_string());
+    sudo_args.extend(leading_args);
+    sudo_args.extend(program_args.to_vec());
+    (Vec::new(), sudo_args)
+}
+
+// Read from the config once, every command that runs asks
+pub fn get_configured_registry(package_manager: &str) -> Option<String> {
+    static CONFIG_REGISTRIES: OnceLock<Vec<(&str, String)>> = OnceLock::new();
+
+    let registries = CONFIG_REGISTRIES.get_or_init(|| {
+        let config = match config::load_config() {
+            Ok(config) => config,
+            Err(_) => return Vec::new(),
+        };
+        [
+            "npm", "pnpm", "yarn", "bun", "cargo", "homebrew", "pip", "uv",
+        ]
+        .into_iter()
+        .filter_map(|package_manager| {
+            let registry = config.get_registry(package_manager)?;
+            Some((package_manager, registry.to_string()))
+        })
+        .collect()
+    });
+    let find = |package_manager: &str| {
+        registries
+            .iter()
+            .find(|(name, _)| *name == package_manager)
+            .map(|(_, registry)| registry.clone())
+    };
+    find(package_manager).or_else(|| match package_manager {
+        "pnpm" | "yarn" | "bun" => find("npm"),
+        "uv" => find("pip"),
+        _ => None,
+    })
+}
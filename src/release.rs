@@ -0,0 +1,102 @@
+use crate::paths;
+use crate::process;
+
+// A link to what changed in `version`: the release of its tag when the repository has one,
+// otherwise the repository (or homepage) itself
+pub fn get_release_link(repository_url: &str, version: &str) -> Option<String> {
+    let repository_url = normalize_repository_url(repository_url)?;
+    let tag = match find_release_tag(&repository_url, version) {
+        Some(tag) => tag,
+        None => return Some(repository_url),
+    };
+
+    let host = repository_url
+        .strip_prefix("https://")
+        .and_then(|rest| rest.split('/').next())
+        .unwrap_or_default();
+    let link = match host {
+        "github.com" | "codeberg.org" => format!("{}/releases/tag/{}", repository_url, tag),
+        "gitlab.com" => format!("{}/-/releases/{}", repository_url, tag),
+        "bitbucket.org" => format!("{}/src/{}", repository_url, tag),
+        _ => repository_url,
+    };
+    Some(link)
+}
+
+// package.json allows `github:owner/repo`, `owner/repo` and git URLs of every kind, all of
+// them are turned into the repository's https page
+fn normalize_repository_url(url: &str) -> Option<String> {
+    let url = url.trim().trim_start_matches("git+");
+    let url = url.split('#').next().unwrap_or(url);
+
+    let url = if let Some(rest) = url.strip_prefix("git@") {
+        // git@github.com:owner/repo.git
+        let (host, path) = rest.split_once(':')?;
+        format!("https://{}/{}", host, path)
+    } else if let Some(rest) = url
+        .strip_prefix("ssh://git@")
+        .or_else(|| url.strip_prefix("git://"))
+        .or_else(|| url.strip_prefix("http://"))
+    {
+        format!("https://{}", rest)
+    } else if url.starts_with("https://") {
+        url.to_string()
+    } else {
+        let (host, path) = match url.split_once(':') {
+            Some(("github", path)) => ("github.com", path),
+            Some(("gitlab", path)) => ("gitlab.com", path),
+            Some(("bitbucket", path)) => ("bitbucket.org", path),
+            Some(_) => return None,
+            None if url.matches('/').count() == 1 => ("github.com", url),
+            None => return None,
+        };
+        format!("https://{}/{}", host, path)
+    };
+
+    let url = url.trim_end_matches('/');
+    Some(url.strip_suffix(".git").unwrap_or(url).to_string())
+}
+
+// Projects tag releases as `1.2.3`, `v1.2.3`, or with the package name in monorepos like
+// `pkg@1.2.3` and `pkg-v1.2.3`. Asks the remote for its tags, a homepage that isn't a git
+// repository simply has none
+fn find_release_tag(repository_url: &str, version: &str) -> Option<String> {
+    if !paths::is_installed("git") {
+        return None;
+    }
+
+    let output = process::query_command("git")
+        .args(["ls-remote", "--tags", "--refs", repository_url])
+        // never wait for credentials of a private or moved repository
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_HTTP_LOW_SPEED_LIMIT", "1000")
+        .env("GIT_HTTP_LOW_SPEED_TIME", "5")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let tags: Vec<&str> = stdout
+        .lines()
+        .filter_map(|line| line.split_once("refs/tags/"))
+        .map(|(_, tag)| tag)
+        .collect();
+
+    let version = version.trim_start_matches('v');
+    let exact_tags = [version.to_string(), format!("v{}", version)];
+    if let Some(tag) = tags
+        .iter()
+        .find(|tag| exact_tags.iter().any(|exact_tag| exact_tag == *tag))
+    {
+        return Some(tag.to_string());
+    }
+
+    let suffixes = [
+        format!("@{}", version),
+        format!("-{}", version),
+        format!("-v{}", version),
+        format!("/v{}", version),
+    ];
+    tags.iter()
+        .find(|tag| suffixes.iter().any(|suffix| tag.ends_with(suffix.as_str())))
+        .map(|tag| tag.to_string())
+}
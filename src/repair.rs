@@ -0,0 +1,55 @@
+use crate::l10n;
+use crate::process;
+use crate::runner;
+use std::io::Write;
+
+// A binary that is on PATH but can't run, and the command that would bring it back
+pub struct Repair {
+    pub problem: String,
+    pub command: Option<(String, Vec<String>)>,
+}
+
+// Ask before running the fix, it reinstalls things. Without a terminal to ask on, say what
+// would fix it instead
+pub fn offer(repair: &Repair) -> Result<(), String> {
+    let (command, args) = match &repair.command {
+        Some(command) => command,
+        None => return Err(repair.problem.clone()),
+    };
+    let full_command = format!("{} {}", command, args.join(" "));
+
+    if !process::can_prompt() {
+        return Err(l10n::text(
+            "repair-run-to-fix",
+            &[("problem", &repair.problem), ("command", &full_command)],
+        ));
+    }
+
+    println!("{}", repair.problem);
+    print!(
+        "{} ",
+        l10n::text("repair-prompt", &[("command", &full_command)])
+    );
+    std::io::stdout().flush().ok();
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).ok();
+    if !matches!(answer.trim(), "y" | "Y" | "yes") {
+        return Err(l10n::text("nothing-was-changed", &[]));
+    }
+
+    let output = runner::run(command, args, None, None)?;
+    match output.status {
+        runner::RunStatus::Succeeded => {
+            println!("{}", l10n::text("repaired", &[("command", &full_command)]));
+            Ok(())
+        }
+        _ => Err(l10n::text(
+            "command-failed-with-output",
+            &[
+                ("command", &full_command),
+                ("output", &runner::format_last_lines(&output.lines)),
+            ],
+        )),
+    }
+}
@@ -0,0 +1,165 @@
+use crate::inventory::{self, InstalledPackage};
+use crate::l10n;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+// How many lines of a failed manager's output go into the report
+const LOG_LINES: usize = 50;
+
+// One package manager's part of an --all run
+pub struct ManagerRun {
+    pub package_manager: String,
+    pub duration: Duration,
+    pub error: Option<String>,
+    pub lines: Vec<String>,
+}
+
+// A report of an --all run, HTML when the file is named `.html`, Markdown otherwise
+pub fn write_report(
+    path: &Path,
+    updates: &[(InstalledPackage, Option<String>)],
+    runs: &[ManagerRun],
+) -> Result<(), String> {
+    let is_html = path
+        .extension()
+        .map(|extension| {
+            let extension = extension.to_string_lossy().to_lowercase();
+            extension == "html" || extension == "htm"
+        })
+        .unwrap_or(false);
+    let report = if is_html {
+        to_html(updates, runs)
+    } else {
+        to_markdown(updates, runs)
+    };
+
+    std::fs::write(path, report).map_err(|e| {
+        l10n::text(
+            "failed-to-write-report",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })
+}
+
+pub fn to_markdown(updates: &[(InstalledPackage, Option<String>)], runs: &[ManagerRun]) -> String {
+    let mut report = format!(
+        "# update-bin report\n\nGenerated at {}\n\n## Updates\n\n",
+        inventory::format_timestamp(SystemTime::now())
+    );
+
+    if updates.is_empty() {
+        report.push_str("Nothing was updated.\n");
+    } else {
+        report.push_str("| Package | Manager | From | To |\n| --- | --- | --- | --- |\n");
+        for (package, old_version) in updates {
+            report.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                package.name,
+                package.package_manager,
+                old_version.as_deref().unwrap_or("-"),
+                package.version
+            ));
+        }
+    }
+
+    report.push_str(
+        "\n## Package managers\n\n| Manager | Status | Duration |\n| --- | --- | --- |\n",
+    );
+    for run in runs {
+        report.push_str(&format!(
+            "| {} | {} | {} |\n",
+            run.package_manager,
+            if run.error.is_some() { "failed" } else { "ok" },
+            format_duration(run.duration)
+        ));
+    }
+
+    for run in runs {
+        let error = match &run.error {
+            Some(error) => error,
+            None => continue,
+        };
+        report.push_str(&format!(
+            "\n## {} failed\n\n{}\n\n```\n{}\n```\n",
+            run.package_manager,
+            error,
+            get_last_lines(&run.lines).join("\n")
+        ));
+    }
+    report
+}
+
+fn to_html(updates: &[(InstalledPackage, Option<String>)], runs: &[ManagerRun]) -> String {
+    let mut report = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>update-bin report</title>\n</head>\n<body>\n<h1>update-bin report</h1>\n<p>Generated at {}</p>\n<h2>Updates</h2>\n",
+        inventory::format_timestamp(SystemTime::now())
+    );
+
+    if updates.is_empty() {
+        report.push_str("<p>Nothing was updated.</p>\n");
+    } else {
+        report.push_str(
+            "<table>\n<tr><th>Package</th><th>Manager</th><th>From</th><th>To</th></tr>\n",
+        );
+        for (package, old_version) in updates {
+            report.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&package.name),
+                escape_html(&package.package_manager),
+                escape_html(old_version.as_deref().unwrap_or("-")),
+                escape_html(&package.version)
+            ));
+        }
+        report.push_str("</table>\n");
+    }
+
+    report.push_str("<h2>Package managers</h2>\n<table>\n<tr><th>Manager</th><th>Status</th><th>Duration</th></tr>\n");
+    for run in runs {
+        report.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(&run.package_manager),
+            if run.error.is_some() { "failed" } else { "ok" },
+            format_duration(run.duration)
+        ));
+    }
+    report.push_str("</table>\n");
+
+    for run in runs {
+        let error = match &run.error {
+            Some(error) => error,
+            None => continue,
+        };
+        report.push_str(&format!(
+            "<h2>{} failed</h2>\n<p>{}</p>\n<pre>{}</pre>\n",
+            escape_html(&run.package_manager),
+            escape_html(error),
+            escape_html(&get_last_lines(&run.lines).join("\n"))
+        ));
+    }
+    report.push_str("</body>\n</html>\n");
+    report
+}
+
+fn get_last_lines(lines: &[String]) -> &[String] {
+    &lines[lines.len().saturating_sub(LOG_LINES)..]
+}
+
+// `1m 05s`, or `12.3s` for anything shorter than a minute
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,233 @@
+use crate::l10n;
+use crate::process;
+use crate::registry;
+use crate::style;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+
+// How many of the last output lines to show when the update has to be killed
+const LAST_LINES: usize = 10;
+
+pub enum RunStatus {
+    Succeeded,
+    Failed,
+    // the reason it was killed
+    TimedOut(String),
+}
+
+pub struct RunOutput {
+    pub status: RunStatus,
+    pub lines: Vec<String>,
+    // `None` when it was killed, or by a signal
+    pub exit_code: Option<i32>,
+}
+
+// Run the update command, streaming its output, and kill it (along with everything it
+// started) when it runs longer than `timeout` or stays silent for longer than `idle_timeout`
+pub fn run(
+    command: &str,
+    args: &[String],
+    timeout: Option<Duration>,
+    idle_timeout: Option<Duration>,
+) -> Result<RunOutput, String> {
+    run_command(command, args, timeout, idle_timeout, true)
+}
+
+// Like run, for updates running at the same time as others, whose output would interleave.
+// Nothing can answer a prompt either, so there's no stdin
+pub fn run_captured(
+    command: &str,
+    args: &[String],
+    timeout: Option<Duration>,
+    idle_timeout: Option<Duration>,
+) -> Result<RunOutput, String> {
+    run_command(command, args, timeout, idle_timeout, false)
+}
+
+fn run_command(
+    command: &str,
+    args: &[String],
+    timeout: Option<Duration>,
+    idle_timeout: Option<Duration>,
+    stream: bool,
+) -> Result<RunOutput, String> {
+    let (env, args) = registry::with_registry_overrides(command, args);
+    let mut child = Command::new(command)
+        .envs(env)
+        .args(args)
+        .stdin(if stream {
+            Stdio::inherit()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            l10n::text(
+                "failed-to-run",
+                &[("command", command), ("error", &e.to_string())],
+            )
+        })?;
+
+    // stdout and stderr are read on their own threads so neither pipe can fill up and block
+    // the child while the other one is being read
+    let (sender, receiver) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout, false, sender.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(stderr, true, sender.clone());
+    }
+    drop(sender);
+
+    let started = Instant::now();
+    let mut last_output = Instant::now();
+    let mut lines = Vec::new();
+
+    loop {
+        match receiver.recv_timeout(Duration::from_millis(250)) {
+            Ok((is_stderr, line)) => {
+                if stream && is_stderr {
+                    eprintln!(
+                        "{}",
+                        style::paint(
+                            &format!("{}{}", style::output_prefix(), line),
+                            style::OUTPUT
+                        )
+                    );
+                } else if stream {
+                    println!(
+                        "{}",
+                        style::paint(
+                            &format!("{}{}", style::output_prefix(), line),
+                            style::OUTPUT
+                        )
+                    );
+                }
+                lines.push(line);
+                last_output = Instant::now();
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+
+        let reason = match (timeout, idle_timeout) {
+            (Some(timeout), _) if started.elapsed() > timeout => Some(l10n::text(
+                "killed-still-running",
+                &[("seconds", &timeout.as_secs().to_string())],
+            )),
+            (_, Some(idle_timeout)) if last_output.elapsed() > idle_timeout => Some(l10n::text(
+                "killed-no-output",
+                &[("seconds", &idle_timeout.as_secs().to_string())],
+            )),
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            kill_process_tree(&mut child);
+            return Ok(RunOutput {
+                status: RunStatus::TimedOut(reason),
+                lines,
+                exit_code: None,
+            });
+        }
+    }
+
+    let status = child.wait().map_err(|e| {
+        l10n::text(
+            "failed-to-wait-for",
+            &[("command", command), ("error", &e.to_string())],
+        )
+    })?;
+    Ok(RunOutput {
+        status: if status.success() {
+            RunStatus::Succeeded
+        } else {
+            RunStatus::Failed
+        },
+        lines,
+        exit_code: status.code(),
+    })
+}
+
+// The tail of the output, indented for an error message
+pub fn format_last_lines(lines: &[String]) -> String {
+    let last_lines = get_last_lines(lines);
+    if last_lines.is_empty() {
+        return format!("  {}", l10n::text("no-output", &[]));
+    }
+
+    last_lines
+        .iter()
+        .map(|line| format!("  {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn get_last_lines(lines: &[String]) -> &[String] {
+    &lines[lines.len().saturating_sub(LAST_LINES)..]
+}
+
+fn forward_lines(
+    stream: impl Read + Send + 'static,
+    is_stderr: bool,
+    sender: Sender<(bool, String)>,
+) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            if sender.send((is_stderr, line)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+// Managers hand the actual work to other processes (brew runs curl and sudo, npm runs
+// install scripts), killing only the direct child would leave those hanging around
+fn kill_process_tree(child: &mut Child) {
+    let pid = child.id();
+
+    if cfg!(windows) {
+        let _ = process::query_command("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .output();
+    } else {
+        for descendant in get_descendants(pid) {
+            let _ = process::query_command("kill")
+                .args(["-KILL", &descendant.to_string()])
+                .output();
+        }
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+fn get_descendants(pid: u32) -> Vec<u32> {
+    let output = match process::query_command("ps")
+        .args(["-A", "-o", "pid=", "-o", "ppid="])
+        .output()
+    {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).to_string(),
+        Err(_) => return Vec::new(),
+    };
+    let processes: Vec<(u32, u32)> = output
+        .lines()
+        .filter_map(|line| {
+            let mut ids = line.split_whitespace().map(|id| id.parse().ok());
+            Some((ids.next()??, ids.next()??))
+        })
+        .collect();
+
+    let mut descendants = Vec::new();
+    let mut parents = vec![pid];
+    while let Some(parent) = parents.pop() {
+        for (child_pid, _) in processes.iter().filter(|(_, ppid)| *ppid == parent) {
+            descendants.push(*child_pid);
+            parents.push(*child_pid);
+        }
+    }
+    descendants
+}
@@ -0,0 +1,75 @@
+// Bumped whenever a field of the machine-readable output is removed or changes meaning, new
+// fields can be added without bumping it
+pub const SCHEMA_VERSION: u64 = 1;
+
+// JSON Schemas of everything update-bin writes for other programs to read. `export` isn't
+// here, it follows the CycloneDX spec
+pub fn get_schemas() -> serde_json::Value {
+    serde_json::json!({
+        "history_entry": {
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "update-bin history entry",
+            "description": "A line of history.jsonl, written for every update",
+            "type": "object",
+            "required": [
+                "schema_version", "timestamp", "bin_name", "package_name", "package_manager",
+                "old_version", "status", "duration_secs"
+            ],
+            "properties": {
+                "schema_version": { "const": SCHEMA_VERSION },
+                "timestamp": { "type": "integer", "description": "Seconds since the Unix epoch" },
+                "bin_name": { "type": "string" },
+                "package_name": { "type": "string" },
+                "package_manager": { "type": "string" },
+                "old_version": { "type": "string" },
+                "new_version": { "type": ["string", "null"] },
+                "status": {
+                    "enum": [
+                        "updated", "reinstalled", "unchanged", "downgraded", "unknown", "failed",
+                        "timed_out"
+                    ]
+                },
+                "duration_secs": { "type": "number" },
+                "error": { "type": ["string", "null"] }
+            }
+        },
+        "webhook_report": {
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "update-bin webhook report",
+            "description": "The body posted to a generic notifications.webhook after an --all run",
+            "type": "object",
+            "required": ["schema_version", "text", "updated", "failed"],
+            "properties": {
+                "schema_version": { "const": SCHEMA_VERSION },
+                "text": { "type": "string", "description": "A summary for humans" },
+                "updated": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["package_name", "package_manager", "new_version"],
+                        "properties": {
+                            "package_name": { "type": "string" },
+                            "package_manager": { "type": "string" },
+                            "old_version": {
+                                "type": ["string", "null"],
+                                "description": "null for packages that weren't installed before"
+                            },
+                            "new_version": { "type": "string" }
+                        }
+                    }
+                },
+                "failed": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["package_manager", "error"],
+                        "properties": {
+                            "package_manager": { "type": "string" },
+                            "error": { "type": "string" }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,99 @@
+use crate::paths;
+use crate::process;
+use std::path::{Path, PathBuf};
+
+// scoop is a PowerShell script, which Windows only runs through its .cmd
+pub fn get_executable() -> &'static str {
+    if cfg!(windows) {
+        "scoop.cmd"
+    } else {
+        "scoop"
+    }
+}
+
+// SCOOP (~\scoop by default) for the user's apps
+pub fn get_root_dir() -> Option<PathBuf> {
+    std::env::var_os("SCOOP")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| paths::get_home_dir().map(|home| home.join("scoop")))
+}
+
+// SCOOP_GLOBAL (%ProgramData%\scoop by default) for the ones installed with --global
+pub fn get_global_root_dir() -> Option<PathBuf> {
+    std::env::var_os("SCOOP_GLOBAL")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("ProgramData")
+                .filter(|dir| !dir.is_empty())
+                .map(|dir| PathBuf::from(dir).join("scoop"))
+        })
+}
+
+// Whether the binary is one of the --global installs, which `scoop update` only touches with
+// --global
+pub fn is_global(bin_path: &Path) -> bool {
+    get_global_root_dir().is_some_and(|root| paths::starts_with(bin_path, &root))
+}
+
+// The app behind a binary in scoop's shims dir, or in an app's own dir. A `rg.exe` shim
+// reads the target from the `rg.shim` next to it, `path = "C:\...\scoop\apps\ripgrep\current\rg.exe"`,
+// .cmd and .ps1 shims hold the target themselves
+pub fn get_app_from_path(bin_path: &str) -> Option<String> {
+    let path = Path::new(bin_path);
+    for root in [get_root_dir(), get_global_root_dir()]
+        .into_iter()
+        .flatten()
+    {
+        let apps_dir = root.join("apps");
+        if paths::starts_with(path, &apps_dir) {
+            return path
+                .components()
+                .nth(apps_dir.components().count())
+                .map(|app| app.as_os_str().to_string_lossy().to_string());
+        }
+        if !paths::starts_with(path, &root.join("shims")) {
+            continue;
+        }
+
+        let shim_path = match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("exe") => path.with_extension("shim"),
+            _ => path.to_path_buf(),
+        };
+        // shims are tiny, don't read anything that isn't one
+        if std::fs::metadata(&shim_path).ok()?.len() > 64 * 1024 {
+            return None;
+        }
+        let content = std::fs::read_to_string(&shim_path).ok()?;
+        return content.lines().find_map(get_app_from_line);
+    }
+    None
+}
+
+// The `<app>` of a `...\apps\<app>\current\...` path anywhere in the line
+fn get_app_from_line(line: &str) -> Option<String> {
+    let mut parts = line.split(['\\', '/']);
+    parts.find(|part| part.eq_ignore_ascii_case("apps"))?;
+    parts
+        .next()
+        .filter(|app| !app.is_empty())
+        .map(|app| app.to_string())
+}
+
+// The installed version, from `scoop list`'s `<app> <version> <bucket> <updated>` rows
+pub fn get_installed_version(app: &str) -> Option<String> {
+    let output = process::query_command(get_executable())
+        .arg("list")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        if !fields.next()?.eq_ignore_ascii_case(app) {
+            return None;
+        }
+        fields.next().map(|version| version.to_string())
+    })
+}
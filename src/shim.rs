@@ -0,0 +1,230 @@
+use std::path::{Path, PathBuf};
+
+// Shim scripts refer to their target relative to the shim's own directory
+const SHIM_DIR_VARIABLES: [&str; 4] = ["%~dp0", "%dp0%", "$basedir", "$dp0"];
+
+// Resolve a .cmd/.ps1 shim (like the ones npm and pnpm generate on Windows) to the
+// script or executable it launches
+pub fn resolve_shim_target(shim_path: &Path) -> Option<PathBuf> {
+    let extension = shim_path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    let is_shim = match extension.as_deref() {
+        Some("cmd") | Some("bat") | Some("ps1") => true,
+        // the extensionless sibling is a sh script for Git Bash / WSL
+        None => cfg!(windows),
+        _ => false,
+    };
+    if !is_shim {
+        return None;
+    }
+
+    // shims are tiny, don't read anything that isn't one
+    let metadata = std::fs::metadata(shim_path).ok()?;
+    if metadata.len() > 64 * 1024 {
+        return None;
+    }
+
+    let content = std::fs::read_to_string(shim_path).ok()?;
+    let shim_dir = shim_path.parent()?;
+
+    let target = content
+        .split('"')
+        .skip(1)
+        .step_by(2)
+        .filter(|quoted| {
+            SHIM_DIR_VARIABLES
+                .iter()
+                .any(|variable| quoted.starts_with(variable))
+        })
+        .filter(|quoted| !is_node_executable(quoted))
+        .last()?;
+
+    let relative_target = SHIM_DIR_VARIABLES
+        .iter()
+        .find_map(|variable| target.strip_prefix(variable))?;
+
+    Some(join_shim_path(shim_dir, relative_target))
+}
+
+fn is_node_executable(path: &str) -> bool {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    file_name.eq_ignore_ascii_case("node.exe") || file_name == "node"
+}
+
+// shims use either separator, and may step out of their dir with `..`
+fn join_shim_path(shim_dir: &Path, relative_target: &str) -> PathBuf {
+    let mut path = shim_dir.to_path_buf();
+    for part in relative_target.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                path.pop();
+            }
+            part => path.push(part),
+        }
+    }
+    path
+}
+
+// Resolve a wrapper script (e.g. `exec /opt/foo/bin/foo "$@"` or a batch file running an
+// absolute path) to the binary it actually runs
+pub fn resolve_script_target(script_path: &Path) -> Option<PathBuf> {
+    get_script_target(script_path).filter(|target| target.is_file())
+}
+
+// The binary a wrapper script runs when it has been removed from under the script, e.g. by
+// uninstalling a runtime the script hardcodes
+pub fn get_missing_script_target(script_path: &Path) -> Option<PathBuf> {
+    get_script_target(script_path).filter(|target| !target.exists())
+}
+
+fn get_script_target(script_path: &Path) -> Option<PathBuf> {
+    let metadata = std::fs::metadata(script_path).ok()?;
+    if metadata.len() > 64 * 1024 {
+        return None;
+    }
+
+    // binaries aren't valid UTF-8, so this also skips anything that isn't a script
+    let content = std::fs::read_to_string(script_path).ok()?;
+    let extension = script_path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+
+    let target = match extension.as_deref() {
+        Some("cmd") | Some("bat") => content.lines().find_map(get_batch_target),
+        _ if content.starts_with("#!") => content.lines().find_map(get_exec_target),
+        _ => None,
+    }?;
+
+    let target = PathBuf::from(target);
+    if target.is_absolute() && target != script_path && is_same_program(script_path, &target) {
+        Some(target)
+    } else {
+        None
+    }
+}
+
+// A wrapper runs the program it's named after. Any other script, like one run by ksh or php or
+// one that execs a tool of its own, is the user's, and belongs to whoever installed the script
+// itself rather than the program it runs
+fn is_same_program(script_path: &Path, target: &Path) -> bool {
+    let stem = |path: &Path| {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase())
+    };
+    stem(script_path).is_some() && stem(script_path) == stem(target)
+}
+
+// `exec "/path/to/bin" "$@"`, `exec -a name /path/to/bin "$@"` or `exec $HOME/bin/foo`
+fn get_exec_target(line: &str) -> Option<String> {
+    let mut words = line.trim().strip_prefix("exec ")?.split_whitespace();
+    let mut word = words.next()?;
+    if word == "-a" {
+        words.next()?;
+        word = words.next()?;
+    }
+
+    let word = word.trim_matches(|c| c == '"' || c == '\'');
+    let home = crate::paths::get_home_dir();
+    for variable in ["$HOME", "${HOME}", "~"] {
+        if let (Some(rest), Some(home)) = (word.strip_prefix(variable), &home) {
+            return Some(format!("{}{}", home.display(), rest));
+        }
+    }
+    Some(word.to_string())
+}
+
+// `@"C:\Program Files\Foo\foo.exe" %*`
+fn get_batch_target(line: &str) -> Option<String> {
+    let line = line.trim().trim_start_matches('@');
+    let target = match line.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => line.split_whitespace().next()?,
+    };
+    let is_executable = Path::new(target)
+        .extension()
+        .map(|extension| extension.eq_ignore_ascii_case("exe"))
+        .unwrap_or(false);
+    if is_executable {
+        Some(target.to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_script(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn exec_target() {
+        assert_eq!(
+            get_exec_target("exec \"/opt/foo/bin/foo\" \"$@\""),
+            Some("/opt/foo/bin/foo".to_string())
+        );
+        assert_eq!(
+            get_exec_target("  exec -a foo /opt/foo/bin/foo \"$@\""),
+            Some("/opt/foo/bin/foo".to_string())
+        );
+        assert_eq!(get_exec_target("echo exec /opt/foo"), None);
+        assert_eq!(
+            get_batch_target("@\"C:\\Program Files\\Foo\\foo.exe\" %*"),
+            Some("C:\\Program Files\\Foo\\foo.exe".to_string())
+        );
+        assert_eq!(get_batch_target("@python.py %*"), None);
+    }
+
+    #[test]
+    fn scripts_run_by_an_interpreter_are_the_users() {
+        let dir = std::env::temp_dir().join(format!("update-bin-test-shim-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("opt")).unwrap();
+        let target_path = write_script(&dir.join("opt"), "foo", "");
+
+        // a wrapper named after the program it runs
+        let wrapper = write_script(
+            &dir,
+            "foo",
+            &format!("#!/bin/sh\nexec {} \"$@\"\n", target_path.display()),
+        );
+        assert_eq!(resolve_script_target(&wrapper), Some(target_path.clone()));
+
+        // scripts that aren't wrappers, whatever interpreter runs them
+        for (name, content) in [
+            ("myscript", "#!/bin/ksh\necho hi\n"),
+            ("report", "#!/usr/bin/php\n<?php echo 1;\n"),
+            ("tool", "#!/usr/bin/env lua\nprint(1)\n"),
+            ("check", "#!/usr/bin/pwsh\nWrite-Output 1\n"),
+            ("sum", "#!/usr/bin/awk -f\n{ s += $1 }\n"),
+            ("gui", "#!/usr/bin/tclsh\nputs 1\n"),
+        ] {
+            let script = write_script(&dir, name, content);
+            assert_eq!(resolve_script_target(&script), None, "{}", name);
+            assert_eq!(get_missing_script_target(&script), None, "{}", name);
+        }
+        // one that execs another program
+        let other = write_script(
+            &dir,
+            "deploy",
+            &format!("#!/bin/ksh\nexec {} --deploy\n", target_path.display()),
+        );
+        assert_eq!(resolve_script_target(&other), None);
+
+        // a wrapper whose program is gone
+        let missing = dir.join("opt").join("bar");
+        let broken = write_script(
+            &dir,
+            "bar",
+            &format!("#!/bin/sh\nexec {}\n", missing.display()),
+        );
+        assert_eq!(get_missing_script_target(&broken), Some(missing));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
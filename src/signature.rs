@@ -0,0 +1,276 @@
+use crate::config;
+use crate::digest;
+use crate::l10n;
+use crate::paths;
+use crate::process;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+// npm's registry keys, from https://registry.npmjs.org/-/npm/v1/keys. The key a signature is
+// checked with comes from here or the config, never from the plan, which whoever swaps the
+// artifacts could change along with them
+const NPM_REGISTRY_KEYS: [(&str, &str); 2] = [
+    (
+        "SHA256:jl3bwswu80PjjokCgh0o2w5c2U4LhQAE57gj9cz1kzA",
+        "MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAE1Olb3zMAFFxXKHiIkQO5cJ3Yhl5i6UPp+IhuteBJbuHcA5UogKo0EWtlWwW6KSaKoTNEYL7JlCQiVnkhBktUgg==",
+    ),
+    (
+        "SHA256:DhQ8wR5APBvFHLF/+Tc+AYvPOdTpcIDqOhxsBHRwC7U",
+        "MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEY6Ya7W++7aUPzvMTrezH6Ycx3c+HOKYCcNGybJZSCJq/fd7Qa8uuAKtdIkUQtQiEKERhAmE5lMMJhP8OkDOa2g==",
+    ),
+];
+
+// A registry's signature of a package version, `<name>@<version>:<integrity>` signed with
+// ECDSA P-256, and the ID of the key it was made with. npm's registry signs every version it
+// serves, which is what's recorded in a plan for the offline machine to check
+pub struct RegistrySignature {
+    pub keyid: String,
+    pub sig: String,
+}
+
+// What checking an artifact's signatures found. There's nothing to check for a package whose
+// registry doesn't sign, and nothing to check with when openssl or gpg isn't installed
+pub enum Verification {
+    Verified,
+    Unsigned,
+    Unverifiable(String),
+}
+
+pub fn get_signed_message(package_name: &str, version: &str, integrity: &str) -> String {
+    format!("{}@{}:{}", package_name, version, integrity)
+}
+
+// The registry's signature, and a detached `<artifact>.asc` GPG signature when one was put next
+// to the artifact. A signature that doesn't match is an error, whatever else was verified
+pub fn verify(
+    artifact: &Path,
+    message: &str,
+    registry_signature: Option<&RegistrySignature>,
+) -> Result<Verification, String> {
+    let mut verifications = Vec::new();
+    if let Some(signature) = registry_signature {
+        verifications.push(verify_registry_signature(message, signature)?);
+    }
+    let mut asc_path = artifact.as_os_str().to_owned();
+    asc_path.push(".asc");
+    let asc_path = PathBuf::from(asc_path);
+    if asc_path.is_file() {
+        verifications.push(verify_gpg_signature(artifact, &asc_path)?);
+    }
+
+    // one that couldn't be checked leaves the artifact unverified
+    let mut verification = Verification::Unsigned;
+    for next in verifications {
+        match next {
+            Verification::Unverifiable(_) => return Ok(next),
+            _ => verification = next,
+        }
+    }
+    Ok(verification)
+}
+
+fn verify_registry_signature(
+    message: &str,
+    signature: &RegistrySignature,
+) -> Result<Verification, String> {
+    if !paths::is_installed("openssl") {
+        return Ok(Verification::Unverifiable(l10n::text(
+            "signature-needs-openssl",
+            &[],
+        )));
+    }
+    let key = match find_trusted_key(&signature.keyid) {
+        Some(key) => key,
+        None => {
+            return Ok(Verification::Unverifiable(l10n::text(
+                "signature-unknown-key",
+                &[("keyid", &signature.keyid)],
+            )))
+        }
+    };
+    let invalid = || l10n::text("invalid-signature-in-plan", &[("keyid", &signature.keyid)]);
+    let key = digest::from_base64(&key).ok_or_else(invalid)?;
+    let sig = digest::from_base64(&signature.sig).ok_or_else(invalid)?;
+
+    // openssl only reads the key and the signature from files
+    let temp_dir = create_private_dir()?;
+    let key_path = temp_dir.join("key.pem");
+    let sig_path = temp_dir.join("signature.der");
+    let result = std::fs::write(&key_path, to_pem(&key))
+        .and_then(|_| std::fs::write(&sig_path, sig))
+        .map_err(|e| {
+            l10n::text(
+                "failed-to-write-to",
+                &[
+                    ("dir", &temp_dir.display().to_string()),
+                    ("error", &e.to_string()),
+                ],
+            )
+        })
+        .and_then(|_| run_openssl(&key_path, &sig_path, message));
+    let _ = std::fs::remove_dir_all(&temp_dir);
+
+    if result? {
+        Ok(Verification::Verified)
+    } else {
+        Err(l10n::text(
+            "signature-doesnt-match",
+            &[("keyid", &signature.keyid), ("message", message)],
+        ))
+    }
+}
+
+// The pinned keys, then the config's `managers.npm.signing_keys` for a registry of one's own
+pub fn find_trusted_key(keyid: &str) -> Option<String> {
+    if let Some((_, key)) = NPM_REGISTRY_KEYS.iter().find(|(id, _)| *id == keyid) {
+        return Some(key.to_string());
+    }
+    let config = config::load_config().ok()?;
+    config
+        .get_signing_keys("npm")
+        .into_iter()
+        .find(|(id, _)| id == keyid)
+        .map(|(_, key)| key)
+}
+
+// Made by this process alone, so nobody else on the machine can put a key of theirs in it or a
+// symlink to one. An existing directory is an error rather than reused
+fn create_private_dir() -> Result<PathBuf, String> {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos())
+        .unwrap_or_default();
+    let temp_dir = std::env::temp_dir().join(format!(
+        "update-bin-signature-{}-{}",
+        std::process::id(),
+        nanos
+    ));
+    // the temp dir is the user's own on Windows
+    #[cfg(unix)]
+    let result = {
+        use std::os::unix::fs::DirBuilderExt;
+        std::fs::DirBuilder::new().mode(0o700).create(&temp_dir)
+    };
+    #[cfg(not(unix))]
+    let result = std::fs::create_dir(&temp_dir);
+    result.map_err(|e| {
+        l10n::text(
+            "failed-to-create",
+            &[
+                ("path", &temp_dir.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })?;
+    Ok(temp_dir)
+}
+
+fn run_openssl(key_path: &Path, sig_path: &Path, message: &str) -> Result<bool, String> {
+    let mut child = process::query_command("openssl")
+        .args(["dgst", "-sha256", "-verify"])
+        .arg(key_path)
+        .arg("-signature")
+        .arg(sig_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| l10n::text("failed-to-run-openssl", &[("error", &e.to_string())]))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(message.as_bytes())
+            .map_err(|e| l10n::text("failed-to-write-to-openssl", &[("error", &e.to_string())]))?;
+    }
+    let status = child
+        .wait()
+        .map_err(|e| l10n::text("failed-to-wait-for-openssl", &[("error", &e.to_string())]))?;
+    Ok(status.success())
+}
+
+// A DER public key as the PEM openssl reads
+fn to_pem(key: &[u8]) -> String {
+    let encoded = digest::to_base64(key);
+    let lines: Vec<&str> = encoded
+        .as_bytes()
+        .chunks(64)
+        .map(|line| std::str::from_utf8(line).unwrap_or_default())
+        .collect();
+    format!(
+        "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n",
+        lines.join("\n")
+    )
+}
+
+// Checked against the keys in the user's keyring
+fn verify_gpg_signature(artifact: &Path, asc_path: &Path) -> Result<Verification, String> {
+    if !paths::is_installed("gpg") {
+        return Ok(Verification::Unverifiable(l10n::text(
+            "signature-needs-gpg",
+            &[("signature", &asc_path.display().to_string())],
+        )));
+    }
+    let output = process::query_command("gpg")
+        .args(["--batch", "--verify"])
+        .arg(asc_path)
+        .arg(artifact)
+        .output()
+        .map_err(|e| l10n::text("failed-to-run-gpg", &[("error", &e.to_string())]))?;
+    if output.status.success() {
+        Ok(Verification::Verified)
+    } else {
+        Err(l10n::text(
+            "signature-doesnt-verify",
+            &[
+                ("signature", &asc_path.display().to_string()),
+                ("artifact", &artifact.display().to_string()),
+                ("output", String::from_utf8_lossy(&output.stderr).trim()),
+            ],
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pem_wraps_at_64_columns() {
+        let pem = to_pem(&[0u8; 91]);
+        let lines: Vec<&str> = pem.lines().collect();
+        assert_eq!(lines[0], "-----BEGIN PUBLIC KEY-----");
+        assert_eq!(lines[1].len(), 64);
+        assert_eq!(lines[2].len(), 60);
+        assert_eq!(lines[3], "-----END PUBLIC KEY-----");
+    }
+
+    #[test]
+    fn pinned_keys_are_trusted() {
+        for (keyid, key) in NPM_REGISTRY_KEYS {
+            assert_eq!(find_trusted_key(keyid).as_deref(), Some(key));
+            // DER SubjectPublicKeyInfo of a P-256 key
+            assert_eq!(digest::from_base64(key).map(|key| key.len()), Some(91));
+        }
+    }
+
+    #[test]
+    fn private_dir_is_new() {
+        let temp_dir = create_private_dir().unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&temp_dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+        assert!(std::fs::DirBuilder::new().create(&temp_dir).is_err());
+        std::fs::remove_dir(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn signed_message() {
+        assert_eq!(
+            get_signed_message("@scope/tool", "1.2.0", "sha512-abc=="),
+            "@scope/tool@1.2.0:sha512-abc=="
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use crate::inventory;
+use crate::l10n;
+use std::path::Path;
+use std::time::SystemTime;
+
+// Bumped when the format changes, so an older update-bin refuses a snapshot it would misread
+const SNAPSHOT_VERSION: u64 = 1;
+
+// A tool installed on a machine, written by `update-bin snapshot` and installed again by
+// `update-bin restore` on another one
+pub struct SnapshotTool {
+    pub package_manager: String,
+    pub name: String,
+    pub version: String,
+    // where cargo installed the crate from, or an adopted binary's update command
+    pub source: Option<String>,
+    // what it was installed with, like a crate's features or Homebrew's --HEAD
+    pub args: Vec<String>,
+}
+
+pub fn to_json(tools: &[SnapshotTool]) -> serde_json::Value {
+    let tools: Vec<serde_json::Value> = tools
+        .iter()
+        .map(|tool| {
+            serde_json::json!({
+                "package_manager": tool.package_manager,
+                "name": tool.name,
+                "version": tool.version,
+                "source": tool.source,
+                "args": tool.args,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "version": SNAPSHOT_VERSION,
+        "created_at": inventory::format_timestamp(SystemTime::now()),
+        "os": std::env::consts::OS,
+        "tools": tools,
+    })
+}
+
+pub fn read_snapshot(path: &Path) -> Result<Vec<SnapshotTool>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        l10n::text(
+            "failed-to-read",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })?;
+    let snapshot: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
+        l10n::text(
+            "invalid-snapshot",
+            &[
+                ("path", &path.display().to_string()),
+                ("error", &e.to_string()),
+            ],
+        )
+    })?;
+    if snapshot["version"].as_u64() != Some(SNAPSHOT_VERSION) {
+        return Err(l10n::text(
+            "snapshot-version-unknown",
+            &[("path", &path.display().to_string())],
+        ));
+    }
+
+    let empty_tools = Vec::new();
+    snapshot["tools"]
+        .as_array()
+        .unwrap_or(&empty_tools)
+        .iter()
+        .map(|tool| {
+            let get = |key: &str| {
+                tool[key]
+                    .as_str()
+                    .map(|value| value.to_string())
+                    .ok_or_else(|| {
+                        l10n::text(
+                            "invalid-snapshot-missing",
+                            &[("path", &path.display().to_string()), ("key", key)],
+                        )
+                    })
+            };
+            Ok(SnapshotTool {
+                package_manager: get("package_manager")?,
+                name: get("name")?,
+                version: get("version")?,
+                source: tool["source"].as_str().map(|source| source.to_string()),
+                args: tool["args"]
+                    .as_array()
+                    .map(|args| {
+                        args.iter()
+                            .filter_map(|arg| arg.as_str())
+                            .map(|arg| arg.to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,132 @@
+use crate::history::{self, RecordedUpdate};
+use crate::inventory;
+use crate::l10n;
+use std::collections::HashMap;
+use std::time::{Duration, UNIX_EPOCH};
+
+// How many of the most frequently updated tools to list
+const TOP_TOOLS: usize = 10;
+
+pub fn print_stats(days: u64) -> Result<(), String> {
+    let entries = history::read_history();
+    if entries.is_empty() {
+        return Err(match history::get_history_path() {
+            Some(path) => l10n::text(
+                "nothing-recorded-in",
+                &[("path", &path.display().to_string())],
+            ),
+            None => l10n::text("nothing-recorded", &[]),
+        });
+    }
+    let is_update = |entry: &RecordedUpdate| entry.status == "updated";
+
+    println!("{}", l10n::text("stats-managers", &[]));
+    let mut managers: Vec<(&str, usize, usize)> = Vec::new();
+    for entry in &entries {
+        match managers
+            .iter_mut()
+            .find(|(manager, _, _)| *manager == entry.package_manager)
+        {
+            Some((_, runs, updates)) => {
+                *runs += 1;
+                *updates += usize::from(is_update(entry));
+            }
+            None => managers.push((&entry.package_manager, 1, usize::from(is_update(entry)))),
+        }
+    }
+    managers.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    for (manager, runs, updates) in &managers {
+        println!(
+            "  {}",
+            l10n::text(
+                "stats-manager",
+                &[
+                    ("manager", manager),
+                    ("runs", &runs.to_string()),
+                    ("updates", &updates.to_string())
+                ]
+            )
+        );
+    }
+
+    println!("{}", l10n::text("stats-most-updated", &[]));
+    let mut update_counts: HashMap<&str, usize> = HashMap::new();
+    for entry in entries.iter().filter(|entry| is_update(entry)) {
+        *update_counts.entry(&entry.bin_name).or_default() += 1;
+    }
+    let mut update_counts: Vec<(&str, usize)> = update_counts.into_iter().collect();
+    update_counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    if update_counts.is_empty() {
+        println!("  {}", l10n::text("stats-none-yet", &[]));
+    }
+    for (bin_name, count) in update_counts.iter().take(TOP_TOOLS) {
+        println!(
+            "  {}",
+            l10n::text(
+                "stats-tool",
+                &[("bin", bin_name), ("updates", &count.to_string())]
+            )
+        );
+    }
+
+    let total_secs: f64 = entries.iter().map(|entry| entry.duration_secs).sum();
+    println!(
+        "{}",
+        l10n::text(
+            "stats-average-duration",
+            &[
+                (
+                    "seconds",
+                    &format!("{:.1}", total_secs / entries.len() as f64)
+                ),
+                ("runs", &entries.len().to_string())
+            ]
+        )
+    );
+
+    println!("{}", l10n::text("stats-last-runs", &[]));
+    let mut last_runs: HashMap<&str, &RecordedUpdate> = HashMap::new();
+    for entry in &entries {
+        let last_run = last_runs.entry(&entry.bin_name).or_insert(entry);
+        if entry.timestamp >= last_run.timestamp {
+            *last_run = entry;
+        }
+    }
+    let mut last_runs: Vec<&RecordedUpdate> = last_runs.into_values().collect();
+    last_runs.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+    for entry in &last_runs {
+        println!(
+            "  {}: {} ({})",
+            entry.bin_name,
+            format_time(entry.timestamp),
+            entry.status
+        );
+    }
+
+    let now = history::now();
+    let stale_bins: Vec<(String, u64)> = history::get_last_up_to_date(&entries)
+        .into_iter()
+        .filter(|(_, timestamp)| now.saturating_sub(*timestamp) > days * 24 * 60 * 60)
+        .collect();
+    println!(
+        "{}",
+        l10n::text("stats-stale", &[("days", &days.to_string())])
+    );
+    if stale_bins.is_empty() {
+        println!("  {}", l10n::text("stats-none", &[]));
+    }
+    for (bin_name, timestamp) in &stale_bins {
+        println!(
+            "  {}",
+            l10n::text(
+                "stats-since",
+                &[("bin", bin_name), ("time", &format_time(*timestamp))]
+            )
+        );
+    }
+    Ok(())
+}
+
+fn format_time(timestamp: u64) -> String {
+    inventory::format_timestamp(UNIX_EPOCH + Duration::from_secs(timestamp))
+}
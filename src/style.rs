@@ -0,0 +1,275 @@
+use crate::config;
+use crate::l10n;
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+// What text is colored for, which the config's `theme.colors` can give other colors
+pub const SUCCESS: &str = "success";
+pub const FAILURE: &str = "failure";
+pub const MAJOR: &str = "major";
+pub const MINOR: &str = "minor";
+pub const PATCH: &str = "patch";
+pub const ALERT: &str = "alert";
+pub const OUTPUT: &str = "output";
+
+// (role, SGR code) without a theme
+const DEFAULT_COLORS: [(&str, &str); 7] = [
+    (SUCCESS, "32"),
+    (FAILURE, "31"),
+    (MAJOR, "31"),
+    (MINOR, "33"),
+    (PATCH, "32"),
+    (ALERT, "1;31"),
+    (OUTPUT, "2"),
+];
+
+// The names a theme's colors can use besides SGR codes, combined like "bold yellow"
+const COLOR_NAMES: [(&str, &str); 21] = [
+    ("bold", "1"),
+    ("dim", "2"),
+    ("italic", "3"),
+    ("underline", "4"),
+    ("black", "30"),
+    ("red", "31"),
+    ("green", "32"),
+    ("yellow", "33"),
+    ("blue", "34"),
+    ("magenta", "35"),
+    ("cyan", "36"),
+    ("white", "37"),
+    ("gray", "90"),
+    ("bright-black", "90"),
+    ("bright-red", "91"),
+    ("bright-green", "92"),
+    ("bright-yellow", "93"),
+    ("bright-blue", "94"),
+    ("bright-magenta", "95"),
+    ("bright-cyan", "96"),
+    ("bright-white", "97"),
+];
+
+// The config's `theme`, e.g.
+// `{ "colors": { "success": "bright-green", "output": "gray" }, "symbols": { "failure": "x" },
+// "output_prefix": "| ", "table": "ascii" }`
+#[derive(Default)]
+struct Theme {
+    colors: HashMap<String, String>,
+    symbols: HashMap<String, String>,
+    output_prefix: Option<String>,
+    ascii_table: bool,
+}
+
+// Set by --plain
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+pub fn set_plain() {
+    PLAIN.store(true, Ordering::Relaxed);
+}
+
+// ASCII only, for screen readers, log files and terminals that can't show Unicode
+pub fn is_plain() -> bool {
+    static SUPPORTS_UNICODE: OnceLock<bool> = OnceLock::new();
+
+    PLAIN.load(Ordering::Relaxed) || !*SUPPORTS_UNICODE.get_or_init(supports_unicode)
+}
+
+// The legacy Windows console shows emoji as boxes, Windows Terminal and VS Code don't. Elsewhere
+// it's up to the locale, which is C without one, except on macOS where terminals are UTF-8
+fn supports_unicode() -> bool {
+    let is_set = |variable: &str| std::env::var_os(variable).is_some_and(|value| !value.is_empty());
+    if cfg!(windows) {
+        return is_set("WT_SESSION") || is_set("TERM_PROGRAM");
+    }
+    if std::env::var("TERM").as_deref() == Ok("linux") {
+        return false;
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"].iter().find_map(|variable| {
+        std::env::var(variable)
+            .ok()
+            .filter(|value| !value.is_empty())
+    });
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        }
+        None => cfg!(target_os = "macos"),
+    }
+}
+
+// Colors only where a person reads them, and never with NO_COLOR (https://no-color.org)
+pub fn is_enabled() -> bool {
+    std::io::stdout().is_terminal()
+        && !is_plain()
+        && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && std::env::var("TERM").as_deref() != Ok("dumb")
+}
+
+// The text in the theme's color for the role, an empty color leaves it as it is
+pub fn paint(text: &str, role: &str) -> String {
+    let color = get_theme()
+        .colors
+        .get(role)
+        .map(|color| color.as_str())
+        .or_else(|| {
+            DEFAULT_COLORS
+                .iter()
+                .find(|(default_role, _)| *default_role == role)
+                .map(|(_, color)| *color)
+        })
+        .unwrap_or("");
+    if is_enabled() && !color.is_empty() {
+        format!("\x1b[{}m{}\x1b[0m", color, text)
+    } else {
+        text.to_string()
+    }
+}
+
+// ✔ or ✘ for SUCCESS or FAILURE, or the theme's. In plain mode only an ASCII one is kept
+pub fn symbol(role: &str) -> String {
+    get_symbol(role, is_plain())
+}
+
+// A symbol for the live table, in ASCII with the theme's `"table": "ascii"`
+pub fn table_symbol(role: &str) -> String {
+    get_symbol(role, is_ascii_table())
+}
+
+pub fn is_ascii_table() -> bool {
+    is_plain() || get_theme().ascii_table
+}
+
+// What every line of a package manager's output is shown after
+pub fn output_prefix() -> &'static str {
+    get_theme().output_prefix.as_deref().unwrap_or("---> ")
+}
+
+fn get_symbol(role: &str, ascii: bool) -> String {
+    if let Some(symbol) = get_theme()
+        .symbols
+        .get(role)
+        .filter(|symbol| !ascii || symbol.is_ascii())
+    {
+        return symbol.clone();
+    }
+    let (symbol, ascii_symbol) = if role == SUCCESS {
+        ("✔", "+")
+    } else {
+        ("✘", "-")
+    };
+    if ascii { ascii_symbol } else { symbol }.to_string()
+}
+
+// Read once, a broken theme is warned about and left out rather than failing every command
+fn get_theme() -> &'static Theme {
+    static THEME: OnceLock<Theme> = OnceLock::new();
+
+    THEME.get_or_init(|| {
+        let theme = config::load_config()
+            .ok()
+            .and_then(|config| config.get_theme().cloned());
+        match theme.map(|theme| parse_theme(&theme)) {
+            Some(Ok(theme)) => theme,
+            Some(Err(e)) => {
+                eprintln!("{}", l10n::text("theme-ignored", &[("error", &e)]));
+                Theme::default()
+            }
+            None => Theme::default(),
+        }
+    })
+}
+
+fn parse_theme(value: &serde_json::Value) -> Result<Theme, String> {
+    let theme = value
+        .as_object()
+        .ok_or(l10n::text("theme-not-object", &[]))?;
+    let mut parsed = Theme::default();
+    for (key, value) in theme {
+        match key.as_str() {
+            "colors" => {
+                for (role, color) in get_strings(key, value)? {
+                    if !DEFAULT_COLORS
+                        .iter()
+                        .any(|(default_role, _)| *default_role == role)
+                    {
+                        return Err(l10n::text("theme-no-color", &[("role", &role)]));
+                    }
+                    let color = parse_color(&color)?;
+                    parsed.colors.insert(role, color);
+                }
+            }
+            "symbols" => {
+                for (role, symbol) in get_strings(key, value)? {
+                    if role != SUCCESS && role != FAILURE {
+                        return Err(l10n::text("theme-no-symbol", &[("role", &role)]));
+                    }
+                    parsed.symbols.insert(role, symbol);
+                }
+            }
+            "output_prefix" => {
+                let prefix = value
+                    .as_str()
+                    .ok_or(l10n::text("theme-output-prefix-not-string", &[]))?;
+                parsed.output_prefix = Some(prefix.to_string());
+            }
+            "table" => {
+                parsed.ascii_table = match value.as_str() {
+                    Some("unicode") => false,
+                    Some("ascii") => true,
+                    _ => return Err(l10n::text("theme-table-unknown", &[])),
+                }
+            }
+            _ => return Err(l10n::text("theme-unknown-setting", &[("key", key)])),
+        }
+    }
+    Ok(parsed)
+}
+
+fn get_strings(key: &str, value: &serde_json::Value) -> Result<Vec<(String, String)>, String> {
+    let invalid = || l10n::text("theme-not-names-to-strings", &[("key", key)]);
+    value
+        .as_object()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|(name, value)| {
+            let value = value.as_str().ok_or_else(invalid)?;
+            Ok((name.clone(), value.to_string()))
+        })
+        .collect()
+}
+
+// "bold yellow" as "1;33", and an SGR code like "38;5;208" as it is
+fn parse_color(color: &str) -> Result<String, String> {
+    if color.chars().all(|c| c.is_ascii_digit() || c == ';') {
+        return Ok(color.to_string());
+    }
+    color
+        .split_whitespace()
+        .map(|name| {
+            COLOR_NAMES
+                .iter()
+                .find(|(color_name, _)| color_name.eq_ignore_ascii_case(name))
+                .map(|(_, code)| code.to_string())
+                .ok_or_else(|| l10n::text("theme-not-color", &[("name", name)]))
+        })
+        .collect::<Result<Vec<String>, String>>()
+        .map(|codes| codes.join(";"))
+}
+
+// The text as it is, or in plain mode with its emoji dropped or spelled out and its symbols
+// in ASCII
+pub fn text(text: &str) -> String {
+    if !is_plain() || text.is_ascii() {
+        return text.to_string();
+    }
+    let mut text = text.replace("✅ ", "").replace("ℹ️  ", "");
+    if text.contains("⚠️  ") {
+        text = text.replace("⚠️  ", &format!("{} ", l10n::text("warning", &[])));
+    }
+    text.replace('→', "->")
+        .replace('•', "-")
+        .replace('✔', "+")
+        .replace('✘', "-")
+}
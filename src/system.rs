@@ -0,0 +1,420 @@
+use crate::l10n;
+use crate::paths;
+use crate::process;
+use crate::trail;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+// Work out the distro package that owns a binary, as (package manager, package name)
+pub fn get_package_from_path(bin_path: &str) -> Option<(String, String)> {
+    let candidates = get_candidate_paths(Path::new(bin_path));
+
+    for path in &candidates {
+        let path = path.to_string_lossy();
+
+        // `coreutils: /bin/ls`, or `libfoo:amd64: /usr/bin/foo` for multiarch packages
+        if let Some(output) = get_owner_output("dpkg", &["-S", &path]) {
+            let package = output
+                .lines()
+                .find(|line| !line.starts_with("diversion by"))
+                .and_then(|line| line.split(": ").next())
+                .map(|package| package.split(':').next().unwrap_or(package).to_string());
+            if let Some(package) = package.filter(|package| !package.contains(',')) {
+                return Some(("apt".to_string(), package));
+            }
+        }
+
+        // `/usr/bin/ls is owned by coreutils 9.4-3`
+        if let Some(output) = get_owner_output("pacman", &["-Qo", &path]) {
+            let package = output
+                .split(" is owned by ")
+                .nth(1)
+                .and_then(|owner| owner.split_whitespace().next());
+            if let Some(package) = package {
+                return Some(("pacman".to_string(), package.to_string()));
+            }
+        }
+
+        if let Some(output) = get_owner_output("rpm", &["-qf", "--qf", "%{NAME}\\n", &path]) {
+            if let Some(package) = output.lines().next() {
+                return Some((get_rpm_frontend().to_string(), package.to_string()));
+            }
+        }
+
+        // `/bin/busybox is owned by busybox-1.36.1-r15`
+        if let Some(output) = get_owner_output("apk", &["info", "--who-owns", &path]) {
+            let package = output
+                .split(" is owned by ")
+                .nth(1)
+                .map(|owner| strip_apk_version(owner.trim()));
+            if let Some(package) = package {
+                return Some(("apk".to_string(), package.to_string()));
+            }
+        }
+
+        if is_available("pkg") {
+            let output = get_owner_output("pkg", &["which", "-q", &path]);
+            if let Some(package) = output.as_deref().and_then(parse_pkg_which) {
+                return Some(("pkg".to_string(), package));
+            }
+        }
+
+        if is_available("pkg_add") {
+            let output = get_owner_output("pkg_info", &["-E", &path]);
+            if let Some(package) = output.as_deref().and_then(parse_pkg_info_owner) {
+                return Some(("pkg_add".to_string(), package));
+            }
+        }
+
+        // IPS paths have no leading slash
+        if is_available("ips") {
+            let attribute = format!("path={}", path.trim_start_matches('/'));
+            let output = get_owner_output(
+                "pkg",
+                &["contents", "-H", "-o", "pkg.name", "-a", &attribute],
+            );
+            if let Some(package) = output.as_deref().and_then(parse_ips_contents) {
+                return Some(("ips".to_string(), package));
+            }
+        }
+    }
+
+    None
+}
+
+// FreeBSD's pkg and illumos' IPS are both `pkg`, so each is only known on its own system
+pub fn is_available(package_manager: &str) -> bool {
+    let systems: &[&str] = match package_manager {
+        "pkg" => &["freebsd"],
+        "pkg_add" => &["openbsd"],
+        "ips" => &["illumos", "solaris"],
+        _ => return true,
+    };
+    systems.contains(&std::env::consts::OS)
+}
+
+pub fn get_executable(package_manager: &str) -> &str {
+    match package_manager {
+        "apt" => "apt-get",
+        "ips" => "pkg",
+        package_manager => package_manager,
+    }
+}
+
+pub fn is_system_package_manager(package_manager: &str) -> bool {
+    matches!(
+        package_manager,
+        "apt" | "dnf" | "yum" | "zypper" | "pacman" | "apk" | "pkg" | "pkg_add" | "ips"
+    )
+}
+
+pub fn get_update_command(package_manager: &str, package_name: &str) -> (String, Vec<String>) {
+    let command: &[&str] = match package_manager {
+        "apt" => &["apt-get", "install", "--only-upgrade", "-y"],
+        "dnf" => &["dnf", "upgrade", "-y"],
+        "yum" => &["yum", "update", "-y"],
+        "zypper" => &["zypper", "--non-interactive", "update"],
+        "pacman" => &["pacman", "-S", "--noconfirm"],
+        "pkg" => &["pkg", "upgrade", "-y"],
+        "pkg_add" => &["pkg_add", "-u"],
+        "ips" => &["pkg", "update"],
+        _ => &["apk", "upgrade"],
+    };
+    let mut args: Vec<String> = command[1..].iter().map(|arg| arg.to_string()).collect();
+    args.push(package_name.to_string());
+    (command[0].to_string(), args)
+}
+
+pub fn get_upgrade_all_command(package_manager: &str) -> (String, Vec<String>) {
+    let command: &[&str] = match package_manager {
+        "apt" => &["apt-get", "upgrade", "-y"],
+        "dnf" => &["dnf", "upgrade", "-y"],
+        "yum" => &["yum", "update", "-y"],
+        "zypper" => &["zypper", "--non-interactive", "update"],
+        "pacman" => &["pacman", "-Syu", "--noconfirm"],
+        "pkg" => &["pkg", "upgrade", "-y"],
+        "pkg_add" => &["pkg_add", "-u"],
+        "ips" => &["pkg", "update"],
+        _ => &["apk", "upgrade"],
+    };
+    let args = command[1..].iter().map(|arg| arg.to_string()).collect();
+    (command[0].to_string(), args)
+}
+
+// Brings back files deleted from under the package, which an update skips when the package
+// is already the latest version
+pub fn get_reinstall_command(package_manager: &str, package_name: &str) -> (String, Vec<String>) {
+    let command: &[&str] = match package_manager {
+        "apt" => &["apt-get", "install", "--reinstall", "-y"],
+        "dnf" => &["dnf", "reinstall", "-y"],
+        "yum" => &["yum", "reinstall", "-y"],
+        "zypper" => &["zypper", "--non-interactive", "install", "--force"],
+        "pacman" => &["pacman", "-S", "--noconfirm"],
+        "pkg" => &["pkg", "install", "-f", "-y"],
+        "pkg_add" => &["pkg_add", "-r", "-D", "installed"],
+        "ips" => &["pkg", "fix"],
+        _ => &["apk", "fix"],
+    };
+    let mut args: Vec<String> = command[1..].iter().map(|arg| arg.to_string()).collect();
+    args.push(package_name.to_string());
+    (command[0].to_string(), args)
+}
+
+pub fn get_installed_version(package_manager: &str, package_name: &str) -> Option<String> {
+    let output = match package_manager {
+        "apt" => get_owner_output("dpkg-query", &["-W", "-f=${Version}", package_name]),
+        "dnf" | "yum" | "zypper" => get_owner_output(
+            "rpm",
+            &["-q", "--qf", "%{VERSION}-%{RELEASE}", package_name],
+        ),
+        // `coreutils 9.4-3`
+        "pacman" => get_owner_output("pacman", &["-Q", package_name])
+            .and_then(|output| output.split_whitespace().nth(1).map(|v| v.to_string())),
+        "pkg" => get_owner_output("pkg", &["query", "%v", package_name]),
+        // `ripgrep-14.1.0`
+        "pkg_add" => get_owner_output("pkg_info", &["-q", "-I", package_name]).and_then(|output| {
+            let package = output.split_whitespace().next()?;
+            let version = package.strip_prefix(strip_bsd_version(package))?;
+            Some(version.trim_start_matches('-').to_string())
+        }),
+        // `system/core-os  0.5.11-151046.0  i--`
+        "ips" => get_owner_output("pkg", &["list", "-H", package_name])
+            .and_then(|output| output.split_whitespace().nth(1).map(|v| v.to_string())),
+        _ => None,
+    }?;
+    let version = output.trim();
+    if version.is_empty() {
+        None
+    } else {
+        Some(version.to_string())
+    }
+}
+
+// Distro packages can only be updated by root
+pub fn is_root() -> bool {
+    if cfg!(windows) {
+        return false;
+    }
+
+    process::query_command("id")
+        .arg("-u")
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "0")
+        .unwrap_or(false)
+}
+
+// sudo forgets the password after 5 minutes by default, refreshing well before that keeps it
+const SUDO_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+// sudo's password, asked for once with `sudo -v` before a run with several updates that need
+// it, and kept valid until the session is dropped so that none of them asks again halfway
+// through, or times out while nobody is watching
+pub struct SudoSession {
+    stop: Option<Sender<()>>,
+    refresher: Option<JoinHandle<()>>,
+}
+
+impl SudoSession {
+    // `None` where there's nothing to keep: doas has no `-v`, and with nobody to type the
+    // password sudo runs with `-n` and uses the credentials it already has, if any
+    pub fn start() -> Result<Option<SudoSession>, String> {
+        if !paths::is_installed("sudo") || !process::can_prompt() {
+            return Ok(None);
+        }
+        let status = Command::new("sudo")
+            .arg("-v")
+            .status()
+            .map_err(|e| l10n::text("failed-to-run-sudo", &[("error", &e.to_string())]))?;
+        if !status.success() {
+            return Err(l10n::text("sudo-failed", &[]));
+        }
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let refresher = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(SUDO_REFRESH_INTERVAL) {
+                // `-n` so a timestamp that's gone anyway doesn't prompt over an update
+                let _ = Command::new("sudo")
+                    .args(["-n", "-v"])
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status();
+            }
+        });
+        Ok(Some(SudoSession {
+            stop: Some(stop),
+            refresher: Some(refresher),
+        }))
+    }
+}
+
+impl Drop for SudoSession {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(refresher) = self.refresher.take() {
+            let _ = refresher.join();
+        }
+    }
+}
+
+// With merged /usr, a binary on PATH as /usr/bin/foo may be recorded as /bin/foo
+fn get_candidate_paths(path: &Path) -> Vec<PathBuf> {
+    let mut candidates = vec![path.to_path_buf()];
+    if let Ok(resolved_path) = std::fs::canonicalize(path) {
+        candidates.push(resolved_path);
+    }
+
+    for (merged, legacy) in [("/usr/bin", "/bin"), ("/usr/sbin", "/sbin")] {
+        let alternatives: Vec<PathBuf> = candidates
+            .iter()
+            .filter_map(|candidate| {
+                if let Ok(rest) = candidate.strip_prefix(merged) {
+                    Some(Path::new(legacy).join(rest))
+                } else if let Ok(rest) = candidate.strip_prefix(legacy) {
+                    Some(Path::new(merged).join(rest))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        candidates.extend(alternatives);
+    }
+
+    let mut unique_candidates: Vec<PathBuf> = Vec::new();
+    for candidate in candidates {
+        if !unique_candidates.contains(&candidate) {
+            unique_candidates.push(candidate);
+        }
+    }
+    unique_candidates
+}
+
+fn get_rpm_frontend() -> &'static str {
+    ["dnf", "zypper", "yum"]
+        .into_iter()
+        .find(|frontend| paths::is_installed(frontend))
+        .unwrap_or("dnf")
+}
+
+// `busybox-1.36.1-r15` -> `busybox`, package names can contain dashes themselves
+fn strip_apk_version(owner: &str) -> &str {
+    let mut parts = owner.rsplitn(3, '-');
+    let release = parts.next().unwrap_or_default();
+    let _version = parts.next();
+    match parts.next() {
+        Some(name) if release.starts_with('r') => name,
+        _ => owner,
+    }
+}
+
+// `ripgrep-14.1.0` from FreeBSD's `pkg which -q`
+fn parse_pkg_which(output: &str) -> Option<String> {
+    output
+        .lines()
+        .next()
+        .filter(|line| !line.is_empty())
+        .map(|package| strip_bsd_version(package.trim()).to_string())
+}
+
+// `/usr/local/bin/rg: ripgrep-14.1.0` from OpenBSD's `pkg_info -E`
+fn parse_pkg_info_owner(output: &str) -> Option<String> {
+    output
+        .lines()
+        .next()
+        .and_then(|line| line.rsplit(": ").next())
+        .filter(|package| !package.is_empty())
+        .map(|package| strip_bsd_version(package.trim()).to_string())
+}
+
+// `system/core-os` from illumos' `pkg contents -H -o pkg.name`
+fn parse_ips_contents(output: &str) -> Option<String> {
+    output
+        .lines()
+        .next()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|package| package.to_string())
+}
+
+// `py311-black-24.1.1` -> `py311-black`, `qt5-3d-5.15.12` -> `qt5-3d`. Names can have dashes
+// followed by digits themselves, the version is what follows the last one. OpenBSD puts the
+// flavor after the version, `vim-9.1.1-gtk3`, which has no digit to start it
+fn strip_bsd_version(package: &str) -> &str {
+    package
+        .rmatch_indices('-')
+        .find(|(index, _)| {
+            package[index + 1..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_digit())
+        })
+        .map_or(package, |(index, _)| &package[..index])
+}
+
+fn get_owner_output(command: &str, args: &[&str]) -> Option<String> {
+    if !paths::is_installed(command) {
+        return None;
+    }
+
+    let output = process::query_command(command)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+    trail::command(command, args, output.is_some());
+    Some(String::from_utf8_lossy(&output?.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_bsd_version_keeps_dashes_in_names() {
+        assert_eq!(strip_bsd_version("ripgrep-14.1.0"), "ripgrep");
+        assert_eq!(strip_bsd_version("py311-black-24.1.1"), "py311-black");
+        assert_eq!(strip_bsd_version("qt5-3d-5.15.12"), "qt5-3d");
+        assert_eq!(strip_bsd_version("py39-foo-1.2"), "py39-foo");
+        assert_eq!(strip_bsd_version("vim-9.1.1-gtk3"), "vim");
+        assert_eq!(strip_bsd_version("ripgrep"), "ripgrep");
+    }
+
+    #[test]
+    fn parse_pkg_which_output() {
+        assert_eq!(
+            parse_pkg_which("ripgrep-14.1.0\n"),
+            Some("ripgrep".to_string())
+        );
+        assert_eq!(
+            parse_pkg_which("qt5-3d-5.15.12p1"),
+            Some("qt5-3d".to_string())
+        );
+        assert_eq!(parse_pkg_which(""), None);
+    }
+
+    #[test]
+    fn parse_pkg_info_owner_output() {
+        assert_eq!(
+            parse_pkg_info_owner("/usr/local/bin/rg: ripgrep-14.1.0"),
+            Some("ripgrep".to_string())
+        );
+        assert_eq!(
+            parse_pkg_info_owner("/usr/local/bin/vim: vim-9.1.1-gtk3"),
+            Some("vim".to_string())
+        );
+        assert_eq!(parse_pkg_info_owner(""), None);
+    }
+
+    #[test]
+    fn parse_ips_contents_output() {
+        assert_eq!(
+            parse_ips_contents("system/core-os\n"),
+            Some("system/core-os".to_string())
+        );
+        assert_eq!(parse_ips_contents(""), None);
+    }
+}
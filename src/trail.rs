@@ -0,0 +1,57 @@
+use std::sync::Mutex;
+
+// A step of working out who installed a binary, for `update-bin explain`
+pub enum Step {
+    // a detector with what it looked at, and whether it matched
+    Check {
+        detector: String,
+        matched: bool,
+        evidence: String,
+    },
+    // a command a detector ran, and whether it succeeded
+    Command(String, bool),
+}
+
+// Only `explain` records anything, every other run leaves it at None
+static TRAIL: Mutex<Option<Vec<Step>>> = Mutex::new(None);
+
+pub fn start() {
+    *TRAIL.lock().unwrap_or_else(|e| e.into_inner()) = Some(Vec::new());
+}
+
+pub fn finish() -> Vec<Step> {
+    TRAIL
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+        .unwrap_or_default()
+}
+
+pub fn is_recording() -> bool {
+    TRAIL.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+}
+
+// The evidence is only put into words when someone is going to read it, and outside the lock
+// since that can run commands of its own
+pub fn check(detector: &str, matched: bool, evidence: impl FnOnce() -> String) {
+    if !is_recording() {
+        return;
+    }
+    let evidence = evidence();
+    if let Some(steps) = TRAIL.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        steps.push(Step::Check {
+            detector: detector.to_string(),
+            matched,
+            evidence,
+        });
+    }
+}
+
+pub fn command(command: &str, args: &[&str], succeeded: bool) {
+    if let Some(steps) = TRAIL.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        steps.push(Step::Command(
+            format!("{} {}", command, args.join(" ")),
+            succeeded,
+        ));
+    }
+}
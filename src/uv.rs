@@ -0,0 +1,100 @@
+use crate::paths;
+use crate::process;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// Where `uv tool install` puts each tool's environment: UV_TOOL_DIR, what `uv tool dir` says,
+// or uv's default, `$XDG_DATA_HOME/uv/tools` (~/.local/share) or `%APPDATA%\uv\data\tools`
+pub fn get_tool_dir() -> Option<&'static Path> {
+    static TOOL_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+    TOOL_DIR
+        .get_or_init(|| {
+            if let Some(tool_dir) = std::env::var_os("UV_TOOL_DIR").filter(|dir| !dir.is_empty()) {
+                return Some(PathBuf::from(tool_dir));
+            }
+            if paths::is_installed("uv") {
+                let tool_dir = process::query_command("uv")
+                    .args(["tool", "dir"])
+                    .output()
+                    .ok()
+                    .filter(|output| output.status.success())
+                    .and_then(|output| paths::path_from_output(&output.stdout));
+                if tool_dir.is_some() {
+                    return tool_dir;
+                }
+            }
+            get_default_tool_dir().filter(|tool_dir| tool_dir.is_dir())
+        })
+        .as_deref()
+}
+
+fn get_default_tool_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        return std::env::var_os("APPDATA")
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| PathBuf::from(dir).join("uv").join("data").join("tools"));
+    }
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| paths::get_home_dir().map(|home| home.join(".local").join("share")))?;
+    Some(data_home.join("uv").join("tools"))
+}
+
+// The tool that provides the binary. On Unix the bin is a symlink into the tool's environment,
+// `<tool dir>/<tool>/bin/<bin>`, on Windows it's a copy that only the tool's receipt lists
+pub fn get_tool_from_path(bin_path: &str) -> Option<String> {
+    let tool_dir = get_tool_dir()?;
+    if let Ok(resolved_path) = std::fs::canonicalize(bin_path) {
+        let tool_dir = std::fs::canonicalize(tool_dir).unwrap_or_else(|_| tool_dir.to_path_buf());
+        if let Ok(relative_path) = resolved_path.strip_prefix(&tool_dir) {
+            return relative_path
+                .components()
+                .next()
+                .map(|tool| tool.as_os_str().to_string_lossy().to_string());
+        }
+    }
+
+    std::fs::read_dir(tool_dir)
+        .ok()?
+        .flatten()
+        .find(|entry| {
+            get_receipt_install_paths(&entry.path().join("uv-receipt.toml"))
+                .iter()
+                .any(|install_path| paths::same_path(install_path, Path::new(bin_path)))
+        })
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+}
+
+// The `install-path`s of a receipt's entrypoints, e.g.
+// `{ name = "ruff", install-path = "/home/user/.local/bin/ruff" }`
+fn get_receipt_install_paths(receipt_path: &Path) -> Vec<PathBuf> {
+    let content = std::fs::read_to_string(receipt_path).unwrap_or_default();
+    content
+        .split("install-path = \"")
+        .skip(1)
+        .filter_map(|rest| rest.split('"').next())
+        // a TOML string escapes the backslashes of Windows paths
+        .map(|install_path| PathBuf::from(install_path.replace("\\\\", "\\")))
+        .collect()
+}
+
+// The installed version of the tool, from `uv tool list`, which prints `<tool> v<version>`
+// followed by a `- <bin>` line per binary
+pub fn get_installed_version(tool: &str) -> Option<String> {
+    let output = process::query_command("uv")
+        .args(["tool", "list"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        if fields.next() != Some(tool) {
+            return None;
+        }
+        let version = fields.next()?;
+        Some(version.strip_prefix('v').unwrap_or(version).to_string())
+    })
+}
@@ -0,0 +1,272 @@
+use crate::style;
+use std::cmp::Ordering;
+
+// A version loosely following semver, e.g. `v1.2.0`, `1.2.0-beta.1`, or brew's `3.1.4_1`
+// where `_1` is the formula revision
+pub struct Version {
+    numbers: Vec<u64>,
+    pre_release: Vec<String>,
+    revision: u64,
+}
+
+// Extract the version from strings like `v1.2.0`, `1.2.0_1` or `ripgrep 14.1.0 (rev abc)`
+pub fn parse_version(version: &str) -> Option<Version> {
+    let start = find_version_start(version)?;
+    let version = &version[start..];
+    let end = version
+        .find(|c: char| c.is_whitespace() || c == '(' || c == ',' || c == '+')
+        .unwrap_or(version.len());
+    let version = &version[..end];
+
+    let (version, revision) = match version.rsplit_once('_') {
+        Some((version, revision)) => (version, revision.parse().ok()?),
+        None => (version, 0),
+    };
+
+    let (version, pre_release) = match version.split_once('-') {
+        Some((version, pre_release)) => (
+            version,
+            pre_release
+                .split('.')
+                .map(|part| part.to_string())
+                .collect(),
+        ),
+        None => (version, Vec::new()),
+    };
+
+    let numbers = version
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+
+    Some(Version {
+        numbers,
+        pre_release,
+        revision,
+    })
+}
+
+// Where the version starts: the first digit that starts a word or follows a `v`, so the `3` of
+// `python3 3.12.1` is skipped. `go1.22.0`, with no such digit, starts at its first one
+fn find_version_start(text: &str) -> Option<usize> {
+    let is_word_start = |index: usize| {
+        let mut before = text[..index].chars().rev();
+        match before.next() {
+            None => true,
+            Some('v' | 'V') => !before.next().is_some_and(|c| c.is_alphanumeric()),
+            Some(c) => !c.is_alphanumeric() && !matches!(c, '.' | '-' | '_'),
+        }
+    };
+    text.char_indices()
+        .find(|(index, c)| c.is_ascii_digit() && is_word_start(*index))
+        .or_else(|| text.char_indices().find(|(_, c)| c.is_ascii_digit()))
+        .map(|(index, _)| index)
+}
+
+// Compare two version strings with semver semantics, None if either can't be parsed
+pub fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    Some(parse_version(a)?.cmp(&parse_version(b)?))
+}
+
+// How big a jump from one version to the next is, by the first component that changed
+#[derive(PartialEq)]
+pub enum Change {
+    Major,
+    Minor,
+    Patch,
+}
+
+// Anything that isn't major or minor, like a pre-release or a formula revision, is a patch.
+// Below 1.0 a minor bump is allowed to break things, so it counts as major like cargo does.
+// The index is the component that changed, past the last number when only the pre-release or
+// the revision did
+pub fn get_change(old_version: &str, new_version: &str) -> Option<(Change, usize)> {
+    let old = parse_version(old_version)?;
+    let new = parse_version(new_version)?;
+    let length = old.numbers.len().max(new.numbers.len());
+    let changed_index = (0..length).find(|index| {
+        old.numbers.get(*index).copied().unwrap_or(0)
+            != new.numbers.get(*index).copied().unwrap_or(0)
+    });
+    let change = match changed_index {
+        Some(0) => Change::Major,
+        Some(1) if old.numbers[0] == 0 => Change::Major,
+        Some(1) => Change::Minor,
+        _ => Change::Patch,
+    };
+    Some((change, changed_index.unwrap_or(new.numbers.len())))
+}
+
+// `1.2.0 → 1.3.0`, see highlight_change
+pub fn format_change(old_version: &str, new_version: &str) -> String {
+    let arrow = if style::is_plain() { "->" } else { "→" };
+    format!(
+        "{} {} {}",
+        old_version,
+        arrow,
+        highlight_change(old_version, new_version)
+    )
+}
+
+// The new version with the part that changed colored, red for a major update, yellow for a
+// minor one and green for a patch unless the theme says otherwise, and a MAJOR UPDATE badge that stays without colors
+pub fn highlight_change(old_version: &str, new_version: &str) -> String {
+    let (change, changed_index) = match get_change(old_version, new_version) {
+        Some(change) => change,
+        None => return new_version.to_string(),
+    };
+    let color = match change {
+        Change::Major => style::MAJOR,
+        Change::Minor => style::MINOR,
+        Change::Patch => style::PATCH,
+    };
+    let (unchanged, changed) = new_version.split_at(get_changed_offset(new_version, changed_index));
+    let mut text = format!("{}{}", unchanged, style::paint(changed, color));
+    if change == Change::Major {
+        text.push_str(&format!(" {}", style::paint("MAJOR UPDATE", style::ALERT)));
+    }
+    text
+}
+
+// Where the changed component starts, the ones before it stay as they are. Past the last number
+// that's the pre-release or the revision, with its `-` or `_`
+fn get_changed_offset(version: &str, changed_index: usize) -> usize {
+    let mut offset = find_version_start(version).unwrap_or(0);
+    for _ in 0..changed_index {
+        match version[offset..].find(['.', '-', '_', '+']) {
+            Some(end) if version[offset + end..].starts_with('.') => offset += end + 1,
+            Some(end) => return offset + end,
+            None => return offset,
+        }
+    }
+    offset
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // missing components count as 0, so 1.2 == 1.2.0
+        let length = self.numbers.len().max(other.numbers.len());
+        for index in 0..length {
+            let a = self.numbers.get(index).copied().unwrap_or(0);
+            let b = other.numbers.get(index).copied().unwrap_or(0);
+            match a.cmp(&b) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+
+        // a pre-release is lower than the release itself
+        let pre_release = match (self.pre_release.is_empty(), other.pre_release.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => compare_pre_release(&self.pre_release, &other.pre_release),
+        };
+
+        pre_release.then(self.revision.cmp(&other.revision))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn compare_pre_release(a: &[String], b: &[String]) -> Ordering {
+    for (a, b) in a.iter().zip(b) {
+        let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            // numeric identifiers have lower precedence than alphanumeric ones
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    a.len().cmp(&b.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(version: &str) -> Vec<u64> {
+        parse_version(version).unwrap().numbers
+    }
+
+    #[test]
+    fn parses_versions_in_output() {
+        assert_eq!(numbers("v1.2.0"), [1, 2, 0]);
+        assert_eq!(numbers("ripgrep 14.1.0 (rev abc)"), [14, 1, 0]);
+        assert_eq!(numbers("python3 3.12.1"), [3, 12, 1]);
+        assert_eq!(numbers("Python 3.12.1"), [3, 12, 1]);
+        assert_eq!(numbers("node v20.11.0"), [20, 11, 0]);
+        assert_eq!(numbers("go version go1.22.0 linux/amd64"), [1, 22, 0]);
+        assert_eq!(numbers("mytool2 version 2.0.1"), [2, 0, 1]);
+        let brew = parse_version("3.1.4_1").unwrap();
+        assert_eq!((brew.numbers, brew.revision), (vec![3, 1, 4], 1));
+        let beta = parse_version("1.2.0-beta.1").unwrap();
+        assert_eq!(beta.pre_release, ["beta", "1"]);
+        assert!(parse_version("unknown").is_none());
+    }
+
+    #[test]
+    fn compares_versions() {
+        let compare = |a, b| compare_versions(a, b).unwrap();
+        assert_eq!(compare("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare("1.10.0", "1.9.0"), Ordering::Greater);
+        assert_eq!(compare("1.2.0-beta.1", "1.2.0"), Ordering::Less);
+        assert_eq!(compare("1.2.0-beta.2", "1.2.0-beta.10"), Ordering::Less);
+        assert_eq!(compare("1.2.0-alpha", "1.2.0-beta"), Ordering::Less);
+        assert_eq!(compare("1.2.0-1", "1.2.0-alpha"), Ordering::Less);
+        assert_eq!(compare("1.2.0-beta", "1.2.0-beta.1"), Ordering::Less);
+        assert_eq!(compare("3.1.4_1", "3.1.4"), Ordering::Greater);
+        // the number in the program's name isn't the version
+        assert_eq!(compare("python3 3.11.8", "python3 3.12.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.2.0", "unknown"), None);
+    }
+
+    #[test]
+    fn changes() {
+        let change = |a, b| get_change(a, b).unwrap();
+        assert!(change("1.2.0", "2.0.0") == (Change::Major, 0));
+        assert!(change("1.2.0", "1.3.0") == (Change::Minor, 1));
+        assert!(change("0.2.0", "0.3.0") == (Change::Major, 1));
+        assert!(change("1.2.0", "1.2.1") == (Change::Patch, 2));
+        assert!(change("1.2", "1.2.1") == (Change::Patch, 2));
+        // only what comes after the numbers changed
+        assert!(change("1.2.0-beta.1", "1.2.0-beta.2") == (Change::Patch, 3));
+        assert!(change("3.1.4", "3.1.4_1") == (Change::Patch, 3));
+    }
+
+    #[test]
+    fn highlights_the_changed_part() {
+        let changed = |old, new: &'static str| {
+            let (_, changed_index) = get_change(old, new).unwrap();
+            &new[get_changed_offset(new, changed_index)..]
+        };
+        assert_eq!(changed("1.2.0", "2.0.0"), "2.0.0");
+        assert_eq!(changed("1.2.0", "v1.3.0"), "3.0");
+        assert_eq!(changed("1.2.0", "1.2.1"), "1");
+        assert_eq!(changed("1.2.0-beta.1", "1.2.0-beta.2"), "-beta.2");
+        assert_eq!(changed("3.1.4", "3.1.4_1"), "_1");
+        assert_eq!(changed("python3 3.11.8", "python3 3.12.1"), "12.1");
+
+        // without a terminal there are no colors, the badge stays
+        assert_eq!(highlight_change("1.2.0", "1.3.0"), "1.3.0");
+        assert_eq!(highlight_change("1.2.0", "2.0.0"), "2.0.0 MAJOR UPDATE");
+        assert_eq!(highlight_change("1.2.0", "unknown"), "unknown");
+    }
+}
@@ -0,0 +1,296 @@
+use crate::l10n;
+use crate::paths;
+use crate::process;
+use crate::repair::Repair;
+use std::path::{Path, PathBuf};
+
+// Version managers put shims on PATH that pick a runtime version when run, the shim itself
+// tells nothing about who installed the binary
+pub fn get_shim_manager(bin_path: &str) -> Option<&'static str> {
+    let bin_dir = Path::new(bin_path).parent()?;
+    let home = paths::get_home_dir()?;
+
+    let shim_dirs = [
+        (
+            "asdf",
+            get_root("ASDF_DATA_DIR", home.join(".asdf")).join("shims"),
+        ),
+        ("mise", get_mise_data_dir(&home).join("shims")),
+        (
+            "volta",
+            get_root("VOLTA_HOME", home.join(".volta")).join("bin"),
+        ),
+        (
+            "pyenv",
+            get_root("PYENV_ROOT", home.join(".pyenv")).join("shims"),
+        ),
+        (
+            "nodenv",
+            get_root("NODENV_ROOT", home.join(".nodenv")).join("shims"),
+        ),
+        (
+            "rbenv",
+            get_root("RBENV_ROOT", home.join(".rbenv")).join("shims"),
+        ),
+    ];
+
+    shim_dirs
+        .into_iter()
+        .find(|(_, shim_dir)| paths::same_path(bin_dir, shim_dir))
+        .map(|(manager, _)| manager)
+}
+
+// Every one of them can tell which binary a shim currently runs
+pub fn resolve_shim(manager: &str, bin_name: &str) -> Option<PathBuf> {
+    if !paths::is_installed(manager) {
+        return None;
+    }
+
+    let output = process::query_command(manager)
+        .args(["which", bin_name])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    paths::path_from_output(&output.stdout)
+}
+
+// A shim whose runtime version is gone, e.g. after `pyenv uninstall`, makes `<mgr> which`
+// fail with the reason, while the shim is still there to run
+pub fn get_stale_shim(manager: &str, bin_name: &str) -> Option<Repair> {
+    if manager == "volta" || !paths::is_installed(manager) {
+        return None;
+    }
+
+    let output = process::query_command(manager)
+        .args(["which", bin_name])
+        .output()
+        .ok()?;
+    if output.status.success() {
+        let target = paths::path_from_output(&output.stdout)?;
+        if target.exists() {
+            return None;
+        }
+        return Some(Repair {
+            problem: l10n::text(
+                "shim-target-gone",
+                &[
+                    ("bin", bin_name),
+                    ("manager", manager),
+                    ("target", &target.display().to_string()),
+                ],
+            ),
+            command: Some(get_reshim_command(manager)),
+        });
+    }
+
+    let error = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let reason = error.lines().next().unwrap_or("no reason given");
+    // `pyenv: version `3.9.1' is not installed (set by ...)`
+    let missing_version = reason
+        .split_once("version `")
+        .and_then(|(_, rest)| rest.split_once('\''))
+        .map(|(version, _)| version.to_string());
+
+    let command = match (manager, missing_version) {
+        ("asdf" | "mise", _) => (manager.to_string(), vec!["install".to_string()]),
+        (_, Some(version)) => (manager.to_string(), vec!["install".to_string(), version]),
+        // no version has the command anymore, the shim is left over
+        _ => get_reshim_command(manager),
+    };
+    Some(Repair {
+        problem: l10n::text(
+            "shim-target-unknown",
+            &[("bin", bin_name), ("manager", manager), ("reason", reason)],
+        ),
+        command: Some(command),
+    })
+}
+
+// A package installed through a shim, like an npm global into asdf's node, only gets a shim
+// for a binary it didn't have before once they're rebuilt. Volta makes those itself
+pub fn get_reshim_after_update(path: &str) -> Option<(String, Vec<String>)> {
+    let manager = get_shim_manager(path).filter(|manager| *manager != "volta")?;
+    if !paths::is_installed(manager) {
+        return None;
+    }
+    Some(get_reshim_command(manager))
+}
+
+// Rebuilding the shims drops the ones nothing provides anymore
+fn get_reshim_command(manager: &str) -> (String, Vec<String>) {
+    let subcommand = match manager {
+        "asdf" | "mise" => "reshim",
+        _ => "rehash",
+    };
+    (manager.to_string(), vec![subcommand.to_string()])
+}
+
+// The tool and version a shim resolved to, from `<root>/installs/<tool>/<version>` (asdf,
+// mise) or `<root>/versions/<version>` (pyenv, nodenv, rbenv)
+pub fn get_installed_tool(manager: &str, target: &Path) -> Option<(String, String)> {
+    let components: Vec<String> = target
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+
+    match manager {
+        "asdf" | "mise" => {
+            let index = components.iter().position(|name| name == "installs")?;
+            Some((
+                components.get(index + 1)?.to_string(),
+                components.get(index + 2)?.to_string(),
+            ))
+        }
+        _ => {
+            let index = components.iter().position(|name| name == "versions")?;
+            let tool = match manager {
+                "pyenv" => "python",
+                "nodenv" => "node",
+                _ => "ruby",
+            };
+            Some((tool.to_string(), components.get(index + 1)?.to_string()))
+        }
+    }
+}
+
+// Volta keeps packages in `tools/image/packages/<package>` and node itself, along with the
+// npm and yarn it manages, in `tools/image/<node|npm|yarn>/<version>`
+pub fn get_volta_package(target: &Path) -> Option<String> {
+    let components: Vec<String> = target
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+    let index = components.iter().position(|name| name == "image")?;
+
+    match components.get(index + 1)?.as_str() {
+        "packages" => {
+            let package = components.get(index + 2)?;
+            // scoped packages are nested one level deeper
+            if package.starts_with('@') {
+                Some(format!("{}/{}", package, components.get(index + 3)?))
+            } else {
+                Some(package.to_string())
+            }
+        }
+        tool => Some(tool.to_string()),
+    }
+}
+
+// pyenv, nodenv, rbenv and asdf install runtimes side by side instead of updating them
+pub fn explain(manager: &str, bin_name: &str, target: Option<&Path>) -> String {
+    let tool = target.and_then(|target| get_installed_tool(manager, target));
+    let (tool, version) = match &tool {
+        Some((tool, version)) => (tool.as_str(), version.as_str()),
+        None => {
+            return l10n::text(
+                "shim-version-unknown",
+                &[("bin", bin_name), ("manager", manager)],
+            )
+        }
+    };
+
+    let install_command = match manager {
+        "asdf" => l10n::text("asdf-install-latest", &[("tool", tool)]),
+        _ => l10n::text("version-manager-install", &[("manager", manager)]),
+    };
+    l10n::text(
+        "version-manager-installs-side-by-side",
+        &[
+            ("bin", bin_name),
+            ("tool", tool),
+            ("version", version),
+            ("manager", manager),
+            ("install-command", &install_command),
+        ],
+    )
+}
+
+// `asdf latest <tool>` prints the newest stable version its plugin knows of
+pub fn get_asdf_latest_version(tool: &str) -> Result<String, String> {
+    process::query_command("asdf")
+        .args(["latest", tool])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|version| !version.is_empty())
+        .ok_or_else(|| l10n::text("asdf-latest-unknown", &[("tool", tool)]))
+}
+
+// Moves the pin in the .tool-versions asdf reads it from: the nearest one up from the current
+// dir that lists the tool, or the one in the home dir. Of several versions on the line, the
+// first is the one in use, the rest are fallbacks and stay
+pub fn write_asdf_pin(tool: &str, version: &str) -> Result<PathBuf, String> {
+    let file_name = std::env::var("ASDF_DEFAULT_TOOL_VERSIONS_FILENAME")
+        .ok()
+        .filter(|file_name| !file_name.is_empty())
+        .unwrap_or_else(|| ".tool-versions".to_string());
+    let current_dir = std::env::current_dir()
+        .map_err(|e| l10n::text("failed-to-get-current-dir", &[("error", &e.to_string())]))?;
+    let candidates = current_dir
+        .ancestors()
+        .map(|dir| dir.join(&file_name))
+        .chain(paths::get_home_dir().map(|home| home.join(&file_name)));
+
+    for path in candidates {
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let mut pinned = false;
+        let lines: Vec<String> = content
+            .lines()
+            .map(|line| {
+                let mut fields = line.split_whitespace();
+                if pinned || fields.next() != Some(tool) {
+                    return line.to_string();
+                }
+                pinned = true;
+                let fallbacks: Vec<&str> = fields.skip(1).collect();
+                [tool, version]
+                    .into_iter()
+                    .chain(fallbacks)
+                    .collect::<Vec<&str>>()
+                    .join(" ")
+            })
+            .collect();
+        if !pinned {
+            continue;
+        }
+        let mut new_content = lines.join("\n");
+        if content.ends_with('\n') {
+            new_content.push('\n');
+        }
+        std::fs::write(&path, new_content).map_err(|e| {
+            l10n::text(
+                "failed-to-write",
+                &[
+                    ("path", &path.display().to_string()),
+                    ("error", &e.to_string()),
+                ],
+            )
+        })?;
+        return Ok(path);
+    }
+
+    Err(l10n::text(
+        "pins-not-moved",
+        &[
+            ("tool", tool),
+            ("version", version),
+            ("file-name", &file_name),
+        ],
+    ))
+}
+
+fn get_root(variable: &str, default_root: PathBuf) -> PathBuf {
+    std::env::var_os(variable)
+        .filter(|root| !root.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or(default_root)
+}
+
+fn get_mise_data_dir(home: &Path) -> PathBuf {
+    let data_home = get_root("XDG_DATA_HOME", home.join(".local").join("share"));
+    get_root("MISE_DATA_DIR", data_home.join("mise"))
+}
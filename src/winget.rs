@@ -0,0 +1,65 @@
+use crate::paths;
+use std::path::Path;
+
+// Work out the winget package behind a binary exposed through an execution alias in
+// %LOCALAPPDATA%\Microsoft\WindowsApps (Store/MSIX apps like Python) or through winget's
+// own %LOCALAPPDATA%\Microsoft\WinGet\Links dir (winget portable packages)
+pub fn get_package_from_path(bin_path: &str) -> Option<String> {
+    let path = Path::new(bin_path);
+
+    if paths::has_components(path, &["Microsoft", "WindowsApps"]) {
+        return get_package_family_name(path);
+    }
+
+    if paths::has_components(path, &["Microsoft", "WinGet"]) {
+        // portable packages are symlinked from Links into Packages\<id>_<source>
+        let resolved_path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        return get_portable_package_id(&resolved_path);
+    }
+
+    None
+}
+
+// Execution aliases are reparse points that can't be followed, but WindowsApps keeps a
+// <PackageFamilyName> dir next to them holding an alias with the same name
+fn get_package_family_name(alias_path: &Path) -> Option<String> {
+    let alias_dir = alias_path.parent()?;
+    let alias_name = alias_path.file_name()?;
+
+    if alias_dir
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .map(|name| name.eq_ignore_ascii_case("WindowsApps"))
+        .unwrap_or(false)
+    {
+        return alias_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+    }
+
+    std::fs::read_dir(alias_dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .find(|entry| entry.path().join(alias_name).exists())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+}
+
+// package dirs are named `<PackageIdentifier>_<SourceId>`, e.g.
+// `BurntSushi.ripgrep.MSVC_Microsoft.Winget.Source_8wekyb3d8bbwe`
+fn get_portable_package_id(path: &Path) -> Option<String> {
+    let components: Vec<String> = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+    let packages_index = components
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case("Packages"))?;
+    let package_dir = components.get(packages_index + 1)?;
+
+    let package_id = package_dir
+        .split_once("_Microsoft.Winget.Source")
+        .map(|(package_id, _)| package_id)
+        .unwrap_or(package_dir);
+    Some(package_id.to_string())
+}